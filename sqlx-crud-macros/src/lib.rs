@@ -1,362 +1,4219 @@
 use inflector::Inflector;
 use proc_macro::{self, TokenStream};
-use proc_macro2::TokenStream as TokenStream2;
-use quote::{format_ident, quote, ToTokens};
+use proc_macro2::{Span, TokenStream as TokenStream2, TokenTree};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::ext::IdentExt;
 use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
 use syn::token::Comma;
 use syn::{
-    parse_macro_input, Attribute, Data, DataStruct, DeriveInput, Field, Fields, FieldsNamed, Ident,
-    LitStr,
+    parse_macro_input, parse_quote, Attribute, Data, DataStruct, DeriveInput, Expr, ExprLit, Field,
+    Fields, FieldsNamed, Generics, Ident, Lit, LitStr, Meta, MetaNameValue, Path, Type,
 };
 
-#[proc_macro_derive(SqlxCrud, attributes(database, external_id, id))]
+#[proc_macro_derive(SqlxCrud, attributes(database, external_id, id, sqlx_crud))]
 pub fn derive(input: TokenStream) -> TokenStream {
     let DeriveInput {
-        ident, data, attrs, ..
+        ident,
+        vis,
+        data,
+        attrs,
+        generics,
+        ..
     } = parse_macro_input!(input);
+    let config = match named_fields("SqlxCrud", &ident, &data)
+        .and_then(|named| Config::new(&attrs, &ident, &generics, named, false))
+    {
+        Ok(config) => config,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let sqlx_crud_impl = build_sqlx_crud_impl(&config);
+    let column_enum = build_column_enum(&config, &vis);
+    let finders_impl = build_finders_impl(&config);
+    let prefixed_impl = build_prefixed_impl(&config);
+    let relations_impl = build_relations_impl(&config);
+    let patch_impl = build_patch_impl(&config, &vis);
+    let touch_impl = build_touch_impl(&config);
+    let new_type_impl = build_new_type_impl(&config, &vis);
+    let checked_test = build_checked_test(&config);
+    let debug_sql = build_debug_sql(&config);
+
+    quote! {
+        #sqlx_crud_impl
+        #column_enum
+        #finders_impl
+        #prefixed_impl
+        #relations_impl
+        #patch_impl
+        #touch_impl
+        #new_type_impl
+        #checked_test
+        #debug_sql
+    }
+    .into()
+}
+
+#[proc_macro_derive(SqlxCrudEmbedded, attributes(database, id, sqlx_crud))]
+pub fn derive_embedded(input: TokenStream) -> TokenStream {
+    let DeriveInput {
+        ident,
+        vis,
+        data,
+        attrs,
+        generics,
+        ..
+    } = parse_macro_input!(input);
+    let config = match named_fields("SqlxCrudEmbedded", &ident, &data)
+        .and_then(|named| Config::new(&attrs, &ident, &generics, named, true))
+    {
+        Ok(config) => config,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let embedded_impl = build_embedded_impl(&config);
+    let column_enum = build_column_enum(&config, &vis);
+
+    quote! {
+        #embedded_impl
+        #column_enum
+    }
+    .into()
+}
+
+/// The fields of a struct with named fields, the only kind of type the
+/// derives map on to columns.
+fn named_fields<'a>(
+    derive: &str,
+    ident: &Ident,
+    data: &'a Data,
+) -> syn::Result<&'a Punctuated<Field, Comma>> {
     match data {
         Data::Struct(DataStruct {
             fields: Fields::Named(FieldsNamed { named, .. }),
             ..
-        }) => {
-            let config = Config::new(&attrs, &ident, &named);
-            let static_model_schema = build_static_model_schema(&config);
-            let sqlx_crud_impl = build_sqlx_crud_impl(&config);
-
-            quote! {
-                #static_model_schema
-                #sqlx_crud_impl
-            }
-            .into()
-        }
-        _ => panic!("this derive macro only works on structs with named fields"),
+        }) => Ok(named),
+        Data::Struct(DataStruct {
+            fields: Fields::Unnamed(fields),
+            ..
+        }) => Err(syn::Error::new_spanned(
+            fields,
+            format!(
+                "`{}` requires named fields to name the columns, tuple structs are not supported",
+                derive
+            ),
+        )),
+        Data::Struct(DataStruct {
+            fields: Fields::Unit,
+            ..
+        }) => Err(syn::Error::new(
+            ident.span(),
+            format!(
+                "`{}` requires named fields to name the columns, unit structs are not supported",
+                derive
+            ),
+        )),
+        Data::Enum(data) => Err(syn::Error::new(
+            data.enum_token.span,
+            format!("`{}` can only be derived for structs, not enums", derive),
+        )),
+        Data::Union(data) => Err(syn::Error::new(
+            data.union_token.span,
+            format!("`{}` can only be derived for structs, not unions", derive),
+        )),
     }
 }
 
-fn build_static_model_schema(config: &Config) -> TokenStream2 {
+/// The `<Struct>Column` enum naming the columns of the struct, one variant
+/// per field in _PascalCase_. Flattened fields hold the column enum of the
+/// embedded struct.
+fn build_column_enum(config: &Config, vis: &syn::Visibility) -> TokenStream2 {
     let crate_name = &config.crate_name;
-    let model_schema_ident = &config.model_schema_ident;
-    let table_name = &config.table_name;
+    let column_ident = config.column_ident();
+    let embedded_column = |ty: &Type| {
+        quote! { <#ty as #crate_name::traits::Embedded>::Column }
+    };
+    let variants = config.fields.iter().map(|f| {
+        let variant = f.column_variant();
+        if f.flatten {
+            let column = embedded_column(f.ty);
+            quote! { #variant(#column) }
+        } else {
+            quote! { #variant }
+        }
+    });
+    let names = config.fields.iter().map(|f| {
+        let variant = f.column_variant();
+        if f.flatten {
+            quote! { Self::#variant(column) => #crate_name::traits::ColumnName::name(column) }
+        } else {
+            let name = &f.column;
+            quote! { Self::#variant => #name }
+        }
+    });
 
-    let id_column = config.id_column_ident.to_string();
-    let columns_len = config.named.iter().count();
-    let columns = config
-        .named
+    // The embedded columns are only known to the embedded struct, their
+    // variants are copied in to the array at compile time
+    let all = if config.fields.iter().any(|f| f.flatten) {
+        let len = config.fields.iter().map(|f| {
+            if f.flatten {
+                let column = embedded_column(f.ty);
+                quote! { <#column as #crate_name::traits::ColumnName>::ALL.len() }
+            } else {
+                quote! { 1usize }
+            }
+        });
+        let first = config.fields.first().map(|f| {
+            let variant = f.column_variant();
+            if f.flatten {
+                let column = embedded_column(f.ty);
+                quote! { #column_ident::#variant(<#column as #crate_name::traits::ColumnName>::ALL[0]) }
+            } else {
+                quote! { #column_ident::#variant }
+            }
+        });
+        let fill = config.fields.iter().map(|f| {
+            let variant = f.column_variant();
+            if f.flatten {
+                let column = embedded_column(f.ty);
+                quote! {
+                    let mut j = 0;
+                    while j < <#column as #crate_name::traits::ColumnName>::ALL.len() {
+                        all[i] = #column_ident::#variant(<#column as #crate_name::traits::ColumnName>::ALL[j]);
+                        i += 1;
+                        j += 1;
+                    }
+                }
+            } else {
+                quote! {
+                    all[i] = #column_ident::#variant;
+                    i += 1;
+                }
+            }
+        });
+        quote! {{
+            const LEN: usize = 0usize #(+ #len)*;
+            #[allow(unused_assignments)]
+            const ALL: [#column_ident; LEN] = {
+                let mut all = [#first; LEN];
+                let mut i = 0;
+                #(#fill)*
+                all
+            };
+            &ALL
+        }}
+    } else {
+        let variants = config.fields.iter().map(|f| f.column_variant());
+        quote! { &[#(Self::#variants),*] }
+    };
+    // Flattened columns are typed by the embedded struct, `with` fields are
+    // bound through their module rather than as the field type, and the
+    // column enum has no generic parameters to type the others with
+    let typed = config
+        .fields
         .iter()
-        .flat_map(|f| &f.ident)
-        .map(|f| LitStr::new(format!("{}", f).as_str(), f.span()));
-
-    let sql_queries = build_sql_queries(config);
+        .filter(|f| !f.flatten && f.with.is_none() && !config.is_generic(f))
+        .map(|f| {
+            let ident = f.ident;
+            let variant = f.column_variant();
+            let ty = f.bind_ty();
+            let doc = format!(
+                "The [`{}`](Self::{}) column typed as its field.",
+                f.column, variant
+            );
+            quote! {
+                #[doc = #doc]
+                #vis const #ident: #crate_name::filter::TypedColumn<Self, #ty> =
+                    #crate_name::filter::TypedColumn::new(Self::#variant);
+            }
+        });
+    let doc = format!(
+        "Columns of [`{}`], displayed as the column name.",
+        config.ident
+    );
 
     quote! {
+        #[doc = #doc]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #vis enum #column_ident {
+            #(#variants,)*
+        }
+
         #[automatically_derived]
-        static #model_schema_ident: #crate_name::schema::Metadata<'static, #columns_len> = #crate_name::schema::Metadata {
-            table_name: #table_name,
-            id_column: #id_column,
-            columns: [#(#columns),*],
-            #sql_queries
-        };
-    }
-}
+        impl #column_ident {
+            /// Every column, in field order.
+            pub const ALL: &'static [Self] = #all;
+        }
 
-fn build_sql_queries(config: &Config) -> TokenStream2 {
-    let table_name = config.quote_ident(&config.table_name);
-    let id_column = format!(
-        "{}.{}",
-        &table_name,
-        config.quote_ident(&config.id_column_ident.to_string())
-    );
+        #[automatically_derived]
+        #[allow(non_upper_case_globals)]
+        impl #column_ident {
+            #(#typed)*
+        }
 
-    let insert_bind_cnt = if config.external_id {
-        config.named.iter().count()
-    } else {
-        config.named.iter().count() - 1
-    };
-    let insert_sql_binds = (0..insert_bind_cnt)
-        .map(|_| "?")
-        .collect::<Vec<_>>()
-        .join(", ");
+        #[automatically_derived]
+        impl #crate_name::traits::ColumnName for #column_ident {
+            const ALL: &'static [Self] = Self::ALL;
 
-    let update_sql_binds = config
-        .named
-        .iter()
-        .flat_map(|f| &f.ident)
-        .filter(|i| *i != &config.id_column_ident)
-        .map(|i| format!("{} = ?", config.quote_ident(&i.to_string())))
-        .collect::<Vec<_>>()
-        .join(", ");
+            fn name(&self) -> &'static str {
+                match self {
+                    #(#names,)*
+                }
+            }
+        }
 
-    let insert_column_list = config
-        .named
-        .iter()
-        .flat_map(|f| &f.ident)
-        .filter(|i| config.external_id || *i != &config.id_column_ident)
-        .map(|i| config.quote_ident(&i.to_string()))
-        .collect::<Vec<_>>()
-        .join(", ");
-    let column_list = config
-        .named
-        .iter()
-        .flat_map(|f| &f.ident)
-        .map(|i| format!("{}.{}", &table_name, config.quote_ident(&i.to_string())))
-        .collect::<Vec<_>>()
-        .join(", ");
+        #[automatically_derived]
+        impl ::std::fmt::Display for #column_ident {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.write_str(#crate_name::traits::ColumnName::name(self))
+            }
+        }
 
-    let select_sql = format!("SELECT {} FROM {}", column_list, table_name);
-    let select_by_id_sql = format!(
-        "SELECT {} FROM {} WHERE {} = ? LIMIT 1",
-        column_list, table_name, id_column
-    );
-    let insert_sql = format!(
-        "INSERT INTO {} ({}) VALUES ({}) RETURNING {}",
-        table_name, insert_column_list, insert_sql_binds, column_list
-    );
-    let update_by_id_sql = format!(
-        "UPDATE {} SET {} WHERE {} = ? RETURNING {}",
-        table_name, update_sql_binds, id_column, column_list
-    );
-    let delete_by_id_sql = format!("DELETE FROM {} WHERE {} = ?", table_name, id_column);
+        #[automatically_derived]
+        impl ::std::convert::AsRef<str> for #column_ident {
+            fn as_ref(&self) -> &str {
+                #crate_name::traits::ColumnName::name(self)
+            }
+        }
+    }
+}
+
+/// Expression building the `Vec<Column>` of the struct in field order.
+fn build_columns(config: &Config) -> TokenStream2 {
+    let crate_name = &config.crate_name;
+    let columns = config.fields.iter().map(|f| {
+        if f.flatten {
+            let ty = f.ty;
+            let id = f.id;
+            quote_spanned! { ty.span()=>
+                columns.extend(
+                    <#ty as #crate_name::traits::Embedded>::columns()
+                        .into_iter()
+                        .map(|c| c.flattened(#id)),
+                );
+            }
+        } else {
+            let name = LitStr::new(&f.column, f.ident.span());
+            let id = f.id;
+            let insert = config.inserted(f);
+            let update = config.updated(f);
+            let value = match f.sql_value() {
+                Some(value) => quote! { ::std::option::Option::Some(#value) },
+                None => quote! { ::std::option::Option::None },
+            };
+            let version = f.version;
+            let soft_delete = f.soft_delete;
+            let none_as_default = f.none_as_default;
+            let find_by = f.find_by;
+            let indexed = f.indexed;
+            let tenant = f.tenant;
+            let sql_type = match f.sql_type(crate_name) {
+                Some(sql_type) => quote! { ::std::option::Option::Some(#sql_type) },
+                None => quote! { ::std::option::Option::None },
+            };
+            let nullable = is_option(f.ty);
+            let rust_type = type_name(f.ty);
+            quote! {
+                columns.push(#crate_name::schema::Column {
+                    name: #name,
+                    id: #id,
+                    insert: #insert,
+                    update: #update,
+                    value: #value,
+                    version: #version,
+                    soft_delete: #soft_delete,
+                    none_as_default: #none_as_default,
+                    find_by: #find_by,
+                    indexed: #indexed,
+                    tenant: #tenant,
+                    sql_type: #sql_type,
+                    nullable: #nullable,
+                    rust_type: #rust_type,
+                });
+            }
+        }
+    });
 
     quote! {
-        select_sql: #select_sql,
-        select_by_id_sql: #select_by_id_sql,
-        insert_sql: #insert_sql,
-        update_by_id_sql: #update_by_id_sql,
-        delete_by_id_sql: #delete_by_id_sql,
+        {
+            let mut columns = ::std::vec::Vec::new();
+            #(#columns)*
+            columns
+        }
     }
 }
 
+/// Statements appending the columns of `None` valued
+/// `#[sqlx_crud(none_as_default)]` fields to `columns`. `columns_mut` is the
+/// mutable reference passed on to embedded structs.
+fn build_defaulted_columns(config: &Config, columns_mut: &TokenStream2) -> TokenStream2 {
+    let crate_name = &config.crate_name;
+    let columns = config.fields.iter().map(|f| {
+        let ident = f.ident;
+        if f.flatten {
+            quote! {
+                #crate_name::traits::Embedded::defaulted_columns(&self.#ident, #columns_mut);
+            }
+        } else if f.none_as_default {
+            let name = LitStr::new(&f.column, f.ident.span());
+            quote! {
+                if self.#ident.is_none() {
+                    columns.push(#name);
+                }
+            }
+        } else {
+            quote! {}
+        }
+    });
+
+    quote! { #(#columns)* }
+}
+
 fn build_sqlx_crud_impl(config: &Config) -> TokenStream2 {
     let crate_name = &config.crate_name;
+    let sqlx = &config.sqlx;
     let ident = &config.ident;
-    let model_schema_ident = &config.model_schema_ident;
-    let db_ty = config.db_ty.sqlx_db();
-    let id_column_ident = &config.id_column_ident;
-
-    let id_ty = config
-        .named
+    let column_ident = config.column_ident();
+    let (impl_generics, ty_generics, where_clause) = config.generics.split_for_impl();
+    let defaulted_columns = build_defaulted_columns(config, &quote! { &mut columns });
+    let database_schema_impls = config
+        .db_tys
         .iter()
-        .find(|f| f.ident.as_ref() == Some(id_column_ident))
-        .map(|f| &f.ty)
-        .expect("the id type");
+        .map(|db_ty| build_database_schema_impl(config, db_ty));
+    let metadata = build_metadata(config);
+    // Structs for several databases bind their fields generically for the
+    // database of the executor
+    let multi_db = config.db_tys.len() > 1;
+    let db_ty = if multi_db {
+        quote! { E::Database }
+    } else {
+        config.db_tys[0].sqlx_db(sqlx)
+    };
+
+    let (id_ty, id_value, take_id, query_args_id, query_args_id_ref);
+    if let Some(f) = config.flattened_id() {
+        // The primary key columns are those of the embedded struct
+        let ty = f.ty;
+        let ident = f.ident;
+        id_ty = quote! { <#ty as #crate_name::traits::Embedded>::Id };
+        id_value = quote! { #crate_name::traits::Embedded::id(&self.#ident) };
+        // The key is read before the embedded struct is moved in to the
+        // update arguments
+        take_id = Some(quote! {
+            let id = #crate_name::traits::Embedded::id(&self.#ident);
+        });
+        query_args_id = quote! {
+            <#ty as #crate_name::traits::Embedded>::id_args(id, &mut args);
+        };
+        query_args_id_ref = query_args_id.clone();
+    } else {
+        let id_idents = config.id_fields().map(|f| f.ident).collect::<Vec<_>>();
+        let id_tys = config.id_fields().map(|f| f.ty);
+        if config.composite_id() {
+            id_ty = quote! { (#(#id_tys),*) };
+            id_value = quote! { (#(::std::clone::Clone::clone(&self.#id_idents)),*) };
+        } else {
+            id_ty = quote! { #(#id_tys)* };
+            id_value = quote! { #(::std::clone::Clone::clone(&self.#id_idents))* };
+        }
+        take_id = None;
+        let binds = config.id_fields().map(|f| f.bind_arg());
+        query_args_id = quote! { #(#binds)* };
+        let binds = config.id_fields().map(|f| f.bind_field(true));
+        query_args_id_ref = quote! { #(#binds)* };
+    }
 
     let insert_query_args = config
-        .named
-        .iter()
-        .flat_map(|f| &f.ident)
-        .filter(|i| config.external_id || *i != &config.id_column_ident)
-        .map(|i| quote! { args.add(self.#i); });
+        .insert_binds()
+        .map(|f| f.insert_arg(crate_name, &quote! { args }, false));
+    let insert_query_args_ref = config
+        .insert_binds()
+        .map(|f| f.insert_arg(crate_name, &quote! { &mut args }, true));
+    let bind_id_impl = build_bind_id_impl(config);
 
     let insert_query_size = config
-        .named
-        .iter()
-        .flat_map(|f| &f.ident)
-        .filter(|i| config.external_id || *i != &config.id_column_ident)
-        .map(|i| quote! { ::sqlx::encode::Encode::<#db_ty>::size_hint(&self.#i) });
+        .insert_binds()
+        .map(|f| f.size_hint(&db_ty))
+        .collect::<Vec<_>>();
 
     let update_query_args = config
-        .named
-        .iter()
-        .flat_map(|f| &f.ident)
-        .filter(|i| *i != &config.id_column_ident)
-        .map(|i| quote! { args.add(self.#i); });
+        .update_binds()
+        .map(|f| f.update_arg(crate_name, &quote! { &mut args }, false));
+    let update_query_args_ref = config
+        .update_binds()
+        .map(|f| f.update_arg(crate_name, &quote! { &mut args }, true));
 
-    let update_query_args_id = quote! { args.add(self.#id_column_ident); };
+    let query_args_version = config.version_field().map(|f| f.bind_arg());
+    let query_args_version_ref = config.version_field().map(|f| f.bind_field(true));
 
-    let update_query_size = config
-        .named
-        .iter()
-        .flat_map(|f| &f.ident)
-        .map(|i| quote! { ::sqlx::encode::Encode::<#db_ty>::size_hint(&self.#i) });
+    let query_args_tenant = config.tenant_field().map(|f| f.bind_arg());
+    let query_args_tenant_ref = config.tenant_field().map(|f| f.bind_field(true));
+
+    // The history INSERT of audited structs binds the WHERE clause of the
+    // write ahead of it. Version values are Copy, the key and the tenant are
+    // cloned.
+    let audit_args = config.audit.then(|| {
+        let id = match config.flattened_id() {
+            Some(f) => {
+                let ty = f.ty;
+                quote! {
+                    <#ty as #crate_name::traits::Embedded>::id_args(::std::clone::Clone::clone(&id), &mut args);
+                }
+            }
+            None => {
+                let binds = config.id_fields().map(|f| {
+                    let ident = f.ident;
+                    let value = f.bind_value(quote! { ::std::clone::Clone::clone(&self.#ident) });
+                    quote_spanned! { f.ty.span()=> args.add(#value); }
+                });
+                quote! { #(#binds)* }
+            }
+        };
+        let tenant = config.tenant_field().map(|f| {
+            let ident = f.ident;
+            quote_spanned! { f.ty.span()=> args.add(::std::clone::Clone::clone(&self.#ident)); }
+        });
+        quote! {
+            #id
+            #tenant
+            #query_args_version
+        }
+    });
+    // PostgreSQL runs the history INSERT in the statement of the write,
+    // other databases run it on its own with the arguments of audit_args.
+    let audit_args_pg = audit_args.as_ref().map(|audit_args| {
+        quote! {
+            if <#db_ty as #sqlx::Database>::NAME == "PostgreSQL" {
+                #audit_args
+            }
+        }
+    });
+    let bind_version_fn = query_args_version_ref.as_ref().map(|bind_version| {
+        quote! {
+            fn bind_version_ref(&'e self, args: &mut <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments) {
+                use #sqlx::Arguments as _;
+                #bind_version
+            }
+        }
+    });
+    let audit_args_fn = audit_args.as_ref().map(|audit_args| {
+        quote! {
+            fn audit_args(&self) -> <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments {
+                use #sqlx::Arguments as _;
+                #take_id
+                let mut args = <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments::default();
+                #audit_args
+                args
+            }
+        }
+    });
+
+    let delete_query_size = config
+        .id_fields()
+        .chain(config.tenant_field())
+        .chain(config.version_field())
+        .map(|f| f.size_hint(&db_ty));
+
+    let delete_permanently_query_size = config
+        .id_fields()
+        .chain(config.tenant_field())
+        .map(|f| f.size_hint(&db_ty));
+
+    // Only structs without a tenant may be read regardless of the tenant,
+    // the next markers gate the operations left out with only(...) or
+    // except(...). Upserts need a key assigned outside of the database and
+    // would skip the tenant scope, version check and audit history, as
+    // would the writes bounded by Unaudited.
+    let ops = &config.ops;
+    let upsertable = ops.create
+        && ops.update
+        && config.id_fields().all(|f| f.flatten || config.inserted(f))
+        && config.tenant_field().is_none()
+        && config.version_field().is_none()
+        && !config.audit;
+    let markers = [
+        ("Unscoped", config.tenant_field().is_none()),
+        ("Creatable", ops.create),
+        ("Readable", ops.read),
+        ("Updatable", ops.update),
+        ("Deletable", ops.delete),
+        ("Upsertable", upsertable),
+        ("Unaudited", !config.audit),
+    ];
+    let marker_impls = markers.iter().filter(|(_, implemented)| *implemented).map(|(marker, _)| {
+        let marker = format_ident!("{}", marker);
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics #crate_name::traits::#marker for #ident #ty_generics #where_clause {}
+        }
+    });
+
+    let create_table_marker = build_create_table_marker(config);
+
+    let update_query_size = config.fields.iter().map(|f| f.size_hint(&db_ty));
+    let update_query_size_ref = update_query_size.clone();
+
+    // The Crud impl adds the executor lifetime and type to the struct's own
+    // generic parameters
+    let mut crud_generics = config.generics.clone();
+    crud_generics.params.insert(0, parse_quote! { 'e });
+    crud_generics.params.push(parse_quote! { E });
+    let crud_where_clause = crud_generics.make_where_clause();
+    if multi_db {
+        crud_where_clause.predicates.push(parse_quote! {
+            E: 'e + #sqlx::Executor<'e>
+        });
+        crud_where_clause.predicates.push(parse_quote! {
+            Self: #crate_name::traits::DatabaseSchema<#db_ty>
+        });
+        crud_where_clause.predicates.push(parse_quote! {
+            <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments:
+                #sqlx::IntoArguments<'e, #db_ty>
+        });
+    } else {
+        crud_where_clause.predicates.push(parse_quote! {
+            E: 'e + #sqlx::Executor<'e, Database = #db_ty>
+        });
+    }
+    if multi_db || !config.generics.params.is_empty() {
+        crud_where_clause.predicates.push(parse_quote! {
+            Self: 'e
+                + ::std::marker::Send
+                + ::std::marker::Unpin
+                + for<'r> #sqlx::FromRow<'r, <#db_ty as #sqlx::Database>::Row>
+        });
+        // Fields typed by a generic parameter need to be bindable, as do all
+        // fields when bound for the executor's database
+        let field_types: Vec<_> = if multi_db {
+            config.field_types().collect()
+        } else {
+            config.generic_field_types().collect()
+        };
+        for ty in field_types {
+            crud_where_clause.predicates.push(parse_quote! {
+                #ty: 'e
+                    + ::std::marker::Send
+                    + ::std::marker::Sync
+                    + #sqlx::Encode<'e, #db_ty>
+                    + #sqlx::Type<#db_ty>
+            });
+        }
+    }
+    let (crud_impl_generics, _, crud_where_clause) = crud_generics.split_for_impl();
+    let tracing_span = build_tracing_span(config);
+    let run_hooks = config.hooks.then(|| {
+        let table_name = &config.table_name;
+        quote! {
+            fn run_hook(
+                &mut self,
+                hook: #crate_name::traits::Hook,
+            ) -> ::std::result::Result<(), #crate_name::Error> {
+                use #crate_name::traits::{CrudHooks, Hook};
+                match hook {
+                    Hook::BeforeCreate => CrudHooks::before_create(self),
+                    Hook::AfterCreate => CrudHooks::after_create(self),
+                    Hook::BeforeUpdate => CrudHooks::before_update(self),
+                    Hook::AfterUpdate => CrudHooks::after_update(self),
+                    Hook::BeforeDelete => CrudHooks::before_delete(self),
+                }
+                .map_err(|source| #crate_name::Error::Hook {
+                    table_name: #table_name,
+                    hook: hook.name(),
+                    source,
+                })
+            }
+
+            fn run_after_delete(id: &Self::Id) -> ::std::result::Result<(), #crate_name::Error> {
+                <Self as #crate_name::traits::CrudHooks>::after_delete(id).map_err(|source| {
+                    #crate_name::Error::Hook { table_name: #table_name, hook: "after_delete", source }
+                })
+            }
+        }
+    });
+    // Upserts skip the hooks, which save() runs around its write
+    let save_upserts = (upsertable && !config.hooks).then(|| {
+        quote! {
+            fn save_upserts() -> bool {
+                true
+            }
+        }
+    });
+    // Tracked structs compare the fields written by update(), which
+    // update_fields() and Tracked bind for a subset of the columns
+    let track_impl = config.tracked.then(|| {
+        let compare = config.update_binds().map(|f| {
+            let field = f.ident;
+            let variant = f.column_variant();
+            quote_spanned! { f.ty.span()=>
+                if self.#field != before.#field {
+                    columns.push(#column_ident::#variant);
+                }
+            }
+        });
+        quote! {
+            #[automatically_derived]
+            impl #crate_name::traits::Track for #ident {
+                fn changed_columns(&self, before: &Self) -> ::std::vec::Vec<#column_ident> {
+                    #[allow(unused_mut)]
+                    let mut columns = ::std::vec::Vec::new();
+                    #(#compare)*
+                    columns
+                }
+            }
+        }
+    });
+    let changed_args = config.ops.update.then(|| {
+        let table_name = &config.table_name;
+        // The columns of flattened fields are bound by the embedded struct
+        // as a whole and cannot be written on their own, nor can fields of a
+        // generic type. Columns are matched by name, the column enum of a
+        // generic struct not being known to be its Schema::Column
+        let binds = config.update_binds().map(|f| {
+            let field = f.ident;
+            if f.flatten || config.is_generic(f) {
+                let matches = if f.flatten {
+                    let ty = f.ty;
+                    quote! {
+                        <<#ty as #crate_name::traits::Embedded>::Column as #crate_name::traits::ColumnName>::ALL
+                            .iter()
+                            .any(|e| #crate_name::traits::ColumnName::name(e) == #crate_name::traits::ColumnName::name(c))
+                    }
+                } else {
+                    let column = &f.column;
+                    quote! { #crate_name::traits::ColumnName::name(c) == #column }
+                };
+                quote_spanned! { f.ty.span()=>
+                    if let ::std::option::Option::Some(column) = columns.iter().find(|&c| #matches) {
+                        return ::std::result::Result::Err(#crate_name::Error::ColumnNotUpdatable {
+                            table_name: #table_name,
+                            column: #crate_name::traits::ColumnName::name(column),
+                        });
+                    }
+                }
+            } else {
+                let column = &f.column;
+                let value = f.bind_ref(quote! { self.#field });
+                quote_spanned! { f.ty.span()=>
+                    if columns.iter().any(|c| #crate_name::traits::ColumnName::name(c) == #column) {
+                        args.add(#value);
+                    }
+                }
+            }
+        });
+        let bind_tenant = config.tenant_field().map(|f| {
+            let ident = f.ident;
+            let value = f.bind_ref(quote! { self.#ident });
+            quote_spanned! { f.ty.span()=> args.add(#value); }
+        });
+        quote! {
+            fn changed_args(
+                &'e self,
+                columns: &[<Self as #crate_name::traits::Schema>::Column],
+            ) -> ::std::result::Result<<#db_ty as #sqlx::database::HasArguments<'e>>::Arguments, #crate_name::Error>
+            where
+                Self: #crate_name::traits::Updatable,
+            {
+                use #sqlx::Arguments as _;
+                let mut args = <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments::default();
+                #(#binds)*
+                #take_id
+                #query_args_id_ref
+                #bind_tenant
+                ::std::result::Result::Ok(args)
+            }
+        }
+    });
+    let validate_write = config.validate.then(|| {
+        let table_name = &config.table_name;
+        quote! {
+            fn validate_write(&self) -> ::std::result::Result<(), #crate_name::Error> {
+                #crate_name::traits::Validate::validate(self).map_err(|source| {
+                    #crate_name::Error::Validation { table_name: #table_name, source }
+                })
+            }
+        }
+    });
 
     quote! {
         #[automatically_derived]
-        impl #crate_name::traits::Schema for #ident {
+        impl #impl_generics #crate_name::traits::Schema for #ident #ty_generics #where_clause {
             type Id = #id_ty;
+            type Column = #column_ident;
 
-            fn table_name() -> &'static str {
-                #model_schema_ident.table_name
-            }
+            #metadata
 
             fn id(&self) -> Self::Id {
-                self.#id_column_ident
+                #id_value
             }
 
-            fn id_column() -> &'static str {
-                #model_schema_ident.id_column
+            fn defaulted_columns(&self) -> ::std::vec::Vec<&'static str> {
+                #[allow(unused_mut)]
+                let mut columns = ::std::vec::Vec::new();
+                #defaulted_columns
+                columns
             }
 
-            fn columns() -> &'static [&'static str] {
-                &#model_schema_ident.columns
-            }
+            #validate_write
+
+            #run_hooks
 
-            fn select_sql() -> &'static str {
-                #model_schema_ident.select_sql
+            #save_upserts
+
+            #tracing_span
+        }
+
+        #(#database_schema_impls)*
+
+        #bind_id_impl
+
+        #(#marker_impls)*
+        #create_table_marker
+
+        #track_impl
+
+        #[automatically_derived]
+        impl #crud_impl_generics #crate_name::traits::Crud<'e, E> for #ident #ty_generics
+        #crud_where_clause
+        {
+            fn insert_args(self) -> <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments {
+                use #sqlx::Arguments as _;
+                let mut args = <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments::default();
+                args.reserve(1usize, 0usize #(+ #insert_query_size)*);
+                <Self as #crate_name::traits::Crud<'e, E>>::bind_insert_args(self, &mut args);
+                args
             }
 
-            fn select_by_id_sql() -> &'static str {
-                #model_schema_ident.select_by_id_sql
+            fn bind_insert_args(self, args: &mut <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments) {
+                use #sqlx::Arguments as _;
+                #(#insert_query_args)*
             }
 
-            fn insert_sql() -> &'static str {
-                #model_schema_ident.insert_sql
+            fn update_args(self) -> <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments {
+                use #sqlx::Arguments as _;
+                #take_id
+                let mut args = <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments::default();
+                args.reserve(1usize, 0usize #(+ #update_query_size)*);
+                #audit_args_pg
+                #(#update_query_args)*
+                #query_args_id
+                #query_args_tenant
+                #query_args_version
+                args
             }
 
-            fn update_by_id_sql() -> &'static str {
-                #model_schema_ident.update_by_id_sql
+            fn insert_args_ref(&'e self) -> <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments {
+                use #sqlx::Arguments as _;
+                let mut args = <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments::default();
+                args.reserve(1usize, 0usize #(+ #insert_query_size)*);
+                #(#insert_query_args_ref)*
+                args
             }
 
-            fn delete_by_id_sql() -> &'static str {
-                #model_schema_ident.delete_by_id_sql
+            fn update_args_ref(&'e self) -> <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments {
+                use #sqlx::Arguments as _;
+                #take_id
+                let mut args = <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments::default();
+                args.reserve(1usize, 0usize #(+ #update_query_size_ref)*);
+                #audit_args_pg
+                #(#update_query_args_ref)*
+                #query_args_id_ref
+                #query_args_tenant_ref
+                #query_args_version_ref
+                args
             }
-        }
 
-        #[automatically_derived]
-        impl<'e, E> #crate_name::traits::Crud<'e, E> for #ident
-        where
-            E: 'e + ::sqlx::Executor<'e, Database = #db_ty>,
-        {
-            fn insert_args(self) -> <#db_ty as ::sqlx::database::HasArguments<'e>>::Arguments {
-                use ::sqlx::Arguments as _;
-                let mut args = <#db_ty as ::sqlx::database::HasArguments<'e>>::Arguments::default();
-                args.reserve(1usize, #(#insert_query_size)+*);
-                #(#insert_query_args)*
+            fn delete_args(self) -> <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments {
+                use #sqlx::Arguments as _;
+                #take_id
+                let mut args = <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments::default();
+                args.reserve(1usize, 0usize #(+ #delete_query_size)*);
+                #audit_args_pg
+                #query_args_id
+                #query_args_tenant
+                #query_args_version
                 args
             }
 
-            fn update_args(self) -> <#db_ty as ::sqlx::database::HasArguments<'e>>::Arguments {
-                use ::sqlx::Arguments as _;
-                let mut args = <#db_ty as ::sqlx::database::HasArguments<'e>>::Arguments::default();
-                args.reserve(1usize, #(#update_query_size)+*);
-                #(#update_query_args)*
-                #update_query_args_id
+            #audit_args_fn
+
+            #bind_version_fn
+
+            fn delete_permanently_args(self) -> <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments {
+                use #sqlx::Arguments as _;
+                #take_id
+                let mut args = <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments::default();
+                args.reserve(1usize, 0usize #(+ #delete_permanently_query_size)*);
+                #query_args_id
+                #query_args_tenant
                 args
             }
+
+            #changed_args
         }
     }
 }
 
-#[allow(dead_code)] // Usage in quote macros aren't flagged as used
-struct Config<'a> {
-    ident: &'a Ident,
-    named: &'a Punctuated<Field, Comma>,
-    crate_name: TokenStream2,
-    db_ty: DbType,
-    model_schema_ident: Ident,
-    table_name: String,
-    id_column_ident: Ident,
-    external_id: bool,
-}
+/// Spans named `sqlx_crud.<table>.<operation>` for the Crud methods, recording
+/// the table and the id when its type implements `Debug`. Only emitted with
+/// the `tracing` feature of the runtime crate.
+fn build_tracing_span(config: &Config) -> TokenStream2 {
+    let crate_name = &config.crate_name;
+    let table_name = &config.table_name;
+    let spans = [
+        (quote! { Create }, "create"),
+        (quote! { CreateMany }, "create_many"),
+        (quote! { CreateManyReturning }, "create_many_returning"),
+        (quote! { CloneRow }, "clone_row"),
+        (quote! { CreateIgnore }, "create_ignore"),
+        (quote! { Upsert }, "upsert"),
+        (quote! { ById }, "by_id"),
+        (quote! { ByIdForUpdate }, "by_id_for_update"),
+        (quote! { Exists }, "exists"),
+        (quote! { Update }, "update"),
+        (quote! { UpdateWhere }, "update_where"),
+        (quote! { Delete }, "delete"),
+        (quote! { DeleteMany }, "delete_many"),
+        (quote! { DeleteWhere }, "delete_where"),
+        (quote! { DeleteAll }, "delete_all"),
+        (quote! { Truncate }, "truncate"),
+    ]
+    .into_iter()
+    .map(|(variant, op)| {
+        let name = format!("sqlx_crud.{}.{}", table_name, op);
+        quote! {
+            #crate_name::instrument::Operation::#variant => #crate_name::tracing::info_span!(
+                #name,
+                table = #table_name,
+                id = #crate_name::tracing::field::Empty,
+            )
+        }
+    });
 
-impl<'a> Config<'a> {
-    fn new(attrs: &[Attribute], ident: &'a Ident, named: &'a Punctuated<Field, Comma>) -> Self {
-        let crate_name = std::env::var("CARGO_PKG_NAME").unwrap();
-        let is_doctest = std::env::vars()
-            .any(|(k, _)| k == "UNSTABLE_RUSTDOC_TEST_LINE" || k == "UNSTABLE_RUSTDOC_TEST_PATH");
-        let crate_name = if !is_doctest && crate_name == "sqlx-crud" {
-            quote! { crate }
-        } else {
-            quote! { ::sqlx_crud }
-        };
+    quote! {
+        #crate_name::__with_tracing! {
+            fn tracing_span(
+                operation: #crate_name::instrument::Operation,
+                id: ::std::option::Option<&dyn ::std::fmt::Debug>,
+            ) -> #crate_name::tracing::Span {
+                let span = match operation {
+                    #(#spans,)*
+                };
+                if let ::std::option::Option::Some(id) = id {
+                    use #crate_name::instrument::{IdField, RecordDebugId as _, RecordNoId as _};
+                    (&&IdField(id)).record_id(&span);
+                }
+                span
+            }
+        }
+    }
+}
 
-        let db_ty = DbType::new(attrs);
+/// Inherent finders and the queries they run. `#[sqlx_crud(find_by)]` fields
+/// get `by_<field>()` and `select_by_<field>_sql()`, `#[sqlx_crud(indexed)]`
+/// fields get `all_by_<field>()`, `stream_by_<field>()` and
+/// `select_all_by_<field>_sql()`. Structs with a `#[sqlx_crud(tenant)]` field
+/// get `all_for_tenant()` and `by_id_for_tenant()` and their finders take the
+/// tenant before the value. Structs without the `read` operation get none.
+fn build_finders_impl(config: &Config) -> TokenStream2 {
+    let tenant_field = config.tenant_field();
+    if !config.ops.read
+        || tenant_field.is_none() && !config.fields.iter().any(|f| f.find_by || f.indexed)
+    {
+        return quote! {};
+    }
 
-        let model_schema_ident =
-            format_ident!("{}_SCHEMA", ident.to_string().to_screaming_snake_case());
+    let crate_name = &config.crate_name;
+    let sqlx = &config.sqlx;
+    let ident = &config.ident;
+    let (impl_generics, ty_generics, where_clause) = config.generics.split_for_impl();
+    let multi_db = config.db_tys.len() > 1;
+    let (db_ty, executor_bounds) = if multi_db {
+        let db_ty = quote! { E::Database };
+        let bounds = quote! {
+            E: 'e + #sqlx::Executor<'e>,
+            Self: #crate_name::traits::DatabaseSchema<#db_ty>,
+            <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments:
+                #sqlx::IntoArguments<'e, #db_ty>,
+        };
+        (db_ty, bounds)
+    } else {
+        let db_ty = config.db_tys[0].sqlx_db(sqlx);
+        let bounds = quote! { E: 'e + #sqlx::Executor<'e, Database = #db_ty>, };
+        (db_ty, bounds)
+    };
+    // The tenant is bound after the value looked up
+    let (tenant_param, tenant_bound, tenant_arg) = match tenant_field {
+        Some(f) => {
+            let ty = f.ty;
+            (
+                quote! { tenant: #ty, },
+                quote! { #ty: 'e + ::std::marker::Send + #sqlx::Encode<'e, #db_ty> + #sqlx::Type<#db_ty>, },
+                quote! { args.add(tenant); },
+            )
+        }
+        None => (quote! {}, quote! {}, quote! {}),
+    };
+    let row_bounds = quote! {
+        Self: 'e
+            + ::std::marker::Send
+            + ::std::marker::Unpin
+            + for<'r> #sqlx::FromRow<'r, <#db_ty as #sqlx::Database>::Row>,
+    };
 
-        let table_name = ident.to_string().to_table_case();
+    let tenant_finders = tenant_field.map(|f| {
+        let all_for_tenant = config.method("all_for_tenant", f.ident.span());
+        let by_id_for_tenant = config.method("by_id_for_tenant", f.ident.span());
+        quote! {
+            /// Queries the rows of a tenant, in the order of `Crud::all`, and
+            /// returns a future that resolves to a `Vec<Self>`.
+            pub fn #all_for_tenant<'e, E>(pool: E, #tenant_param) -> #crate_name::traits::CrudFut<'e, ::std::vec::Vec<Self>>
+            where
+                #executor_bounds
+                #tenant_bound
+                #row_bounds
+            {
+                use #sqlx::Arguments as _;
+                let sql = &<Self as #crate_name::traits::DatabaseSchema<#db_ty>>::database_metadata().select_all_sql;
+                let mut args = <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments::default();
+                #tenant_arg
+                ::std::boxed::Box::pin(async move {
+                    #sqlx::query_as_with::<#db_ty, Self, _>(sql, args)
+                        .fetch_all(pool)
+                        .await
+                        .map_err(#crate_name::Error::from)
+                })
+            }
 
-        // Search for a field with the #[id] attribute
-        let id_attr = &named
-            .iter()
-            .find(|f| f.attrs.iter().any(|a| a.path().is_ident("id")))
-            .and_then(|f| f.ident.as_ref());
-        // Otherwise default to the first field as the "id" column
-        let id_column_ident = id_attr
-            .unwrap_or_else(|| {
-                named
-                    .iter()
-                    .flat_map(|f| &f.ident)
-                    .next()
-                    .expect("the first field")
-            })
-            .clone();
+            /// Looks up a row of a tenant by primary key and returns a future
+            /// that resolves to `Some` if it exists.
+            pub fn #by_id_for_tenant<'e, E>(
+                pool: E,
+                #tenant_param
+                id: <Self as #crate_name::traits::Schema>::Id,
+            ) -> #crate_name::traits::CrudFut<'e, ::std::option::Option<Self>>
+            where
+                #executor_bounds
+                #tenant_bound
+                Self: #crate_name::traits::Crud<'e, E>,
+            {
+                use #sqlx::Arguments as _;
+                let sql = &<Self as #crate_name::traits::DatabaseSchema<#db_ty>>::database_metadata()
+                    .select_by_id_sql;
+                let mut args = <Self as #crate_name::traits::Crud<'e, E>>::id_args(id);
+                #tenant_arg
+                ::std::boxed::Box::pin(async move {
+                    #sqlx::query_as_with::<#db_ty, Self, _>(sql, args)
+                        .fetch_optional(pool)
+                        .await
+                        .map_err(#crate_name::Error::from)
+                })
+            }
+        }
+    });
 
-        let external_id = attrs.iter().any(|a| a.path().is_ident("external_id"));
+    let finders = config.fields.iter().filter(|f| f.find_by).map(|f| {
+        let name = f.ident.unraw();
+        let column = LitStr::new(&f.column, f.ident.span());
+        let sql_fn = config.method(&format!("select_by_{}_sql", name), f.ident.span());
+        let finder = config.method(&format!("by_{}", name), f.ident.span());
+        let sql_doc = format!(
+            "Returns the query looking up a row by the unique `{}` column.",
+            f.column
+        );
+        let finder_doc = format!(
+            "Looks up a row by the unique `{}` column and returns a future that resolves to `Some` if it exists.",
+            f.column
+        );
+        quote! {
+            #[doc = #sql_doc]
+            pub fn #sql_fn() -> &'static str {
+                &<Self as #crate_name::traits::Schema>::metadata().select_by_sql[#column]
+            }
 
-        Self {
-            ident,
-            named,
-            crate_name,
-            db_ty,
-            model_schema_ident,
-            table_name,
-            id_column_ident,
-            external_id,
+            #[doc = #finder_doc]
+            pub fn #finder<'e, E, V>(pool: E, #tenant_param value: V) -> #crate_name::traits::CrudFut<'e, ::std::option::Option<Self>>
+            where
+                #executor_bounds
+                #tenant_bound
+                V: 'e + ::std::marker::Send + #sqlx::Encode<'e, #db_ty> + #sqlx::Type<#db_ty>,
+                #row_bounds
+            {
+                use #sqlx::Arguments as _;
+                let sql = &<Self as #crate_name::traits::DatabaseSchema<#db_ty>>::database_metadata()
+                    .select_by_sql[#column];
+                let mut args = <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments::default();
+                args.add(value);
+                #tenant_arg
+                ::std::boxed::Box::pin(async move {
+                    #sqlx::query_as_with::<#db_ty, Self, _>(sql, args)
+                        .fetch_optional(pool)
+                        .await
+                        .map_err(#crate_name::Error::from)
+                })
+            }
         }
-    }
+    });
 
-    fn quote_ident(&self, ident: &str) -> String {
-        self.db_ty.quote_ident(ident)
-    }
-}
+    let order = if config.order_by.is_empty() {
+        "primary key"
+    } else {
+        "`Schema::order_by_sql()`"
+    };
+    let collection_finders = config.fields.iter().filter(|f| f.indexed).map(|f| {
+        let name = f.ident.unraw();
+        let column = LitStr::new(&f.column, f.ident.span());
+        let sql_fn = config.method(&format!("select_all_by_{}_sql", name), f.ident.span());
+        let all_by = config.method(&format!("all_by_{}", name), f.ident.span());
+        let stream_by = config.method(&format!("stream_by_{}", name), f.ident.span());
+        let sql_doc = format!(
+            "Returns the query selecting the rows with a given `{}`, ordered by {}.",
+            f.column, order
+        );
+        let all_by_doc = format!(
+            "Queries the rows with a given `{}`, ordered by {}, and returns a future that resolves to a `Vec<Self>`.",
+            f.column, order
+        );
+        let stream_by_doc = format!(
+            "Streams the rows with a given `{}`, ordered by {}.",
+            f.column, order
+        );
+        let bounds = quote! {
+            #executor_bounds
+            #tenant_bound
+            V: 'e + ::std::marker::Send + #sqlx::Encode<'e, #db_ty> + #sqlx::Type<#db_ty>,
+            #row_bounds
+        };
+        let query = quote! {
+            use #sqlx::Arguments as _;
+            let sql = &<Self as #crate_name::traits::DatabaseSchema<#db_ty>>::database_metadata()
+                .select_all_by_sql[#column];
+            let mut args = <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments::default();
+            args.add(value);
+            #tenant_arg
+            let query = #sqlx::query_as_with::<#db_ty, Self, _>(sql, args);
+        };
+        quote! {
+            #[doc = #sql_doc]
+            pub fn #sql_fn() -> &'static str {
+                &<Self as #crate_name::traits::Schema>::metadata().select_all_by_sql[#column]
+            }
 
-enum DbType {
-    Any,
-    Mssql,
-    MySql,
-    Postgres,
-    Sqlite,
-}
+            #[doc = #all_by_doc]
+            pub fn #all_by<'e, E, V>(pool: E, #tenant_param value: V) -> #crate_name::traits::CrudFut<'e, ::std::vec::Vec<Self>>
+            where
+                #bounds
+            {
+                #query
+                ::std::boxed::Box::pin(async move {
+                    query.fetch_all(pool).await.map_err(#crate_name::Error::from)
+                })
+            }
 
-impl From<&str> for DbType {
-    fn from(db_type: &str) -> Self {
-        match db_type {
-            "Any" => Self::Any,
-            "Mssql" => Self::Mssql,
-            "MySql" => Self::MySql,
-            "Postgres" => Self::Postgres,
-            "Sqlite" => Self::Sqlite,
-            _ => panic!("unknown #[database] type {}", db_type),
+            #[doc = #stream_by_doc]
+            pub fn #stream_by<'e, E, V>(pool: E, #tenant_param value: V) -> #crate_name::traits::CrudStream<'e, Self>
+            where
+                #bounds
+            {
+                #query
+                #crate_name::traits::crud_stream(query.fetch(pool))
+            }
+        }
+    });
+
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #tenant_finders
+            #(#finders)*
+            #(#collection_finders)*
         }
     }
 }
 
-impl DbType {
-    fn new(attrs: &[Attribute]) -> Self {
-        let mut db_type = None;
-        attrs
-            .iter()
-            .find(|a| a.path().is_ident("database"))
-            .map(|a| {
-                a.parse_nested_meta(|m| {
-                    if let Some(path) = m.path.get_ident() {
-                        db_type = Some(DbType::from(path.to_string().as_str()));
-                    }
-                    Ok(())
-                })
-            });
-
-        db_type.expect("The `database` attribute is required. Available database types are `Any`, `Mssql`, `MySql`, `Postgres`, `Sqlite`.")
+/// Inherent accessors loading related rows. A `#[sqlx_crud(belongs_to = "...")]`
+/// field `<relation>_id` gets `<relation>()`, looking up the referenced row
+/// with its `Crud::by_id()`. Each `#[sqlx_crud(has_many(...))]` relation gets
+/// an accessor querying the rows referencing the struct. Each
+/// `#[sqlx_crud(many_to_many(...))]` relation gets an accessor querying the
+/// joined rows, `add_<other>()` and `remove_<other>()`.
+fn build_relations_impl(config: &Config) -> TokenStream2 {
+    if config.has_many.is_empty()
+        && config.many_to_many.is_empty()
+        && !config.fields.iter().any(|f| f.belongs_to.is_some())
+    {
+        return quote! {};
     }
 
-    fn sqlx_db(&self) -> TokenStream2 {
+    let crate_name = &config.crate_name;
+    let sqlx = &config.sqlx;
+    let ident = &config.ident;
+    let (impl_generics, ty_generics, where_clause) = config.generics.split_for_impl();
+
+    let belongs_to = config.fields.iter().filter_map(|f| {
+        let parent = f.belongs_to.as_ref()?;
+        let field = f.ident;
+        let name = field.unraw().to_string();
+        let accessor = config.method(name.trim_end_matches("_id"), field.span());
+        // A nullable key references no row when it is `None`
+        let (id_ty, id) = match option_inner(f.ty) {
+            Some(inner) => (inner, quote! { ::std::clone::Clone::clone(&self.#field) }),
+            None => (f.ty, quote! { ::std::option::Option::Some(::std::clone::Clone::clone(&self.#field)) }),
+        };
+        let doc = format!(
+            "Looks up the `{}` referenced by `{}` and returns a future that resolves to `Some` if it exists.",
+            parent.to_token_stream().to_string().replace(' ', ""),
+            f.column
+        );
+        Some(quote_spanned! { parent.span()=>
+            #[doc = #doc]
+            pub fn #accessor<'e, E>(
+                &self,
+                pool: E,
+            ) -> #crate_name::traits::CrudFut<'e, ::std::option::Option<#parent>>
+            where
+                E: 'e + #sqlx::Executor<'e>,
+                #parent: #crate_name::traits::Crud<'e, E>
+                    + #crate_name::traits::Schema<Id = #id_ty>
+                    + #crate_name::traits::Unscoped
+                    + #crate_name::traits::Readable,
+                <E::Database as #sqlx::database::HasArguments<'e>>::Arguments:
+                    #sqlx::IntoArguments<'e, E::Database>,
+            {
+                match #id {
+                    ::std::option::Option::Some(id) => {
+                        <#parent as #crate_name::traits::Crud<'e, E>>::by_id(pool, id)
+                    }
+                    ::std::option::Option::None => {
+                        ::std::boxed::Box::pin(::std::future::ready(::std::result::Result::Ok(
+                            ::std::option::Option::None,
+                        )))
+                    }
+                }
+            }
+        })
+    });
+
+    let has_many = config.has_many.iter().map(|relation| {
+        let child = &relation.child;
+        let foreign_key = &relation.foreign_key;
+        let accessor = config.method(&relation.method.to_string(), relation.method.span());
+        let all_with = config.method(&format!("all_with_{}", relation.method), relation.method.span());
+        let order_by = relation
+            .order_by
+            .iter()
+            .map(|(column, descending)| {
+                quote! {
+                    #crate_name::schema::OrderBy { column: #column, descending: #descending }
+                }
+            })
+            .collect::<Vec<_>>();
+        let child_name = child.to_token_stream().to_string().replace(' ', "");
+        let doc = format!(
+            "Queries the `{}` rows whose `{}` column holds the primary key of this row and returns a future that resolves to a `Vec`.",
+            child_name,
+            foreign_key.value()
+        );
+        let all_with_doc = format!(
+            "Queries every row along with its `{}` rows in one query and returns a future that resolves to a `Vec` of pairs. Rows without `{}` rows are paired with an empty `Vec`.",
+            child_name, child_name
+        );
+        let row = quote! { #crate_name::schema::TableRow<<E::Database as #sqlx::Database>::Row> };
+        quote_spanned! { child.span()=>
+            #[doc = #doc]
+            pub fn #accessor<'e, E>(
+                &self,
+                pool: E,
+            ) -> #crate_name::traits::CrudFut<'e, ::std::vec::Vec<#child>>
+            where
+                E: 'e + #sqlx::Executor<'e>,
+                #child: #crate_name::traits::Crud<'e, E>
+                    + #crate_name::traits::Unscoped
+                    + #crate_name::traits::Readable,
+                <Self as #crate_name::traits::Schema>::Id: 'e
+                    + #sqlx::Encode<'e, E::Database>
+                    + #sqlx::Type<E::Database>,
+                <E::Database as #sqlx::database::HasArguments<'e>>::Arguments:
+                    #sqlx::IntoArguments<'e, E::Database>,
+            {
+                use #sqlx::Arguments as _;
+                let sql = <#child as #crate_name::traits::DatabaseSchema<E::Database>>::database_metadata()
+                    .select_related_sql(#foreign_key, &[#(#order_by),*]);
+                let mut args = <E::Database as #sqlx::database::HasArguments<'e>>::Arguments::default();
+                args.add(<Self as #crate_name::traits::Schema>::id(self));
+                ::std::boxed::Box::pin(async move {
+                    #sqlx::query_as_with::<E::Database, #child, _>(sql, args)
+                        .fetch_all(pool)
+                        .await
+                        .map_err(#crate_name::Error::from)
+                })
+            }
+
+            #[doc = #all_with_doc]
+            pub fn #all_with<'e, E>(
+                pool: E,
+            ) -> #crate_name::traits::CrudFut<'e, ::std::vec::Vec<(Self, ::std::vec::Vec<#child>)>>
+            where
+                E: 'e + #sqlx::Executor<'e>,
+                Self: #crate_name::traits::Crud<'e, E>
+                    + #crate_name::traits::Unscoped
+                    + #crate_name::traits::Readable
+                    + for<'r> #sqlx::FromRow<'r, #row>,
+                #child: #crate_name::traits::Crud<'e, E>
+                    + #crate_name::traits::Unscoped
+                    + #crate_name::traits::Readable
+                    + for<'r> #sqlx::FromRow<'r, #row>,
+                <Self as #crate_name::traits::Schema>::Id: ::std::cmp::PartialEq,
+                usize: #sqlx::ColumnIndex<<E::Database as #sqlx::Database>::Row>,
+                <E::Database as #sqlx::database::HasArguments<'e>>::Arguments:
+                    #sqlx::IntoArguments<'e, E::Database>,
+            {
+                #crate_name::traits::fetch_with_related::<Self, #child, E>(pool, #foreign_key, &[#(#order_by),*])
+            }
+        }
+    });
+
+    let many_to_many = config.many_to_many.iter().map(|relation| {
+        let child = &relation.child;
+        let child_name = child.to_token_stream().to_string().replace(' ', "");
+        let accessor = config.method(&relation.method.to_string(), relation.method.span());
+        let add = config.method(&format!("add_{}", relation.singular), child.span());
+        let remove = config.method(&format!("remove_{}", relation.singular), child.span());
+        let (through, fk, other_fk) = (&relation.through, &relation.fk, &relation.other_fk);
+        let join = quote! {
+            #crate_name::schema::JoinTable { table: #through, foreign_key: #fk, other_key: #other_fk }
+        };
+        let accessor_doc = format!(
+            "Queries the `{}` rows joined to this row through `{}` and returns a future that resolves to a `Vec`.",
+            child_name,
+            through.value()
+        );
+        let add_doc = format!("Joins a `{}` row to this row by inserting a row of `{}`.", child_name, through.value());
+        let remove_doc = format!("Removes the `{}` row joining a `{}` row to this row.", through.value(), child_name);
+        let bounds = quote! {
+            E: 'e + #sqlx::Executor<'e>,
+            Self: #crate_name::traits::DatabaseSchema<E::Database>,
+            #child: #crate_name::traits::Crud<'e, E>
+                + #crate_name::traits::Unscoped
+                + #crate_name::traits::Readable,
+            <Self as #crate_name::traits::Schema>::Id: 'e
+                + #sqlx::Encode<'e, E::Database>
+                + #sqlx::Type<E::Database>,
+            <#child as #crate_name::traits::Schema>::Id: 'e
+                + #sqlx::Encode<'e, E::Database>
+                + #sqlx::Type<E::Database>,
+            <E::Database as #sqlx::database::HasArguments<'e>>::Arguments:
+                #sqlx::IntoArguments<'e, E::Database>,
+        };
+        let execute = |sql_fn: Ident| {
+            quote! {
+                use #sqlx::Arguments as _;
+                let sql = <Self as #crate_name::traits::DatabaseSchema<E::Database>>::database_metadata()
+                    .#sql_fn(#join);
+                let mut args = <E::Database as #sqlx::database::HasArguments<'e>>::Arguments::default();
+                args.add(<Self as #crate_name::traits::Schema>::id(self));
+                args.add(<#child as #crate_name::traits::Schema>::id(other));
+                ::std::boxed::Box::pin(async move {
+                    #sqlx::query_with::<E::Database, _>(sql, args)
+                        .execute(pool)
+                        .await
+                        .map(|_| ())
+                        .map_err(#crate_name::Error::from)
+                })
+            }
+        };
+        let insert = execute(format_ident!("insert_through_sql"));
+        let delete = execute(format_ident!("delete_through_sql"));
+        quote_spanned! { child.span()=>
+            #[doc = #accessor_doc]
+            pub fn #accessor<'e, E>(
+                &self,
+                pool: E,
+            ) -> #crate_name::traits::CrudFut<'e, ::std::vec::Vec<#child>>
+            where
+                #bounds
+            {
+                use #sqlx::Arguments as _;
+                let sql = <#child as #crate_name::traits::DatabaseSchema<E::Database>>::database_metadata()
+                    .select_through_sql(#join);
+                let mut args = <E::Database as #sqlx::database::HasArguments<'e>>::Arguments::default();
+                args.add(<Self as #crate_name::traits::Schema>::id(self));
+                ::std::boxed::Box::pin(async move {
+                    #sqlx::query_as_with::<E::Database, #child, _>(sql, args)
+                        .fetch_all(pool)
+                        .await
+                        .map_err(#crate_name::Error::from)
+                })
+            }
+
+            #[doc = #add_doc]
+            pub fn #add<'e, E>(&self, pool: E, other: &#child) -> #crate_name::traits::CrudFut<'e, ()>
+            where
+                #bounds
+            {
+                #insert
+            }
+
+            #[doc = #remove_doc]
+            pub fn #remove<'e, E>(&self, pool: E, other: &#child) -> #crate_name::traits::CrudFut<'e, ()>
+            where
+                #bounds
+            {
+                #delete
+            }
+        }
+    });
+
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #(#belongs_to)*
+            #(#has_many)*
+            #(#many_to_many)*
+        }
+    }
+}
+
+/// The `BindId` impl binding the keys accepted by `IntoId` as they are, for
+/// any database encoding them. A composite key takes a tuple with a key per
+/// column. Embedded keys are converted and bound by `Embedded::id_args`.
+fn build_bind_id_impl(config: &Config) -> TokenStream2 {
+    let crate_name = &config.crate_name;
+    let sqlx = &config.sqlx;
+    let ident = &config.ident;
+    let (_, ty_generics, _) = config.generics.split_for_impl();
+    let mut generics = config.generics.clone();
+    generics.params.insert(0, parse_quote! { 'q });
+    let (db_ty, key_ty, bind) = if let Some(f) = config.flattened_id() {
+        let ty = f.ty;
+        generics.params.push(parse_quote! { K });
+        let where_clause = generics.make_where_clause();
+        where_clause.predicates.push(parse_quote! {
+            K: #crate_name::traits::IntoId<<#ty as #crate_name::traits::Embedded>::Id>
+        });
+        where_clause.predicates.push(parse_quote! { #ty: 'q });
+        let bind = quote! {
+            <#ty as #crate_name::traits::Embedded>::id_args(#crate_name::traits::IntoId::into_id(key), args);
+        };
+        (
+            quote! { <#ty as #crate_name::traits::Embedded>::Database },
+            quote! { K },
+            bind,
+        )
+    } else {
+        generics.params.push(parse_quote! { DB: #sqlx::Database });
+        let keys = config
+            .id_fields()
+            .enumerate()
+            .map(|(i, f)| (format_ident!("K{}", i), f.ty));
+        let keys = keys.collect::<Vec<_>>();
+        for (key, ty) in &keys {
+            generics.params.push(parse_quote! { #key });
+            generics.make_where_clause().predicates.push(parse_quote! {
+                #key: #crate_name::traits::IntoId<#ty>
+                    + 'q
+                    + ::std::marker::Send
+                    + #sqlx::Encode<'q, DB>
+                    + #sqlx::Type<DB>
+            });
+        }
+        let key_idents = keys.iter().map(|(key, _)| key).collect::<Vec<_>>();
+        if config.composite_id() {
+            let values = config.id_fields().map(|f| f.ident).collect::<Vec<_>>();
+            let bind = quote! {
+                use #sqlx::Arguments as _;
+                let (#(#values),*) = key;
+                #(args.add(#values);)*
+            };
+            (quote! { DB }, quote! { (#(#key_idents),*) }, bind)
+        } else {
+            let bind = quote! {
+                use #sqlx::Arguments as _;
+                args.add(key);
+            };
+            (quote! { DB }, quote! { #(#key_idents)* }, bind)
+        }
+    };
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics #crate_name::traits::BindId<'q, #db_ty, #key_ty> for #ident #ty_generics #where_clause {
+            fn bind_key(key: #key_ty, args: &mut <#db_ty as #sqlx::database::HasArguments<'q>>::Arguments) {
+                #bind
+            }
+        }
+    }
+}
+
+/// Statements binding the primary key `id` to `args`, as the WHERE clause
+/// of the by id queries expects it. `args_mut` is the mutable reference
+/// passed on to embedded keys.
+fn build_bind_id(config: &Config, args_mut: &TokenStream2) -> TokenStream2 {
+    let crate_name = &config.crate_name;
+    if let Some(f) = config.flattened_id() {
+        let ty = f.ty;
+        quote! { <#ty as #crate_name::traits::Embedded>::id_args(id, #args_mut); }
+    } else {
+        let id_idents = config.id_fields().map(|f| f.ident).collect::<Vec<_>>();
+        if config.composite_id() {
+            quote! {
+                let (#(#id_idents),*) = id;
+                #(args.add(#id_idents);)*
+            }
+        } else {
+            quote! { args.add(id); }
+        }
+    }
+}
+
+/// The `<Struct>Patch` struct of `#[sqlx_crud(patch)]`, holding an `Option`
+/// of each field bound by the UPDATE statement, and `patch()` writing the
+/// `Some` fields of a patch to a row.
+fn build_patch_impl(config: &Config, vis: &syn::Visibility) -> TokenStream2 {
+    let Some(derives) = &config.patch else {
+        return quote! {};
+    };
+
+    let crate_name = &config.crate_name;
+    let sqlx = &config.sqlx;
+    let ident = &config.ident;
+    let patch_ident = format_ident!("{}Patch", ident.unraw());
+    let multi_db = config.db_tys.len() > 1;
+    let db_ty = if multi_db {
+        quote! { E::Database }
+    } else {
+        config.db_tys[0].sqlx_db(sqlx)
+    };
+    let fields = config
+        .update_binds()
+        .filter(|f| !f.flatten)
+        .collect::<Vec<_>>();
+    let field_idents = fields.iter().map(|f| f.ident).collect::<Vec<_>>();
+    let field_tys = fields.iter().map(|f| f.ty);
+    let binds = fields.iter().map(|f| {
+        let field = f.ident;
+        let column = &f.column;
+        let value = f.bind_value(quote! { value });
+        quote_spanned! { f.ty.span()=>
+            if let ::std::option::Option::Some(value) = patch.#field {
+                columns.push(#column);
+                args.add(#value);
+            }
+        }
+    });
+    let bind_id = build_bind_id(config, &quote! { &mut args });
+    // Structs for several databases bind for the database of the executor
+    let bounds = if multi_db {
+        let tys = fields
+            .iter()
+            .map(|f| f.bind_ty())
+            .chain(config.id_fields().map(|f| f.ty.clone()));
+        quote! {
+            E: 'e + #sqlx::Executor<'e>,
+            Self: #crate_name::traits::DatabaseSchema<#db_ty>,
+            <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments:
+                #sqlx::IntoArguments<'e, #db_ty>,
+            #(#tys: 'e + ::std::marker::Send + #sqlx::Encode<'e, #db_ty> + #sqlx::Type<#db_ty>,)*
+        }
+    } else {
+        quote! { E: 'e + #sqlx::Executor<'e, Database = #db_ty>, }
+    };
+    let method = config.method("patch", ident.span());
+    let struct_doc = format!(
+        "Partial update of [`{}`] written by [`{}::{}()`], `None` fields are left unchanged.",
+        ident, ident, method
+    );
+
+    quote! {
+        #[doc = #struct_doc]
+        #[derive(Default, #derives)]
+        #vis struct #patch_ident {
+            #(pub #field_idents: ::std::option::Option<#field_tys>,)*
+        }
+
+        #[automatically_derived]
+        impl #ident {
+            /// Writes the `Some` fields of `patch` to the row with primary key
+            /// `id` in a single UPDATE, leaving the other columns unchanged.
+            /// A patch without `Some` fields resolves to `Ok` without a query.
+            /// Resolves to `sqlx_crud::Error::NotFound` if there is no such row.
+            pub fn #method<'e, E>(
+                pool: E,
+                id: <Self as #crate_name::traits::Schema>::Id,
+                patch: #patch_ident,
+            ) -> #crate_name::traits::CrudFut<'e, ()>
+            where
+                #bounds
+            {
+                use #sqlx::Arguments as _;
+                let mut columns = ::std::vec::Vec::new();
+                let mut args = <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments::default();
+                #(#binds)*
+                if columns.is_empty() {
+                    return ::std::boxed::Box::pin(::std::future::ready(::std::result::Result::Ok(())));
+                }
+                let key = ::std::clone::Clone::clone(&id);
+                #bind_id
+                let sql = <Self as #crate_name::traits::DatabaseSchema<#db_ty>>::database_metadata().patch_sql(&columns);
+                ::std::boxed::Box::pin(async move {
+                    let result = #sqlx::query_with::<#db_ty, _>(sql, args).execute(pool).await?;
+                    if <Self as #crate_name::traits::DatabaseSchema<#db_ty>>::rows_affected(&result) == 0 {
+                        return ::std::result::Result::Err(#crate_name::Error::NotFound {
+                            table_name: <Self as #crate_name::traits::Schema>::table_name(),
+                            id: ::std::format!("{:?}", key),
+                        });
+                    }
+                    ::std::result::Result::Ok(())
+                })
+            }
+        }
+    }
+}
+
+/// `touch()` of structs with an `#[sqlx_crud(updated_at)]` field, writing
+/// only the timestamp and the version of a row and reading them back.
+fn build_touch_impl(config: &Config) -> TokenStream2 {
+    // Audited structs are only written through Crud::update, which records
+    // the row in the history table
+    if !config.ops.update || config.audit || !config.fields.iter().any(|f| f.updated_at) {
+        return quote! {};
+    }
+
+    let crate_name = &config.crate_name;
+    let sqlx = &config.sqlx;
+    let ident = &config.ident;
+    let (impl_generics, ty_generics, where_clause) = config.generics.split_for_impl();
+    let multi_db = config.db_tys.len() > 1;
+    let (db_ty, executor_bounds) = if multi_db {
+        let db_ty = quote! { E::Database };
+        let bounds = quote! {
+            E: 'e + #sqlx::Executor<'e>,
+            Self: #crate_name::traits::DatabaseSchema<#db_ty>,
+            <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments:
+                #sqlx::IntoArguments<'e, #db_ty>,
+        };
+        (db_ty, bounds)
+    } else {
+        let db_ty = config.db_tys[0].sqlx_db(sqlx);
+        let bounds = quote! { E: 'e + #sqlx::Executor<'e, Database = #db_ty>, };
+        (db_ty, bounds)
+    };
+    // The WHERE clause binds the key, the tenant and the version, as the
+    // UPDATE statement does
+    let fields = config
+        .tenant_field()
+        .into_iter()
+        .chain(config.version_field())
+        .collect::<Vec<_>>();
+    let binds = fields.iter().map(|f| {
+        let ident = f.ident;
+        let value = f.bind_value(quote! { ::std::clone::Clone::clone(&self.#ident) });
+        quote_spanned! { f.ty.span()=> args.add(#value); }
+    });
+    let bind_bounds = fields.iter().map(|f| {
+        let ty = f.bind_ty();
+        quote! { #ty: 'e + ::std::marker::Send + #sqlx::Encode<'e, #db_ty> + #sqlx::Type<#db_ty>, }
+    });
+    let method = config.method("touch", ident.span());
+
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Writes the current time to the `updated_at` columns of the row,
+            /// and increments its version if it has one, leaving the other
+            /// columns unchanged. The stored row is read back in to `self`.
+            /// Resolves to `false` if there is no such row, or it was soft
+            /// deleted or its version changed since it was loaded.
+            pub fn #method<'e, E>(&'e mut self, pool: E) -> #crate_name::traits::CrudFut<'e, bool>
+            where
+                #executor_bounds
+                #(#bind_bounds)*
+                Self: #crate_name::traits::Crud<'e, E>,
+            {
+                use #sqlx::Arguments as _;
+                let sql = <Self as #crate_name::traits::DatabaseSchema<#db_ty>>::database_metadata()
+                    .touch_sql
+                    .as_deref()
+                    .expect("an updated_at column");
+                let id = <Self as #crate_name::traits::Schema>::id(self);
+                let mut args = <Self as #crate_name::traits::Crud<'e, E>>::id_args(id);
+                #(#binds)*
+                ::std::boxed::Box::pin(async move {
+                    let row = #sqlx::query_as_with::<#db_ty, Self, _>(sql, args)
+                        .fetch_optional(pool)
+                        .await?;
+                    match row {
+                        ::std::option::Option::Some(row) => {
+                            *self = row;
+                            ::std::result::Result::Ok(true)
+                        }
+                        ::std::option::Option::None => ::std::result::Result::Ok(false),
+                    }
+                })
+            }
+        }
+    }
+}
+
+/// The `New<Struct>` struct of `#[sqlx_crud(new_type)]`, holding the fields
+/// bound by the INSERT statement, and its `create()` inserting a row and
+/// returning the whole struct with the values assigned by the database.
+fn build_new_type_impl(config: &Config, vis: &syn::Visibility) -> TokenStream2 {
+    let Some(derives) = &config.new_type else {
+        return quote! {};
+    };
+
+    let crate_name = &config.crate_name;
+    let sqlx = &config.sqlx;
+    let ident = &config.ident;
+    let new_ident = format_ident!("New{}", ident.unraw());
+    let multi_db = config.db_tys.len() > 1;
+    let db_ty = if multi_db {
+        quote! { E::Database }
+    } else {
+        config.db_tys[0].sqlx_db(sqlx)
+    };
+    let fields = config.insert_binds().collect::<Vec<_>>();
+    let field_idents = fields.iter().map(|f| f.ident);
+    let field_tys = fields.iter().map(|f| f.ty);
+    let insert_args = fields
+        .iter()
+        .map(|f| f.insert_arg(crate_name, &quote! { &mut args }, false));
+    let insert_size = fields.iter().map(|f| f.size_hint(&db_ty));
+    let defaulted_columns = build_defaulted_columns(config, &quote! { &mut columns });
+    // Structs for several databases bind for the database of the executor
+    let bounds = if multi_db {
+        let tys = fields.iter().map(|f| f.bind_ty());
+        quote! {
+            E: 'e + #sqlx::Executor<'e>,
+            #ident: #crate_name::traits::DatabaseSchema<#db_ty>
+                + for<'r> #sqlx::FromRow<'r, <#db_ty as #sqlx::Database>::Row>,
+            <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments:
+                #sqlx::IntoArguments<'e, #db_ty>,
+            #(#tys: 'e + ::std::marker::Send + #sqlx::Encode<'e, #db_ty> + #sqlx::Type<#db_ty>,)*
+        }
+    } else {
+        quote! { E: 'e + #sqlx::Executor<'e, Database = #db_ty>, }
+    };
+    let struct_doc = format!(
+        "The columns of [`{}`] written on insert, created by [`{}::create()`].",
+        ident, new_ident
+    );
+
+    quote! {
+        #[doc = #struct_doc]
+        #[derive(#derives)]
+        #vis struct #new_ident {
+            #(pub #field_idents: #field_tys,)*
+        }
+
+        #[automatically_derived]
+        impl #new_ident {
+            /// Inserts the row and returns a future that resolves to the whole
+            /// struct as stored, including the generated id and the columns
+            /// left to their database defaults.
+            pub fn create<'e, E>(self, pool: E) -> #crate_name::traits::CrudFut<'e, #ident>
+            where
+                #bounds
+            {
+                use #sqlx::Arguments as _;
+                let mut columns = ::std::vec::Vec::new();
+                #defaulted_columns
+                let sql = <#ident as #crate_name::traits::DatabaseSchema<#db_ty>>::database_metadata()
+                    .insert_sql_without(&columns);
+                let mut args = <#db_ty as #sqlx::database::HasArguments<'e>>::Arguments::default();
+                args.reserve(1usize, 0usize #(+ #insert_size)*);
+                #(#insert_args)*
+                ::std::boxed::Box::pin(async move {
+                    let row = #sqlx::query_as_with::<#db_ty, #ident, _>(sql, args).fetch_one(pool).await?;
+                    ::std::result::Result::Ok(row)
+                })
+            }
+        }
+    }
+}
+
+/// The `#[test]` of `#[sqlx_crud(checked)]`, checking the statements of the
+/// struct against the database at `DATABASE_URL` for each of its databases.
+fn build_checked_test(config: &Config) -> TokenStream2 {
+    if !config.checked {
+        return quote! {};
+    }
+
+    let crate_name = &config.crate_name;
+    let sqlx = &config.sqlx;
+    let ident = &config.ident;
+    let test_ident = format_ident!(
+        "__sqlx_crud_check_{}",
+        ident.unraw().to_string().to_snake_case()
+    );
+    let ops = &config.ops;
+    let (create, update, delete) = (ops.create, ops.update, ops.delete);
+    let checks = config.db_tys.iter().map(|db| {
+        let db_ty = db.sqlx_db(sqlx);
+        quote! {
+            if let ::std::result::Result::Err(e) =
+                #crate_name::check::run::<#db_ty, #ident>(&url, #create, #update, #delete)
+            {
+                ::std::panic!("{}", e);
+            }
+        }
+    });
+
+    quote! {
+        #[cfg(test)]
+        #[test]
+        fn #test_ident() {
+            let url = match #crate_name::check::database_url() {
+                ::std::option::Option::Some(url) => url,
+                ::std::option::Option::None => return,
+            };
+            #(#checks)*
+        }
+    }
+}
+
+/// The statements of `#[sqlx_crud(debug_sql)]`, reported in debug builds as
+/// the note of a deprecated item used at the attribute. The generated code
+/// is unchanged, the item is private to an anonymous constant.
+fn build_debug_sql(config: &Config) -> TokenStream2 {
+    let Some(span) = config.debug_sql else {
+        return quote! {};
+    };
+
+    let mut note = format!("statements of `{}`", config.ident.unraw());
+    let flattened = config
+        .fields
+        .iter()
+        .filter(|f| f.flatten)
+        .map(|f| format!("`{}`", f.ident.unraw()))
+        .collect::<Vec<_>>();
+    if !flattened.is_empty() {
+        note.push_str(&format!(
+            ", without the columns of flattened fields {}",
+            flattened.join(", ")
+        ));
+    }
+    for db in &config.db_tys {
+        note.push_str(&format!("\n{:?}:", db));
+        for sql in debug_statements(config, db) {
+            note.push_str("\n    ");
+            note.push_str(&sql);
+        }
+    }
+    let item = Ident::new("debug_sql", span);
+    quote_spanned! { span=>
+        #[cfg(debug_assertions)]
+        const _: () = {
+            #[deprecated(note = #note)]
+            #[allow(non_camel_case_types)]
+            struct #item;
+            let _ = #item;
+        };
+    }
+}
+
+/// The SELECT, INSERT, UPDATE and DELETE statements `Metadata::new` builds
+/// for database `db`, over the columns of the fields that are not
+/// flattened. Audited writes are preceded by their history INSERT, which
+/// PostgreSQL runs in the same statement.
+fn debug_statements(config: &Config, db: &DbType) -> Vec<String> {
+    let quote_ident = |ident: &str| match db {
+        DbType::MySql => format!("`{}`", ident),
+        _ => format!(r#""{}""#, ident),
+    };
+    let qualify = |name: &str| match &config.schema_name {
+        Some(schema_name) => format!("{}.{}", quote_ident(schema_name), quote_ident(name)),
+        None => quote_ident(name),
+    };
+    let and = |clause: String, condition: Option<String>| match condition {
+        Some(condition) => format!("{} AND {}", clause, condition),
+        None => clause,
+    };
+    let list = |columns: &mut dyn Iterator<Item = String>| columns.collect::<Vec<_>>().join(", ");
+
+    let table = qualify(&config.table_name);
+    let columns = config
+        .fields
+        .iter()
+        .filter(|f| !f.flatten)
+        .collect::<Vec<_>>();
+    let qualified = |f: &FieldConfig| format!("{}.{}", table, quote_ident(&f.column));
+    let tenant = columns
+        .iter()
+        .find(|f| f.tenant)
+        .map(|f| format!("{} = ?", qualified(f)));
+    let id_condition = and(
+        columns
+            .iter()
+            .filter(|f| f.id)
+            .map(|f| format!("{} = ?", qualified(f)))
+            .collect::<Vec<_>>()
+            .join(" AND "),
+        tenant.clone(),
+    );
+    let versioned_id_condition = and(
+        id_condition.clone(),
+        columns
+            .iter()
+            .find(|f| f.version)
+            .map(|f| format!("{} = ?", qualified(f))),
+    );
+    let soft_delete = columns.iter().find(|f| f.soft_delete);
+    let not_deleted = soft_delete.map(|f| format!("{} IS NULL", qualified(f)));
+    let read_condition = match tenant {
+        Some(tenant) => Some(and(tenant, not_deleted.clone())),
+        None => not_deleted.clone(),
+    };
+    let column_list = list(&mut columns.iter().map(|f| qualified(f)));
+    let returning = list(&mut columns.iter().map(|f| {
+        format!(
+            "{}.{}",
+            quote_ident(&config.table_name),
+            quote_ident(&f.column)
+        )
+    }));
+
+    let mut statements = vec![
+        match read_condition {
+            Some(read_condition) => format!(
+                "SELECT {} FROM {} WHERE {}",
+                column_list, table, read_condition
+            ),
+            None => format!("SELECT {} FROM {}", column_list, table),
+        },
+        format!(
+            "SELECT {} FROM {} WHERE {} LIMIT 1",
+            column_list,
+            table,
+            and(id_condition, not_deleted.clone())
+        ),
+    ];
+    if config.ops.create {
+        let inserted = columns
+            .iter()
+            .filter(|f| config.inserted(f))
+            .collect::<Vec<_>>();
+        statements.push(if inserted.is_empty() {
+            let default_values = match db {
+                DbType::MySql => "() VALUES ()",
+                _ => "DEFAULT VALUES",
+            };
+            format!(
+                "INSERT INTO {} {} RETURNING {}",
+                table, default_values, returning
+            )
+        } else {
+            format!(
+                "INSERT INTO {} ({}) VALUES ({}) RETURNING {}",
+                table,
+                list(&mut inserted.iter().map(|f| quote_ident(&f.column))),
+                list(
+                    &mut inserted
+                        .iter()
+                        .map(|f| f.sql_value().unwrap_or("?").to_string())
+                ),
+                returning
+            )
+        });
+    }
+    let audit = |statements: &mut Vec<String>, operation: &str, condition: &str, sql: String| {
+        if !config.audit {
+            statements.push(sql);
+            return;
+        }
+        let audit_sql = format!(
+            "INSERT INTO {} ({}, {}, {}) SELECT {}, CURRENT_TIMESTAMP, '{}' FROM {} WHERE {}",
+            qualify(&format!("{}_history", config.table_name)),
+            list(&mut columns.iter().map(|f| quote_ident(&f.column))),
+            quote_ident("audited_at"),
+            quote_ident("operation"),
+            column_list,
+            operation,
+            table,
+            condition
+        );
+        match db {
+            DbType::Postgres => statements.push(format!("WITH audit AS ({}) {}", audit_sql, sql)),
+            _ => statements.extend([audit_sql, sql]),
+        }
+    };
+    if config.ops.update {
+        let set = list(&mut columns.iter().filter(|f| config.updated(f)).map(|f| {
+            let column = quote_ident(&f.column);
+            if f.version {
+                format!("{0} = {0} + 1", column)
+            } else {
+                format!("{} = {}", column, f.sql_value().unwrap_or("?"))
+            }
+        }));
+        let sql = format!(
+            "UPDATE {} SET {} WHERE {} RETURNING {}",
+            table, set, versioned_id_condition, returning
+        );
+        audit(&mut statements, "update", &versioned_id_condition, sql);
+    }
+    if config.ops.delete {
+        let (condition, sql) = match soft_delete {
+            Some(f) => {
+                let condition = and(versioned_id_condition, not_deleted);
+                let sql = format!(
+                    "UPDATE {} SET {} = CURRENT_TIMESTAMP WHERE {}",
+                    table,
+                    quote_ident(&f.column),
+                    condition
+                );
+                (condition, sql)
+            }
+            None => {
+                let sql = format!("DELETE FROM {} WHERE {}", table, versioned_id_condition);
+                (versioned_id_condition, sql)
+            }
+        };
+        audit(&mut statements, "delete", &condition, sql);
+    }
+
+    match db {
+        DbType::Postgres => statements.into_iter().map(numbered).collect(),
+        _ => statements,
+    }
+}
+
+/// Numbers the `?` bind parameters of `sql` as `$1`, `$2`, ... for
+/// PostgreSQL, leaving quoted identifiers and string literals untouched.
+fn numbered(sql: String) -> String {
+    let mut numbered = String::with_capacity(sql.len());
+    let mut quote = None;
+    let mut n = 0;
+    for c in sql.chars() {
+        match (quote, c) {
+            (None, '"' | '\'' | '`') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '?') => {
+                n += 1;
+                numbered.push_str(&format!("${}", n));
+                continue;
+            }
+            _ => {}
+        }
+        numbered.push(c);
+    }
+    numbered
+}
+
+/// `CreateTable` of structs with an SQL type for each column. The columns of
+/// flattened fields are typed by their embedded struct, which has to
+/// implement `CreateTable` too. That bound is higher-ranked so that it is
+/// only checked where the marker is required, not on the impl itself.
+fn build_create_table_marker(config: &Config) -> TokenStream2 {
+    let crate_name = &config.crate_name;
+    if !config
+        .fields
+        .iter()
+        .all(|f| f.flatten || f.sql_type(crate_name).is_some())
+    {
+        return quote! {};
+    }
+
+    let ident = &config.ident;
+    let (impl_generics, ty_generics, _) = config.generics.split_for_impl();
+    let predicates = config
+        .generics
+        .where_clause
+        .iter()
+        .flat_map(|w| w.predicates.iter());
+    let embedded = config.fields.iter().filter(|f| f.flatten).map(|f| f.ty);
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics #crate_name::traits::CreateTable for #ident #ty_generics
+        where
+            #(#predicates,)*
+            #(for<'__create_table> #embedded: #crate_name::traits::CreateTable,)*
+        {
+        }
+    }
+}
+
+/// Inherent methods forwarding to the `Crud` methods under the names given by
+/// `#[sqlx_crud(method_prefix = "...")]`, so they stay reachable next to
+/// methods of the same name defined on the struct. Only generated for the
+/// operations the struct supports.
+fn build_prefixed_impl(config: &Config) -> TokenStream2 {
+    if config.method_prefix.is_empty() {
+        return quote! {};
+    }
+
+    let crate_name = &config.crate_name;
+    let sqlx = &config.sqlx;
+    let ident = &config.ident;
+    let (impl_generics, ty_generics, where_clause) = config.generics.split_for_impl();
+    let span = ident.span();
+    let bounds = quote! {
+        E: 'e + #sqlx::Executor<'e>,
+        <E::Database as #sqlx::database::HasArguments<'e>>::Arguments:
+            #sqlx::IntoArguments<'e, E::Database>,
+        Self: #crate_name::traits::Crud<'e, E>,
+    };
+    // Crud::update and Crud::delete begin a transaction for the history
+    // INSERT of audited structs
+    let acquire_bounds = quote! {
+        E: 'e
+            + #sqlx::Executor<'e>
+            + #sqlx::Acquire<'e, Database = <E as #sqlx::Executor<'e>>::Database>,
+        <<E as #sqlx::Executor<'e>>::Database as #sqlx::database::HasArguments<'e>>::Arguments:
+            #sqlx::IntoArguments<'e, <E as #sqlx::Executor<'e>>::Database>,
+        for<'c> &'c mut <<E as #sqlx::Executor<'e>>::Database as #sqlx::Database>::Connection:
+            #sqlx::Executor<'c, Database = <E as #sqlx::Executor<'e>>::Database>,
+        Self: #crate_name::traits::Crud<'e, E>,
+    };
+    let ops = &config.ops;
+    let unscoped = config.tenant_field().is_none();
+
+    let create = config.method("create", span);
+    let create = ops.create.then(|| {
+        quote! {
+            /// Forwards to `Crud::create`.
+            pub fn #create<'e, E>(self, pool: E) -> #crate_name::traits::CrudFut<'e, Self>
+            where
+                #bounds
+            {
+                <Self as #crate_name::traits::Crud<'e, E>>::create(self, pool)
+            }
+        }
+    });
+    let all = config.method("all", span);
+    let by_id = config.method("by_id", span);
+    let read = (ops.read && unscoped).then(|| {
+        quote! {
+            /// Forwards to `Crud::all`.
+            pub fn #all<'e, E>(pool: E) -> #crate_name::traits::TryCollectFut<'e, Self>
+            where
+                #bounds
+            {
+                <Self as #crate_name::traits::Crud<'e, E>>::all(pool)
+            }
+
+            /// Forwards to `Crud::by_id`.
+            pub fn #by_id<'e, E, K>(
+                pool: E,
+                id: K,
+            ) -> #crate_name::traits::CrudFut<'e, ::std::option::Option<Self>>
+            where
+                #bounds
+                Self: #crate_name::traits::BindId<'e, E::Database, K>,
+                K: ::std::fmt::Debug,
+            {
+                <Self as #crate_name::traits::Crud<'e, E>>::by_id(pool, id)
+            }
+        }
+    });
+    let update = config.method("update", span);
+    let update = ops.update.then(|| {
+        quote! {
+            /// Forwards to `Crud::update`.
+            pub fn #update<'e, E>(self, pool: E) -> #crate_name::traits::CrudFut<'e, Self>
+            where
+                #acquire_bounds
+            {
+                <Self as #crate_name::traits::Crud<'e, E>>::update(self, pool)
+            }
+        }
+    });
+    let delete = config.method("delete", span);
+    let delete_permanently = config.method("delete_permanently", span);
+    let delete = ops.delete.then(|| {
+        quote! {
+            /// Forwards to `Crud::delete`.
+            pub fn #delete<'e, E>(self, pool: E) -> #crate_name::traits::CrudFut<'e, ()>
+            where
+                #acquire_bounds
+            {
+                <Self as #crate_name::traits::Crud<'e, E>>::delete(self, pool)
+            }
+
+            /// Forwards to `Crud::delete_permanently`.
+            pub fn #delete_permanently<'e, E>(self, pool: E) -> #crate_name::traits::CrudFut<'e, ()>
+            where
+                #bounds
+            {
+                <Self as #crate_name::traits::Crud<'e, E>>::delete_permanently(self, pool)
+            }
+        }
+    });
+
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #create
+            #read
+            #update
+            #delete
+        }
+    }
+}
+
+/// `Schema::metadata()`, the metadata of the first listed database. Listed
+/// databases missing their sqlx-crud cargo feature are skipped, the build
+/// failing when none is enabled.
+fn build_metadata(config: &Config) -> TokenStream2 {
+    let crate_name = &config.crate_name;
+    let sqlx = &config.sqlx;
+    if let [db_ty] = config.db_tys.as_slice() {
+        let db_ty = db_ty.sqlx_db(sqlx);
+        return quote! {
+            fn metadata() -> &'static #crate_name::schema::Metadata {
+                <Self as #crate_name::traits::DatabaseSchema<#db_ty>>::database_metadata()
+            }
+        };
+    }
+
+    let databases = config
+        .db_tys
+        .iter()
+        .map(|db_ty| db_ty.feature())
+        .collect::<Vec<_>>()
+        .join("`, `");
+    let message = format!(
+        "none of the databases of {} are enabled, enable one of the `{}` sqlx-crud features",
+        config.ident, databases
+    );
+    // The first enabled database in turn, failing the build without one
+    let metadata = config.db_tys.iter().rev().fold(
+        quote! { ::std::compile_error!(#message) },
+        |otherwise, db_ty| {
+            let gate = db_ty.feature_gate(crate_name);
+            let db_ty = db_ty.sqlx_db(sqlx);
+            quote! {
+                #gate! {
+                    { <Self as #crate_name::traits::DatabaseSchema<#db_ty>>::database_metadata() }
+                    else { #otherwise }
+                }
+            }
+        },
+    );
+    quote! {
+        fn metadata() -> &'static #crate_name::schema::Metadata {
+            #metadata
+        }
+    }
+}
+
+/// `DatabaseSchema` impl holding the queries rendered for `db_ty`. With
+/// several databases listed the impl is only compiled when the sqlx-crud
+/// feature of the database is enabled.
+fn build_database_schema_impl(config: &Config, db_ty: &DbType) -> TokenStream2 {
+    let crate_name = &config.crate_name;
+    let sqlx = &config.sqlx;
+    let ident = &config.ident;
+    let (impl_generics, ty_generics, where_clause) = config.generics.split_for_impl();
+    let table_name = &config.table_name;
+    let schema_name = match &config.schema_name {
+        Some(schema_name) => quote! { ::std::option::Option::Some(#schema_name) },
+        None => quote! { ::std::option::Option::None },
+    };
+    let columns = build_columns(config);
+    let audit = config.audit;
+    let order_by = config.order_by.iter().map(|(column, descending)| {
+        quote! {
+            #crate_name::schema::OrderBy { column: #column, descending: #descending }
+        }
+    });
+    let gate = db_ty.feature_gate(crate_name);
+    let db_ty = db_ty.sqlx_db(sqlx);
+
+    let database_schema_impl = quote! {
+        #[automatically_derived]
+        impl #impl_generics #crate_name::traits::DatabaseSchema<#db_ty> for #ident #ty_generics #where_clause {
+            fn database_metadata() -> &'static #crate_name::schema::Metadata {
+                static METADATA: ::std::sync::OnceLock<#crate_name::schema::Metadata> =
+                    ::std::sync::OnceLock::new();
+                METADATA.get_or_init(|| {
+                    #crate_name::schema::Metadata::new::<#db_ty>(
+                        #schema_name,
+                        #table_name,
+                        #columns,
+                        ::std::vec![#(#order_by),*],
+                        #audit,
+                    )
+                })
+            }
+
+            fn rows_affected(result: &<#db_ty as #sqlx::Database>::QueryResult) -> u64 {
+                result.rows_affected()
+            }
+
+            fn upsert_inserted(
+                row: &<#db_ty as #sqlx::Database>::Row,
+            ) -> ::std::result::Result<bool, #sqlx::Error> {
+                #sqlx::Row::try_get(row, #crate_name::schema::UPSERT_INSERTED)
+            }
+        }
+    };
+    if config.db_tys.len() > 1 {
+        quote! { #gate! { #database_schema_impl } }
+    } else {
+        database_schema_impl
+    }
+}
+
+fn build_embedded_impl(config: &Config) -> TokenStream2 {
+    let crate_name = &config.crate_name;
+    let sqlx = &config.sqlx;
+    let ident = &config.ident;
+    let column_ident = config.column_ident();
+    let (impl_generics, ty_generics, where_clause) = config.generics.split_for_impl();
+    let db_ty = config.db_tys[0].sqlx_db(sqlx);
+    let columns = build_columns(config);
+    let defaulted_columns = build_defaulted_columns(config, &quote! { columns });
+    let id_idents = config.id_fields().map(|f| f.ident).collect::<Vec<_>>();
+    let id_tys = config.id_fields().map(|f| f.ty);
+    // Structs without #[id] fields have the empty key `()`
+    let (id_ty, id_value, id_args_bind) = if config.id_fields().count() != 1 {
+        (
+            quote! { (#(#id_tys),*) },
+            quote! { (#(::std::clone::Clone::clone(&self.#id_idents)),*) },
+            quote! { let (#(#id_idents),*) = id; },
+        )
+    } else {
+        (
+            quote! { #(#id_tys)* },
+            quote! { #(::std::clone::Clone::clone(&self.#id_idents))* },
+            quote! { let #(#id_idents)* = id; },
+        )
+    };
+
+    let insert_query_args = config
+        .insert_binds()
+        .map(|f| f.insert_arg(crate_name, &quote! { args }, false));
+    let insert_query_args_ref = config
+        .insert_binds()
+        .map(|f| f.insert_arg(crate_name, &quote! { args }, true));
+
+    // Id columns are only written when the embedded struct is not the key
+    let update_arg = |f: &FieldConfig, by_ref: bool| {
+        let arg = f.update_arg(crate_name, &quote! { args }, by_ref);
+        if f.id {
+            quote! { if !id { #arg } }
+        } else {
+            arg
+        }
+    };
+    let update_query_args = config.update_binds().map(|f| update_arg(f, false));
+    let update_query_args_ref = config.update_binds().map(|f| update_arg(f, true));
+    let create_table_marker = build_create_table_marker(config);
+
+    quote! {
+        #create_table_marker
+
+        #[automatically_derived]
+        impl #impl_generics #crate_name::traits::Embedded for #ident #ty_generics #where_clause {
+            type Database = #db_ty;
+            type Id = #id_ty;
+            type Column = #column_ident;
+
+            fn columns() -> ::std::vec::Vec<#crate_name::schema::Column> {
+                #columns
+            }
+
+            fn id(&self) -> Self::Id {
+                #id_value
+            }
+
+            #[allow(unused_variables)]
+            fn defaulted_columns(&self, columns: &mut ::std::vec::Vec<&'static str>) {
+                #defaulted_columns
+            }
+
+            fn insert_args<'q>(self, args: &mut <#db_ty as #sqlx::database::HasArguments<'q>>::Arguments)
+            where
+                Self: 'q,
+            {
+                use #sqlx::Arguments as _;
+                #(#insert_query_args)*
+            }
+
+            fn update_args<'q>(self, id: bool, args: &mut <#db_ty as #sqlx::database::HasArguments<'q>>::Arguments)
+            where
+                Self: 'q,
+            {
+                use #sqlx::Arguments as _;
+                #(#update_query_args)*
+            }
+
+            fn insert_args_ref<'q>(&'q self, args: &mut <#db_ty as #sqlx::database::HasArguments<'q>>::Arguments) {
+                use #sqlx::Arguments as _;
+                #(#insert_query_args_ref)*
+            }
+
+            fn update_args_ref<'q>(&'q self, id: bool, args: &mut <#db_ty as #sqlx::database::HasArguments<'q>>::Arguments) {
+                use #sqlx::Arguments as _;
+                #(#update_query_args_ref)*
+            }
+
+            fn id_args<'q>(id: Self::Id, args: &mut <#db_ty as #sqlx::database::HasArguments<'q>>::Arguments)
+            where
+                Self: 'q,
+            {
+                use #sqlx::Arguments as _;
+                #id_args_bind
+                #(args.add(#id_idents);)*
+            }
+        }
+    }
+}
+
+#[allow(dead_code)] // Usage in quote macros aren't flagged as used
+struct Config<'a> {
+    ident: &'a Ident,
+    generics: &'a Generics,
+    named: &'a Punctuated<Field, Comma>,
+    fields: Vec<FieldConfig<'a>>,
+    crate_name: TokenStream2,
+    /// Path to sqlx, `::sqlx` unless set with `#[sqlx_crud(sqlx = "...")]`.
+    sqlx: TokenStream2,
+    /// Databases listed in `#[database(...)]`, in order.
+    db_tys: Vec<DbType>,
+    schema_name: Option<String>,
+    table_name: String,
+    external_id: bool,
+    embedded: bool,
+    ops: Operations,
+    /// Prefix of the generated inherent methods, see
+    /// `#[sqlx_crud(method_prefix = "...")]`.
+    method_prefix: String,
+    /// Columns of `#[sqlx_crud(order_by = "...")]` and whether they sort
+    /// descending.
+    order_by: Vec<(String, bool)>,
+    /// Updates and deletes are recorded in the history table, see
+    /// `#[sqlx_crud(audit)]`.
+    audit: bool,
+    /// Creates and updates run the `Validate` check first, see
+    /// `#[sqlx_crud(validate)]`.
+    validate: bool,
+    /// Writes run the `CrudHooks` hooks, see `#[sqlx_crud(hooks)]`.
+    hooks: bool,
+    /// Relations of `#[sqlx_crud(has_many(...))]`, in declaration order.
+    has_many: Vec<HasMany>,
+    /// Relations of `#[sqlx_crud(many_to_many(...))]`, in declaration order.
+    many_to_many: Vec<ManyToMany>,
+    /// Derives of the `<Struct>Patch` struct generated for
+    /// `#[sqlx_crud(patch)]`.
+    patch: Option<TokenStream2>,
+    /// Derives of the `New<Struct>` struct generated for
+    /// `#[sqlx_crud(new_type)]`.
+    new_type: Option<TokenStream2>,
+    /// Changes are tracked by `Tracked`, see `#[sqlx_crud(tracked)]`.
+    tracked: bool,
+    /// A test checks the statements against `DATABASE_URL`, see
+    /// `#[sqlx_crud(checked)]`.
+    checked: bool,
+    /// Span of `#[sqlx_crud(debug_sql)]`, where the statements are reported.
+    debug_sql: Option<Span>,
+}
+
+impl<'a> Config<'a> {
+    /// `embedded` configures a struct deriving `SqlxCrudEmbedded`, which has
+    /// no table of its own.
+    fn new(
+        attrs: &[Attribute],
+        ident: &'a Ident,
+        generics: &'a Generics,
+        named: &'a Punctuated<Field, Comma>,
+        embedded: bool,
+    ) -> syn::Result<Self> {
+        let crate_name = std::env::var("CARGO_PKG_NAME").unwrap();
+        let is_doctest = std::env::vars()
+            .any(|(k, _)| k == "UNSTABLE_RUSTDOC_TEST_LINE" || k == "UNSTABLE_RUSTDOC_TEST_PATH");
+        let mut crate_name = if !is_doctest && crate_name == "sqlx-crud" {
+            quote! { crate }
+        } else {
+            quote! { ::sqlx_crud }
+        };
+        let mut sqlx = quote! { ::sqlx };
+
+        let db_tys = DbType::list(ident, attrs)?;
+
+        let mut schema_name = None;
+        let mut table_name = None;
+        let mut rename_all = None;
+        let mut table_naming = TableNaming::Plural;
+        let mut ops = None;
+        let mut view = false;
+        let mut method_prefix = String::new();
+        let mut order_by = None;
+        let mut audit = false;
+        let mut validate = false;
+        let mut hooks = false;
+        let mut has_many = Vec::new();
+        let mut many_to_many = Vec::new();
+        let mut patch = None;
+        let mut new_type = None;
+        let mut tracked = false;
+        let mut checked = false;
+        let mut debug_sql = None;
+        for attr in attrs.iter().filter(|a| a.path().is_ident("sqlx_crud")) {
+            attr.parse_nested_meta(|m| {
+                if m.path.is_ident("table_name") && !embedded {
+                    let name: LitStr = m.value()?.parse()?;
+                    if name.value().is_empty() {
+                        return Err(syn::Error::new(
+                            name.span(),
+                            "`table_name` must not be an empty string",
+                        ));
+                    }
+                    table_name = Some(name.value());
+                    Ok(())
+                } else if m.path.is_ident("schema") && !embedded {
+                    let name: LitStr = m.value()?.parse()?;
+                    if name.value().is_empty() {
+                        return Err(syn::Error::new(
+                            name.span(),
+                            "`schema` must not be an empty string",
+                        ));
+                    }
+                    schema_name = Some(name.value());
+                    Ok(())
+                } else if m.path.is_ident("table_naming") && !embedded {
+                    let strategy: LitStr = m.value()?.parse()?;
+                    table_naming = TableNaming::new(&strategy)?;
+                    Ok(())
+                } else if m.path.is_ident("crate") {
+                    let path: LitStr = m.value()?.parse()?;
+                    crate_name = path.parse::<Path>()?.into_token_stream();
+                    Ok(())
+                } else if m.path.is_ident("sqlx") {
+                    let path: LitStr = m.value()?.parse()?;
+                    sqlx = path.parse::<Path>()?.into_token_stream();
+                    Ok(())
+                } else if m.path.is_ident("rename_all") {
+                    let policy: LitStr = m.value()?.parse()?;
+                    rename_all = Some(RenameAll::new(&policy)?);
+                    Ok(())
+                } else if m.path.is_ident("audit") && !embedded {
+                    audit = true;
+                    Ok(())
+                } else if m.path.is_ident("validate") && !embedded {
+                    validate = true;
+                    Ok(())
+                } else if m.path.is_ident("hooks") && !embedded {
+                    hooks = true;
+                    Ok(())
+                } else if m.path.is_ident("patch") && !embedded {
+                    patch = Some(parse_derives(&m, "patch")?);
+                    Ok(())
+                } else if m.path.is_ident("new_type") && !embedded {
+                    new_type = Some(parse_derives(&m, "new_type")?);
+                    Ok(())
+                } else if m.path.is_ident("tracked") && !embedded {
+                    tracked = true;
+                    Ok(())
+                } else if m.path.is_ident("checked") && !embedded {
+                    checked = true;
+                    Ok(())
+                } else if m.path.is_ident("debug_sql") && !embedded {
+                    debug_sql = Some(m.path.span());
+                    Ok(())
+                } else if m.path.is_ident("has_many") && !embedded {
+                    has_many.push(HasMany::parse(&m)?);
+                    Ok(())
+                } else if m.path.is_ident("many_to_many") && !embedded {
+                    many_to_many.push(ManyToMany::parse(&m)?);
+                    Ok(())
+                } else if m.path.is_ident("order_by") && !embedded {
+                    order_by = Some(m.value()?.parse::<LitStr>()?);
+                    Ok(())
+                } else if m.path.is_ident("method_prefix") && !embedded {
+                    let prefix: LitStr = m.value()?.parse()?;
+                    if syn::parse_str::<Ident>(&format!("{}create", prefix.value())).is_err() {
+                        return Err(syn::Error::new(
+                            prefix.span(),
+                            "`method_prefix` must start method names, such as `db_`",
+                        ));
+                    }
+                    method_prefix = prefix.value();
+                    Ok(())
+                } else if m.path.is_ident("view") && !embedded {
+                    if ops.is_some() {
+                        return Err(m.error(
+                            "views are read only and cannot list `only(...)` or `except(...)`",
+                        ));
+                    }
+                    view = true;
+                    ops = Some(Operations {
+                        create: false,
+                        read: true,
+                        update: false,
+                        delete: false,
+                    });
+                    Ok(())
+                } else if (m.path.is_ident("only") || m.path.is_ident("except")) && !embedded {
+                    if view {
+                        return Err(m.error(
+                            "views are read only and cannot list `only(...)` or `except(...)`",
+                        ));
+                    }
+                    if ops.is_some() {
+                        return Err(
+                            m.error("only one `only(...)` or `except(...)` list is allowed")
+                        );
+                    }
+                    let only = m.path.is_ident("only");
+                    let mut listed = if only {
+                        Operations {
+                            create: false,
+                            read: false,
+                            update: false,
+                            delete: false,
+                        }
+                    } else {
+                        Operations::all()
+                    };
+                    listed.parse(&m, only)?;
+                    ops = Some(listed);
+                    Ok(())
+                } else {
+                    Err(m.error("unsupported sqlx_crud attribute"))
+                }
+            })?;
+        }
+        // Otherwise derive it from the struct name, by default the plural
+        // snake_case
+        let table_name =
+            table_name.unwrap_or_else(|| table_naming.apply(&ident.unraw().to_string()));
+        if view && audit {
+            return Err(syn::Error::new(
+                ident.span(),
+                "views are read only and have no writes to `audit`",
+            ));
+        }
+        if view && validate {
+            return Err(syn::Error::new(
+                ident.span(),
+                "views are read only and have no writes to `validate`",
+            ));
+        }
+        if view && hooks {
+            return Err(syn::Error::new(
+                ident.span(),
+                "views are read only and have no writes to run `hooks` around",
+            ));
+        }
+        let mut ops = ops.unwrap_or_else(Operations::all);
+        if ops.none() {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`only(...)` or `except(...)` leaves no operation to generate",
+            ));
+        }
+
+        let mut fields = named
+            .iter()
+            .map(|f| FieldConfig::new(f, rename_all.as_ref(), &sqlx))
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        if let Some(f) = fields.iter().find(|f| f.id && f.skip) {
+            return Err(syn::Error::new(
+                f.ident.span(),
+                format!("the `#[id]` field `{}` cannot be skipped", f.ident),
+            ));
+        }
+        fields.retain(|f| !f.skip);
+
+        if embedded {
+            Self::validate_embedded(&fields)?;
+        } else {
+            Self::validate(ident, &mut fields, &mut ops)?;
+        }
+
+        let order_by = match order_by {
+            Some(order_by) => {
+                let columns = fields
+                    .iter()
+                    .filter(|f| !f.flatten)
+                    .map(|f| f.column.as_str())
+                    .collect::<Vec<_>>();
+                parse_order_by(&order_by, Some(&columns))?
+            }
+            None => Vec::new(),
+        };
+
+        // The related rows are looked up by the single primary key column
+        let relation = has_many
+            .first()
+            .map(|r| (r.child.span(), "has_many"))
+            .or_else(|| {
+                many_to_many
+                    .first()
+                    .map(|r| (r.child.span(), "many_to_many"))
+            });
+        if let Some((span, relation)) = relation {
+            if fields.iter().filter(|f| f.id).count() > 1
+                || fields.iter().any(|f| f.id && f.flatten)
+            {
+                return Err(syn::Error::new(
+                    span,
+                    format!(
+                        "`{}` requires a single column primary key to look up the related rows by",
+                        relation
+                    ),
+                ));
+            }
+        }
+
+        if patch.is_some() {
+            let reason = if !ops.update {
+                Some("`patch` writes updates, which the struct does not support")
+            } else if fields.iter().any(|f| f.tenant) {
+                Some("`patch` is not supported with a `tenant` column, the patched row would not be scoped")
+            } else if audit {
+                Some("`patch` writes are not recorded in the `audit` history")
+            } else if !generics.params.is_empty() {
+                Some("`patch` is not supported on generic structs")
+            } else {
+                None
+            };
+            if let Some(reason) = reason {
+                return Err(syn::Error::new(ident.span(), reason));
+            }
+        }
+
+        if tracked {
+            let reason = if !ops.update {
+                Some("`tracked` writes updates, which the struct does not support")
+            } else if fields.iter().any(|f| f.tenant) {
+                Some("`tracked` is not supported with a `tenant` column, the updated row would not be scoped")
+            } else if audit {
+                Some("`tracked` writes are not recorded in the `audit` history")
+            } else if !generics.params.is_empty() {
+                Some("`tracked` is not supported on generic structs")
+            } else {
+                None
+            };
+            if let Some(reason) = reason {
+                return Err(syn::Error::new(ident.span(), reason));
+            }
+            if let Some(f) = fields.iter().find(|f| f.flatten) {
+                return Err(syn::Error::new(
+                    f.ident.span(),
+                    "changes to flattened fields are not tracked, `tracked` requires a struct without them",
+                ));
+            }
+        }
+
+        if checked && !generics.params.is_empty() {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`checked` is not supported on generic structs, the test has no type arguments to check",
+            ));
+        }
+
+        if new_type.is_some() {
+            let reason = if !ops.create {
+                Some("`new_type` creates rows, which the struct does not support")
+            } else if validate {
+                Some("`new_type` cannot run the `Validate` check, which needs the whole struct")
+            } else if hooks {
+                Some("`new_type` cannot run the `CrudHooks` hooks, which need the whole struct")
+            } else if !generics.params.is_empty() {
+                Some("`new_type` is not supported on generic structs")
+            } else {
+                None
+            };
+            if let Some(reason) = reason {
+                return Err(syn::Error::new(ident.span(), reason));
+            }
+        }
+
+        let external_id = attrs.iter().find(|a| a.path().is_ident("external_id"));
+        if let (Some(attr), Some(_)) = (external_id, fields.iter().find(|f| f.generated_id)) {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "`#[external_id]` cannot be combined with `#[id(generated)]`",
+            ));
+        }
+        let external_id = external_id.is_some();
+
+        if db_tys.len() > 1 {
+            if embedded {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "embedded structs are bound for a single `#[database]`",
+                ));
+            }
+            if let Some(f) = fields.iter().find(|f| f.flatten) {
+                return Err(syn::Error::new(
+                    f.ident.span(),
+                    "flattened fields are only supported with a single `#[database]`",
+                ));
+            }
+            if let Some(f) = fields.iter().find(|f| f.with.is_some()) {
+                return Err(syn::Error::new(
+                    f.ident.span(),
+                    "`with` fields are only supported with a single `#[database]`",
+                ));
+            }
+        }
+
+        Ok(Self {
+            ident,
+            generics,
+            named,
+            fields,
+            crate_name,
+            sqlx,
+            db_tys,
+            schema_name,
+            table_name,
+            external_id,
+            embedded,
+            ops,
+            method_prefix,
+            order_by,
+            audit,
+            validate,
+            hooks,
+            has_many,
+            many_to_many,
+            patch,
+            new_type,
+            tracked,
+            checked,
+            debug_sql,
+        })
+    }
+
+    fn validate(
+        ident: &Ident,
+        fields: &mut [FieldConfig],
+        ops: &mut Operations,
+    ) -> syn::Result<()> {
+        // Fields with the #[id] attribute form the primary key. Otherwise
+        // default to the first field as the "id" column
+        if fields.is_empty() {
+            return Err(syn::Error::new(
+                ident.span(),
+                "a struct without fields has no columns, add a field for the primary key",
+            ));
+        }
+        if !fields.iter().any(|f| f.id) {
+            fields.first_mut().expect("the first field").id = true;
+        }
+        if fields.iter().filter(|f| f.id).count() > 1 {
+            if let Some(f) = fields.iter().find(|f| f.generated_id) {
+                let other = fields
+                    .iter()
+                    .find(|o| o.id && o.ident != f.ident)
+                    .expect("another `#[id]` field");
+                return Err(syn::Error::new(
+                    f.ident.span(),
+                    format!(
+                        "`#[id]` specified on both `{}` and `{}`; composite keys are assigned outside of the database and cannot use `#[id(generated)]`",
+                        other.ident, f.ident
+                    ),
+                ));
+            }
+            if let Some(f) = fields.iter().find(|f| f.uuid_id) {
+                return Err(syn::Error::new(
+                    f.ident.span(),
+                    "`#[id(uuid)]` must be the only `#[id]` field",
+                ));
+            }
+            if let Some(f) = fields.iter().find(|f| f.id && f.flatten) {
+                return Err(syn::Error::new(
+                    f.ident.span(),
+                    "a flattened `#[id]` field must be the only `#[id]` field",
+                ));
+            }
+        }
+        if let Some(f) = fields.iter().find(|f| f.find_by && f.id) {
+            return Err(syn::Error::new(
+                f.ident.span(),
+                "`#[id]` fields are looked up with `by_id()` and cannot be `find_by`",
+            ));
+        }
+        if let Some(f) = fields.iter().find(|f| f.flatten && f.generated_id) {
+            return Err(syn::Error::new(
+                f.ident.span(),
+                "flattened keys are assigned outside of the database and cannot use `#[id(generated)]`",
+            ));
+        }
+
+        let mut versions = fields.iter().filter(|f| f.version);
+        if let (Some(_), Some(f)) = (versions.next(), versions.next()) {
+            return Err(syn::Error::new(
+                f.ident.span(),
+                "only one field can be `#[sqlx_crud(version)]`",
+            ));
+        }
+        if let Some(f) = fields.iter().find(|f| f.version && f.id) {
+            return Err(syn::Error::new(
+                f.ident.span(),
+                "the `#[id]` field cannot be `#[sqlx_crud(version)]`",
+            ));
+        }
+        let mut soft_deletes = fields.iter().filter(|f| f.soft_delete);
+        if let (Some(_), Some(f)) = (soft_deletes.next(), soft_deletes.next()) {
+            return Err(syn::Error::new(
+                f.ident.span(),
+                "only one field can be `#[sqlx_crud(soft_delete)]`",
+            ));
+        }
+        if let Some(f) = fields.iter().find(|f| f.soft_delete && f.id) {
+            return Err(syn::Error::new(
+                f.ident.span(),
+                "the `#[id]` field cannot be `#[sqlx_crud(soft_delete)]`",
+            ));
+        }
+        let mut tenants = fields.iter().filter(|f| f.tenant);
+        if let (Some(_), Some(f)) = (tenants.next(), tenants.next()) {
+            return Err(syn::Error::new(
+                f.ident.span(),
+                "only one field can be `#[sqlx_crud(tenant)]`",
+            ));
+        }
+        if let Some(f) = fields.iter().find(|f| f.tenant && f.id) {
+            return Err(syn::Error::new(
+                f.ident.span(),
+                "the `#[id]` field cannot be `#[sqlx_crud(tenant)]`",
+            ));
+        }
+
+        // A struct of only key columns has nothing for UPDATE to set, so
+        // update() is left out as with `except(update)`
+        if fields.iter().all(|f| f.id) && ops.update {
+            ops.update = false;
+            if ops.none() {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "a struct of only `#[id]` columns has nothing for update() to write, leaving no operation to generate",
+                ));
+            }
+        }
+
+        // Flattened fields may bring updatable columns of their own
+        if ops.update
+            && fields.iter().any(|f| !f.id)
+            && fields
+                .iter()
+                .all(|f| f.id || (!f.flatten && (f.readonly || f.created_at || f.generated)))
+        {
+            return Err(syn::Error::new(
+                ident.span(),
+                "every non-id field is `#[sqlx_crud(readonly)]`, leaving nothing for update() to write",
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn validate_embedded(fields: &[FieldConfig]) -> syn::Result<()> {
+        if let Some(f) = fields.iter().find(|f| f.find_by || f.indexed) {
+            return Err(syn::Error::new(
+                f.ident.span(),
+                "`find_by` and `indexed` are not supported on embedded fields, the finders are generated for the table struct",
+            ));
+        }
+        if let Some(f) = fields
+            .iter()
+            .find(|f| f.version || f.soft_delete || f.tenant)
+        {
+            return Err(syn::Error::new(
+                f.ident.span(),
+                "`version`, `soft_delete` and `tenant` are not supported on embedded fields",
+            ));
+        }
+        if let Some(f) = fields.iter().find(|f| f.flatten && f.id) {
+            return Err(syn::Error::new(
+                f.ident.span(),
+                "the key of an embedded struct cannot be flattened",
+            ));
+        }
+        if let Some(f) = fields.iter().find(|f| f.generated_id) {
+            return Err(syn::Error::new(
+                f.ident.span(),
+                "embedded keys are assigned outside of the database and cannot use `#[id(generated)]`",
+            ));
+        }
+        if let Some(f) = fields.iter().find(|f| f.uuid_id) {
+            return Err(syn::Error::new(
+                f.ident.span(),
+                "embedded keys are assigned by the struct they are flattened in to and cannot use `#[id(uuid)]`",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Types of the values bound for the fields. The values of `with` fields
+    /// are typed by their encode function and left out.
+    fn field_types(&self) -> impl Iterator<Item = Type> + '_ {
+        self.fields
+            .iter()
+            .filter(|f| !f.flatten && f.with.is_none())
+            .map(|f| f.bind_ty())
+    }
+
+    /// Types of the bound fields referring to one of the struct's type
+    /// parameters.
+    fn generic_field_types(&self) -> impl Iterator<Item = Type> + '_ {
+        let params = self
+            .generics
+            .type_params()
+            .map(|p| &p.ident)
+            .collect::<Vec<_>>();
+        self.field_types()
+            .filter(move |ty| mentions_ident(ty.to_token_stream(), &params))
+    }
+
+    /// The type of `f` mentions one of the struct's generic parameters.
+    fn is_generic(&self, f: &FieldConfig) -> bool {
+        let params = self
+            .generics
+            .params
+            .iter()
+            .map(|p| match p {
+                syn::GenericParam::Type(p) => &p.ident,
+                syn::GenericParam::Lifetime(p) => &p.lifetime.ident,
+                syn::GenericParam::Const(p) => &p.ident,
+            })
+            .collect::<Vec<_>>();
+        mentions_ident(f.ty.to_token_stream(), &params)
+    }
+
+    /// Fields making up the primary key, in column order.
+    fn id_fields(&self) -> impl Iterator<Item = &FieldConfig<'a>> {
+        self.fields.iter().filter(|f| f.id)
+    }
+
+    /// The `#[id]` field holding an embedded key.
+    fn flattened_id(&self) -> Option<&FieldConfig<'a>> {
+        self.id_fields().find(|f| f.flatten)
+    }
+
+    /// Composite keys are made up of more than one `#[id]` field. They are
+    /// always assigned outside of the database.
+    fn composite_id(&self) -> bool {
+        self.id_fields().count() > 1
+    }
+
+    /// The column of `f` is written by the INSERT statement. Embedded keys
+    /// are assigned outside of the database, UUID keys by `create()`.
+    fn inserted(&self, f: &FieldConfig) -> bool {
+        let external_id = self.embedded || self.external_id || self.composite_id() || f.uuid_id;
+        (external_id || !f.id) && !f.insert_default && !f.generated
+    }
+
+    /// The column of `f` is written by the UPDATE statement. The id columns
+    /// of embedded structs are left to the struct they are flattened in to.
+    fn updated(&self, f: &FieldConfig) -> bool {
+        (self.embedded || !f.id)
+            && !f.readonly
+            && !f.created_at
+            && !f.soft_delete
+            && !f.generated
+            && !f.tenant
+    }
+
+    /// Fields bound by the INSERT statement, in bind order.
+    fn insert_binds(&self) -> impl Iterator<Item = &FieldConfig<'a>> {
+        self.fields
+            .iter()
+            .filter(|f| f.flatten || (self.inserted(f) && f.sql_value().is_none()))
+    }
+
+    /// Fields bound by the SET clause of the UPDATE statement, in bind order.
+    /// The version column is incremented in SQL and bound in the WHERE
+    /// clause instead.
+    fn update_binds(&self) -> impl Iterator<Item = &FieldConfig<'a>> {
+        self.fields.iter().filter(|f| {
+            (f.flatten && !f.id) || (self.updated(f) && f.sql_value().is_none() && !f.version)
+        })
+    }
+
+    /// The `#[sqlx_crud(version)]` field used for optimistic locking.
+    fn version_field(&self) -> Option<&FieldConfig<'a>> {
+        self.fields.iter().find(|f| f.version)
+    }
+
+    /// Name of the generated `<Struct>Column` enum.
+    fn column_ident(&self) -> Ident {
+        format_ident!("{}Column", self.ident.unraw())
+    }
+
+    /// Name of the generated inherent method `name`, spanned to `span`.
+    fn method(&self, name: &str, span: proc_macro2::Span) -> Ident {
+        format_ident!("{}{}", self.method_prefix, name, span = span)
+    }
+
+    /// The `#[sqlx_crud(tenant)]` field every query is scoped to.
+    fn tenant_field(&self) -> Option<&FieldConfig<'a>> {
+        self.fields.iter().find(|f| f.tenant)
+    }
+}
+
+struct FieldConfig<'a> {
+    ident: &'a Ident,
+    ty: &'a Type,
+    column: String,
+    id: bool,
+    generated_id: bool,
+    /// The id is a UUID minted by `create()` when the field is `None`.
+    uuid_id: bool,
+    skip: bool,
+    flatten: bool,
+    insert_default: bool,
+    readonly: bool,
+    /// The column is computed by the database and only ever read.
+    generated: bool,
+    created_at: bool,
+    updated_at: bool,
+    version: bool,
+    soft_delete: bool,
+    none_as_default: bool,
+    find_by: bool,
+    indexed: bool,
+    /// Every query is scoped to the value of the column.
+    tenant: bool,
+    /// The value is bound serialized as JSON, see `#[sqlx_crud(json)]`.
+    json: bool,
+    /// Module encoding the value bound for the field, see
+    /// `#[sqlx_crud(with = "...")]`.
+    with: Option<Path>,
+    /// Type of the row the field references, see
+    /// `#[sqlx_crud(belongs_to = "...")]`.
+    belongs_to: Option<Path>,
+    /// SQL type of the column in the CREATE TABLE statement, see
+    /// `#[sqlx_crud(sql_type = "...")]`.
+    sql_type: Option<LitStr>,
+    sqlx: TokenStream2,
+}
+
+impl<'a> FieldConfig<'a> {
+    /// Moves the field value in to `args`. The tokens are spanned to the
+    /// field type so unbindable types are reported on the offending field.
+    fn bind_arg(&self) -> TokenStream2 {
+        self.bind_field(false)
+    }
+
+    /// Binds the field moved out of `self`, or borrowed from it when
+    /// `by_ref`. `with` modules encode a reference either way.
+    fn bind_field(&self, by_ref: bool) -> TokenStream2 {
+        let ident = self.ident;
+        let value = if !by_ref || self.with.is_some() {
+            quote! { self.#ident }
+        } else if self.json && is_option(self.ty) {
+            quote! { self.#ident.as_ref() }
+        } else {
+            quote! { &self.#ident }
+        };
+        let value = self.bind_value(value);
+        quote_spanned! { self.ty.span()=> args.add(#value); }
+    }
+
+    /// The value bound for the field borrowing the field `field`, as
+    /// [FieldConfig::bind_value] binds it by value.
+    fn bind_ref(&self, field: TokenStream2) -> TokenStream2 {
+        let sqlx = &self.sqlx;
+        if let Some(with) = &self.with {
+            quote_spanned! { with.span()=> #with::encode(&#field) }
+        } else if !self.json {
+            quote_spanned! { self.ty.span()=> &#field }
+        } else if is_option(self.ty) {
+            quote_spanned! { self.ty.span()=> #field.as_ref().map(#sqlx::types::Json) }
+        } else {
+            quote_spanned! { self.ty.span()=> #sqlx::types::Json(&#field) }
+        }
+    }
+
+    /// The value bound for the field given the field value `value`. JSON
+    /// fields are wrapped in `sqlx::types::Json`, `None` staying NULL.
+    /// `with` fields are bound as returned by the module's `encode()`.
+    fn bind_value(&self, value: TokenStream2) -> TokenStream2 {
+        let sqlx = &self.sqlx;
+        if let Some(with) = &self.with {
+            quote_spanned! { with.span()=> #with::encode(&#value) }
+        } else if !self.json {
+            value
+        } else if is_option(self.ty) {
+            quote_spanned! { self.ty.span()=> #value.map(#sqlx::types::Json) }
+        } else {
+            quote_spanned! { self.ty.span()=> #sqlx::types::Json(#value) }
+        }
+    }
+
+    /// Type of the value bound for the field.
+    fn bind_ty(&self) -> Type {
+        let sqlx = &self.sqlx;
+        let ty = self.ty;
+        match (self.json, option_inner(ty)) {
+            (false, _) => ty.clone(),
+            (true, Some(inner)) => {
+                parse_quote! { ::std::option::Option<#sqlx::types::Json<#inner>> }
+            }
+            (true, None) => parse_quote! { #sqlx::types::Json<#ty> },
+        }
+    }
+
+    /// The `SqlType` of the column, as set with `sql_type` or inferred from
+    /// the field type. `None` for types without an inferred SQL type.
+    fn sql_type(&self, crate_name: &TokenStream2) -> Option<TokenStream2> {
+        if let Some(name) = &self.sql_type {
+            return Some(quote! { #crate_name::schema::SqlType::Custom(#name) });
+        }
+        let variant = if self.json {
+            "Json"
+        } else if self.with.is_some() {
+            return None;
+        } else {
+            infer_sql_type(option_inner(self.ty).unwrap_or(self.ty))?
+        };
+        let variant = format_ident!("{}", variant);
+        Some(quote! { #crate_name::schema::SqlType::#variant })
+    }
+
+    /// Variant of the `<Struct>Column` enum naming the field's column.
+    fn column_variant(&self) -> Ident {
+        format_ident!(
+            "{}",
+            self.ident.unraw().to_string().to_pascal_case(),
+            span = self.ident.span()
+        )
+    }
+
+    /// Moves the field value, or the embedded insert values, in to `args`.
+    /// `args_mut` is the mutable reference passed on to embedded structs.
+    /// `None` values of `#[sqlx_crud(none_as_default)]` fields are left out.
+    fn insert_arg(
+        &self,
+        crate_name: &TokenStream2,
+        args_mut: &TokenStream2,
+        by_ref: bool,
+    ) -> TokenStream2 {
+        let ident = self.ident;
+        if self.flatten && by_ref {
+            quote_spanned! { self.ty.span()=>
+                #crate_name::traits::Embedded::insert_args_ref(&self.#ident, #args_mut);
+            }
+        } else if self.flatten {
+            quote_spanned! { self.ty.span()=>
+                #crate_name::traits::Embedded::insert_args(self.#ident, #args_mut);
+            }
+        } else if self.uuid_id {
+            quote_spanned! { self.ty.span()=>
+                args.add(self.#ident.or_else(|| {
+                    ::std::option::Option::Some(#crate_name::__new_uuid_v4!())
+                }));
+            }
+        } else if self.none_as_default {
+            let bind_arg = self.bind_field(by_ref);
+            quote! {
+                if self.#ident.is_some() {
+                    #bind_arg
+                }
+            }
+        } else {
+            self.bind_field(by_ref)
+        }
+    }
+
+    /// Moves the field value, or the embedded update values, in to `args`,
+    /// or borrows them when `by_ref`.
+    fn update_arg(
+        &self,
+        crate_name: &TokenStream2,
+        args_mut: &TokenStream2,
+        by_ref: bool,
+    ) -> TokenStream2 {
+        let ident = self.ident;
+        if self.flatten && by_ref {
+            quote_spanned! { self.ty.span()=>
+                #crate_name::traits::Embedded::update_args_ref(&self.#ident, false, #args_mut);
+            }
+        } else if self.flatten {
+            quote_spanned! { self.ty.span()=>
+                #crate_name::traits::Embedded::update_args(self.#ident, false, #args_mut);
+            }
+        } else {
+            self.bind_field(by_ref)
+        }
+    }
+
+    /// SQL expression written in place of a bind for generated values.
+    fn sql_value(&self) -> Option<&'static str> {
+        if self.created_at || self.updated_at {
+            Some("CURRENT_TIMESTAMP")
+        } else {
+            None
+        }
+    }
+
+    /// Flattened fields are bound by the embedded struct and left out of the
+    /// size hint, as are JSON and `with` fields only sized once encoded.
+    fn size_hint(&self, db_ty: &TokenStream2) -> TokenStream2 {
+        let sqlx = &self.sqlx;
+        let ident = self.ident;
+        if self.flatten || self.json || self.with.is_some() {
+            quote! { 0usize }
+        } else {
+            quote_spanned! { self.ty.span()=>
+                #sqlx::encode::Encode::<#db_ty>::size_hint(&self.#ident)
+            }
+        }
+    }
+
+    fn new(
+        field: &'a Field,
+        rename_all: Option<&RenameAll>,
+        sqlx: &TokenStream2,
+    ) -> syn::Result<Self> {
+        let ident = field.ident.as_ref().expect("a named field");
+
+        let mut id = false;
+        let mut generated_id = false;
+        let mut uuid_id = false;
+        let mut id_column: Option<LitStr> = None;
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("id")) {
+            if id {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    format!("`#[id]` specified more than once on `{}`", ident),
+                ));
+            }
+            id = true;
+            if let Meta::List(_) = attr.meta {
+                attr.parse_nested_meta(|m| {
+                    if m.path.is_ident("generated") {
+                        generated_id = true;
+                        Ok(())
+                    } else if m.path.is_ident("uuid") {
+                        if !is_option(&field.ty) {
+                            return Err(syn::Error::new(
+                                field.ty.span(),
+                                "`#[id(uuid)]` requires an `Option<Uuid>` field",
+                            ));
+                        }
+                        uuid_id = true;
+                        Ok(())
+                    } else if m.path.is_ident("column") {
+                        let name: LitStr = m.value()?.parse()?;
+                        if name.value().is_empty() {
+                            return Err(syn::Error::new(
+                                name.span(),
+                                "`column` must not be an empty string",
+                            ));
+                        }
+                        id_column = Some(name);
+                        Ok(())
+                    } else {
+                        Err(m.error("unsupported id attribute"))
+                    }
+                })?;
+            }
+        }
+        if generated_id && uuid_id {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`#[id(uuid)]` ids are assigned by `create()` and cannot be `generated`",
+            ));
+        }
+
+        // sqlx's own FromRow attributes
+        let mut flatten = false;
+        let mut sqlx_rename = None;
+        // Fields FromRow skips or defaults are not read from a column
+        let mut sqlx_skip = false;
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("sqlx")) {
+            if let Meta::List(list) = &attr.meta {
+                let metas = list.parse_args_with(Punctuated::<Meta, Comma>::parse_terminated)?;
+                for meta in &metas {
+                    match meta {
+                        Meta::Path(path) if path.is_ident("flatten") => flatten = true,
+                        Meta::Path(path) if path.is_ident("skip") || path.is_ident("default") => {
+                            sqlx_skip = true
+                        }
+                        Meta::NameValue(MetaNameValue {
+                            path,
+                            value:
+                                Expr::Lit(ExprLit {
+                                    lit: Lit::Str(name),
+                                    ..
+                                }),
+                            ..
+                        }) if path.is_ident("rename") => sqlx_rename = Some(name.clone()),
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        let mut column: Option<LitStr> = None;
+        // PhantomData fields never have a column
+        let mut skip = is_phantom_data(&field.ty);
+        let mut include = false;
+        let mut insert_default = false;
+        let mut readonly = false;
+        let mut generated = false;
+        let mut created_at = false;
+        let mut updated_at = false;
+        let mut version = false;
+        let mut soft_delete = false;
+        let mut none_as_default = false;
+        let mut find_by = false;
+        let mut indexed = false;
+        let mut tenant = false;
+        let mut json = false;
+        let mut with = None;
+        let mut belongs_to = None;
+        let mut sql_type = None;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|a| a.path().is_ident("sqlx_crud"))
+        {
+            attr.parse_nested_meta(|m| {
+                if m.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else if m.path.is_ident("include") {
+                    include = true;
+                    Ok(())
+                } else if flatten {
+                    Err(m.error(
+                        "only `skip` is supported on flattened fields, tag the fields of the embedded struct instead",
+                    ))
+                } else if m.path.is_ident("rename") {
+                    let name: LitStr = m.value()?.parse()?;
+                    if name.value().is_empty() {
+                        return Err(syn::Error::new(
+                            name.span(),
+                            "`rename` must not be an empty string",
+                        ));
+                    }
+                    column = Some(name);
+                    Ok(())
+                } else if m.path.is_ident("insert_default") {
+                    insert_default = true;
+                    Ok(())
+                } else if m.path.is_ident("readonly") {
+                    readonly = true;
+                    Ok(())
+                } else if m.path.is_ident("generated") {
+                    generated = true;
+                    Ok(())
+                } else if m.path.is_ident("created_at") {
+                    created_at = true;
+                    Ok(())
+                } else if m.path.is_ident("updated_at") {
+                    updated_at = true;
+                    Ok(())
+                } else if m.path.is_ident("version") {
+                    if !is_integer(&field.ty) {
+                        return Err(syn::Error::new(
+                            field.ty.span(),
+                            "`#[sqlx_crud(version)]` requires an integer field",
+                        ));
+                    }
+                    version = true;
+                    Ok(())
+                } else if m.path.is_ident("soft_delete") {
+                    if !is_option(&field.ty) {
+                        return Err(syn::Error::new(
+                            field.ty.span(),
+                            "`#[sqlx_crud(soft_delete)]` requires an `Option` field",
+                        ));
+                    }
+                    soft_delete = true;
+                    Ok(())
+                } else if m.path.is_ident("none_as_default") {
+                    if !is_option(&field.ty) {
+                        return Err(syn::Error::new(
+                            field.ty.span(),
+                            "`#[sqlx_crud(none_as_default)]` requires an `Option` field",
+                        ));
+                    }
+                    none_as_default = true;
+                    Ok(())
+                } else if m.path.is_ident("find_by") {
+                    find_by = true;
+                    Ok(())
+                } else if m.path.is_ident("indexed") {
+                    indexed = true;
+                    Ok(())
+                } else if m.path.is_ident("tenant") {
+                    if is_option(&field.ty) {
+                        return Err(syn::Error::new(
+                            field.ty.span(),
+                            "`#[sqlx_crud(tenant)]` cannot be an `Option` field, every row belongs to a tenant",
+                        ));
+                    }
+                    tenant = true;
+                    Ok(())
+                } else if m.path.is_ident("json") {
+                    json = true;
+                    Ok(())
+                } else if m.path.is_ident("with") {
+                    let module: LitStr = m.value()?.parse()?;
+                    with = Some(module.parse::<Path>()?);
+                    Ok(())
+                } else if m.path.is_ident("sql_type") {
+                    let name: LitStr = m.value()?.parse()?;
+                    if name.value().trim().is_empty() {
+                        return Err(syn::Error::new(
+                            name.span(),
+                            "`sql_type` must not be an empty string",
+                        ));
+                    }
+                    sql_type = Some(name);
+                    Ok(())
+                } else if m.path.is_ident("belongs_to") {
+                    let parent: LitStr = m.value()?.parse()?;
+                    // The accessor is named after the field without `_id`
+                    if !ident.unraw().to_string().ends_with("_id") {
+                        return Err(syn::Error::new(
+                            ident.span(),
+                            "`belongs_to` fields must be named `<relation>_id`, the accessor is named `<relation>`",
+                        ));
+                    }
+                    belongs_to = Some(parent.parse::<Path>()?);
+                    Ok(())
+                } else {
+                    Err(m.error("unsupported sqlx_crud field attribute"))
+                }
+            })?;
+        }
+        if include && skip {
+            return Err(syn::Error::new(
+                ident.span(),
+                "a field cannot be both `skip` and `include`",
+            ));
+        }
+        skip |= sqlx_skip && !include;
+
+        if created_at && updated_at {
+            return Err(syn::Error::new(
+                ident.span(),
+                "a field cannot be both `created_at` and `updated_at`",
+            ));
+        }
+        if generated
+            && (id
+                || insert_default
+                || created_at
+                || updated_at
+                || version
+                || soft_delete
+                || none_as_default)
+        {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`generated` columns are computed by the database and cannot be `#[id]`, `insert_default`, `created_at`, `updated_at`, `version`, `soft_delete` or `none_as_default`, use `#[id(generated)]` for database assigned keys",
+            ));
+        }
+        if none_as_default && (id || insert_default || created_at || updated_at) {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`none_as_default` cannot be combined with `#[id]`, `insert_default`, `created_at` or `updated_at`",
+            ));
+        }
+
+        if tenant
+            && (id
+                || insert_default
+                || generated
+                || created_at
+                || updated_at
+                || version
+                || soft_delete
+                || none_as_default
+                || find_by
+                || indexed
+                || json
+                || with.is_some())
+        {
+            return Err(syn::Error::new(
+                ident.span(),
+                "the `tenant` column is bound as is and cannot be combined with other column attributes",
+            ));
+        }
+
+        if json && (id || find_by || indexed) {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`json` fields cannot be `#[id]`, `find_by` or `indexed` columns",
+            ));
+        }
+
+        if with.is_some() && (id || json) {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`with` fields cannot be `#[id]` or `json` columns",
+            ));
+        }
+
+        // The finders sit next to the Crud methods
+        if find_by && ident.unraw() == "id" {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`find_by` on a field named `id` would shadow `Crud::by_id()`",
+            ));
+        }
+
+        if let (true, Some(id_column)) = (flatten, &id_column) {
+            return Err(syn::Error::new(
+                id_column.span(),
+                "a flattened `#[id]` takes its columns from the embedded struct and cannot set `column`",
+            ));
+        }
+
+        // Raw identifiers such as `r#type` name the column without the `r#`
+        // An explicit id column wins over the sqlx_crud rename. A rename for
+        // FromRow names the column too, unless it contradicts either
+        let (column, column_attr) = match id_column {
+            Some(id_column) => (Some(id_column), "id(column"),
+            None => (column, "sqlx_crud(rename"),
+        };
+        if let (Some(column), Some(sqlx_rename)) = (&column, &sqlx_rename) {
+            if column.value() != sqlx_rename.value() {
+                let mut error = syn::Error::new(
+                    column.span(),
+                    format!(
+                        "`#[{} = \"{}\")]` disagrees with `#[sqlx(rename = \"{}\")]`",
+                        column_attr,
+                        column.value(),
+                        sqlx_rename.value()
+                    ),
+                );
+                error.combine(syn::Error::new(
+                    sqlx_rename.span(),
+                    "FromRow reads the column renamed here",
+                ));
+                return Err(error);
+            }
+        }
+        let column = column
+            .or(sqlx_rename)
+            .map(|c| c.value())
+            .unwrap_or_else(|| {
+                let name = ident.unraw().to_string();
+                match rename_all {
+                    Some(rename_all) => rename_all.apply(&name),
+                    None => name,
+                }
+            });
+
+        Ok(Self {
+            ident,
+            ty: &field.ty,
+            column,
+            id,
+            generated_id,
+            uuid_id,
+            skip,
+            flatten,
+            insert_default,
+            readonly,
+            generated,
+            created_at,
+            updated_at,
+            version,
+            soft_delete,
+            none_as_default,
+            find_by,
+            indexed,
+            tenant,
+            json,
+            with,
+            belongs_to,
+            sql_type,
+            sqlx: sqlx.clone(),
+        })
+    }
+}
+
+/// Naming policy applied to field names to derive column names.
+enum RenameAll {
+    Lower,
+    Upper,
+    Camel,
+    Pascal,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+}
+
+impl RenameAll {
+    fn new(policy: &LitStr) -> syn::Result<Self> {
+        match policy.value().as_str() {
+            "lowercase" => Ok(Self::Lower),
+            "UPPERCASE" => Ok(Self::Upper),
+            "camelCase" => Ok(Self::Camel),
+            "PascalCase" => Ok(Self::Pascal),
+            "snake_case" => Ok(Self::Snake),
+            "SCREAMING_SNAKE_CASE" => Ok(Self::ScreamingSnake),
+            "kebab-case" => Ok(Self::Kebab),
+            other => Err(syn::Error::new(
+                policy.span(),
+                format!(
+                    "unknown `rename_all` policy `{}`. Available policies are `lowercase`, `UPPERCASE`, `camelCase`, `PascalCase`, `snake_case`, `SCREAMING_SNAKE_CASE`, `kebab-case`.",
+                    other
+                ),
+            )),
+        }
+    }
+
+    fn apply(&self, name: &str) -> String {
+        match self {
+            Self::Lower => name.to_lowercase(),
+            Self::Upper => name.to_uppercase(),
+            Self::Camel => name.to_camel_case(),
+            Self::Pascal => name.to_pascal_case(),
+            Self::Snake => name.to_snake_case(),
+            Self::ScreamingSnake => name.to_screaming_snake_case(),
+            Self::Kebab => name.to_kebab_case(),
+        }
+    }
+}
+
+/// Inflection deriving the table name from the struct name. Mirrors
+/// `sqlx_crud::naming::TableNaming`.
+enum TableNaming {
+    Plural,
+    Singular,
+    AsIs,
+}
+
+impl TableNaming {
+    fn new(strategy: &LitStr) -> syn::Result<Self> {
+        match strategy.value().as_str() {
+            "plural" => Ok(Self::Plural),
+            "singular" => Ok(Self::Singular),
+            "as_is" => Ok(Self::AsIs),
+            other => Err(syn::Error::new(
+                strategy.span(),
+                format!(
+                    "unknown `table_naming` strategy `{}`. Available strategies are `plural`, `singular`, `as_is`.",
+                    other
+                ),
+            )),
+        }
+    }
+
+    fn apply(&self, name: &str) -> String {
+        match self {
+            Self::Plural => name.to_table_case(),
+            Self::Singular => name.to_snake_case(),
+            Self::AsIs => name.to_string(),
+        }
+    }
+}
+
+/// CRUD operations generated for a struct, all of them unless narrowed with
+/// `#[sqlx_crud(only(...))]` or `#[sqlx_crud(except(...))]`.
+struct Operations {
+    create: bool,
+    read: bool,
+    update: bool,
+    delete: bool,
+}
+
+impl Operations {
+    fn all() -> Self {
+        Self {
+            create: true,
+            read: true,
+            update: true,
+            delete: true,
+        }
+    }
+
+    /// Sets the operations listed in `only(...)` or `except(...)` to
+    /// `enabled`.
+    fn parse(&mut self, meta: &syn::meta::ParseNestedMeta, enabled: bool) -> syn::Result<()> {
+        meta.parse_nested_meta(|op| {
+            let flag = if op.path.is_ident("create") {
+                &mut self.create
+            } else if op.path.is_ident("read") {
+                &mut self.read
+            } else if op.path.is_ident("update") {
+                &mut self.update
+            } else if op.path.is_ident("delete") {
+                &mut self.delete
+            } else {
+                return Err(op.error(
+                    "unknown operation. Available operations are `create`, `read`, `update`, `delete`.",
+                ));
+            };
+            *flag = enabled;
+            Ok(())
+        })
+    }
+
+    fn none(&self) -> bool {
+        !(self.create || self.read || self.update || self.delete)
+    }
+}
+
+/// Parses the optional `(derive(...))` of a container option generating a
+/// companion struct, such as `patch(derive(Deserialize))`, returning the
+/// listed derives.
+fn parse_derives(m: &syn::meta::ParseNestedMeta, option: &str) -> syn::Result<TokenStream2> {
+    let mut derives = TokenStream2::new();
+    if m.input.peek(syn::token::Paren) {
+        m.parse_nested_meta(|p| {
+            if p.path.is_ident("derive") {
+                let content;
+                syn::parenthesized!(content in p.input);
+                derives = content.parse()?;
+                Ok(())
+            } else {
+                Err(p.error(format!(
+                    "unsupported {} attribute, expected `derive(...)`",
+                    option
+                )))
+            }
+        })?;
+    }
+    Ok(derives)
+}
+
+/// Parses an `order_by = "..."` list of columns, each optionally followed by
+/// `ASC` or `DESC`, separated by commas. With `columns` the listed columns
+/// must be among them.
+fn parse_order_by(order_by: &LitStr, columns: Option<&[&str]>) -> syn::Result<Vec<(String, bool)>> {
+    let value = order_by.value();
+    let mut terms = Vec::new();
+    for term in value.split(',') {
+        let mut words = term.split_whitespace();
+        let column = match words.next() {
+            Some(column) => column,
+            None => {
+                return Err(syn::Error::new(
+                    order_by.span(),
+                    "`order_by` has an empty column",
+                ))
+            }
+        };
+        if let Some(columns) = columns.filter(|columns| !columns.contains(&column)) {
+            return Err(syn::Error::new(
+                order_by.span(),
+                format!(
+                    "`order_by` column `{}` is not a column of the struct, expected one of `{}`",
+                    column,
+                    columns.join("`, `")
+                ),
+            ));
+        }
+        let descending = match words.next() {
+            None => false,
+            Some(direction) if direction.eq_ignore_ascii_case("asc") => false,
+            Some(direction) if direction.eq_ignore_ascii_case("desc") => true,
+            Some(direction) => {
+                return Err(syn::Error::new(
+                    order_by.span(),
+                    format!(
+                        "unknown `order_by` direction `{}`, expected `ASC` or `DESC`",
+                        direction
+                    ),
+                ))
+            }
+        };
+        if let Some(extra) = words.next() {
+            return Err(syn::Error::new(
+                order_by.span(),
+                format!(
+                    "unexpected `{}` in `order_by`, separate columns with commas",
+                    extra
+                ),
+            ));
+        }
+        terms.push((column.to_string(), descending));
+    }
+    Ok(terms)
+}
+
+/// A `#[sqlx_crud(has_many(Child, foreign_key = "..."))]` relation, loading
+/// the `Child` rows whose foreign key holds the primary key of the struct.
+struct HasMany {
+    child: Path,
+    foreign_key: LitStr,
+    /// Name of the accessor, by default the plural snake_case of the child
+    /// type like its default table name.
+    method: Ident,
+    /// Columns of the child sorting the rows and whether they sort
+    /// descending. By primary key of the child when empty.
+    order_by: Vec<(String, bool)>,
+}
+
+impl HasMany {
+    fn parse(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Self> {
+        let mut child: Option<Path> = None;
+        let mut foreign_key = None;
+        let mut method = None;
+        let mut order_by = Vec::new();
+        meta.parse_nested_meta(|m| {
+            if m.path.is_ident("foreign_key") {
+                let column: LitStr = m.value()?.parse()?;
+                if column.value().is_empty() {
+                    return Err(syn::Error::new(column.span(), "`foreign_key` must not be an empty string"));
+                }
+                foreign_key = Some(column);
+                Ok(())
+            } else if m.path.is_ident("method") {
+                let name: LitStr = m.value()?.parse()?;
+                method = Some(name.parse::<Ident>()?);
+                Ok(())
+            } else if m.path.is_ident("order_by") {
+                order_by = parse_order_by(&m.value()?.parse()?, None)?;
+                Ok(())
+            } else if m.input.is_empty() || m.input.peek(Comma) {
+                if child.is_some() {
+                    return Err(m.error("`has_many` names a single related type"));
+                }
+                child = Some(m.path.clone());
+                Ok(())
+            } else {
+                Err(m.error("unsupported has_many attribute, expected the related type, `foreign_key`, `method` or `order_by`"))
+            }
+        })?;
+        let Some(child) = child else {
+            return Err(meta.error("`has_many(...)` must name the related type, as in `has_many(Post, foreign_key = \"user_id\")`"));
+        };
+        let Some(foreign_key) = foreign_key else {
+            return Err(
+                meta.error("`has_many(...)` requires the `foreign_key` column of the related type")
+            );
+        };
+        let method = method.unwrap_or_else(|| {
+            let name = child
+                .segments
+                .last()
+                .expect("a type name")
+                .ident
+                .unraw()
+                .to_string();
+            format_ident!("{}", TableNaming::Plural.apply(&name), span = child.span())
+        });
+        Ok(Self {
+            child,
+            foreign_key,
+            method,
+            order_by,
+        })
+    }
+}
+
+/// A `#[sqlx_crud(many_to_many(Other, through = "...", fk = "...", other_fk = "..."))]`
+/// relation, loading the `Other` rows joined to the struct through a join
+/// table.
+struct ManyToMany {
+    child: Path,
+    through: LitStr,
+    /// Column of the join table holding the primary key of the struct.
+    fk: LitStr,
+    /// Column of the join table holding the primary key of the other type.
+    other_fk: LitStr,
+    /// Name of the accessor, by default the plural snake_case of the other
+    /// type.
+    method: Ident,
+    /// Snake_case of the other type, naming `add_<name>()` and
+    /// `remove_<name>()`.
+    singular: String,
+}
+
+impl ManyToMany {
+    fn parse(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Self> {
+        let mut child: Option<Path> = None;
+        let mut through = None;
+        let mut fk = None;
+        let mut other_fk = None;
+        let mut method = None;
+        meta.parse_nested_meta(|m| {
+            let column = if m.path.is_ident("through") {
+                &mut through
+            } else if m.path.is_ident("fk") {
+                &mut fk
+            } else if m.path.is_ident("other_fk") {
+                &mut other_fk
+            } else if m.path.is_ident("method") {
+                let name: LitStr = m.value()?.parse()?;
+                method = Some(name.parse::<Ident>()?);
+                return Ok(());
+            } else if m.input.is_empty() || m.input.peek(Comma) {
+                if child.is_some() {
+                    return Err(m.error("`many_to_many` names a single related type"));
+                }
+                child = Some(m.path.clone());
+                return Ok(());
+            } else {
+                return Err(m.error(
+                    "unsupported many_to_many attribute, expected the related type, `through`, `fk`, `other_fk` or `method`",
+                ));
+            };
+            let name: LitStr = m.value()?.parse()?;
+            if name.value().is_empty() {
+                return Err(syn::Error::new(name.span(), "join table names must not be empty strings"));
+            }
+            *column = Some(name);
+            Ok(())
+        })?;
+        let Some(child) = child else {
+            return Err(meta.error(
+                "`many_to_many(...)` must name the related type, as in `many_to_many(Role, through = \"user_roles\", fk = \"user_id\", other_fk = \"role_id\")`",
+            ));
+        };
+        let (Some(through), Some(fk), Some(other_fk)) = (through, fk, other_fk) else {
+            return Err(meta.error(
+                "`many_to_many(...)` requires the `through` join table and its `fk` and `other_fk` columns",
+            ));
+        };
+        let name = child
+            .segments
+            .last()
+            .expect("a type name")
+            .ident
+            .unraw()
+            .to_string();
+        let method = method.unwrap_or_else(|| {
+            format_ident!("{}", TableNaming::Plural.apply(&name), span = child.span())
+        });
+        Ok(Self {
+            child,
+            through,
+            fk,
+            other_fk,
+            method,
+            singular: name.to_snake_case(),
+        })
+    }
+}
+
+fn is_phantom_data(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|s| s.ident == "PhantomData")
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// The type as written in the struct, without the spaces between tokens.
+fn type_name(ty: &Type) -> String {
+    let mut name = ty.to_token_stream().to_string();
+    for (spaced, unspaced) in [
+        (" :: ", "::"),
+        (":: ", "::"),
+        (" < ", "<"),
+        (" >", ">"),
+        (" ,", ","),
+        ("& ", "&"),
+    ] {
+        name = name.replace(spaced, unspaced);
+    }
+    name
+}
+
+/// The `SqlType` variant of the common scalar types, by the last segment
+/// of the type path.
+fn infer_sql_type(ty: &Type) -> Option<&'static str> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    let variant = match segment.ident.to_string().as_str() {
+        "bool" => "Boolean",
+        "i8" | "i16" | "u8" => "SmallInt",
+        "i32" | "u16" => "Integer",
+        "i64" | "u32" | "u64" => "BigInt",
+        "f32" => "Real",
+        "f64" => "Double",
+        "String" => "Text",
+        "Uuid" => "Uuid",
+        "NaiveDate" | "Date" => "Date",
+        "NaiveTime" | "Time" => "Time",
+        "NaiveDateTime" | "PrimitiveDateTime" => "Timestamp",
+        "DateTime" | "OffsetDateTime" => "TimestampTz",
+        "Json" | "JsonValue" => "Json",
+        "Vec" => match &segment.arguments {
+            syn::PathArguments::AngleBracketed(args) => match args.args.first() {
+                Some(syn::GenericArgument::Type(Type::Path(inner)))
+                    if inner.path.is_ident("u8") =>
+                {
+                    "Blob"
+                }
+                _ => return None,
+            },
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(variant)
+}
+
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|s| s.ident == "Option")
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// The `T` of an `Option<T>` type.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => match args.args.first() {
+            Some(syn::GenericArgument::Type(inner)) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn is_integer(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .get_ident()
+            .map(|i| {
+                ["i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64"]
+                    .contains(&i.to_string().as_str())
+            })
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+fn mentions_ident(tokens: TokenStream2, idents: &[&Ident]) -> bool {
+    tokens.into_iter().any(|tt| match tt {
+        TokenTree::Ident(i) => idents.contains(&&i),
+        TokenTree::Group(g) => mentions_ident(g.stream(), idents),
+        _ => false,
+    })
+}
+
+#[derive(Debug, PartialEq)]
+enum DbType {
+    Any,
+    Mssql,
+    MySql,
+    Postgres,
+    Sqlite,
+}
+
+const DB_TYPES: &str = "`Any`, `Mssql`, `MySql`, `Postgres`, `Sqlite`";
+
+impl DbType {
+    /// Databases listed in the `#[database(...)]` attribute. Several databases
+    /// can be listed from `MySql`, `Postgres` and `Sqlite`.
+    fn list(ident: &Ident, attrs: &[Attribute]) -> syn::Result<Vec<Self>> {
+        let mut db_types = Vec::new();
+        let mut database_attrs = attrs.iter().filter(|a| a.path().is_ident("database"));
+        let attr = database_attrs.next();
+        let Some(attr) = attr else {
+            return Err(syn::Error::new(
+                ident.span(),
+                format!(
+                    "missing `#[database(...)]` attribute; expected one of {}",
+                    DB_TYPES
+                ),
+            ));
+        };
+        if let Some(other) = database_attrs.next() {
+            return Err(syn::Error::new_spanned(
+                other,
+                "`#[database(...)]` is given more than once, list every database in one attribute",
+            ));
+        }
+        attr.parse_nested_meta(|m| {
+            let db_type = Self::parse(&m.path)?;
+            if db_types.contains(&db_type) {
+                return Err(m.error(format!("`{:?}` is listed more than once", db_type)));
+            }
+            db_types.push(db_type);
+            Ok(())
+        })?;
+
+        if db_types.is_empty() {
+            return Err(syn::Error::new_spanned(
+                attr,
+                format!(
+                    "`#[database(...)]` lists no database; expected one of {}",
+                    DB_TYPES
+                ),
+            ));
+        }
+        if db_types.len() > 1 {
+            if let Some(db_type) = db_types
+                .iter()
+                .find(|d| matches!(d, Self::Any | Self::Mssql))
+            {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    format!(
+                        "`{:?}` cannot be listed with other databases, choose from `MySql`, `Postgres` and `Sqlite`",
+                        db_type
+                    ),
+                ));
+            }
+        }
+        Ok(db_types)
+    }
+
+    fn parse(path: &Path) -> syn::Result<Self> {
+        let name = path.get_ident().map(|i| i.to_string());
+        match name.as_deref() {
+            Some("Any") => Ok(Self::Any),
+            Some("Mssql") => Ok(Self::Mssql),
+            Some("MySql") => Ok(Self::MySql),
+            Some("Postgres") => Ok(Self::Postgres),
+            Some("Sqlite") => Ok(Self::Sqlite),
+            _ => Err(syn::Error::new_spanned(
+                path,
+                format!(
+                    "unknown database `{}`; expected one of {}",
+                    path.to_token_stream().to_string().replace(' ', ""),
+                    DB_TYPES
+                ),
+            )),
+        }
+    }
+
+    /// Name of the sqlx-crud cargo feature enabling the database.
+    fn feature(&self) -> &'static str {
         match self {
-            Self::Any => quote! { ::sqlx::Any },
-            Self::Mssql => quote! { ::sqlx::Mssql },
-            Self::MySql => quote! { ::sqlx::MySql },
-            Self::Postgres => quote! { ::sqlx::Postgres },
-            Self::Sqlite => quote! { ::sqlx::Sqlite },
+            Self::Any => "any",
+            Self::Mssql => "mssql",
+            Self::MySql => "mysql",
+            Self::Postgres => "postgres",
+            Self::Sqlite => "sqlite",
         }
     }
 
-    fn quote_ident(&self, ident: &str) -> String {
+    /// Macro expanding its input only when the sqlx-crud feature of the
+    /// database is enabled.
+    fn feature_gate(&self, crate_name: &TokenStream2) -> TokenStream2 {
+        let gate = format_ident!("__with_{}", self.feature());
+        quote! { #crate_name::#gate }
+    }
+
+    fn sqlx_db(&self, sqlx: &TokenStream2) -> TokenStream2 {
         match self {
-            Self::Any => format!(r#""{}""#, &ident),
-            Self::Mssql => format!(r#""{}""#, &ident),
-            Self::MySql => format!("`{}`", &ident),
-            Self::Postgres => format!(r#""{}""#, &ident),
-            Self::Sqlite => format!(r#""{}""#, &ident),
+            Self::Any => quote! { #sqlx::Any },
+            Self::Mssql => quote! { #sqlx::Mssql },
+            Self::MySql => quote! { #sqlx::MySql },
+            Self::Postgres => quote! { #sqlx::Postgres },
+            Self::Sqlite => quote! { #sqlx::Sqlite },
         }
     }
 }
@@ -0,0 +1,18 @@
+// The statements of `debug_sql` are reported as warnings, denied here so
+// that they are recorded
+#![deny(deprecated)]
+
+extern crate db as sqlx;
+
+use crud::SqlxCrud;
+use db::FromRow;
+
+#[derive(FromRow, SqlxCrud)]
+#[database(Sqlite)]
+#[sqlx_crud(crate = "crud", sqlx = "db", debug_sql)]
+struct User {
+    id: i64,
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use crud::SqlxCrud;
+
+#[derive(SqlxCrud)]
+#[database(Sqlite)]
+struct User {
+    #[id]
+    #[id(generated)]
+    id: i64,
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use crud::SqlxCrud;
+
+#[derive(SqlxCrud)]
+#[database(Sqlite)]
+enum User {
+    Active { id: i64 },
+    Disabled { id: i64 },
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use crud::SqlxCrud;
+
+// Several `#[id]` fields form a composite key, which the database cannot
+// generate
+#[derive(SqlxCrud)]
+#[database(Sqlite)]
+struct Membership {
+    #[id]
+    user_id: i64,
+    #[id(generated)]
+    group_id: i64,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use crud::SqlxCrud;
+
+#[derive(SqlxCrud)]
+#[database(Sqlite)]
+#[sqlx_crud(has_many(Post))]
+struct User {
+    id: i64,
+    name: String,
+}
+
+fn main() {}
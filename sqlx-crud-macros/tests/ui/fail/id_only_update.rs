@@ -0,0 +1,10 @@
+use crud::SqlxCrud;
+
+#[derive(SqlxCrud)]
+#[database(Sqlite)]
+#[sqlx_crud(only(update))]
+struct Marker {
+    id: i64,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use crud::SqlxCrud;
+
+#[derive(SqlxCrud)]
+struct User {
+    id: i64,
+    name: String,
+}
+
+fn main() {}
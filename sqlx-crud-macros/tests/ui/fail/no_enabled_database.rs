@@ -0,0 +1,14 @@
+// The sqlx-crud features of the listed databases are not enabled
+extern crate db as sqlx;
+
+use crud::SqlxCrud;
+
+#[derive(SqlxCrud)]
+#[database(Sqlite, Postgres)]
+#[sqlx_crud(crate = "crud", sqlx = "db")]
+struct User {
+    id: i64,
+    name: String,
+}
+
+fn main() {}
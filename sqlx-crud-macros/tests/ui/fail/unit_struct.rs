@@ -0,0 +1,7 @@
+use crud::SqlxCrud;
+
+#[derive(SqlxCrud)]
+#[database(Sqlite)]
+struct User;
+
+fn main() {}
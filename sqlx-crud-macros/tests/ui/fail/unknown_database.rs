@@ -0,0 +1,10 @@
+use crud::SqlxCrud;
+
+#[derive(SqlxCrud)]
+#[database(Sqlite3)]
+struct User {
+    id: i64,
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,58 @@
+// Inherent methods named like the Crud methods coexist with the prefixed
+// forwarding methods
+
+// sqlx's FromRow derive refers to `::sqlx`
+extern crate db as sqlx;
+
+use crud::SqlxCrud;
+use db::sqlite::SqlitePoolOptions;
+use db::FromRow;
+
+#[derive(Debug, FromRow, SqlxCrud)]
+#[database(Sqlite)]
+#[sqlx_crud(crate = "crud", sqlx = "db", method_prefix = "db_")]
+struct User {
+    id: i64,
+    name: String,
+}
+
+impl User {
+    fn create(name: &str) -> Self {
+        User {
+            id: 0,
+            name: name.to_string(),
+        }
+    }
+
+    fn update(&mut self, name: &str) {
+        self.name = name.to_string();
+    }
+
+    fn delete(self) -> String {
+        self.name
+    }
+}
+
+fn main() {
+    tokio_test::block_on(async {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        db::query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let mut user = User::create("ada").db_create(&pool).await.unwrap();
+        user.update("grace");
+        let user = user.db_update(&pool).await.unwrap();
+        assert_eq!("grace", User::db_by_id(&pool, user.id).await.unwrap().unwrap().name);
+
+        // The trait methods stay reachable through the trait
+        let user = crud::Crud::update(user, &pool).await.unwrap();
+        assert_eq!("grace", User::db_by_id(&pool, 1).await.unwrap().unwrap().delete());
+        user.db_delete(&pool).await.unwrap();
+        assert!(User::db_all(&pool).await.unwrap().is_empty());
+    });
+}
@@ -0,0 +1,65 @@
+// sqlx and sqlx-crud are only reachable through a facade module, as when
+// they are re-exported by another crate or renamed in Cargo.toml
+mod facade {
+    pub use crud as sqlx_crud;
+    pub use db as sqlx;
+}
+
+use facade::sqlx::sqlite::{SqlitePoolOptions, SqliteRow};
+use facade::sqlx::{FromRow, Row};
+use facade::sqlx_crud::{Crud, Schema, SqlxCrud};
+
+#[derive(SqlxCrud)]
+#[database(Sqlite)]
+#[sqlx_crud(crate = "crate::facade::sqlx_crud", sqlx = "crate::facade::sqlx")]
+struct User {
+    id: i64,
+    #[sqlx_crud(find_by)]
+    name: String,
+    #[sqlx_crud(created_at)]
+    created_at: String,
+}
+
+// sqlx's FromRow derive refers to `::sqlx` itself
+impl<'r> FromRow<'r, SqliteRow> for User {
+    fn from_row(row: &'r SqliteRow) -> Result<Self, facade::sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+fn main() -> Result<(), facade::sqlx_crud::Error> {
+    tokio_test::block_on(async {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect(":memory:")
+            .await?;
+        facade::sqlx::query(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL, created_at TEXT NOT NULL)",
+        )
+        .execute(&pool)
+        .await?;
+
+        assert_eq!(&["id", "name", "created_at"], User::columns());
+
+        let mut user = User {
+            id: 0,
+            name: "ferris".to_string(),
+            created_at: String::new(),
+        }
+        .create(&pool)
+        .await?;
+        assert!(!user.created_at.is_empty());
+
+        user.name = "corro".to_string();
+        let user = user.update(&pool).await?;
+        assert_eq!(user.id, User::by_name(&pool, "corro").await?.unwrap().id);
+
+        user.delete(&pool).await?;
+        assert!(User::all(&pool).await?.is_empty());
+        Ok(())
+    })
+}
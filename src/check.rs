@@ -0,0 +1,255 @@
+//! Checks of the generated statements against a live database.
+//!
+//! The generated SQL is built from the struct, so a column renamed in the
+//! database only fails when the statement runs. [check_statements] has the
+//! database describe statements without running them, failing with
+//! [Error::Check] for the first statement the database rejects.
+//!
+//! The `#[sqlx_crud(checked)]` struct attribute generates a `#[test]`
+//! checking the queries of the struct against the database at `DATABASE_URL`,
+//! so a struct drifting from its table fails `cargo test`. The test passes
+//! without checking when `DATABASE_URL` is unset, when it names a database the
+//! struct is not derived for, or when `SQLX_OFFLINE` is `true` or `1`. The
+//! statements are not in sqlx's `.sqlx` query cache, so offline builds cannot
+//! check them.
+//!
+//! [Crud::verify] compares the columns of a struct with the table in the
+//! database catalog instead, for a check at startup, and reports the
+//! differences in a [SchemaMismatch].
+//!
+//! # Example
+//!
+//! ```rust
+//! # sqlx_crud::doctest_setup! { |pool| {
+//! use sqlx_crud::check::check_statements;
+//! use sqlx_crud::{Error, Schema};
+//!
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! #[sqlx_crud(checked)]
+//! struct Account {
+//!     id: i64,
+//!     name: String,
+//! }
+//!
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! #[sqlx_crud(table_name = "accounts")]
+//! struct RenamedAccount {
+//!     id: i64,
+//!     display_name: String,
+//! }
+//!
+//! sqlx::query("CREATE TABLE accounts (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+//!     .execute(&pool)
+//!     .await?;
+//!
+//! check_statements(&pool, Account::table_name(), &[Account::select_sql(), Account::insert_sql()]).await?;
+//!
+//! let result = check_statements(&pool, RenamedAccount::table_name(), &[RenamedAccount::update_by_id_sql()]).await;
+//! assert!(matches!(result, Err(Error::Check { table_name: "accounts", .. })));
+//! assert!(result.unwrap_err().to_string().contains("display_name"));
+//! # }}
+//! ```
+//!
+//! [Crud::verify]: ../traits/trait.Crud.html#method.verify
+
+use std::fmt;
+
+use sqlx::{Database, Executor, Pool};
+
+use crate::schema::Metadata;
+use crate::traits::DatabaseSchema;
+use crate::Error;
+
+/// Differences between the columns of a struct and its table, returned by
+/// [Crud::verify] as [Error::SchemaMismatch]. The `Display` output lists
+/// every difference, for logging before refusing to start.
+///
+/// [Crud::verify]: ../traits/trait.Crud.html#method.verify
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaMismatch {
+    /// Name of the table, qualified by the database schema if any.
+    pub table_name: String,
+    /// Columns of the struct the table does not have, in field order. All
+    /// columns are missing when the table does not exist.
+    pub missing: Vec<&'static str>,
+    /// Columns of the table the struct does not have, in table order.
+    pub extra: Vec<String>,
+    /// Columns whose nullability differs, in field order.
+    pub nullability: Vec<NullabilityMismatch>,
+}
+
+/// A column of [SchemaMismatch::nullability], nullable on one side only.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NullabilityMismatch {
+    pub column: &'static str,
+    /// The field is an `Option`.
+    pub field_nullable: bool,
+    /// The column accepts NULL.
+    pub column_nullable: bool,
+}
+
+impl SchemaMismatch {
+    /// Compares the columns of `metadata` with the `(name, nullable)` rows
+    /// of [Metadata::catalog_sql], `nullable` being `YES` or `NO`. The
+    /// nullability of primary key columns is not compared, as SQLite reports
+    /// undeclared `INTEGER PRIMARY KEY` columns as nullable.
+    pub(crate) fn compare(
+        metadata: &Metadata,
+        catalog: &[(String, String)],
+        ignore_case: bool,
+    ) -> Result<(), Self> {
+        let same = |a: &str, b: &str| {
+            if ignore_case {
+                a.eq_ignore_ascii_case(b)
+            } else {
+                a == b
+            }
+        };
+        let mut mismatch = Self {
+            table_name: metadata.qualified_table_name.clone(),
+            missing: Vec::new(),
+            extra: Vec::new(),
+            nullability: Vec::new(),
+        };
+        for column in &metadata.columns {
+            match catalog.iter().find(|(name, _)| same(name, column)) {
+                Some((_, nullable)) if !metadata.id_columns.contains(column) => {
+                    let field_nullable = metadata.nullable_columns.contains(column);
+                    let column_nullable = nullable.eq_ignore_ascii_case("YES");
+                    if field_nullable != column_nullable {
+                        mismatch.nullability.push(NullabilityMismatch {
+                            column,
+                            field_nullable,
+                            column_nullable,
+                        });
+                    }
+                }
+                Some(_) => {}
+                None => mismatch.missing.push(column),
+            }
+        }
+        mismatch.extra = catalog
+            .iter()
+            .filter(|(name, _)| !metadata.columns.iter().any(|c| same(name, c)))
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        if mismatch.missing.is_empty()
+            && mismatch.extra.is_empty()
+            && mismatch.nullability.is_empty()
+        {
+            Ok(())
+        } else {
+            Err(mismatch)
+        }
+    }
+}
+
+impl fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut differences = Vec::new();
+        if !self.missing.is_empty() {
+            differences.push(format!("missing columns {}", self.missing.join(", ")));
+        }
+        if !self.extra.is_empty() {
+            differences.push(format!("extra columns {}", self.extra.join(", ")));
+        }
+        for n in &self.nullability {
+            differences.push(if n.column_nullable {
+                format!(
+                    "column {} is nullable but its field is not an Option",
+                    n.column
+                )
+            } else {
+                format!("column {} is NOT NULL but its field is an Option", n.column)
+            });
+        }
+        write!(
+            f,
+            "{} does not match the database: {}",
+            self.table_name,
+            differences.join("; ")
+        )
+    }
+}
+
+impl std::error::Error for SchemaMismatch {}
+
+/// Has the database describe each of `statements`, which prepares them
+/// without running them. Returns [Error::Check] for the first statement the
+/// database rejects, such as one naming a column the table does not have.
+pub async fn check_statements<'c, E>(
+    executor: E,
+    table_name: &'static str,
+    statements: &[&str],
+) -> Result<(), Error>
+where
+    E: Executor<'c> + Copy,
+{
+    for sql in statements {
+        if let Err(source) = executor.describe(sql).await {
+            return Err(Error::Check {
+                table_name,
+                sql: sql.to_string(),
+                source,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// The URL of the database the `#[sqlx_crud(checked)]` tests run against:
+/// `DATABASE_URL`, unless `SQLX_OFFLINE` is `true` or `1`.
+pub fn database_url() -> Option<String> {
+    let offline = std::env::var("SQLX_OFFLINE").is_ok_and(|v| v == "true" || v == "1");
+    if offline {
+        return None;
+    }
+    std::env::var("DATABASE_URL").ok()
+}
+
+/// Checks the statements of `T` against the database at `url`, if it is a
+/// `DB` database. Run by the tests generated for `#[sqlx_crud(checked)]`,
+/// which pass the operations `T` supports.
+#[doc(hidden)]
+pub fn run<DB, T>(url: &str, create: bool, update: bool, delete: bool) -> Result<(), Error>
+where
+    DB: Database,
+    T: DatabaseSchema<DB>,
+    for<'c> &'c Pool<DB>: Executor<'c, Database = DB>,
+{
+    let scheme = url.split(':').next().unwrap_or_default();
+    if !DB::URL_SCHEMES.contains(&scheme) {
+        return Ok(());
+    }
+
+    let metadata = T::database_metadata();
+    let mut statements = vec![
+        metadata.select_all_sql.as_str(),
+        metadata.select_by_id_sql.as_str(),
+        metadata.select_by_id_for_update_sql.as_str(),
+        metadata.exists_sql.as_str(),
+    ];
+    statements.extend(metadata.select_by_sql.values().map(String::as_str));
+    statements.extend(metadata.select_all_by_sql.values().map(String::as_str));
+    if create {
+        statements.push(&metadata.insert_sql);
+    }
+    if update {
+        statements.push(&metadata.update_by_id_sql);
+        statements.extend(metadata.audit_update_sql.as_deref());
+    }
+    if delete {
+        statements.push(&metadata.delete_by_id_sql);
+        statements.extend(metadata.audit_delete_sql.as_deref());
+    }
+
+    sqlx::test_block_on(async {
+        let pool = Pool::<DB>::connect(url).await?;
+        let result = check_statements(&pool, metadata.table_name, &statements).await;
+        pool.close().await;
+        result
+    })
+}
@@ -0,0 +1,199 @@
+//! Object safe access to the tables of several structs, for tooling such as
+//! an admin or debug endpoint written once for every registered model.
+//!
+//! The [Crud] methods are generic and cannot be called through `dyn`.
+//! [DynCrud] offers a reduced set of them with the row type erased: keys are
+//! passed as strings and parsed with [FromStr], and rows are returned as
+//! [serde_json::Value]. A [Model] of a struct implements [DynCrud] for the
+//! pool of a database, so models can be stored together as
+//! `Box<dyn DynCrud<DB>>`.
+//!
+//! Requires the `dynamic` feature.
+//!
+//! [Crud]: ../traits/trait.Crud.html
+//! [FromStr]: https://doc.rust-lang.org/std/str/trait.FromStr.html
+
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use futures::TryFutureExt;
+use serde::Serialize;
+use sqlx::database::HasArguments;
+use sqlx::{Database, Executor, FromRow, IntoArguments, Pool};
+
+use crate::error::Error;
+use crate::traits::{Crud, CrudFut, Deletable, Readable, Schema, Unaudited, Unscoped};
+
+/// Table level operations of a struct, callable through `dyn DynCrud<DB>`.
+///
+/// Implemented by [Model] for every [Crud] struct that is [Serialize] and
+/// whose key implements [FromStr]. Keys that fail to parse are reported as
+/// [Error::InvalidId] before any query runs.
+///
+/// # Example
+///
+/// ```rust
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use serde::Serialize;
+/// use sqlx::Sqlite;
+/// use sqlx_crud::dynamic::{DynCrud, Model};
+///
+/// #[derive(Debug, FromRow, Serialize, SqlxCrud)]
+/// #[database(Sqlite)]
+/// struct Country {
+///     code: String,
+///     name: String,
+/// }
+///
+/// #[derive(Debug, FromRow, Serialize, SqlxCrud)]
+/// #[database(Sqlite)]
+/// struct Post {
+///     id: i64,
+///     title: String,
+/// }
+///
+/// sqlx::query("CREATE TABLE countries (code TEXT PRIMARY KEY NOT NULL, name TEXT NOT NULL)")
+///     .execute(&pool)
+///     .await?;
+/// sqlx::query("INSERT INTO countries (code, name) VALUES ('fr', 'France')")
+///     .execute(&pool)
+///     .await?;
+/// sqlx::query("CREATE TABLE posts (id INTEGER PRIMARY KEY NOT NULL, title TEXT NOT NULL)")
+///     .execute(&pool)
+///     .await?;
+/// sqlx::query("INSERT INTO posts (id, title) VALUES (7, 'Hello')")
+///     .execute(&pool)
+///     .await?;
+///
+/// let models: Vec<Box<dyn DynCrud<Sqlite>>> = vec![Box::new(Model::<Country>::new()), Box::new(Model::<Post>::new())];
+/// for model in &models {
+///     assert_eq!(1, model.count(&pool).await?);
+/// }
+///
+/// let posts = &models[1];
+/// assert_eq!("posts", posts.table_name());
+/// assert_eq!(
+///     Some(serde_json::json!({ "id": 7, "title": "Hello" })),
+///     posts.by_id_json(&pool, "7").await?
+/// );
+/// assert_eq!(None, posts.by_id_json(&pool, "8").await?);
+/// assert!(matches!(posts.exists(&pool, "seven").await, Err(sqlx_crud::Error::InvalidId { .. })));
+///
+/// assert!(models[0].delete_by_id(&pool, "fr").await?);
+/// assert!(!models[0].exists(&pool, "fr").await?);
+/// # }}
+/// ```
+///
+/// [Crud]: ../traits/trait.Crud.html
+/// [Serialize]: https://docs.rs/serde/latest/serde/trait.Serialize.html
+/// [FromStr]: https://doc.rust-lang.org/std/str/trait.FromStr.html
+pub trait DynCrud<DB: Database>: Send + Sync {
+    /// Returns the name of the table.
+    fn table_name(&self) -> &'static str;
+
+    /// Counts the rows of the table, like [Crud::count].
+    ///
+    /// [Crud::count]: ../traits/trait.Crud.html#method.count
+    fn count<'e>(&self, pool: &'e Pool<DB>) -> CrudFut<'e, u64>;
+
+    /// Returns whether a row has the key `id`, like [Crud::exists].
+    ///
+    /// [Crud::exists]: ../traits/trait.Crud.html#method.exists
+    fn exists<'e>(&self, pool: &'e Pool<DB>, id: &str) -> CrudFut<'e, bool>;
+
+    /// Deletes the row with the key `id` and returns whether there was one,
+    /// like [Crud::delete_by_id].
+    ///
+    /// [Crud::delete_by_id]: ../traits/trait.Crud.html#method.delete_by_id
+    fn delete_by_id<'e>(&self, pool: &'e Pool<DB>, id: &str) -> CrudFut<'e, bool>;
+
+    /// Looks up the row with the key `id` like [Crud::by_id] and returns it
+    /// serialized as JSON, or `None` if there is no such row.
+    ///
+    /// [Crud::by_id]: ../traits/trait.Crud.html#method.by_id
+    fn by_id_json<'e>(
+        &self,
+        pool: &'e Pool<DB>,
+        id: &str,
+    ) -> CrudFut<'e, Option<serde_json::Value>>;
+}
+
+/// The [DynCrud] implementation of the `T` struct.
+pub struct Model<T>(PhantomData<fn() -> T>);
+
+impl<T> Model<T> {
+    /// Returns the model of `T`.
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Default for Model<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Model<T>
+where
+    T: Schema,
+    <T as Schema>::Id: FromStr,
+{
+    /// Parses `id` as a key of `T`, or returns [Error::InvalidId].
+    fn parse_id(id: &str) -> Result<<T as Schema>::Id, Error> {
+        id.parse().map_err(|_| Error::InvalidId {
+            table_name: T::table_name(),
+            id: id.to_string(),
+        })
+    }
+}
+
+impl<T, DB> DynCrud<DB> for Model<T>
+where
+    DB: Database,
+    T: for<'e> Crud<'e, &'e Pool<DB>> + Unscoped + Readable + Deletable + Unaudited + Serialize,
+    <T as Schema>::Id: FromStr,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'e> <DB as HasArguments<'e>>::Arguments: IntoArguments<'e, DB>,
+    (i64,): for<'r> FromRow<'r, DB::Row>,
+{
+    fn table_name(&self) -> &'static str {
+        T::table_name()
+    }
+
+    fn count<'e>(&self, pool: &'e Pool<DB>) -> CrudFut<'e, u64> {
+        T::count(pool)
+    }
+
+    fn exists<'e>(&self, pool: &'e Pool<DB>, id: &str) -> CrudFut<'e, bool> {
+        match Self::parse_id(id) {
+            Ok(id) => T::exists(pool, id),
+            Err(e) => Box::pin(futures::future::err(e)),
+        }
+    }
+
+    fn delete_by_id<'e>(&self, pool: &'e Pool<DB>, id: &str) -> CrudFut<'e, bool> {
+        match Self::parse_id(id) {
+            Ok(id) => T::delete_by_id(pool, id),
+            Err(e) => Box::pin(futures::future::err(e)),
+        }
+    }
+
+    fn by_id_json<'e>(
+        &self,
+        pool: &'e Pool<DB>,
+        id: &str,
+    ) -> CrudFut<'e, Option<serde_json::Value>> {
+        match Self::parse_id(id) {
+            Ok(id) => Box::pin(T::by_id(pool, id).and_then(|row| async move {
+                row.map(serde_json::to_value)
+                    .transpose()
+                    .map_err(|source| Error::Json {
+                        table_name: T::table_name(),
+                        source: source.into(),
+                    })
+            })),
+            Err(e) => Box::pin(futures::future::err(e)),
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use crate::traits::Schema;
+
+/// Errors returned by the [Crud] methods.
+///
+/// Database errors are passed through unchanged as [Error::Sqlx]. The other
+/// variants report conditions detected by sqlx-crud itself. New variants may
+/// be added in minor releases, so matches need a wildcard arm.
+///
+/// [Crud]: ../traits/trait.Crud.html
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// An error returned by sqlx.
+    #[error(transparent)]
+    Sqlx(#[from] sqlx::Error),
+
+    /// The row was modified or deleted since it was loaded. Returned by
+    /// [Crud::update] and [Crud::delete] for structs with a
+    /// `#[sqlx_crud(version)]` column when the stored version no longer
+    /// matches the version of the instance.
+    ///
+    /// [Crud::update]: ../traits/trait.Crud.html#method.update
+    /// [Crud::delete]: ../traits/trait.Crud.html#method.delete
+    #[error("{table_name} row was modified or deleted since it was loaded")]
+    StaleVersion { table_name: &'static str },
+
+    /// The instance failed its [Validate] check. Returned by [Crud::create]
+    /// and [Crud::update] for structs with `#[sqlx_crud(validate)]` before
+    /// any query runs.
+    ///
+    /// [Validate]: ../traits/trait.Validate.html
+    /// [Crud::create]: ../traits/trait.Crud.html#method.create
+    /// [Crud::update]: ../traits/trait.Crud.html#method.update
+    #[error("{table_name} row is invalid: {source}")]
+    Validation {
+        table_name: &'static str,
+        source: ValidationError,
+    },
+
+    /// A [CrudHooks] hook of a struct with `#[sqlx_crud(hooks)]` failed.
+    /// A failed `before_*` hook aborts the operation before any query runs,
+    /// a failed `after_*` hook is reported after the query.
+    ///
+    /// [CrudHooks]: ../traits/trait.CrudHooks.html
+    #[error("{hook} hook of {table_name} failed: {source}")]
+    Hook {
+        table_name: &'static str,
+        /// Name of the failed hook method, such as `before_create`.
+        hook: &'static str,
+        source: HookError,
+    },
+
+    /// There is no row with the primary key, with the id formatted with
+    /// `Debug`. Returned by [Crud::try_by_id], and by the methods writing a
+    /// row by its key such as [Crud::update] and [Crud::delete] when the row
+    /// no longer exists.
+    ///
+    /// [Crud::try_by_id]: ../traits/trait.Crud.html#method.try_by_id
+    /// [Crud::update]: ../traits/trait.Crud.html#method.update
+    /// [Crud::delete]: ../traits/trait.Crud.html#method.delete
+    #[error("no {table_name} row with id {id}")]
+    NotFound {
+        table_name: &'static str,
+        id: String,
+    },
+
+    /// The record built by [Crud::find_or_create] has another primary key
+    /// than the one looked up, with both formatted with `Debug`. Returned
+    /// before the record is inserted.
+    ///
+    /// [Crud::find_or_create]: ../traits/trait.Crud.html#method.find_or_create
+    #[error("{table_name} row built for id {id} has id {found}")]
+    IdMismatch {
+        table_name: &'static str,
+        id: String,
+        found: String,
+    },
+
+    /// The rows passed to [Crud::create_many] leave different
+    /// `#[sqlx_crud(none_as_default)]` columns to their defaults, which a
+    /// single INSERT statement cannot express. Returned before any query
+    /// runs.
+    ///
+    /// [Crud::create_many]: ../traits/trait.Crud.html#method.create_many
+    #[error("{table_name} rows inserted together leave different columns to their defaults")]
+    MixedDefaults { table_name: &'static str },
+
+    /// The [Filter] passed to [Crud::delete_where] or [Crud::update_where]
+    /// has no conditions and would match every row. Returned before any
+    /// query runs, [Crud::delete_all] deletes every row.
+    ///
+    /// [Crud::delete_all]: ../traits/trait.Crud.html#method.delete_all
+    /// [Crud::delete_where]: ../traits/trait.Crud.html#method.delete_where
+    /// [Crud::update_where]: ../traits/trait.Crud.html#method.update_where
+    /// [Filter]: ../filter/struct.Filter.html
+    #[error("filter of {table_name} has no conditions")]
+    EmptyFilter { table_name: &'static str },
+
+    /// A column set by [Crud::update_where] or incremented by
+    /// [Crud::increment] is not written by [Crud::update], such as the
+    /// primary key or a column written by the database. Returned before any
+    /// query runs.
+    ///
+    /// [Crud::increment]: ../traits/trait.Crud.html#method.increment
+    /// [Crud::update]: ../traits/trait.Crud.html#method.update
+    /// [Crud::update_where]: ../traits/trait.Crud.html#method.update_where
+    #[error("{column} is not an updated column of {table_name}")]
+    ColumnNotUpdatable {
+        table_name: &'static str,
+        column: &'static str,
+    },
+
+    /// The page requested from [Crud::paginated] or a [Select] has a `limit`
+    /// that is not positive or a negative `offset`. Returned before any
+    /// query runs.
+    ///
+    /// [Crud::paginated]: ../traits/trait.Crud.html#method.paginated
+    /// [Select]: ../select/struct.Select.html
+    #[error("invalid page of {table_name}: limit {limit} must be positive and offset {offset} not negative")]
+    InvalidPage {
+        table_name: &'static str,
+        limit: i64,
+        offset: i64,
+    },
+
+    /// The database rejected a generated statement when checked with
+    /// [check_statements], usually because the struct no longer matches the
+    /// table.
+    ///
+    /// [check_statements]: ../check/fn.check_statements.html
+    #[error("{table_name} statement does not match the database: {source}\n{sql}")]
+    Check {
+        table_name: &'static str,
+        /// The rejected statement.
+        sql: String,
+        source: sqlx::Error,
+    },
+
+    /// The columns of the struct differ from the table in the database
+    /// catalog. Returned by [Crud::verify].
+    ///
+    /// [Crud::verify]: ../traits/trait.Crud.html#method.verify
+    #[error(transparent)]
+    SchemaMismatch(#[from] crate::check::SchemaMismatch),
+
+    /// A key passed as a string to a [DynCrud] method does not parse as a
+    /// key of the table. Returned before any query runs.
+    ///
+    /// [DynCrud]: ../dynamic/trait.DynCrud.html
+    #[error("invalid {table_name} id {id:?}")]
+    InvalidId {
+        table_name: &'static str,
+        id: String,
+    },
+
+    /// A row read by [DynCrud::by_id_json] could not be serialized as JSON.
+    /// The source is the error of the serializer.
+    ///
+    /// [DynCrud::by_id_json]: ../dynamic/trait.DynCrud.html#tymethod.by_id_json
+    #[error("{table_name} row could not be serialized: {source}")]
+    Json {
+        table_name: &'static str,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+impl Error {
+    /// Returns [Error::NotFound] for the row of `T` with the key `id`, which
+    /// may be borrowed in place of a `T::Id`.
+    pub(crate) fn not_found<T: Schema + ?Sized>(id: &dyn std::fmt::Debug) -> Self {
+        Error::NotFound {
+            table_name: T::table_name(),
+            id: format!("{:?}", id),
+        }
+    }
+}
+
+/// Error returned by a [CrudHooks] hook.
+///
+/// [CrudHooks]: ../traits/trait.CrudHooks.html
+pub type HookError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A failed [Validate] check, naming the offending field if there is one.
+///
+/// [Validate]: ../traits/trait.Validate.html
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("{}{}", .field.map(|f| format!("{f}: ")).unwrap_or_default(), .message)]
+pub struct ValidationError {
+    /// The field that failed the check.
+    pub field: Option<&'static str>,
+    /// Describes the failed check.
+    pub message: String,
+}
+
+impl ValidationError {
+    /// A failed check of the instance as a whole.
+    pub fn new(message: impl Into<String>) -> Self {
+        ValidationError {
+            field: None,
+            message: message.into(),
+        }
+    }
+
+    /// A failed check of a single field.
+    pub fn field(field: &'static str, message: impl Into<String>) -> Self {
+        ValidationError {
+            field: Some(field),
+            message: message.into(),
+        }
+    }
+}
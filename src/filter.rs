@@ -0,0 +1,573 @@
+//! Conditions on the columns of a table, for statements matching more than
+//! one row without writing SQL.
+//!
+//! A [Filter] is built from the generated `<Struct>Column` enum and binds
+//! its values as it goes, so the values are never part of the statement.
+//! The conditions are joined with AND. [Crud::delete_where] deletes the rows
+//! a filter matches, [Crud::update_where] writes the [Assignments] to them
+//! and [Crud::count_where] counts them.
+//!
+//! The [TypedColumn] constants of the column enum build filters checking
+//! the values against the field types, such as `UserColumn::name.eq("bob")`.
+//!
+//! [Crud::count_where]: ../traits/trait.Crud.html#method.count_where
+//! [Crud::delete_where]: ../traits/trait.Crud.html#method.delete_where
+//! [Crud::update_where]: ../traits/trait.Crud.html#method.update_where
+
+use std::marker::PhantomData;
+
+use sqlx::database::HasArguments;
+use sqlx::{Arguments, Database, Encode, Type};
+
+use crate::schema::{bind_limit, padded_len};
+use crate::traits::ColumnName;
+
+/// The conditions of a [Filter], each naming its unquoted column. The SQL is
+/// built by [Metadata::delete_where_sql], which quotes and qualifies the
+/// columns.
+///
+/// [Metadata::delete_where_sql]: ../schema/struct.Metadata.html#method.delete_where_sql
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Condition {
+    Eq(&'static str),
+    Ne(&'static str),
+    Lt(&'static str),
+    Le(&'static str),
+    Gt(&'static str),
+    Ge(&'static str),
+    /// The column matches a bound `LIKE` pattern.
+    Like(&'static str),
+    IsNull(&'static str),
+    /// The column equals one of a number of bound values.
+    InList(&'static str, usize),
+}
+
+impl Condition {
+    /// Returns the condition on `column`, the quoted column name, with `?`
+    /// placeholders. An empty `InList` matches no rows.
+    pub(crate) fn sql(&self, column: &str) -> String {
+        match self {
+            Condition::Eq(_) => format!("{} = ?", column),
+            Condition::Ne(_) => format!("{} <> ?", column),
+            Condition::Lt(_) => format!("{} < ?", column),
+            Condition::Le(_) => format!("{} <= ?", column),
+            Condition::Gt(_) => format!("{} > ?", column),
+            Condition::Ge(_) => format!("{} >= ?", column),
+            Condition::Like(_) => format!("{} LIKE ?", column),
+            Condition::IsNull(_) => format!("{} IS NULL", column),
+            Condition::InList(_, 0) => "1 = 0".to_string(),
+            Condition::InList(_, n) => format!("{} IN ({})", column, vec!["?"; *n].join(", ")),
+        }
+    }
+
+    /// Returns the unquoted name of the column of the condition.
+    pub fn column(&self) -> &'static str {
+        match self {
+            Condition::Eq(c)
+            | Condition::Ne(c)
+            | Condition::Lt(c)
+            | Condition::Le(c)
+            | Condition::Gt(c)
+            | Condition::Ge(c)
+            | Condition::Like(c)
+            | Condition::IsNull(c)
+            | Condition::InList(c, _) => c,
+        }
+    }
+}
+
+/// Conditions on the `C` columns of a table joined with AND, with their
+/// values bound for the `DB` database in condition order.
+///
+/// Statements run with a filter are built once per distinct list of
+/// conditions and kept for the life of the program, like
+/// [Metadata::patch_sql]. The values are not part of the conditions, and
+/// the length of an IN list is rounded up by [Filter::in_list], so the
+/// statements of a filter built by the same code are few. A filter without conditions would match every row
+/// and is refused by [Crud::delete_where], which returns
+/// [Error::EmptyFilter] instead.
+///
+/// # Example
+///
+/// ```rust
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use sqlx::Sqlite;
+/// use sqlx_crud::filter::{Condition, Filter};
+///
+/// let filter = Filter::<Sqlite, _>::new()
+///     .eq(UserColumn::Name, "test")
+///     .in_list(UserColumn::UserId, [1, 2, 3]);
+/// assert_eq!(
+///     &[Condition::Eq("name"), Condition::InList("user_id", 4)],
+///     filter.conditions()
+/// );
+/// # }}
+/// ```
+///
+/// [Crud::delete_where]: ../traits/trait.Crud.html#method.delete_where
+/// [Error::EmptyFilter]: ../error/enum.Error.html#variant.EmptyFilter
+/// [Metadata::patch_sql]: ../schema/struct.Metadata.html#method.patch_sql
+pub struct Filter<'q, DB: Database, C: ColumnName> {
+    conditions: Vec<Condition>,
+    binds: Binds<'q, DB>,
+    column: PhantomData<C>,
+}
+
+/// Values bound once the statement is known, so the values of several
+/// builders are bound in statement order.
+type Binds<'q, DB> = Vec<Box<dyn FnOnce(&mut <DB as HasArguments<'q>>::Arguments) + Send + 'q>>;
+
+fn bind<'q, DB, V>(binds: &mut Binds<'q, DB>, value: V)
+where
+    DB: Database,
+    V: 'q + Send + Encode<'q, DB> + Type<DB>,
+{
+    binds.push(Box::new(move |args| args.add(value)));
+}
+
+impl<'q, DB: Database, C: ColumnName> Filter<'q, DB, C> {
+    /// Returns a filter without conditions.
+    pub fn new() -> Self {
+        Self {
+            conditions: Vec::new(),
+            binds: Vec::new(),
+            column: PhantomData,
+        }
+    }
+
+    /// Matches the rows whose `column` equals `value`.
+    pub fn eq<V>(self, column: C, value: V) -> Self
+    where
+        V: 'q + Send + Encode<'q, DB> + Type<DB>,
+    {
+        self.bound(Condition::Eq(column.name()), value)
+    }
+
+    /// Matches the rows whose `column` differs from `value`. Rows where the
+    /// column is NULL are not matched.
+    pub fn ne<V>(self, column: C, value: V) -> Self
+    where
+        V: 'q + Send + Encode<'q, DB> + Type<DB>,
+    {
+        self.bound(Condition::Ne(column.name()), value)
+    }
+
+    /// Matches the rows whose `column` is less than `value`.
+    pub fn lt<V>(self, column: C, value: V) -> Self
+    where
+        V: 'q + Send + Encode<'q, DB> + Type<DB>,
+    {
+        self.bound(Condition::Lt(column.name()), value)
+    }
+
+    /// Matches the rows whose `column` is less than or equal to `value`.
+    pub fn le<V>(self, column: C, value: V) -> Self
+    where
+        V: 'q + Send + Encode<'q, DB> + Type<DB>,
+    {
+        self.bound(Condition::Le(column.name()), value)
+    }
+
+    /// Matches the rows whose `column` is greater than `value`.
+    pub fn gt<V>(self, column: C, value: V) -> Self
+    where
+        V: 'q + Send + Encode<'q, DB> + Type<DB>,
+    {
+        self.bound(Condition::Gt(column.name()), value)
+    }
+
+    /// Matches the rows whose `column` is greater than or equal to `value`.
+    pub fn ge<V>(self, column: C, value: V) -> Self
+    where
+        V: 'q + Send + Encode<'q, DB> + Type<DB>,
+    {
+        self.bound(Condition::Ge(column.name()), value)
+    }
+
+    /// Matches the rows whose `column` matches the `LIKE` pattern `value`,
+    /// where `%` stands for any text and `_` for any character. SQLite and
+    /// the default collations of MySQL match ASCII letters case
+    /// insensitively, PostgreSQL case sensitively.
+    pub fn like<V>(self, column: C, value: V) -> Self
+    where
+        V: 'q + Send + Encode<'q, DB> + Type<DB>,
+    {
+        self.bound(Condition::Like(column.name()), value)
+    }
+
+    /// Matches the rows whose `column` is NULL.
+    pub fn is_null(mut self, column: C) -> Self {
+        self.conditions.push(Condition::IsNull(column.name()));
+        self
+    }
+
+    /// Matches the rows whose `column` equals one of `values`. No rows are
+    /// matched if `values` is empty. The list is rounded up to a power of
+    /// two values, or to the [bind_limit] of the database, by binding the
+    /// last value again, so lists of any length share a few statements.
+    ///
+    /// [bind_limit]: ../schema/fn.bind_limit.html
+    pub fn in_list<V, I>(mut self, column: C, values: I) -> Self
+    where
+        V: 'q + Clone + Send + Encode<'q, DB> + Type<DB>,
+        I: IntoIterator<Item = V>,
+    {
+        let values = values.into_iter().collect::<Vec<_>>();
+        let n = match values.len() {
+            0 => 0,
+            len => padded_len(len, bind_limit::<DB>().max(len)),
+        };
+        if let Some(last) = values.last().cloned() {
+            let padding = std::iter::repeat_n(last, n - values.len());
+            values
+                .into_iter()
+                .chain(padding)
+                .for_each(|value| bind(&mut self.binds, value));
+        }
+        self.conditions.push(Condition::InList(column.name(), n));
+        self
+    }
+
+    /// Adds the conditions of `other` after those of the filter.
+    pub fn and(mut self, other: Self) -> Self {
+        self.conditions.extend(other.conditions);
+        self.binds.extend(other.binds);
+        self
+    }
+
+    /// Returns the conditions, in the order they were added.
+    pub fn conditions(&self) -> &[Condition] {
+        &self.conditions
+    }
+
+    /// Returns true if the filter has no conditions.
+    pub fn is_empty(&self) -> bool {
+        self.conditions.is_empty()
+    }
+
+    /// Returns the bound values, for the statement built from
+    /// [Filter::conditions].
+    pub fn into_arguments(self) -> <DB as HasArguments<'q>>::Arguments {
+        let mut args = Default::default();
+        self.bind_to(&mut args);
+        args
+    }
+
+    /// Binds the values to `args`, following any bound before.
+    pub(crate) fn bind_to(self, args: &mut <DB as HasArguments<'q>>::Arguments) {
+        self.binds.into_iter().for_each(|bind| bind(args));
+    }
+
+    fn bound<V>(mut self, condition: Condition, value: V) -> Self
+    where
+        V: 'q + Send + Encode<'q, DB> + Type<DB>,
+    {
+        bind(&mut self.binds, value);
+        self.conditions.push(condition);
+        self
+    }
+}
+
+impl<DB: Database, C: ColumnName> Default for Filter<'_, DB, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Values written to the `C` columns of a table by [Crud::update_where],
+/// bound for the `DB` database in the order they were set.
+///
+/// Only the columns written by [Crud::update] can be set: not the primary
+/// key, nor the columns written by the database such as the version.
+///
+/// [Crud::update]: ../traits/trait.Crud.html#method.update
+/// [Crud::update_where]: ../traits/trait.Crud.html#method.update_where
+pub struct Assignments<'q, DB: Database, C: ColumnName> {
+    columns: Vec<&'static str>,
+    binds: Binds<'q, DB>,
+    column: PhantomData<C>,
+}
+
+impl<'q, DB: Database, C: ColumnName> Assignments<'q, DB, C> {
+    /// Returns assignments without columns.
+    pub fn new() -> Self {
+        Self {
+            columns: Vec::new(),
+            binds: Vec::new(),
+            column: PhantomData,
+        }
+    }
+
+    /// Writes `value` to `column`.
+    pub fn set<V>(mut self, column: C, value: V) -> Self
+    where
+        V: 'q + Send + Encode<'q, DB> + Type<DB>,
+    {
+        bind(&mut self.binds, value);
+        self.columns.push(column.name());
+        self
+    }
+
+    /// Returns the unquoted names of the set columns, in the order they were
+    /// set.
+    pub fn columns(&self) -> &[&'static str] {
+        &self.columns
+    }
+
+    /// Returns true if no column is set.
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    /// Binds the values to `args`, following any bound before.
+    pub(crate) fn bind_to(self, args: &mut <DB as HasArguments<'q>>::Arguments) {
+        self.binds.into_iter().for_each(|bind| bind(args));
+    }
+}
+
+impl<DB: Database, C: ColumnName> Default for Assignments<'_, DB, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A column of the generated `<Struct>Column` enum with the type `F` of its
+/// field, building [Filter]s whose values are checked against the field type
+/// at compile time.
+///
+/// The derive macro generates a constant of each column on the column enum,
+/// named after the field: `UserColumn::name` is the typed column of
+/// `UserColumn::Name`. Values are converted in to the field type with
+/// `Into`, so a `&str` filters a `String` column and a plain value an
+/// `Option` column. The columns of flattened fields and of fields with a
+/// `with` module have no typed column.
+///
+/// A condition comparing a column with NULL is never true: [TypedColumn::eq]
+/// with `None` matches no rows, [TypedColumn::ne] and the other comparisons
+/// never match rows where the column is NULL. Match those rows with
+/// [TypedColumn::is_null].
+///
+/// # Example
+///
+/// ```rust
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use sqlx::Sqlite;
+/// use sqlx_crud::filter::{Condition, Filter};
+///
+/// let filter: Filter<Sqlite, _> = UserColumn::name.like("te%").and(UserColumn::user_id.ge(1));
+/// assert_eq!(&[Condition::Like("name"), Condition::Ge("user_id")], filter.conditions());
+/// # }}
+/// ```
+///
+/// Values of another type do not compile:
+///
+/// ```rust,compile_fail
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use sqlx::Sqlite;
+/// use sqlx_crud::filter::Filter;
+///
+/// let filter: Filter<Sqlite, _> = UserColumn::name.eq(18_i64);
+/// # }}
+/// ```
+pub struct TypedColumn<C, F> {
+    column: C,
+    value: PhantomData<fn() -> F>,
+}
+
+impl<C: ColumnName, F> TypedColumn<C, F> {
+    /// The column `column` holding values of type `F`.
+    pub const fn new(column: C) -> Self {
+        Self {
+            column,
+            value: PhantomData,
+        }
+    }
+
+    /// Returns the untyped column.
+    pub fn column(&self) -> C {
+        self.column
+    }
+
+    /// Matches the rows where the column equals `value`.
+    pub fn eq<'q, DB, V>(self, value: V) -> Filter<'q, DB, C>
+    where
+        DB: Database,
+        V: Into<F>,
+        F: 'q + Send + Encode<'q, DB> + Type<DB>,
+    {
+        Filter::new().eq(self.column, value.into())
+    }
+
+    /// Matches the rows where the column differs from `value`.
+    pub fn ne<'q, DB, V>(self, value: V) -> Filter<'q, DB, C>
+    where
+        DB: Database,
+        V: Into<F>,
+        F: 'q + Send + Encode<'q, DB> + Type<DB>,
+    {
+        Filter::new().ne(self.column, value.into())
+    }
+
+    /// Matches the rows where the column is less than `value`.
+    pub fn lt<'q, DB, V>(self, value: V) -> Filter<'q, DB, C>
+    where
+        DB: Database,
+        V: Into<F>,
+        F: 'q + Send + Encode<'q, DB> + Type<DB>,
+    {
+        Filter::new().lt(self.column, value.into())
+    }
+
+    /// Matches the rows where the column is less than or equal to `value`.
+    pub fn le<'q, DB, V>(self, value: V) -> Filter<'q, DB, C>
+    where
+        DB: Database,
+        V: Into<F>,
+        F: 'q + Send + Encode<'q, DB> + Type<DB>,
+    {
+        Filter::new().le(self.column, value.into())
+    }
+
+    /// Matches the rows where the column is greater than `value`.
+    pub fn gt<'q, DB, V>(self, value: V) -> Filter<'q, DB, C>
+    where
+        DB: Database,
+        V: Into<F>,
+        F: 'q + Send + Encode<'q, DB> + Type<DB>,
+    {
+        Filter::new().gt(self.column, value.into())
+    }
+
+    /// Matches the rows where the column is greater than or equal to
+    /// `value`.
+    pub fn ge<'q, DB, V>(self, value: V) -> Filter<'q, DB, C>
+    where
+        DB: Database,
+        V: Into<F>,
+        F: 'q + Send + Encode<'q, DB> + Type<DB>,
+    {
+        Filter::new().ge(self.column, value.into())
+    }
+
+    /// Matches the rows where the column matches the `LIKE` pattern
+    /// `value`, see [Filter::like].
+    pub fn like<'q, DB, V>(self, value: V) -> Filter<'q, DB, C>
+    where
+        DB: Database,
+        V: Into<F>,
+        F: 'q + Send + Encode<'q, DB> + Type<DB>,
+    {
+        Filter::new().like(self.column, value.into())
+    }
+
+    /// Matches the rows where the column is NULL.
+    pub fn is_null<'q, DB: Database>(self) -> Filter<'q, DB, C> {
+        Filter::new().is_null(self.column)
+    }
+}
+
+impl<C: Clone, F> Clone for TypedColumn<C, F> {
+    fn clone(&self) -> Self {
+        Self {
+            column: self.column.clone(),
+            value: PhantomData,
+        }
+    }
+}
+
+impl<C: Copy, F> Copy for TypedColumn<C, F> {}
+
+/// Numeric field types, whose columns can be incremented by
+/// [Crud::increment] and [Increments].
+///
+/// [Crud::increment]: ../traits/trait.Crud.html#method.increment
+pub trait Numeric {}
+
+macro_rules! numeric {
+    ($($ty:ty),*) => {
+        $(impl Numeric for $ty {})*
+    };
+}
+
+numeric!(i8, i16, i32, i64, u8, u16, u32, u64, f32, f64);
+
+impl<T: Numeric> Numeric for Option<T> {}
+
+/// Deltas added to the numeric `C` columns of a row by
+/// [Crud::increment_columns], relative to the stored values.
+///
+/// # Example
+///
+/// ```rust
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use sqlx_crud::filter::Increments;
+///
+/// let increments = Increments::new().add(UserColumn::user_id, 2).add(UserColumn::user_id, -1);
+/// assert_eq!(&["user_id"], increments.columns());
+/// assert_eq!(&[1], increments.deltas());
+/// # }}
+/// ```
+///
+/// Only numeric columns can be incremented:
+///
+/// ```rust,compile_fail
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use sqlx_crud::filter::Increments;
+///
+/// let increments = Increments::new().add(UserColumn::name, 1);
+/// # }}
+/// ```
+///
+/// [Crud::increment_columns]: ../traits/trait.Crud.html#method.increment_columns
+#[derive(Clone, Debug)]
+pub struct Increments<C: ColumnName> {
+    columns: Vec<&'static str>,
+    deltas: Vec<i64>,
+    column: PhantomData<C>,
+}
+
+impl<C: ColumnName> Increments<C> {
+    /// Returns increments without columns.
+    pub fn new() -> Self {
+        Self {
+            columns: Vec::new(),
+            deltas: Vec::new(),
+            column: PhantomData,
+        }
+    }
+
+    /// Adds `delta` to `column`, on top of any delta added to it before.
+    pub fn add<F: Numeric>(mut self, column: TypedColumn<C, F>, delta: i64) -> Self {
+        let name = column.column().name();
+        match self.columns.iter().position(|c| *c == name) {
+            Some(i) => self.deltas[i] += delta,
+            None => {
+                self.columns.push(name);
+                self.deltas.push(delta);
+            }
+        }
+        self
+    }
+
+    /// Returns the unquoted names of the incremented columns, in the order
+    /// they were added.
+    pub fn columns(&self) -> &[&'static str] {
+        &self.columns
+    }
+
+    /// Returns the delta of each of [Increments::columns].
+    pub fn deltas(&self) -> &[i64] {
+        &self.deltas
+    }
+
+    /// Returns true if no column is incremented.
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+}
+
+impl<C: ColumnName> Default for Increments<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
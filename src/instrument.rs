@@ -0,0 +1,67 @@
+//! Spans around the [Crud] methods, enabled by the `tracing` feature.
+//!
+//! [Crud]: ../traits/trait.Crud.html
+
+use std::fmt::Debug;
+
+use tracing::{Instrument, Span};
+
+use crate::traits::CrudFut;
+
+/// The [Crud] method a span is named after.
+///
+/// [Crud]: ../traits/trait.Crud.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    Create,
+    CreateMany,
+    CreateManyReturning,
+    CloneRow,
+    CreateIgnore,
+    Upsert,
+    ById,
+    ByIdForUpdate,
+    Exists,
+    Update,
+    UpdateWhere,
+    Delete,
+    DeleteMany,
+    DeleteWhere,
+    DeleteAll,
+    Truncate,
+}
+
+/// Id recorded on a span. The `id` field is recorded when the id type
+/// implements `Debug` and left empty otherwise: `(&&IdField(id)).record_id()`
+/// resolves to [RecordDebugId] first and falls back to [RecordNoId].
+pub struct IdField<'a, T: ?Sized>(pub &'a T);
+
+pub trait RecordDebugId {
+    fn record_id(&self, span: &Span);
+}
+
+impl<T: Debug + ?Sized> RecordDebugId for &IdField<'_, T> {
+    fn record_id(&self, span: &Span) {
+        span.record("id", tracing::field::debug(self.0));
+    }
+}
+
+pub trait RecordNoId {
+    fn record_id(&self, _span: &Span) {}
+}
+
+impl<T: ?Sized> RecordNoId for IdField<'_, T> {}
+
+/// Runs `future` in `span`, recording a failed query as an error event.
+pub(crate) fn traced<'e, T: 'e>(span: Span, future: CrudFut<'e, T>) -> CrudFut<'e, T> {
+    Box::pin(
+        async move {
+            let result = future.await;
+            if let Err(e) = &result {
+                tracing::error!(error = %e, "query failed");
+            }
+            result
+        }
+        .instrument(span),
+    )
+}
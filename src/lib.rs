@@ -15,17 +15,1127 @@
 //!
 //! # Design Considerations
 //!
-//! The code currently assumes identifiers are assigned outside of the database.
-//! This likely means the identifier is a UUID. Database generated IDs will be
-//! added in a subsequent release.
+//! By default identifiers are assigned by the database. The id column is left
+//! out of the INSERT statement and [Crud::create] returns the stored row,
+//! including the assigned id, using `RETURNING`. The `#[id(generated)]` field
+//! attribute states this explicitly. Identifiers assigned outside of the
+//! database, such as UUIDs, are supported with the `#[external_id]` struct
+//! attribute, which includes the id column in the INSERT statement.
 //!
 //! The primary key for the table can be indicated by use of the [sqlx_crud_macros::SqlxCrud]
 //! `#[id]` field attribute. If no field is tagged as the [sqlx_crud_macros::SqlxCrud] `#[id]`
-//! then the first field in the struct is assumed to be the ID.
+//! then the first field in the struct is assumed to be the ID. Tagging several
+//! fields with `#[id]` declares a composite primary key, see [Schema::id_columns].
+//!
+//! The table name is inferred from the plural _snake_case_ of the struct name.
+//! The `#[sqlx_crud(table_naming = "...")]` struct attribute switches to the
+//! singular or the struct name as is, see [naming::TableNaming]. The name can
+//! be set explicitly with the `#[sqlx_crud(table_name = "...")]` struct
+//! attribute.
+//! Tables outside of the default database schema are qualified with the
+//! `#[sqlx_crud(schema = "...")]` struct attribute, see [Schema::schema_name].
+//!
+//! Column names default to the field names and can be changed per field with
+//! sqlx's `#[sqlx(rename = "...")]` field attribute, which [FromRow] reads as
+//! well, or the `#[sqlx_crud(rename = "...")]` field attribute.
+//!
+//! Columns with a unique constraint can be tagged with the
+//! `#[sqlx_crud(find_by)]` field attribute. For each tagged field the derive
+//! adds a `by_<field>()` method looking up a row like [Crud::by_id], and the
+//! `select_by_<field>_sql()` query it runs. The value can be of any type
+//! bindable to the column, such as `&str` for a `String` field.
+//!
+//! ```rust
+//! # sqlx_crud::doctest_setup! { |pool| {
+//! use sqlx_crud::Crud;
+//!
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! struct Account {
+//!     id: i64,
+//!     #[sqlx_crud(find_by)]
+//!     username: String,
+//!     #[sqlx_crud(find_by)]
+//!     email: String,
+//! }
+//!
+//! sqlx::query("CREATE TABLE accounts (id INTEGER PRIMARY KEY, username TEXT NOT NULL UNIQUE, email TEXT NOT NULL UNIQUE)")
+//!     .execute(&pool)
+//!     .await?;
+//! let account = Account {
+//!     id: 0,
+//!     username: "ferris".to_string(),
+//!     email: "ferris@example.com".to_string(),
+//! };
+//! account.create(&pool).await?;
+//!
+//! let account = Account::by_username(&pool, "ferris").await?.unwrap();
+//! assert_eq!("ferris@example.com", account.email);
+//! assert!(Account::by_email(&pool, "nobody@example.com").await?.is_none());
+//! assert_eq!(
+//!     r#"SELECT "accounts"."id", "accounts"."username", "accounts"."email" FROM "accounts" WHERE "accounts"."email" = ? LIMIT 1"#,
+//!     Account::select_by_email_sql()
+//! );
+//! # }}
+//! ```
+//!
+//! Foreign keys and other indexed columns can be tagged with the
+//! `#[sqlx_crud(indexed)]` field attribute. The derive then adds
+//! `all_by_<field>()`, resolving to all rows with the given value, and
+//! `stream_by_<field>()`, streaming them, along with the
+//! `select_all_by_<field>_sql()` query. Rows are ordered by primary key,
+//! unless set otherwise with [Schema::order_by_sql].
+//!
+//! ```rust
+//! # sqlx_crud::doctest_setup! { |pool| {
+//! use futures::stream::TryStreamExt;
+//! use sqlx_crud::Crud;
+//!
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! #[external_id]
+//! struct Post {
+//!     id: i64,
+//!     #[sqlx_crud(indexed)]
+//!     user_id: i32,
+//!     title: String,
+//! }
+//!
+//! sqlx::query("CREATE TABLE posts (id INTEGER PRIMARY KEY, user_id INTEGER NOT NULL, title TEXT NOT NULL)")
+//!     .execute(&pool)
+//!     .await?;
+//! for (id, user_id) in [(3, 1), (1, 1), (2, 2)] {
+//!     Post { id, user_id, title: format!("post {}", id) }.create(&pool).await?;
+//! }
+//!
+//! let posts = Post::all_by_user_id(&pool, 1).await?;
+//! assert_eq!(vec![1, 3], posts.iter().map(|p| p.id).collect::<Vec<_>>());
+//!
+//! let mut stream = Post::stream_by_user_id(&pool, 2);
+//! assert_eq!("post 2", stream.try_next().await?.unwrap().title);
+//! assert!(stream.try_next().await?.is_none());
+//! # }}
+//! ```
+//!
+//! A finder cannot replace an existing method, so `find_by` is rejected on
+//! `#[id]` fields and on a field named `id`:
+//!
+//! ```rust,compile_fail
+//! # use sqlx::FromRow;
+//! # use sqlx_crud::SqlxCrud;
+//! #[derive(FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! struct Account {
+//!     #[id]
+//!     account_id: i64,
+//!     #[sqlx_crud(find_by)]
+//!     id: i64,
+//! }
+//! ```
+//!
+//! A foreign key field named `<relation>_id` can be tagged with the
+//! `#[sqlx_crud(belongs_to = "...")]` field attribute naming the type of the
+//! referenced row. The derive adds a `<relation>()` method looking up that row
+//! with its [Crud::by_id], so the referenced type must derive [SqlxCrud] for
+//! the same database with an id of the field type. An `Option` key resolves
+//! to `None` without a query when it is `None`.
+//!
+//! ```rust
+//! # sqlx_crud::doctest_setup! { |pool| {
+//! use sqlx_crud::Crud;
+//!
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! struct Comment {
+//!     id: i64,
+//!     #[sqlx_crud(belongs_to = "User")]
+//!     user_id: i32,
+//!     #[sqlx_crud(belongs_to = "User")]
+//!     reviewer_id: Option<i32>,
+//!     body: String,
+//! }
+//!
+//! sqlx::query("CREATE TABLE comments (id INTEGER PRIMARY KEY, user_id INTEGER NOT NULL, reviewer_id INTEGER, body TEXT NOT NULL)")
+//!     .execute(&pool)
+//!     .await?;
+//! let comment = Comment { id: 1, user_id: 1, reviewer_id: None, body: "first".to_string() }
+//!     .create(&pool)
+//!     .await?;
+//!
+//! let user = comment.user(&pool).await?.unwrap();
+//! assert_eq!(1, user.user_id);
+//! assert!(comment.reviewer(&pool).await?.is_none());
+//! # }}
+//! ```
+//!
+//! The referenced type is checked when the struct is compiled:
+//!
+//! ```rust,compile_fail
+//! # use sqlx::FromRow;
+//! # use sqlx_crud::SqlxCrud;
+//! #[derive(FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! struct Comment {
+//!     id: i64,
+//!     #[sqlx_crud(belongs_to = "Usr")]
+//!     user_id: i32,
+//! }
+//! ```
+//!
+//! In the other direction, the `#[sqlx_crud(has_many(...))]` struct attribute
+//! names a type whose `foreign_key` column holds the primary key of the
+//! struct. The derive adds an accessor resolving to those rows, ordered by
+//! the columns of `order_by` or else by their primary key. It is named after
+//! the plural snake_case of the type, as default table names are, unless set
+//! with `method`. A struct can declare any number of relations. Each relation
+//! also gets `all_with_<relation>()`, loading every row paired with its
+//! related rows in a single LEFT JOIN. Rows without related rows are paired
+//! with an empty `Vec`.
+//!
+//! ```rust
+//! # sqlx_crud::doctest_setup! { |pool| {
+//! use sqlx_crud::Crud;
+//!
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! #[sqlx_crud(
+//!     has_many(Book, foreign_key = "author_id", order_by = "title DESC"),
+//!     has_many(Book, foreign_key = "editor_id", method = "edited_books")
+//! )]
+//! struct Author {
+//!     id: i64,
+//!     name: String,
+//! }
+//!
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! struct Book {
+//!     id: i64,
+//!     #[sqlx_crud(belongs_to = "Author")]
+//!     author_id: i64,
+//!     #[sqlx_crud(belongs_to = "Author")]
+//!     editor_id: Option<i64>,
+//!     title: String,
+//! }
+//!
+//! sqlx::query("CREATE TABLE authors (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+//!     .execute(&pool)
+//!     .await?;
+//! sqlx::query("CREATE TABLE books (id INTEGER PRIMARY KEY, author_id INTEGER NOT NULL, editor_id INTEGER, title TEXT NOT NULL)")
+//!     .execute(&pool)
+//!     .await?;
+//! let ursula = Author { id: 1, name: "Ursula".to_string() }.create(&pool).await?;
+//! let terry = Author { id: 2, name: "Terry".to_string() }.create(&pool).await?;
+//! for (id, author_id, editor_id, title) in [(1, 1, None, "Earthsea"), (2, 1, Some(2), "Lathe"), (3, 2, Some(2), "Mort")] {
+//!     Book { id, author_id, editor_id, title: title.to_string() }.create(&pool).await?;
+//! }
+//!
+//! let titles = |books: Vec<Book>| books.into_iter().map(|b| b.title).collect::<Vec<_>>();
+//! assert_eq!(vec!["Lathe", "Earthsea"], titles(ursula.books(&pool).await?));
+//! assert_eq!(vec!["Lathe", "Mort"], titles(terry.edited_books(&pool).await?));
+//! assert!(ursula.edited_books(&pool).await?.is_empty());
+//!
+//! Author { id: 3, name: "Iain".to_string() }.create(&pool).await?;
+//! let authors = Author::all_with_books(&pool).await?;
+//! let authors = authors
+//!     .into_iter()
+//!     .map(|(author, books)| (author.name, titles(books)))
+//!     .collect::<Vec<_>>();
+//! assert_eq!(
+//!     vec![
+//!         ("Ursula".to_string(), vec!["Lathe".to_string(), "Earthsea".to_string()]),
+//!         ("Terry".to_string(), vec!["Mort".to_string()]),
+//!         ("Iain".to_string(), vec![]),
+//!     ],
+//!     authors
+//! );
+//! # }}
+//! ```
+//!
+//! Rows related through a join table are declared with the
+//! `#[sqlx_crud(many_to_many(...))]` struct attribute, naming the other type,
+//! the join table with `through`, its column holding the primary key of the
+//! struct with `fk` and its column holding the primary key of the other type
+//! with `other_fk`. Besides the accessor, named as for `has_many`, the derive
+//! adds `add_<other>()` and `remove_<other>()` inserting and deleting rows of
+//! the join table.
+//!
+//! ```rust
+//! # sqlx_crud::doctest_setup! { |pool| {
+//! use sqlx_crud::{Crud, Schema};
+//!
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! #[sqlx_crud(many_to_many(Role, through = "member_roles", fk = "member_id", other_fk = "role_id"))]
+//! struct Member {
+//!     id: i64,
+//!     name: String,
+//! }
+//!
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! struct Role {
+//!     id: i64,
+//!     name: String,
+//! }
+//!
+//! sqlx::query("CREATE TABLE members (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+//!     .execute(&pool)
+//!     .await?;
+//! sqlx::query("CREATE TABLE roles (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+//!     .execute(&pool)
+//!     .await?;
+//! sqlx::query("CREATE TABLE member_roles (member_id INTEGER NOT NULL, role_id INTEGER NOT NULL, PRIMARY KEY (member_id, role_id))")
+//!     .execute(&pool)
+//!     .await?;
+//! let member = Member { id: 1, name: "ada".to_string() }.create(&pool).await?;
+//! let admin = Role { id: 1, name: "admin".to_string() }.create(&pool).await?;
+//! let editor = Role { id: 2, name: "editor".to_string() }.create(&pool).await?;
+//! sqlx::query("INSERT INTO member_roles (member_id, role_id) VALUES (1, 2)")
+//!     .execute(&pool)
+//!     .await?;
+//!
+//! let names = |roles: Vec<Role>| roles.into_iter().map(|r| r.name).collect::<Vec<_>>();
+//! assert_eq!(vec!["editor"], names(member.roles(&pool).await?));
+//!
+//! member.add_role(&pool, &admin).await?;
+//! assert_eq!(vec!["admin", "editor"], names(member.roles(&pool).await?));
+//!
+//! member.remove_role(&pool, &editor).await?;
+//! assert_eq!(vec!["admin"], names(member.roles(&pool).await?));
+//! assert_eq!(
+//!     r#"SELECT "roles"."id", "roles"."name" FROM "roles" INNER JOIN "member_roles" ON "member_roles"."role_id" = "roles"."id" WHERE "member_roles"."member_id" = ? ORDER BY "roles"."id""#,
+//!     Role::metadata().select_through_sql(sqlx_crud::schema::JoinTable {
+//!         table: "member_roles",
+//!         foreign_key: "member_id",
+//!         other_key: "role_id",
+//!     })
+//! );
+//! # }}
+//! ```
 //!
 //! The ordering of the columns used by queries and which columns are present
-//! is controlled by the field order of the struct. Ignored fields are not
-//! currently supported but will be added.
+//! is controlled by the field order of the struct. Fields without a
+//! corresponding column can be ignored with the `#[sqlx_crud(skip)]` field
+//! attribute. Skipped fields are left out of [Schema::columns] and all of the
+//! generated queries. Pair it with sqlx's `#[sqlx(skip)]` so [FromRow]
+//! populates the field with its `Default` value when reading. As shown
+//! below, `#[sqlx(skip)]` alone skips the field as well.
+//!
+//! ```rust
+//! # sqlx_crud::doctest_setup! { |pool| {
+//! use std::collections::HashSet;
+//! use sqlx_crud::{Crud, Schema};
+//!
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! #[external_id]
+//! struct Member {
+//!     id: i32,
+//!     first_name: String,
+//!     #[sqlx(skip)]
+//!     #[sqlx_crud(skip)]
+//!     permissions: HashSet<String>,
+//!     last_name: String,
+//! }
+//!
+//! assert_eq!(&["id", "first_name", "last_name"], Member::columns());
+//!
+//! sqlx::query("CREATE TABLE members (id INTEGER PRIMARY KEY NOT NULL, first_name TEXT NOT NULL, last_name TEXT NOT NULL)")
+//!     .execute(&pool)
+//!     .await?;
+//!
+//! let member = Member {
+//!     id: 1,
+//!     first_name: "Ada".to_string(),
+//!     permissions: HashSet::from(["admin".to_string()]),
+//!     last_name: "Lovelace".to_string(),
+//! };
+//! let mut member = member.create(&pool).await?;
+//! assert!(member.permissions.is_empty());
+//!
+//! member.last_name = "King".to_string();
+//! member.permissions.insert("admin".to_string());
+//! member.update(&pool).await?;
+//!
+//! let member = Member::by_id(&pool, 1).await?.unwrap();
+//! assert_eq!("Ada", member.first_name);
+//! assert_eq!("King", member.last_name);
+//! # }}
+//! ```
+//!
+//! The `#[id]` field cannot be skipped:
+//!
+//! ```compile_fail
+//! use sqlx::FromRow;
+//! use sqlx_crud::SqlxCrud;
+//!
+//! #[derive(FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! struct Member {
+//!     #[id]
+//!     #[sqlx_crud(skip)]
+//!     id: i32,
+//!     first_name: String,
+//! }
+//! ```
+//!
+//! Fields typed `PhantomData` are always skipped.
+//!
+//! Fields [FromRow] does not read from a column, those tagged with sqlx's
+//! `#[sqlx(skip)]` or `#[sqlx(default)]`, are skipped as well. Reading is
+//! left to [FromRow], which fills in the `Default` value. A `#[sqlx(default)]`
+//! field backed by a real column is kept with the `#[sqlx_crud(include)]`
+//! field attribute, so it is read when the column is selected and written
+//! like any other column.
+//!
+//! ```rust
+//! # sqlx_crud::doctest_setup! { |pool| {
+//! use sqlx_crud::{Crud, Schema};
+//!
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! struct Article {
+//!     #[id]
+//!     id: i64,
+//!     title: String,
+//!     #[sqlx(default)]
+//!     word_count: i32,
+//!     #[sqlx(default)]
+//!     #[sqlx_crud(include)]
+//!     views: i64,
+//! }
+//!
+//! assert_eq!(&["id", "title", "views"], Article::columns());
+//! assert_eq!(
+//!     r#"UPDATE "articles" SET "title" = ?, "views" = ? WHERE "articles"."id" = ? RETURNING "articles"."id", "articles"."title", "articles"."views""#,
+//!     Article::update_by_id_sql()
+//! );
+//!
+//! sqlx::query("CREATE TABLE articles (id INTEGER PRIMARY KEY, title TEXT NOT NULL, views INTEGER NOT NULL)")
+//!     .execute(&pool)
+//!     .await?;
+//!
+//! let article = Article { id: 0, title: "Hello".to_string(), word_count: 42, views: 3 };
+//! let mut article = article.create(&pool).await?;
+//! assert_eq!(0, article.word_count);
+//! assert_eq!(3, article.views);
+//!
+//! article.title = "Hello again".to_string();
+//! article.views = 4;
+//! article.update(&pool).await?;
+//!
+//! let article = Article::by_id(&pool, 1).await?.unwrap();
+//! assert_eq!("Hello again", article.title);
+//! assert_eq!(4, article.views);
+//! # }}
+//! ```
+//!
+//! Any field type implementing sqlx's `Encode`, `Decode` and `Type` traits for
+//! the struct's database can be used, including enums deriving `sqlx::Type`.
+//! A field type missing one of those traits is reported on the field itself.
+//!
+//! ```rust
+//! # sqlx_crud::doctest_setup! { |pool| {
+//! use sqlx_crud::Crud;
+//!
+//! #[derive(Debug, Clone, Copy, PartialEq, sqlx::Type)]
+//! #[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+//! enum OrderStatus {
+//!     Pending,
+//!     Shipped,
+//! }
+//!
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! struct Order {
+//!     id: i64,
+//!     status: OrderStatus,
+//! }
+//!
+//! sqlx::query("CREATE TABLE orders (id INTEGER PRIMARY KEY, status TEXT NOT NULL)")
+//!     .execute(&pool)
+//!     .await?;
+//!
+//! let mut order = Order { id: 0, status: OrderStatus::Pending }.create(&pool).await?;
+//! assert_eq!(OrderStatus::Pending, order.status);
+//!
+//! order.status = OrderStatus::Shipped;
+//! order.update(&pool).await?;
+//!
+//! let order = Order::by_id(&pool, 1).await?.unwrap();
+//! assert_eq!(OrderStatus::Shipped, order.status);
+//!
+//! let status: String = sqlx::query_scalar("SELECT status FROM orders WHERE id = 1")
+//!     .fetch_one(&pool)
+//!     .await?;
+//! assert_eq!("shipped", status);
+//! # }}
+//! ```
+//!
+//! [Crud::create] and [Crud::update] consume the instance and move the field
+//! values in to the query arguments, so large values such as multi-megabyte
+//! `Vec<u8>` BLOBs are bound without being cloned.
+//!
+//! ```rust
+//! # sqlx_crud::doctest_setup! { |pool| {
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//! use sqlx::encode::IsNull;
+//! use sqlx::error::BoxDynError;
+//! use sqlx::sqlite::{Sqlite, SqliteArgumentValue, SqliteTypeInfo, SqliteValueRef};
+//! use sqlx::{Decode, Encode, Type};
+//! use sqlx_crud::Crud;
+//!
+//! static CLONES: AtomicUsize = AtomicUsize::new(0);
+//!
+//! // Counts its clones, including those of binding by reference
+//! #[derive(Debug)]
+//! struct Blob(Vec<u8>);
+//!
+//! impl Clone for Blob {
+//!     fn clone(&self) -> Self {
+//!         CLONES.fetch_add(1, Ordering::SeqCst);
+//!         Blob(self.0.clone())
+//!     }
+//! }
+//!
+//! impl Type<Sqlite> for Blob {
+//!     fn type_info() -> SqliteTypeInfo {
+//!         <Vec<u8> as Type<Sqlite>>::type_info()
+//!     }
+//! }
+//!
+//! impl<'q> Encode<'q, Sqlite> for Blob {
+//!     fn encode(self, args: &mut Vec<SqliteArgumentValue<'q>>) -> IsNull {
+//!         <Vec<u8> as Encode<'q, Sqlite>>::encode(self.0, args)
+//!     }
+//!
+//!     fn encode_by_ref(&self, args: &mut Vec<SqliteArgumentValue<'q>>) -> IsNull {
+//!         self.clone().encode(args)
+//!     }
+//! }
+//!
+//! impl<'r> Decode<'r, Sqlite> for Blob {
+//!     fn decode(value: SqliteValueRef<'r>) -> Result<Self, BoxDynError> {
+//!         Ok(Blob(<Vec<u8> as Decode<'r, Sqlite>>::decode(value)?))
+//!     }
+//! }
+//!
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! struct Attachment {
+//!     id: i64,
+//!     data: Blob,
+//! }
+//!
+//! sqlx::query("CREATE TABLE attachments (id INTEGER PRIMARY KEY, data BLOB NOT NULL)")
+//!     .execute(&pool)
+//!     .await?;
+//!
+//! let data = Blob(vec![7; 4 * 1024 * 1024]);
+//! let mut attachment = Attachment { id: 0, data }.create(&pool).await?;
+//! attachment.data.0[0] = 8;
+//! let attachment = attachment.update(&pool).await?;
+//! assert_eq!(8, attachment.data.0[0]);
+//! assert_eq!(0, CLONES.load(Ordering::SeqCst));
+//! # }}
+//! ```
+//!
+//! Writing a few columns without reading the row first is done with the
+//! `#[sqlx_crud(patch)]` struct attribute. The derive generates a
+//! `<Struct>Patch` struct holding an `Option` of each field written by
+//! [Crud::update] and a `patch()` method updating only the columns of its
+//! `Some` fields, so concurrent writes to other columns are kept. A patch
+//! without `Some` fields does not run a query. Traits such as serde's
+//! `Deserialize` can be derived for the patch struct with
+//! `#[sqlx_crud(patch(derive(...)))]`, to read partial JSON bodies. Patches
+//! bypass the [Validate] check and [CrudHooks], as they never hold the whole
+//! row.
+//! Rows loaded and changed in place can be written the same way with a
+//! [Tracked] value, which records the columns changed since it was loaded
+//! for structs tagged `#[sqlx_crud(tracked)]`.
+//!
+//! ```rust
+//! # sqlx_crud::doctest_setup! { |pool| {
+//! use serde::Deserialize;
+//! use sqlx_crud::{Crud, Schema};
+//!
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! #[sqlx_crud(patch(derive(Debug, Deserialize)))]
+//! struct Profile {
+//!     id: i64,
+//!     name: String,
+//!     email: String,
+//!     bio: Option<String>,
+//! }
+//!
+//! sqlx::query("CREATE TABLE profiles (id INTEGER PRIMARY KEY, name TEXT NOT NULL, email TEXT NOT NULL, bio TEXT)")
+//!     .execute(&pool)
+//!     .await?;
+//! let profile = Profile {
+//!     id: 1,
+//!     name: "ada".to_string(),
+//!     email: "ada@example.com".to_string(),
+//!     bio: Some("analyst".to_string()),
+//! };
+//! profile.create(&pool).await?;
+//!
+//! let patch = ProfilePatch {
+//!     email: Some("ada@example.org".to_string()),
+//!     bio: Some(None),
+//!     ..Default::default()
+//! };
+//! Profile::patch(&pool, 1, patch).await?;
+//!
+//! let profile = Profile::by_id(&pool, 1).await?.unwrap();
+//! assert_eq!("ada", profile.name);
+//! assert_eq!("ada@example.org", profile.email);
+//! assert_eq!(None, profile.bio);
+//!
+//! // Nothing to write
+//! Profile::patch(&pool, 2, ProfilePatch::default()).await?;
+//! assert!(Profile::patch(&pool, 2, ProfilePatch { name: Some("x".to_string()), ..Default::default() })
+//!     .await
+//!     .is_err());
+//! assert_eq!(
+//!     r#"UPDATE "profiles" SET "email" = ?, "bio" = ? WHERE "profiles"."id" = ?"#,
+//!     Profile::metadata().patch_sql(&["email", "bio"])
+//! );
+//! # }}
+//! ```
+//!
+//! Creating a row with a database generated id otherwise means filling in a
+//! placeholder id. The `#[sqlx_crud(new_type)]` struct attribute generates a
+//! `New<Struct>` struct holding only the fields written by [Crud::create],
+//! leaving out generated ids, `created_at` and `updated_at` timestamps and
+//! skipped fields. Its `create()` method inserts the row and resolves to the
+//! struct as stored, generated id included. Columns are named as in the
+//! struct, renames included. Derives are added with
+//! `#[sqlx_crud(new_type(derive(...)))]`. Like patches, `New<Struct>` values
+//! never hold the whole row, so `new_type` cannot be combined with
+//! `#[sqlx_crud(validate)]` or `#[sqlx_crud(hooks)]`.
+//!
+//! ```rust
+//! # sqlx_crud::doctest_setup! { |pool| {
+//! use sqlx_crud::Crud;
+//!
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! #[sqlx_crud(new_type(derive(Debug, Clone)))]
+//! struct Contact {
+//!     id: i64,
+//!     #[sqlx_crud(rename = "full_name")]
+//!     #[sqlx(rename = "full_name")]
+//!     name: String,
+//!     email: String,
+//!     #[sqlx(skip)]
+//!     #[sqlx_crud(skip)]
+//!     selected: bool,
+//! }
+//!
+//! sqlx::query("CREATE TABLE contacts (id INTEGER PRIMARY KEY, full_name TEXT NOT NULL, email TEXT NOT NULL)")
+//!     .execute(&pool)
+//!     .await?;
+//!
+//! let new_contact = NewContact {
+//!     name: "ada".to_string(),
+//!     email: "ada@example.com".to_string(),
+//! };
+//! let first = new_contact.clone().create(&pool).await?;
+//! let second = new_contact.create(&pool).await?;
+//! assert_eq!("ada", first.name);
+//! assert_ne!(first.id, second.id);
+//!
+//! let contact = Contact::by_id(&pool, second.id).await?.unwrap();
+//! assert_eq!("ada@example.com", contact.email);
+//! # }}
+//! ```
+//!
+//! The struct has to support creating rows:
+//!
+//! ```compile_fail
+//! use sqlx::FromRow;
+//! use sqlx_crud::SqlxCrud;
+//!
+//! #[derive(FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! #[sqlx_crud(only(read), new_type)]
+//! struct Contact {
+//!     id: i64,
+//!     name: String,
+//! }
+//! ```
+//!
+//! Fields of types without a column mapping of their own can be stored as
+//! JSON with the `#[sqlx_crud(json)]` field attribute. The values are bound
+//! as `sqlx::types::Json`, so any `serde::Serialize` type works and an
+//! `Option` field stores `None` as NULL. [FromRow] needs the matching
+//! `#[sqlx(json)]` attribute to deserialize the column when reading.
+//! `Option` fields are read with [types::JsonOption] instead, which maps NULL
+//! to `None`. The `json` feature enables sqlx's JSON support.
+//!
+//! ```rust
+//! # sqlx_crud::doctest_setup! { |pool| {
+//! use std::collections::BTreeMap;
+//! use serde::{Deserialize, Serialize};
+//! use sqlx_crud::Crud;
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Config {
+//!     tags: Vec<String>,
+//!     limits: BTreeMap<String, i64>,
+//! }
+//!
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! struct Service {
+//!     id: i64,
+//!     #[sqlx(json)]
+//!     #[sqlx_crud(json)]
+//!     config: Config,
+//! }
+//!
+//! sqlx::query("CREATE TABLE services (id INTEGER PRIMARY KEY, config TEXT NOT NULL)")
+//!     .execute(&pool)
+//!     .await?;
+//!
+//! let config = Config {
+//!     tags: vec!["web".to_string(), "public".to_string()],
+//!     limits: BTreeMap::from([("rps".to_string(), 100)]),
+//! };
+//! let mut service = Service { id: 0, config }.create(&pool).await?;
+//! assert_eq!(vec!["web", "public"], service.config.tags);
+//!
+//! let stored: String = sqlx::query_scalar("SELECT config FROM services")
+//!     .fetch_one(&pool)
+//!     .await?;
+//! assert_eq!(r#"{"tags":["web","public"],"limits":{"rps":100}}"#, stored);
+//!
+//! service.config.limits.insert("burst".to_string(), 10);
+//! service.update(&pool).await?;
+//!
+//! let service = Service::by_id(&pool, 1).await?.unwrap();
+//! assert_eq!(Some(&10), service.config.limits.get("burst"));
+//! assert_eq!(Some(&100), service.config.limits.get("rps"));
+//! # }}
+//! ```
+//!
+//! Other field types can be converted at the database boundary with the
+//! `#[sqlx_crud(with = "module")]` field attribute, in the manner of serde's
+//! `with`. The module provides `fn encode(value: &T) -> V` and the generated
+//! binds store the returned `V`, which can be any type sqlx binds. Reading is
+//! left to [FromRow], for example with sqlx's `#[sqlx(try_from = "...")]`
+//! field attribute or a newtype decoding the column.
+//!
+//! ```rust
+//! # sqlx_crud::doctest_setup! { |pool| {
+//! use std::time::Duration;
+//! use sqlx_crud::Crud;
+//!
+//! mod millis {
+//!     use std::time::Duration;
+//!
+//!     #[derive(Debug, PartialEq)]
+//!     pub struct Millis(pub Duration);
+//!
+//!     impl TryFrom<i64> for Millis {
+//!         type Error = std::num::TryFromIntError;
+//!
+//!         fn try_from(ms: i64) -> Result<Self, Self::Error> {
+//!             Ok(Millis(Duration::from_millis(u64::try_from(ms)?)))
+//!         }
+//!     }
+//!
+//!     pub fn encode(value: &Millis) -> i64 {
+//!         value.0.as_millis() as i64
+//!     }
+//! }
+//! use millis::Millis;
+//!
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! struct Timeout {
+//!     id: i64,
+//!     #[sqlx(try_from = "i64")]
+//!     #[sqlx_crud(with = "millis")]
+//!     after: Millis,
+//! }
+//!
+//! sqlx::query("CREATE TABLE timeouts (id INTEGER PRIMARY KEY, after INTEGER NOT NULL)")
+//!     .execute(&pool)
+//!     .await?;
+//!
+//! let mut timeout = Timeout { id: 0, after: Millis(Duration::from_secs(2)) }.create(&pool).await?;
+//! assert_eq!(Millis(Duration::from_secs(2)), timeout.after);
+//!
+//! timeout.after = Millis(Duration::from_millis(1500));
+//! timeout.update(&pool).await?;
+//!
+//! let stored: i64 = sqlx::query_scalar("SELECT after FROM timeouts")
+//!     .fetch_one(&pool)
+//!     .await?;
+//! assert_eq!(1500, stored);
+//! # }}
+//! ```
+//!
+//! Structs with generic parameters are supported. Fields whose type mentions
+//! a type parameter must be bindable for the struct's database, which the
+//! derive adds as `sqlx::Encode` and `sqlx::Type` bounds on the generated
+//! [Crud] implementation.
+//!
+//! ```rust
+//! # sqlx_crud::doctest_setup! { |pool| {
+//! use std::marker::PhantomData;
+//! use sqlx_crud::{Crud, Schema};
+//!
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! #[external_id]
+//! struct Document<T> {
+//!     id: i32,
+//!     body: T,
+//! }
+//!
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! #[external_id]
+//! struct Revision<'a> {
+//!     id: i32,
+//!     note: String,
+//!     #[sqlx(skip)]
+//!     marker: PhantomData<&'a ()>,
+//! }
+//!
+//! assert_eq!(&["id", "body"], Document::<String>::columns());
+//! assert_eq!(&["id", "note"], Revision::columns());
+//!
+//! sqlx::query("CREATE TABLE documents (id INTEGER PRIMARY KEY NOT NULL, body)")
+//!     .execute(&pool)
+//!     .await?;
+//!
+//! Document { id: 1, body: "text".to_string() }.create(&pool).await?;
+//! Document { id: 2, body: 42i64 }.create(&pool).await?;
+//! assert_eq!("text", Document::<String>::by_id(&pool, 1).await?.unwrap().body);
+//! assert_eq!(42, Document::<i64>::by_id(&pool, 2).await?.unwrap().body);
+//! # }}
+//! ```
+//!
+//! The `#[sqlx_crud(created_at)]` and `#[sqlx_crud(updated_at)]` field
+//! attributes let the database stamp rows with `CURRENT_TIMESTAMP`.
+//! [Crud::create] sets both columns and [Crud::update] refreshes only the
+//! `updated_at` column. The values actually stored are returned, so the
+//! in-memory copy never goes stale. Any timestamp type sqlx can decode works,
+//! such as `chrono::DateTime<Utc>` or `time::OffsetDateTime`.
+//!
+//! ```rust
+//! # sqlx_crud::doctest_setup! { |pool| {
+//! use sqlx::types::chrono::{DateTime, Utc};
+//! use sqlx::types::time::OffsetDateTime;
+//! use sqlx_crud::Crud;
+//!
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! struct Post {
+//!     id: i64,
+//!     title: String,
+//!     #[sqlx_crud(created_at)]
+//!     created_at: DateTime<Utc>,
+//!     #[sqlx_crud(updated_at)]
+//!     updated_at: OffsetDateTime,
+//! }
+//!
+//! sqlx::query("CREATE TABLE posts (id INTEGER PRIMARY KEY, title TEXT NOT NULL, created_at TEXT NOT NULL, updated_at TEXT NOT NULL)")
+//!     .execute(&pool)
+//!     .await?;
+//!
+//! let post = Post {
+//!     id: 0,
+//!     title: "Hello".to_string(),
+//!     created_at: DateTime::<Utc>::UNIX_EPOCH,
+//!     updated_at: OffsetDateTime::UNIX_EPOCH,
+//! };
+//! let mut post = post.create(&pool).await?;
+//! assert!(post.created_at > DateTime::<Utc>::UNIX_EPOCH);
+//! assert!(post.updated_at > OffsetDateTime::UNIX_EPOCH);
+//! let created_at = post.created_at;
+//!
+//! post.title = "Hello, world".to_string();
+//! post.created_at = DateTime::<Utc>::UNIX_EPOCH;
+//! post.updated_at = OffsetDateTime::UNIX_EPOCH;
+//! let post = post.update(&pool).await?;
+//! assert_eq!(created_at, post.created_at);
+//! assert!(post.updated_at > OffsetDateTime::UNIX_EPOCH);
+//! # }}
+//! ```
+//!
+//! Structs with an `updated_at` field also get a `touch()` method, writing
+//! the current time to the row without changing its other columns, and
+//! bumping its `#[sqlx_crud(version)]` if it has one. It resolves to `false`
+//! if the row is gone or its version changed. Audited structs get no
+//! `touch()`, their rows being only written by `Crud::update`.
+//!
+//! ```rust
+//! # sqlx_crud::doctest_setup! { |pool| {
+//! use sqlx::types::chrono::{DateTime, Utc};
+//! use sqlx_crud::Crud;
+//!
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! struct Session {
+//!     id: i64,
+//!     token: String,
+//!     #[sqlx_crud(version)]
+//!     version: i64,
+//!     #[sqlx_crud(updated_at)]
+//!     seen_at: DateTime<Utc>,
+//! }
+//!
+//! sqlx::query("CREATE TABLE sessions (id INTEGER PRIMARY KEY, token TEXT NOT NULL, version INTEGER NOT NULL, seen_at TEXT NOT NULL)")
+//!     .execute(&pool)
+//!     .await?;
+//! sqlx::query("INSERT INTO sessions VALUES (1, 'stored', 1, '2000-01-01T00:00:00Z')")
+//!     .execute(&pool)
+//!     .await?;
+//!
+//! let mut session = Session::by_id(&pool, 1).await?.unwrap();
+//! session.token = "unsaved".to_string();
+//! assert!(session.touch(&pool).await?);
+//! assert_eq!(2, session.version);
+//! assert!(session.seen_at > "2000-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap());
+//! // The other columns are left unchanged, and read back in to the instance
+//! assert_eq!("stored", session.token);
+//! assert_eq!("stored", Session::by_id(&pool, 1).await?.unwrap().token);
+//!
+//! let mut stale = Session::by_id(&pool, 1).await?.unwrap();
+//! stale.version = 1;
+//! assert!(!stale.touch(&pool).await?);
+//! # }}
+//! ```
+//!
+//! The `#[sqlx_crud(only(...))]` and `#[sqlx_crud(except(...))]` struct
+//! attributes narrow the generated operations to a subset of `create`,
+//! `read`, `update` and `delete`. Calling a left out method, or asking
+//! [Schema] for its query, fails to compile. See [Creatable] for an
+//! append-only table.
+//!
+//! Structs mapped to database views are tagged with the
+//! `#[sqlx_crud(view)]` struct attribute, usually along with a
+//! `#[sqlx_crud(table_name = "...")]` naming the view. Only the read side is
+//! generated: [Crud::all], [Crud::by_id], the finders and the select queries.
+//! The `#[id]` field, by default the first field, is only used to look rows up
+//! and can be any column unique within the view.
+//!
+//! ```rust
+//! # sqlx_crud::doctest_setup! { |pool| {
+//! use sqlx_crud::{Crud, Schema};
+//!
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! #[sqlx_crud(view, table_name = "order_totals")]
+//! struct OrderTotal {
+//!     customer_id: i64,
+//!     #[sqlx_crud(find_by)]
+//!     orders: i64,
+//! }
+//!
+//! sqlx::query("CREATE TABLE orders (id INTEGER PRIMARY KEY, customer_id INTEGER NOT NULL)")
+//!     .execute(&pool)
+//!     .await?;
+//! sqlx::query("INSERT INTO orders (customer_id) VALUES (1), (1), (2)")
+//!     .execute(&pool)
+//!     .await?;
+//! sqlx::query("CREATE VIEW order_totals AS SELECT customer_id, COUNT(*) AS orders FROM orders GROUP BY customer_id")
+//!     .execute(&pool)
+//!     .await?;
+//!
+//! assert_eq!(2, OrderTotal::by_id(&pool, 1).await?.unwrap().orders);
+//! assert_eq!(2, OrderTotal::by_orders(&pool, 1).await?.unwrap().customer_id);
+//! assert_eq!(2, OrderTotal::all(&pool).await?.len());
+//! assert_eq!(
+//!     r#"SELECT "order_totals"."customer_id", "order_totals"."orders" FROM "order_totals""#,
+//!     OrderTotal::select_sql()
+//! );
+//! # }}
+//! ```
+//!
+//! Views have no write methods:
+//!
+//! ```rust,compile_fail
+//! # sqlx_crud::doctest_setup! { |pool| {
+//! use sqlx_crud::Crud;
+//!
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! #[sqlx_crud(view, table_name = "order_totals")]
+//! struct OrderTotal {
+//!     customer_id: i64,
+//!     orders: i64,
+//! }
+//!
+//! OrderTotal { customer_id: 3, orders: 0 }.create(&pool).await?;
+//! # }}
+//! ```
+//!
+//! Inherent methods named like the [Crud] methods take precedence over them,
+//! leaving the trait methods reachable only as `Crud::create(value, &pool)`.
+//! The
+//! `#[sqlx_crud(method_prefix = "...")]` struct attribute adds inherent
+//! methods forwarding to [Crud] under prefixed names, `db_create()`,
+//! `db_by_id()` and so on for a `db_` prefix. The generated finders are
+//! prefixed as well.
+//!
+//! ```rust
+//! # sqlx_crud::doctest_setup! { |pool| {
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! #[sqlx_crud(method_prefix = "db_")]
+//! struct Player {
+//!     id: i64,
+//!     #[sqlx_crud(find_by)]
+//!     name: String,
+//! }
+//!
+//! impl Player {
+//!     fn create(name: &str) -> Self {
+//!         Player { id: 0, name: name.to_string() }
+//!     }
+//! }
+//!
+//! sqlx::query("CREATE TABLE players (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+//!     .execute(&pool)
+//!     .await?;
+//!
+//! let mut player = Player::create("ada").db_create(&pool).await?;
+//! player.name = "grace".to_string();
+//! player.db_update(&pool).await?;
+//!
+//! let player = Player::db_by_name(&pool, "grace").await?.unwrap();
+//! assert_eq!(player.id, Player::db_by_id(&pool, player.id).await?.unwrap().id);
+//! player.db_delete(&pool).await?;
+//! assert!(Player::db_all(&pool).await?.is_empty());
+//! # }}
+//! ```
+//!
+//! The `#[sqlx_crud(debug_sql)]` struct attribute reports the SELECT, INSERT,
+//! UPDATE and DELETE statements of the struct as a compiler warning, for each
+//! listed database. It is meant to be set while writing the struct and
+//! removed after. The statements are those of debug builds, release builds
+//! report nothing, and the generated code is the same with or without the
+//! attribute. The columns of flattened fields are only known to their
+//! embedded struct and are left out, [Metadata] has the complete statements.
+//!
+//! ```rust
+//! # use sqlx::FromRow;
+//! # use sqlx_crud::SqlxCrud;
+//! #[derive(FromRow, SqlxCrud)]
+//! #[database(Sqlite)]
+//! #[sqlx_crud(debug_sql)]
+//! struct Note {
+//!     id: i64,
+//!     body: String,
+//! }
+//! ```
+//!
+//! Columns shared by several tables can be factored out in to a struct
+//! deriving [SqlxCrudEmbedded] and embedded with sqlx's `#[sqlx(flatten)]`.
+//! Its fields are expanded in to the columns and queries of the outer struct.
+//! See [Embedded] for an example.
+//!
+//! One struct can be used with several databases by listing them in the
+//! `#[database(...)]` attribute, for example `#[database(Sqlite, Postgres)]`.
+//! The queries are rendered for each database, with `$1`, `$2`, ... bind
+//! parameters for PostgreSQL, and [Crud] runs the queries of the database of
+//! its executor. The [Schema] methods return the queries of the first listed
+//! database, see [DatabaseSchema] for the others. Each listed database needs
+//! the matching `sqlite`, `postgres` or `mysql` cargo feature of sqlx-crud.
+//! Databases without their feature are left out, so the struct still compiles
+//! when only some of the features are enabled, and fails to compile when none
+//! is.
+//!
+//! ```rust
+//! # sqlx_crud::doctest_setup! { |pool| {
+//! use sqlx_crud::Crud;
+//!
+//! #[cfg(any(feature = "sqlite", feature = "postgres"))]
+//! #[derive(Debug, FromRow, SqlxCrud)]
+//! #[database(Sqlite, Postgres)]
+//! struct Item {
+//!     id: i64,
+//!     #[sqlx_crud(find_by)]
+//!     name: String,
+//! }
+//!
+//! // The same calls run against either database
+//! macro_rules! round_trip {
+//!     ($pool:expr) => {{
+//!         let mut item = Item { id: 0, name: "first".to_string() }.create($pool).await?;
+//!         item.name = "second".to_string();
+//!         let item = item.update($pool).await?;
+//!         assert_eq!("second", Item::by_id($pool, item.id).await?.unwrap().name);
+//!         assert_eq!(item.id, Item::by_name($pool, "second").await?.unwrap().id);
+//!         item.delete($pool).await?;
+//!         assert!(Item::all($pool).await?.is_empty());
+//!     }};
+//! }
+//!
+//! #[cfg(feature = "sqlite")]
+//! {
+//!     sqlx::query("CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+//!         .execute(&pool)
+//!         .await?;
+//!     round_trip!(&pool);
+//! }
+//!
+//! #[cfg(feature = "postgres")]
+//! if let Ok(url) = std::env::var("DATABASE_URL") {
+//!     let pool = sqlx::PgPool::connect(&url).await?;
+//!     sqlx::query("DROP TABLE IF EXISTS items").execute(&pool).await?;
+//!     sqlx::query("CREATE TABLE items (id BIGSERIAL PRIMARY KEY, name TEXT NOT NULL)")
+//!         .execute(&pool)
+//!         .await?;
+//!     round_trip!(&pool);
+//! }
+//! # }}
+//! ```
+//!
+//! The generated code refers to sqlx and sqlx-crud as `::sqlx` and
+//! `::sqlx_crud`. When either is renamed in `Cargo.toml` or only reachable
+//! through a re-export, point the derive at it with the `crate` and `sqlx`
+//! struct attributes, in the manner of serde's `#[serde(crate = "...")]`.
+//!
+//! ```rust
+//! # sqlx_crud::doctest_setup! { |pool| {
+//! mod db {
+//!     pub(crate) use ::sqlx;
+//!     pub(crate) use ::sqlx_crud;
+//! }
+//!
+//! use db::sqlx_crud::Crud;
+//!
+//! #[derive(Debug, FromRow, db::sqlx_crud::SqlxCrud)]
+//! #[database(Sqlite)]
+//! #[sqlx_crud(crate = "db::sqlx_crud", sqlx = "db::sqlx")]
+//! struct Tag {
+//!     id: i64,
+//!     label: String,
+//! }
+//!
+//! sqlx::query("CREATE TABLE tags (id INTEGER PRIMARY KEY, label TEXT NOT NULL)")
+//!     .execute(&pool)
+//!     .await?;
+//! let tag = Tag { id: 0, label: "rust".to_string() }.create(&pool).await?;
+//! assert_eq!("rust", Tag::by_id(&pool, tag.id).await?.unwrap().label);
+//! # }}
+//! ```
+//!
+//! [FromRow]: https://docs.rs/sqlx/latest/sqlx/trait.FromRow.html
+//! [types::JsonOption]: types/struct.JsonOption.html
+//! [Embedded]: traits/trait.Embedded.html
+//! [SqlxCrudEmbedded]: derive.SqlxCrudEmbedded.html
+//! [DatabaseSchema]: traits/trait.DatabaseSchema.html
+//! [Creatable]: traits/trait.Creatable.html
+//! [Metadata]: schema/struct.Metadata.html
 //!
 //! # Features
 //!
@@ -33,6 +1143,27 @@
 //! feature pair as you did with sqlx. If for example you used `tokio-rustls`
 //! with sqlx you should also use the same feature with sqlx-crud.
 //!
+//! Structs listing several databases in `#[database(...)]` also need the
+//! `sqlite`, `postgres` or `mysql` feature of each database they are used
+//! with.
+//!
+//! The `uuid` feature enables the `#[id(uuid)]` field attribute, which mints
+//! UUID keys in [Crud::create].
+//!
+//! The `json` feature enables sqlx's JSON support used by the
+//! `#[sqlx_crud(json)]` field attribute.
+//!
+//! The `tracing` feature runs [Crud::create], [Crud::by_id], [Crud::update]
+//! and [Crud::delete] in [tracing](https://docs.rs/tracing) spans named after
+//! the table and method, such as `sqlx_crud.users.by_id`.
+//!
+//! The `serde` feature makes `schema::SchemaMetadata`, the table and column
+//! metadata of a struct, serializable for external tooling.
+//!
+//! The `dynamic` feature adds the `dynamic` module, whose object safe
+//! `DynCrud` trait counts, looks up and deletes the rows of any struct
+//! through `dyn`, returning rows as `serde_json` values.
+//!
 //! Hopefully I can figure out a way to remove this requirement. I think
 //! I might need to use a build.rs script and interrogate the sqlx features that way.
 //!
@@ -111,7 +1242,9 @@
 //! # }}
 //! ```
 //!
-//! Reusing and modifying the [select_sql] query string:
+//! Reusing and modifying the [select_sql] query string, here for a limit
+//! without an offset. [Crud::paginated] returns pages of rows in a stable
+//! order:
 //!
 //! ```rust
 //! # sqlx_crud::doctest_setup! { |pool| {
@@ -150,17 +1283,35 @@
 //! # Planned Future Improvements
 //!
 //! Subsequent updates will extend the library to be more useful in a larger
-//! variety of situations.
-//!
-//! * Allow database assigned primary keys
-//! * Crud::create() should return the assigned ID
-//! * Add a field attribute to ignore fields
+//! variety of situations. Planned updates are listed in the MILESTONES file.
 
+pub mod check;
+#[cfg(feature = "dynamic")]
+pub mod dynamic;
+pub mod error;
+pub mod filter;
+#[cfg(feature = "tracing")]
+#[doc(hidden)]
+pub mod instrument;
+pub mod naming;
+pub mod page;
+pub mod repository;
 pub mod schema;
+pub mod select;
+pub mod tracked;
 pub mod traits;
+#[cfg(feature = "json")]
+pub mod types;
 
-pub use sqlx_crud_macros::SqlxCrud;
-pub use traits::{Crud, Schema};
+pub use error::{Error, HookError, ValidationError};
+pub use page::Page;
+pub use repository::Repository;
+pub use sqlx_crud_macros::{SqlxCrud, SqlxCrudEmbedded};
+pub use tracked::Tracked;
+pub use traits::{
+    BindId, ColumnName, Creatable, CreateTable, Crud, CrudHooks, Deletable, Embedded, IntoId,
+    Readable, SaveOutcome, Schema, Track, Unaudited, Unscoped, Updatable, Upsertable, Validate,
+};
 
 #[macro_export]
 #[doc(hidden)]
@@ -177,11 +1328,15 @@ macro_rules! doctest_setup {
             pub name: String,
         }
 
-        fn main() -> Result<(), sqlx::Error> {
+        // rustdoc wraps the example in its own main, which runs this
+        fn run() -> Result<(), sqlx_crud::Error> {
             tokio_test::block_on(async {
-                let $pool = SqlitePool::connect(":memory:")
+                // Each connection opens its own in-memory database
+                let $pool = sqlx::sqlite::SqlitePoolOptions::new()
+                    .max_connections(1)
+                    .connect(":memory:")
                     .await?;
-                sqlx::query("CREATE TABLE users (user_id INTEGER NOT NULL, name TEXT NOT NULL)")
+                sqlx::query("CREATE TABLE users (user_id INTEGER PRIMARY KEY NOT NULL, name TEXT NOT NULL)")
                     .execute(&$pool)
                     .await?;
                 sqlx::query("INSERT INTO users (user_id, name) VALUES(?, ?)")
@@ -192,9 +1347,104 @@ macro_rules! doctest_setup {
 
                 $($t)*;
 
-                Ok::<(), sqlx::Error>(())
-            });
-            Ok::<(), sqlx::Error>(())
+                Ok::<(), sqlx_crud::Error>(())
+            })
         }
+
+        run().unwrap();
     }
 }
+
+#[cfg(feature = "uuid")]
+#[doc(hidden)]
+pub use uuid;
+
+#[cfg(feature = "tracing")]
+#[doc(hidden)]
+pub use tracing;
+
+// The span naming the table of a SqlxCrud struct is only generated with the
+// `tracing` feature
+#[cfg(feature = "tracing")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __with_tracing {
+    ($($t:tt)*) => { $($t)* };
+}
+
+#[cfg(not(feature = "tracing"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __with_tracing {
+    ($($t:tt)*) => {};
+}
+
+/// A new random UUID for `#[id(uuid)]` fields.
+#[cfg(feature = "uuid")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __new_uuid_v4 {
+    () => {
+        $crate::uuid::Uuid::new_v4()
+    };
+}
+
+#[cfg(not(feature = "uuid"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __new_uuid_v4 {
+    () => {
+        compile_error!("`#[id(uuid)]` requires the `uuid` feature of sqlx-crud")
+    };
+}
+
+// Code generated by the SqlxCrud derive for several databases is only compiled
+// for the databases enabled with the matching cargo feature, the `else` form
+// picking one of two alternatives
+#[cfg(feature = "sqlite")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __with_sqlite {
+    ({ $($then:tt)* } else { $($else:tt)* }) => { $($then)* };
+    ($($t:tt)*) => { $($t)* };
+}
+
+#[cfg(not(feature = "sqlite"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __with_sqlite {
+    ({ $($then:tt)* } else { $($else:tt)* }) => { $($else)* };
+    ($($t:tt)*) => {};
+}
+
+#[cfg(feature = "postgres")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __with_postgres {
+    ({ $($then:tt)* } else { $($else:tt)* }) => { $($then)* };
+    ($($t:tt)*) => { $($t)* };
+}
+
+#[cfg(not(feature = "postgres"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __with_postgres {
+    ({ $($then:tt)* } else { $($else:tt)* }) => { $($else)* };
+    ($($t:tt)*) => {};
+}
+
+#[cfg(feature = "mysql")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __with_mysql {
+    ({ $($then:tt)* } else { $($else:tt)* }) => { $($then)* };
+    ($($t:tt)*) => { $($t)* };
+}
+
+#[cfg(not(feature = "mysql"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __with_mysql {
+    ({ $($then:tt)* } else { $($else:tt)* }) => { $($else)* };
+    ($($t:tt)*) => {};
+}
@@ -0,0 +1,85 @@
+//! Table names derived from struct names.
+//!
+//! The [SqlxCrud] derive names the table of a struct with [table_name] unless
+//! it is set with `#[sqlx_crud(table_name = "...")]`. The helper is public so
+//! the table a struct maps to can be checked without deriving it.
+//!
+//! [SqlxCrud]: ../derive.SqlxCrud.html
+
+use inflector::Inflector;
+
+/// Inflection applied to the struct name, chosen with the
+/// `#[sqlx_crud(table_naming = "...")]` struct attribute.
+///
+/// # Example
+///
+/// ```rust
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use sqlx_crud::naming::{table_name, TableNaming};
+/// use sqlx_crud::{Crud, Schema};
+///
+/// #[derive(Debug, FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// #[sqlx_crud(table_naming = "singular")]
+/// struct Person {
+///     id: i64,
+///     name: String,
+/// }
+///
+/// #[derive(Debug, FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// #[sqlx_crud(table_naming = "as_is")]
+/// struct OrderStatus {
+///     id: i64,
+///     label: String,
+/// }
+///
+/// assert_eq!("person", Person::table_name());
+/// assert_eq!(table_name("Person", TableNaming::Singular), Person::table_name());
+/// assert_eq!(r#"SELECT "person"."id", "person"."name" FROM "person""#, Person::select_sql());
+/// assert_eq!("OrderStatus", OrderStatus::table_name());
+/// assert_eq!("order_statuses", table_name("OrderStatus", TableNaming::Plural));
+///
+/// sqlx::query("CREATE TABLE person (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+///     .execute(&pool)
+///     .await?;
+/// let person = Person { id: 0, name: "Ada".to_string() }.create(&pool).await?;
+/// assert_eq!("Ada", Person::by_id(&pool, person.id).await?.unwrap().name);
+/// # }}
+/// ```
+///
+/// Unknown strategies are rejected at compile time:
+///
+/// ```compile_fail
+/// use sqlx::FromRow;
+/// use sqlx_crud::SqlxCrud;
+///
+/// #[derive(FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// #[sqlx_crud(table_naming = "camel")]
+/// struct Person {
+///     id: i64,
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TableNaming {
+    /// Plural _snake_case_, `OrderItem` is stored in `order_items`. This is
+    /// the default. See: [Inflector to_table_case].
+    ///
+    /// [Inflector to_table_case]: https://docs.rs/Inflector/latest/inflector/cases/tablecase/fn.to_table_case.html
+    #[default]
+    Plural,
+    /// Singular _snake_case_, `OrderItem` is stored in `order_item`.
+    Singular,
+    /// The struct name unchanged, `OrderItem` is stored in `OrderItem`.
+    AsIs,
+}
+
+/// Returns the table name derived from `struct_name` by `naming`.
+pub fn table_name(struct_name: &str, naming: TableNaming) -> String {
+    match naming {
+        TableNaming::Plural => struct_name.to_table_case(),
+        TableNaming::Singular => struct_name.to_snake_case(),
+        TableNaming::AsIs => struct_name.to_string(),
+    }
+}
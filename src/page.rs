@@ -0,0 +1,73 @@
+//! Pages of rows with the total row count, for paginated API responses, and
+//! the cursors of keyset pagination.
+//!
+//! Offset pagination with [Crud::paginated] and [Crud::page] reads and skips
+//! the rows before the page, which gets slower the further the page is.
+//! Keyset pagination with [Crud::after] and [Crud::after_by] instead selects
+//! the rows following a cursor, the sort key of the last row of the previous
+//! page, which the database finds with an index.
+//!
+//! [Crud::after]: ../traits/trait.Crud.html#method.after
+//! [Crud::after_by]: ../traits/trait.Crud.html#method.after_by
+//! [Crud::page]: ../traits/trait.Crud.html#method.page
+//! [Crud::paginated]: ../traits/trait.Crud.html#method.paginated
+
+use crate::traits::Schema;
+
+/// A page of `limit` rows after the first `offset`, returned by
+/// [Crud::page] with the number of rows in the table. Serializable with the
+/// `serde` feature.
+///
+/// # Example
+///
+/// ```rust
+/// use sqlx_crud::Page;
+///
+/// let page = Page { items: vec!["d", "e", "f"], total: 7, limit: 3, offset: 3 };
+/// assert_eq!(3, page.total_pages());
+/// assert!(page.has_next());
+///
+/// let last = Page { items: vec!["g"], total: 7, limit: 3, offset: 6 };
+/// assert!(!last.has_next());
+/// ```
+///
+/// [Crud::page]: ../traits/trait.Crud.html#method.page
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Page<T> {
+    /// The rows of the page, at most `limit`.
+    pub items: Vec<T>,
+    /// Number of rows in the table.
+    pub total: u64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+impl<T> Page<T> {
+    /// Returns true if rows follow this page.
+    pub fn has_next(&self) -> bool {
+        ((self.offset + self.limit) as u64) < self.total
+    }
+
+    /// Returns the number of pages of `limit` rows holding every row, `0`
+    /// for an empty table.
+    pub fn total_pages(&self) -> u64 {
+        self.total.div_ceil(self.limit as u64)
+    }
+}
+
+/// Order of the rows of keyset pagination.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Direction {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+/// Returns the cursor of [Crud::after] following the last of `rows`, `None`
+/// if `rows` is empty.
+///
+/// [Crud::after]: ../traits/trait.Crud.html#method.after
+pub fn next_cursor<T: Schema>(rows: &[T]) -> Option<T::Id> {
+    rows.last().map(Schema::id)
+}
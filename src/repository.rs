@@ -0,0 +1,152 @@
+//! A pool bundled with the [Crud] methods of one struct.
+//!
+//! Services otherwise pass a pool alongside every call of a [Crud] method.
+//! A [Repository] holds the pool, so the service only holds the repository
+//! of each struct it uses.
+//!
+//! [Crud]: ../traits/trait.Crud.html
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use sqlx::database::HasArguments;
+use sqlx::{Database, Encode, Executor, FromRow, IntoArguments, Pool, Type};
+
+use crate::page::Page;
+use crate::traits::{
+    BindId, Creatable, Crud, CrudFut, Deletable, IntoId, Readable, Schema, Unaudited, Unscoped,
+    Updatable,
+};
+
+/// The [Crud] methods of `T` run on a [Pool] of the `DB` database.
+///
+/// Every method forwards to the [Crud] method of the same purpose with the
+/// pool of the repository. Cloning a repository clones the pool, which
+/// shares its connections.
+///
+/// # Example
+///
+/// ```rust
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use sqlx_crud::Repository;
+///
+/// let users = Repository::<User, _>::new(pool.clone());
+///
+/// let user = users.insert(User { user_id: 2, name: "new".to_string() }).await?;
+/// assert_eq!("new", users.get(2).await?.unwrap().name);
+///
+/// users.update(User { name: "renamed".to_string(), ..user }).await?;
+/// assert_eq!("renamed", users.get(2).await?.unwrap().name);
+///
+/// let page = users.list(1, 1).await?;
+/// assert_eq!(2, page.total);
+/// assert_eq!(vec!["renamed"], page.items.iter().map(|u| u.name.as_str()).collect::<Vec<_>>());
+///
+/// assert!(users.clone().remove(2).await?);
+/// assert!(users.get(2).await?.is_none());
+/// assert!(!users.remove(2).await?);
+/// # }}
+/// ```
+///
+/// [Crud]: ../traits/trait.Crud.html
+/// [Pool]: https://docs.rs/sqlx/latest/sqlx/struct.Pool.html
+pub struct Repository<T, DB: Database> {
+    pool: Pool<DB>,
+    row: PhantomData<fn() -> T>,
+}
+
+impl<T: Schema, DB: Database> Repository<T, DB> {
+    /// Returns the repository of `T` running its queries on `pool`.
+    pub fn new(pool: Pool<DB>) -> Self {
+        Self {
+            pool,
+            row: PhantomData,
+        }
+    }
+
+    /// Returns the pool of the repository.
+    pub fn pool(&self) -> &Pool<DB> {
+        &self.pool
+    }
+}
+
+impl<T, DB> Repository<T, DB>
+where
+    DB: Database,
+    T: for<'e> Crud<'e, &'e Pool<DB>>,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'e> <DB as HasArguments<'e>>::Arguments: IntoArguments<'e, DB>,
+{
+    /// Looks up the row with the primary key `id`, like [Crud::by_id].
+    ///
+    /// [Crud::by_id]: ../traits/trait.Crud.html#method.by_id
+    pub fn get<'e, K>(&'e self, id: K) -> CrudFut<'e, Option<T>>
+    where
+        T: Unscoped + Readable + BindId<'e, DB, K>,
+        K: std::fmt::Debug,
+    {
+        T::by_id(&self.pool, id)
+    }
+
+    /// Inserts `row` and returns it as stored, like [Crud::create].
+    ///
+    /// [Crud::create]: ../traits/trait.Crud.html#method.create
+    pub fn insert<'e>(&'e self, row: T) -> CrudFut<'e, T>
+    where
+        T: Creatable,
+    {
+        row.create(&self.pool)
+    }
+
+    /// Writes `row` over the row with its primary key, like [Crud::update].
+    ///
+    /// [Crud::update]: ../traits/trait.Crud.html#method.update
+    pub fn update<'e>(&'e self, row: T) -> CrudFut<'e, T>
+    where
+        T: Updatable,
+    {
+        row.update(&self.pool)
+    }
+
+    /// Deletes the row with the primary key `id` and returns whether there
+    /// was one, like [Crud::delete_by_id].
+    ///
+    /// [Crud::delete_by_id]: ../traits/trait.Crud.html#method.delete_by_id
+    pub fn remove<'e, K>(&'e self, id: K) -> CrudFut<'e, bool>
+    where
+        T: Unscoped + Deletable + Unaudited + BindId<'e, DB, K>,
+        K: IntoId<<T as Schema>::Id> + Clone + std::fmt::Debug + Send + 'e,
+    {
+        T::delete_by_id(&self.pool, id)
+    }
+
+    /// Returns the page of at most `limit` rows after the first `offset`,
+    /// with the total number of rows, like [Crud::page].
+    ///
+    /// [Crud::page]: ../traits/trait.Crud.html#method.page
+    pub fn list<'e>(&'e self, limit: i64, offset: i64) -> CrudFut<'e, Page<T>>
+    where
+        T: Unscoped + Readable,
+        i64: for<'q> Encode<'q, DB> + Type<DB>,
+        (i64,): for<'r> FromRow<'r, DB::Row>,
+    {
+        T::page(&self.pool, limit, offset)
+    }
+}
+
+impl<T, DB: Database> Clone for Repository<T, DB> {
+    fn clone(&self) -> Self {
+        Self {
+            pool: self.pool.clone(),
+            row: PhantomData,
+        }
+    }
+}
+
+impl<T: Schema, DB: Database> fmt::Debug for Repository<T, DB> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Repository")
+            .field("table", &T::table_name())
+            .finish_non_exhaustive()
+    }
+}
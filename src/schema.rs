@@ -1,10 +1,2394 @@
-pub struct Metadata<'s, const C: usize> {
-    pub table_name: &'s str,
-    pub id_column: &'s str,
-    pub columns: [&'s str; C],
-    pub select_sql: &'s str,
-    pub select_by_id_sql: &'s str,
-    pub insert_sql: &'s str,
-    pub update_by_id_sql: &'s str,
-    pub delete_by_id_sql: &'s str,
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use sqlx::database::HasValueRef;
+use sqlx::{ColumnIndex, Database, Row};
+
+use crate::filter::Condition;
+use crate::page::Direction;
+
+/// Mapping of a struct field to a table column. The [SqlxCrud] derive
+/// generates one for every bound field and [Metadata] builds the queries
+/// from them.
+///
+/// [SqlxCrud]: ../derive.SqlxCrud.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Column {
+    /// Column name, unquoted.
+    pub name: &'static str,
+    /// The column is part of the primary key.
+    pub id: bool,
+    /// The column is written by the INSERT statement.
+    pub insert: bool,
+    /// The column is written by the UPDATE statement.
+    pub update: bool,
+    /// SQL expression written in place of a bind parameter.
+    pub value: Option<&'static str>,
+    /// The column holds the `#[sqlx_crud(version)]` of the row.
+    pub version: bool,
+    /// The column holds the `#[sqlx_crud(soft_delete)]` timestamp of the row.
+    pub soft_delete: bool,
+    /// The column is left out of the INSERT statement when the field is
+    /// `None`, see `#[sqlx_crud(none_as_default)]`.
+    pub none_as_default: bool,
+    /// Rows are looked up by the unique value of the column, see
+    /// `#[sqlx_crud(find_by)]`.
+    pub find_by: bool,
+    /// All rows sharing a value of the column are looked up together, see
+    /// `#[sqlx_crud(indexed)]`.
+    pub indexed: bool,
+    /// The column holds the `#[sqlx_crud(tenant)]` every query is scoped to.
+    pub tenant: bool,
+    /// SQL type of the column in [Metadata::create_table_sql], inferred from
+    /// the field type or set with `#[sqlx_crud(sql_type = "...")]`.
+    pub sql_type: Option<SqlType>,
+    /// The column accepts NULL, the field is an `Option`.
+    pub nullable: bool,
+    /// The field type as written in the struct, such as `Option<String>`.
+    pub rust_type: &'static str,
+}
+
+impl Column {
+    /// Adapts a column of an [Embedded] struct to the struct it is flattened
+    /// in to. The embedded id columns only form the primary key when the
+    /// flattened field is the `#[id]` field.
+    ///
+    /// [Embedded]: ../traits/trait.Embedded.html
+    pub fn flattened(self, id: bool) -> Self {
+        let id = id && self.id;
+        Self {
+            id,
+            update: self.update && !id,
+            ..self
+        }
+    }
+
+    fn placeholder(&self) -> &'static str {
+        self.value.unwrap_or("?")
+    }
+}
+
+/// SQL type of a column in the CREATE TABLE statement of
+/// [Metadata::create_table_sql]. The [SqlxCrud] derive infers the type of
+/// fields of the common scalar types: integers, floats, `bool`, `String`,
+/// `Vec<u8>`, UUIDs, the chrono and time date and time types, and JSON.
+/// Other types need the `#[sqlx_crud(sql_type = "...")]` field attribute.
+///
+/// [SqlxCrud]: ../derive.SqlxCrud.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SqlType {
+    Boolean,
+    SmallInt,
+    Integer,
+    BigInt,
+    Real,
+    Double,
+    Text,
+    Blob,
+    Uuid,
+    Date,
+    Time,
+    /// A date and time without time zone.
+    Timestamp,
+    /// A date and time with time zone.
+    TimestampTz,
+    Json,
+    /// The type of `#[sqlx_crud(sql_type = "...")]`, written as is.
+    Custom(&'static str),
+}
+
+impl SqlType {
+    /// Name of the type in database `DB`. SQLite stores dates and JSON as
+    /// text, as sqlx reads and writes them.
+    pub fn name<DB: Database>(self) -> &'static str {
+        let (sqlite, postgres, mysql) = match self {
+            Self::Boolean => ("BOOLEAN", "BOOLEAN", "BOOLEAN"),
+            Self::SmallInt => ("INTEGER", "SMALLINT", "SMALLINT"),
+            Self::Integer => ("INTEGER", "INTEGER", "INT"),
+            Self::BigInt => ("INTEGER", "BIGINT", "BIGINT"),
+            Self::Real => ("REAL", "REAL", "FLOAT"),
+            Self::Double => ("REAL", "DOUBLE PRECISION", "DOUBLE"),
+            Self::Text => ("TEXT", "TEXT", "TEXT"),
+            Self::Blob => ("BLOB", "BYTEA", "BLOB"),
+            Self::Uuid => ("BLOB", "UUID", "BINARY(16)"),
+            Self::Date => ("TEXT", "DATE", "DATE"),
+            Self::Time => ("TEXT", "TIME", "TIME"),
+            Self::Timestamp => ("TEXT", "TIMESTAMP", "DATETIME"),
+            Self::TimestampTz => ("TEXT", "TIMESTAMPTZ", "TIMESTAMP"),
+            Self::Json => ("TEXT", "JSONB", "JSON"),
+            Self::Custom(name) => (name, name, name),
+        };
+        match DB::NAME {
+            "PostgreSQL" => postgres,
+            "MySQL" => mysql,
+            _ => sqlite,
+        }
+    }
+
+    fn is_integer(self) -> bool {
+        matches!(self, Self::SmallInt | Self::Integer | Self::BigInt)
+    }
+}
+
+/// A column of the `#[sqlx_crud(order_by = "...")]` struct attribute, in
+/// sort order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct OrderBy {
+    /// Column name, unquoted.
+    pub column: &'static str,
+    /// The column sorts descending rather than ascending.
+    pub descending: bool,
+}
+
+/// An aggregate function of a column, see [Metadata::aggregate_sql].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Aggregate {
+    Min,
+    Max,
+    Sum,
+    Avg,
+}
+
+impl Aggregate {
+    /// Name of the SQL function.
+    pub fn function(self) -> &'static str {
+        match self {
+            Self::Min => "MIN",
+            Self::Max => "MAX",
+            Self::Sum => "SUM",
+            Self::Avg => "AVG",
+        }
+    }
+}
+
+/// Table metadata and queries shared by every instance of a [Schema].
+///
+/// The statements are assembled from the columns when the metadata is first
+/// used, quoted for the `#[database]` and including the columns of flattened
+/// fields, which the derive macro cannot see. `#[sqlx_crud(debug_sql)]`
+/// reports the statements over the other columns at expansion time, see the
+/// [crate] documentation. The complete statements are read from here, such as
+/// by printing them from a test:
+///
+/// ```rust
+/// use sqlx::FromRow;
+/// use sqlx_crud::{Schema, SqlxCrud};
+///
+/// #[derive(FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// struct Task {
+///     id: i64,
+///     title: String,
+/// }
+///
+/// let metadata = Task::metadata();
+/// for sql in [&metadata.select_sql, &metadata.insert_sql, &metadata.update_sql, &metadata.delete_sql] {
+///     println!("{}", sql);
+/// }
+/// assert_eq!(
+///     r#"UPDATE "tasks" SET "title" = ? WHERE "tasks"."id" = ? RETURNING "tasks"."id", "tasks"."title""#,
+///     metadata.update_sql
+/// );
+/// ```
+///
+/// [Schema]: ../traits/trait.Schema.html
+#[derive(Debug)]
+pub struct Metadata {
+    pub schema_name: Option<&'static str>,
+    pub table_name: &'static str,
+    pub qualified_table_name: String,
+    pub id_columns: Vec<&'static str>,
+    pub columns: Vec<&'static str>,
+    pub version_column: Option<&'static str>,
+    pub soft_delete_column: Option<&'static str>,
+    pub tenant_column: Option<&'static str>,
+    pub select_sql: String,
+    /// [Metadata::select_sql] ordered by [Metadata::order_by_sql], the query
+    /// of `Crud::all`.
+    pub select_all_sql: String,
+    /// The ORDER BY clause of `#[sqlx_crud(order_by = "...")]`, without the
+    /// `ORDER BY` keywords.
+    pub order_by_sql: Option<String>,
+    /// [Metadata::select_sql] ordered by [Metadata::order_by_sql] and then
+    /// the primary key, or else the primary key, with the bound `LIMIT` and
+    /// `OFFSET` of a page. The query of `Crud::paginated`.
+    pub paginated_sql: String,
+    /// SELECT of the number of rows, not counting soft deleted rows. Run by
+    /// `Crud::page`.
+    pub count_sql: String,
+    /// SELECT of the first row ordered by [Metadata::order_by_sql] and then
+    /// the primary key, or else by the primary key. Run by `Crud::first`.
+    pub first_sql: String,
+    /// [Metadata::first_sql] in reverse order, selecting the last row. Run
+    /// by `Crud::last`.
+    pub last_sql: String,
+    /// [Metadata::select_sql] with columns not prefixed by the table name.
+    pub select_sql_unqualified: String,
+    /// The quoted columns, not prefixed by the table name, separated by
+    /// commas.
+    pub unqualified_column_list: String,
+    pub select_by_id_sql: String,
+    /// [Metadata::select_by_id_sql] locking the row until the end of the
+    /// transaction with `FOR UPDATE` on PostgreSQL and MySQL. SQLite has no
+    /// row locks, the statement is [Metadata::select_by_id_sql]. Run by
+    /// `Crud::by_id_for_update`.
+    pub select_by_id_for_update_sql: String,
+    /// SELECT of a constant for the row with a given primary key, not
+    /// selecting soft deleted rows. Run by `Crud::exists`.
+    pub exists_sql: String,
+    pub insert_sql: String,
+    /// [Metadata::insert_sql] updating the non-key columns of the row with
+    /// the same primary key instead, run by `Crud::upsert`.
+    pub upsert_sql: String,
+    /// [Metadata::insert_sql] skipping the row if one with the same primary
+    /// key exists, without RETURNING. Run by `Crud::create_ignore`.
+    pub insert_ignore_sql: String,
+    pub update_by_id_sql: String,
+    /// UPDATE of the row with a given primary key writing only the
+    /// `updated_at` timestamps and the version, if any, returning the row.
+    /// `None` for structs without an `updated_at` column. Run by the
+    /// `touch()` method generated for them.
+    pub touch_sql: Option<String>,
+    /// The statement run by `Crud::update`, [Metadata::update_by_id_sql]
+    /// preceded by [Metadata::audit_update_sql] for audited structs on
+    /// PostgreSQL. Other databases run the INSERT on its own first.
+    pub update_sql: String,
+    pub delete_by_id_sql: String,
+    /// [Metadata::delete_by_id_sql] returning the deleted row, `None` on
+    /// MySQL, which has no RETURNING clause. Run by
+    /// `Crud::delete_by_id_returning`.
+    pub delete_by_id_returning_sql: Option<String>,
+    pub delete_sql: String,
+    pub delete_permanently_by_id_sql: String,
+    /// Statement deleting every row, soft deleting the rows not yet deleted
+    /// for structs with a soft delete column. Run by `Crud::delete_all`.
+    pub delete_all_sql: String,
+    /// Statement removing every row, `TRUNCATE TABLE` on PostgreSQL and
+    /// MySQL and a DELETE on SQLite. Run by `Crud::truncate`.
+    pub truncate_sql: String,
+    /// The history table of `#[sqlx_crud(audit)]` structs, qualified by the
+    /// database schema if any.
+    pub history_table_name: Option<String>,
+    /// INSERT copying the row matched by [Metadata::update_by_id_sql] in to
+    /// the history table.
+    pub audit_update_sql: Option<String>,
+    /// INSERT copying the row matched by [Metadata::delete_sql] in to the
+    /// history table.
+    pub audit_delete_sql: Option<String>,
+    /// SELECT statements looking up a row by a `#[sqlx_crud(find_by)]`
+    /// column, by column name.
+    pub select_by_sql: HashMap<&'static str, String>,
+    /// SELECT statements of all rows matching an `#[sqlx_crud(indexed)]`
+    /// column ordered by [Metadata::order_by_sql] or else primary key, by
+    /// column name.
+    pub select_all_by_sql: HashMap<&'static str, String>,
+    /// Columns of `Option` fields, which may hold NULL.
+    pub nullable_columns: Vec<&'static str>,
+    /// SELECT of the name and nullability, `YES` or `NO`, of each column of
+    /// the table in the database catalog. Run by `Crud::verify`.
+    pub catalog_sql: String,
+    /// CREATE TABLE IF NOT EXISTS statement of the table, `None` if a column
+    /// has no [SqlType]. See `Schema::create_table_sql`.
+    pub create_table_sql: Option<String>,
+    /// The columns in field order, see [SchemaMetadata].
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    definitions: Vec<Column>,
+    insert: Insert,
+    deferred: Deferred,
+}
+
+/// Table and column metadata of a [Schema] for external tooling, such as
+/// code generators reading the models as JSON. Built from
+/// [Schema::metadata] and serializable with the `serde` feature.
+///
+/// # Example
+///
+#[cfg_attr(feature = "serde", doc = "```rust")]
+#[cfg_attr(not(feature = "serde"), doc = "```rust,ignore")]
+/// use sqlx::FromRow;
+/// use sqlx_crud::schema::SchemaMetadata;
+/// use sqlx_crud::{Schema, SqlxCrud};
+///
+/// #[derive(FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// struct Invoice {
+///     id: i64,
+///     #[sqlx_crud(rename = "memo_text")]
+///     memo: Option<String>,
+///     lines: Vec<u8>,
+/// }
+///
+/// let metadata = SchemaMetadata::from(Invoice::metadata());
+/// let json = serde_json::to_value(&metadata).unwrap();
+/// assert_eq!(
+///     serde_json::json!({
+///         "table_name": "invoices",
+///         "id_column": "id",
+///         "columns": [
+///             { "name": "id", "rust_type": "i64", "nullable": false, "is_id": true },
+///             { "name": "memo_text", "rust_type": "Option<String>", "nullable": true, "is_id": false },
+///             { "name": "lines", "rust_type": "Vec<u8>", "nullable": false, "is_id": false },
+///         ],
+///     }),
+///     json
+/// );
+/// assert_eq!(metadata, serde_json::from_value::<SchemaMetadata>(json).unwrap());
+/// ```
+///
+/// [Schema]: ../traits/trait.Schema.html
+/// [Schema::metadata]: ../traits/trait.Schema.html#tymethod.metadata
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SchemaMetadata {
+    /// Name of the table, qualified by the database schema if any.
+    pub table_name: String,
+    /// The first primary key column, see `Schema::id_column`.
+    pub id_column: String,
+    /// The columns in field order.
+    pub columns: Vec<ColumnMeta>,
+}
+
+/// A column of [SchemaMetadata].
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ColumnMeta {
+    /// Column name, unquoted.
+    pub name: String,
+    /// The field type as written in the struct.
+    pub rust_type: String,
+    /// The field is an `Option`.
+    pub nullable: bool,
+    /// The column is part of the primary key.
+    pub is_id: bool,
+}
+
+#[cfg(feature = "serde")]
+impl From<&Metadata> for SchemaMetadata {
+    fn from(metadata: &Metadata) -> Self {
+        Self {
+            table_name: metadata.qualified_table_name.clone(),
+            id_column: metadata.id_columns[0].to_string(),
+            columns: metadata
+                .definitions
+                .iter()
+                .map(|c| ColumnMeta {
+                    name: c.name.to_string(),
+                    rust_type: c.rust_type.to_string(),
+                    nullable: c.nullable,
+                    is_id: c.id,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Parts of the INSERT statement used to build it with columns left out.
+#[derive(Debug)]
+struct Insert {
+    table: String,
+    /// Quoted name and placeholder of each inserted column.
+    columns: Vec<(&'static str, String, &'static str)>,
+    returning: String,
+    default_values: &'static str,
+    /// Start of the conflict clause of the upsert statements, up to the SET
+    /// terms.
+    on_conflict: String,
+    /// Name and SET term of each column updated on conflict.
+    upserted: Vec<(&'static str, String)>,
+    /// SET term keeping the row as is, for upserts updating no column.
+    upsert_unchanged: String,
+    /// Statement keyword and conflict clause of the statements skipping rows
+    /// whose key exists.
+    ignore: (&'static str, String),
+    /// Upserts can return whether the row was inserted.
+    reports_upsert: bool,
+    /// Rewrites the `?` placeholders for the database, see [placeholders].
+    placeholders: fn(String) -> String,
+    /// Statements built by [Metadata::insert_sql_without] and its upsert
+    /// and ignoring variants, by omitted columns and conflict handling.
+    cache: Mutex<HashMap<(Vec<&'static str>, OnConflict), &'static str>>,
+    /// Statements of [Metadata::insert_many_sql] run by `Crud::create_many`,
+    /// by omitted columns and number of rows.
+    many_cache: Mutex<HashMap<(Vec<&'static str>, usize), &'static str>>,
+    /// Statements of [Metadata::insert_many_returning_sql], like
+    /// `many_cache`.
+    returning_cache: Mutex<HashMap<(Vec<&'static str>, usize), &'static str>>,
+}
+
+/// Handling of rows whose primary key exists by an INSERT statement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum OnConflict {
+    Fail,
+    Update,
+    /// Skips the row without returning it.
+    Ignore,
+    /// Updates the row like `Update` and also returns whether it was
+    /// inserted, as [UPSERT_INSERTED]. PostgreSQL only.
+    UpdateReporting,
+}
+
+/// Alias of the column of [Metadata::save_sql_without] telling whether the
+/// row was inserted.
+#[doc(hidden)]
+pub const UPSERT_INSERTED: &str = "sqlx_crud_inserted";
+
+/// Parts of the statements built on first use, for a given relation or set
+/// of columns.
+#[derive(Debug)]
+struct Deferred {
+    table: String,
+    column_list: String,
+    /// The qualified columns aliased `<table>_<column>`, keeping them apart
+    /// from the columns of joined tables.
+    aliased_column_list: String,
+    /// Hides soft deleted rows.
+    read_condition: Option<String>,
+    order_by_id: String,
+    /// SET clause term of each updated column, `None` for bound columns and
+    /// the SQL written for the others.
+    patch_columns: Vec<(&'static str, String, Option<String>)>,
+    /// WHERE clause of [Metadata::patch_sql].
+    patch_condition: String,
+    /// WHERE clause of [Metadata::versioned_patch_sql].
+    versioned_patch_condition: String,
+    /// Statement of [Metadata::delete_by_ids_sql] up to the WHERE clause, a
+    /// DELETE or the UPDATE of a soft delete.
+    delete_by_ids: String,
+    /// The qualified primary key columns.
+    id_columns: Vec<String>,
+    quote_ident: fn(&str) -> String,
+    /// Rewrites the `?` placeholders for the database, see [placeholders].
+    placeholders: fn(String) -> String,
+    /// Statements built by [Metadata::select_related_sql], the join table
+    /// queries and [Metadata::patch_sql].
+    cache: Mutex<HashMap<DeferredQuery, &'static str>>,
+}
+
+/// Key of a statement built on first use.
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum DeferredQuery {
+    Patch(Vec<&'static str>),
+    VersionedPatch(Vec<&'static str>),
+    Increment(Vec<&'static str>),
+    DeleteByIds(usize),
+    SelectByIds(usize),
+    DeleteWhere(Vec<Condition>),
+    SelectWhere(Vec<Condition>),
+    CountWhere(Vec<Condition>),
+    ExistsWhere(Vec<Condition>),
+    UpdateWhere(Vec<&'static str>, Vec<Condition>),
+    Select(Vec<Condition>, Vec<OrderBy>, bool, bool),
+    Aggregate(Aggregate, &'static str),
+    Pluck(&'static str, Vec<Condition>),
+    CloneRow(bool, bool),
+    SelectInserted(bool),
+    Keyset(Option<&'static str>, Direction, bool),
+    ForeignKey(&'static str, Vec<OrderBy>),
+    WithRelated(&'static str, &'static str, Vec<OrderBy>),
+    Through(JoinTable),
+    InsertThrough(JoinTable),
+    DeleteThrough(JoinTable),
+}
+
+/// A join table of a many-to-many relation, see
+/// [Metadata::select_through_sql].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct JoinTable {
+    /// Name of the join table, unquoted.
+    pub table: &'static str,
+    /// Column referencing the primary key of the struct declaring the
+    /// relation.
+    pub foreign_key: &'static str,
+    /// Column referencing the primary key of the related rows.
+    pub other_key: &'static str,
+}
+
+impl Insert {
+    fn sql(&self, omitted: &[&str], on_conflict: OnConflict) -> String {
+        (self.placeholders)(self.unnumbered_sql(omitted, on_conflict))
+    }
+
+    fn unnumbered_sql(&self, omitted: &[&str], on_conflict: OnConflict) -> String {
+        let columns = self
+            .columns
+            .iter()
+            .filter(|(name, _, _)| !omitted.contains(name))
+            .collect::<Vec<_>>();
+        let (insert, returning) = match on_conflict {
+            OnConflict::Ignore => (self.ignore.0, String::new()),
+            // A row inserted by the statement has no deleting transaction
+            OnConflict::UpdateReporting => (
+                "INSERT",
+                format!(
+                    " RETURNING {}, (xmax = 0) AS {}",
+                    self.returning, UPSERT_INSERTED
+                ),
+            ),
+            _ => ("INSERT", format!(" RETURNING {}", self.returning)),
+        };
+        // Without inserted columns the key is assigned by the database and
+        // never conflicts
+        if columns.is_empty() {
+            format!(
+                "{} INTO {} {}{}",
+                insert, self.table, self.default_values, returning
+            )
+        } else {
+            let on_conflict = match on_conflict {
+                OnConflict::Fail => String::new(),
+                // Omitted columns keep their stored value on conflict
+                OnConflict::Update | OnConflict::UpdateReporting => {
+                    let terms = self
+                        .upserted
+                        .iter()
+                        .filter(|(name, _)| !omitted.contains(name))
+                        .map(|(_, term)| term.as_str())
+                        .collect::<Vec<_>>();
+                    let terms = if terms.is_empty() {
+                        self.upsert_unchanged.clone()
+                    } else {
+                        terms.join(", ")
+                    };
+                    format!(" {} {}", self.on_conflict, terms)
+                }
+                OnConflict::Ignore => self.ignore.1.clone(),
+            };
+            format!(
+                "{} INTO {} ({}) VALUES ({}){}{}",
+                insert,
+                self.table,
+                columns
+                    .iter()
+                    .map(|(_, quoted, _)| quoted.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                columns
+                    .iter()
+                    .map(|(_, _, placeholder)| *placeholder)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                on_conflict,
+                returning
+            )
+        }
+    }
+
+    /// Returns the INSERT statement of `rows` rows, with a RETURNING clause
+    /// if `returning` is set.
+    fn many_sql(&self, omitted: &[&str], rows: usize, returning: bool) -> String {
+        let columns = self
+            .columns
+            .iter()
+            .filter(|(name, _, _)| !omitted.contains(name))
+            .collect::<Vec<_>>();
+        let returning = if returning {
+            format!(" RETURNING {}", self.returning)
+        } else {
+            String::new()
+        };
+        let sql = if columns.is_empty() {
+            vec![
+                format!(
+                    "INSERT INTO {} {}{}",
+                    self.table, self.default_values, returning
+                );
+                rows
+            ]
+            .join("; ")
+        } else {
+            let values = format!(
+                "({})",
+                columns
+                    .iter()
+                    .map(|(_, _, placeholder)| *placeholder)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            format!(
+                "INSERT INTO {} ({}) VALUES {}{}",
+                self.table,
+                columns
+                    .iter()
+                    .map(|(_, quoted, _)| quoted.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                vec![values; rows].join(", "),
+                returning
+            )
+        };
+        (self.placeholders)(sql)
+    }
+
+    /// Returns the statement with the `omitted` columns left out, built once
+    /// and kept for the life of the program.
+    fn cached_sql(&self, omitted: &[&'static str], on_conflict: OnConflict) -> &'static str {
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache
+            .entry((omitted.to_vec(), on_conflict))
+            .or_insert_with(|| Box::leak(self.sql(omitted, on_conflict).into_boxed_str()))
+    }
+}
+
+impl Metadata {
+    /// Builds the metadata of `table_name`, optionally in database schema
+    /// `schema_name`, for database `DB` from `columns`, given in field order.
+    /// The queries returning several rows sort them by `order_by`, or by
+    /// primary key if it is empty. With `audit` the update and delete
+    /// queries run by `Crud` first copy the row in to the history table.
+    pub fn new<DB: Database>(
+        schema_name: Option<&'static str>,
+        table_name: &'static str,
+        columns: Vec<Column>,
+        order_by: Vec<OrderBy>,
+        audit: bool,
+    ) -> Self {
+        let quote_ident = |ident: &str| quote_ident::<DB>(ident);
+        // Columns are prefixed with the qualified name so they stay
+        // unambiguous in joins across schemas
+        let (table, qualified_table_name) = match schema_name {
+            Some(schema_name) => (
+                format!("{}.{}", quote_ident(schema_name), quote_ident(table_name)),
+                format!("{}.{}", schema_name, table_name),
+            ),
+            None => (quote_ident(table_name), table_name.to_string()),
+        };
+        let qualified = |c: &Column| format!("{}.{}", &table, quote_ident(c.name));
+
+        assert!(
+            columns.iter().any(|c| c.id),
+            "{} has no primary key columns, tag the key fields of the flattened struct with #[id]",
+            table_name
+        );
+        let tenant = columns.iter().find(|c| c.tenant);
+        // Rows of other tenants are never matched, the tenant is bound after
+        // the primary key
+        let tenant_condition = tenant.map(|c| format!("{} = ?", qualified(c)));
+        let id_condition = with_condition(
+            &columns
+                .iter()
+                .filter(|c| c.id)
+                .map(|c| format!("{} = ?", qualified(c)))
+                .collect::<Vec<_>>()
+                .join(" AND "),
+            tenant_condition.as_deref(),
+        );
+        let version = columns.iter().find(|c| c.version);
+        // Versioned rows are only written when the stored version is unchanged
+        let versioned_id_condition = with_condition(
+            &id_condition,
+            version.map(|c| format!("{} = ?", qualified(c))).as_deref(),
+        );
+        let soft_delete = columns.iter().find(|c| c.soft_delete);
+        // Soft deleted rows are hidden from the read queries
+        let not_deleted = soft_delete.map(|c| format!("{} IS NULL", qualified(c)));
+        let read_condition_of = |column: &dyn Fn(&Column) -> String| {
+            let tenant = tenant.map(|c| format!("{} = ?", column(c)));
+            let not_deleted = soft_delete.map(|c| format!("{} IS NULL", column(c)));
+            match tenant {
+                Some(tenant) => Some(with_condition(&tenant, not_deleted.as_deref())),
+                None => not_deleted,
+            }
+        };
+        let read_condition = read_condition_of(&qualified);
+
+        let column_list = columns.iter().map(qualified).collect::<Vec<_>>().join(", ");
+        let unqualified = |c: &Column| quote_ident(c.name);
+        let unqualified_column_list = columns
+            .iter()
+            .map(unqualified)
+            .collect::<Vec<_>>()
+            .join(", ");
+        // SQLite only accepts table qualified columns in RETURNING
+        let returning = columns
+            .iter()
+            .map(|c| format!("{}.{}", quote_ident(table_name), quote_ident(c.name)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        // Upserts write the inserted value of each updated column, which
+        // MySQL refers to with VALUES() and the others with excluded
+        let excluded = |column: &str| match DB::NAME {
+            "MySQL" => format!("{0} = VALUES({0})", column),
+            _ => format!("{0} = excluded.{0}", column),
+        };
+        // MySQL has no conflict target, its conflicts are on any unique key
+        let conflict_target = columns
+            .iter()
+            .filter(|c| c.id)
+            .map(unqualified)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let (on_conflict, ignore) = match DB::NAME {
+            "MySQL" => (
+                "ON DUPLICATE KEY UPDATE".to_string(),
+                ("INSERT IGNORE", String::new()),
+            ),
+            _ => (
+                format!("ON CONFLICT ({}) DO UPDATE SET", conflict_target),
+                (
+                    "INSERT",
+                    format!(" ON CONFLICT ({}) DO NOTHING", conflict_target),
+                ),
+            ),
+        };
+        let upserted = columns
+            .iter()
+            .filter(|c| c.update && (c.insert || c.value.is_some() || c.version))
+            .map(|c| {
+                let column = quote_ident(c.name);
+                let term = if c.version {
+                    format!("{0} = {0} + 1", column)
+                } else if let Some(value) = c.value {
+                    format!("{} = {}", column, value)
+                } else {
+                    excluded(&column)
+                };
+                (c.name, term)
+            })
+            .collect();
+        let insert = Insert {
+            table: table.clone(),
+            columns: columns
+                .iter()
+                .filter(|c| c.insert)
+                .map(|c| (c.name, quote_ident(c.name), c.placeholder()))
+                .collect(),
+            returning: returning.clone(),
+            default_values: default_values::<DB>(),
+            on_conflict,
+            upserted,
+            upsert_unchanged: columns
+                .iter()
+                .find(|c| c.id)
+                .map(|c| excluded(&quote_ident(c.name)))
+                .unwrap_or_default(),
+            ignore,
+            reports_upsert: DB::NAME == "PostgreSQL",
+            placeholders: placeholders::<DB>,
+            cache: Mutex::new(HashMap::new()),
+            many_cache: Mutex::new(HashMap::new()),
+            returning_cache: Mutex::new(HashMap::new()),
+        };
+        let update_sql_binds = columns
+            .iter()
+            .filter(|c| c.update)
+            .map(|c| {
+                let column = quote_ident(c.name);
+                if c.version {
+                    format!("{0} = {0} + 1", column)
+                } else {
+                    format!("{} = {}", column, c.placeholder())
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let select = |column_list: &str, read_condition: Option<String>| match read_condition {
+            Some(read_condition) => format!(
+                "SELECT {} FROM {} WHERE {}",
+                column_list, table, read_condition
+            ),
+            None => format!("SELECT {} FROM {}", column_list, table),
+        };
+        let select_sql = select(&column_list, read_condition.clone());
+        let select_sql_unqualified =
+            select(&unqualified_column_list, read_condition_of(&unqualified));
+        let count_sql = select("COUNT(*)", read_condition.clone());
+        let select_by_id_sql = format!(
+            "SELECT {} FROM {} WHERE {} LIMIT 1",
+            column_list,
+            table,
+            with_condition(&id_condition, not_deleted.as_deref())
+        );
+        let select_by_id_for_update_sql = match DB::NAME {
+            "PostgreSQL" | "MySQL" => format!("{} FOR UPDATE", select_by_id_sql),
+            _ => select_by_id_sql.clone(),
+        };
+        let exists_sql = format!(
+            "SELECT 1 FROM {} WHERE {} LIMIT 1",
+            table,
+            with_condition(&id_condition, not_deleted.as_deref())
+        );
+        let select_by_sql = columns
+            .iter()
+            .filter(|c| c.find_by)
+            .map(|c| {
+                let condition = format!("{} = ?", qualified(c));
+                let sql = format!(
+                    "SELECT {} FROM {} WHERE {} LIMIT 1",
+                    column_list,
+                    table,
+                    with_condition(&condition, read_condition.as_deref())
+                );
+                (c.name, placeholders::<DB>(sql))
+            })
+            .collect();
+        let order_by_sql = (!order_by.is_empty())
+            .then(|| order_by_clause(&table, &order_by, self::quote_ident::<DB>));
+        // The first and last rows in the order of the rows, ties broken by
+        // the primary key
+        let row_order = order_by
+            .iter()
+            .copied()
+            .chain(columns.iter().filter(|c| c.id).map(|c| OrderBy {
+                column: c.name,
+                descending: false,
+            }))
+            .collect::<Vec<_>>();
+        let reversed = row_order
+            .iter()
+            .map(|o| OrderBy {
+                descending: !o.descending,
+                ..*o
+            })
+            .collect::<Vec<_>>();
+        let first_sql = format!(
+            "{} ORDER BY {} LIMIT 1",
+            select_sql,
+            order_by_clause(&table, &row_order, self::quote_ident::<DB>)
+        );
+        let last_sql = format!(
+            "{} ORDER BY {} LIMIT 1",
+            select_sql,
+            order_by_clause(&table, &reversed, self::quote_ident::<DB>)
+        );
+        let order_by_id = columns
+            .iter()
+            .filter(|c| c.id)
+            .map(qualified)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let order_rows = order_by_sql.as_ref().unwrap_or(&order_by_id);
+        let select_all_sql = match &order_by_sql {
+            Some(order_by_sql) => format!("{} ORDER BY {}", select_sql, order_by_sql),
+            None => select_sql.clone(),
+        };
+        // Ties of the order_by columns are broken by the primary key, so
+        // each row is on exactly one page
+        let paginated_sql = match &order_by_sql {
+            Some(order_by_sql) => format!(
+                "{} ORDER BY {}, {} LIMIT ? OFFSET ?",
+                select_sql, order_by_sql, order_by_id
+            ),
+            None => format!("{} ORDER BY {} LIMIT ? OFFSET ?", select_sql, order_by_id),
+        };
+        let select_all_by_sql = columns
+            .iter()
+            .filter(|c| c.indexed)
+            .map(|c| {
+                let condition = format!("{} = ?", qualified(c));
+                let sql = format!(
+                    "SELECT {} FROM {} WHERE {} ORDER BY {}",
+                    column_list,
+                    table,
+                    with_condition(&condition, read_condition.as_deref()),
+                    order_rows
+                );
+                (c.name, placeholders::<DB>(sql))
+            })
+            .collect();
+        let insert_sql = insert.sql(&[], OnConflict::Fail);
+        let upsert_sql = insert.sql(&[], OnConflict::Update);
+        let insert_ignore_sql = insert.sql(&[], OnConflict::Ignore);
+        let update_by_id_sql = format!(
+            "UPDATE {} SET {} WHERE {} RETURNING {}",
+            table, update_sql_binds, versioned_id_condition, returning
+        );
+        // Touching a row writes the generated values of the update alone:
+        // the updated_at timestamps and the version
+        let touch_sql = columns
+            .iter()
+            .any(|c| c.update && !c.version && c.value.is_some())
+            .then(|| {
+                let set = columns
+                    .iter()
+                    .filter(|c| c.update && (c.version || c.value.is_some()))
+                    .map(|c| {
+                        let column = quote_ident(c.name);
+                        match c.value {
+                            Some(value) if !c.version => format!("{} = {}", column, value),
+                            _ => format!("{0} = {0} + 1", column),
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!(
+                    "UPDATE {} SET {} WHERE {} RETURNING {}",
+                    table,
+                    set,
+                    with_condition(&versioned_id_condition, not_deleted.as_deref()),
+                    returning
+                )
+            });
+        let delete_permanently_by_id_sql = format!("DELETE FROM {} WHERE {}", table, id_condition);
+        let delete_all_sql = match soft_delete {
+            Some(c) => format!(
+                "UPDATE {} SET {1} = CURRENT_TIMESTAMP WHERE {1} IS NULL",
+                table,
+                quote_ident(c.name)
+            ),
+            None => format!("DELETE FROM {}", table),
+        };
+        let truncate_sql = match DB::NAME {
+            "PostgreSQL" | "MySQL" => format!("TRUNCATE TABLE {}", table),
+            _ => format!("DELETE FROM {}", table),
+        };
+        let delete_by_ids = match soft_delete {
+            Some(c) => format!(
+                "UPDATE {} SET {} = CURRENT_TIMESTAMP WHERE",
+                table,
+                quote_ident(c.name)
+            ),
+            None => format!("DELETE FROM {} WHERE", table),
+        };
+        let (delete_by_id_sql, delete_sql, delete_condition) = match soft_delete {
+            Some(_) => {
+                let soft_delete = delete_by_ids.clone();
+                let delete_condition =
+                    with_condition(&versioned_id_condition, not_deleted.as_deref());
+                (
+                    format!(
+                        "{} {}",
+                        soft_delete,
+                        with_condition(&id_condition, not_deleted.as_deref())
+                    ),
+                    format!("{} {}", soft_delete, delete_condition),
+                    delete_condition,
+                )
+            }
+            None => (
+                delete_permanently_by_id_sql.clone(),
+                format!("DELETE FROM {} WHERE {}", table, versioned_id_condition),
+                versioned_id_condition.clone(),
+            ),
+        };
+
+        // Audited writes first copy the row they match in to the history
+        // table, binding the same WHERE clause. PostgreSQL runs both in one
+        // statement, other databases run the INSERT as a statement of its
+        // own in the transaction of the write.
+        let history = audit.then(|| {
+            let history_name = format!("{}_history", table_name);
+            match schema_name {
+                Some(schema_name) => (
+                    format!(
+                        "{}.{}",
+                        quote_ident(schema_name),
+                        quote_ident(&history_name)
+                    ),
+                    format!("{}.{}", schema_name, history_name),
+                ),
+                None => (quote_ident(&history_name), history_name),
+            }
+        });
+        let audit_sql = |operation: &str, condition: &str| {
+            history.as_ref().map(|(history, _)| {
+                format!(
+                    "INSERT INTO {} ({}, {}, {}) SELECT {}, CURRENT_TIMESTAMP, '{}' FROM {} WHERE {}",
+                    history,
+                    unqualified_column_list,
+                    quote_ident("audited_at"),
+                    quote_ident("operation"),
+                    column_list,
+                    operation,
+                    table,
+                    condition
+                )
+            })
+        };
+        let audit_update_sql = audit_sql("update", &versioned_id_condition);
+        let audit_delete_sql = audit_sql("delete", &delete_condition);
+        let audited = |audit_sql: &Option<String>, sql: String| match audit_sql {
+            Some(audit_sql) if DB::NAME == "PostgreSQL" => {
+                format!("WITH audit AS ({}) {}", audit_sql, sql)
+            }
+            _ => sql,
+        };
+        let update_sql = audited(&audit_update_sql, update_by_id_sql.clone());
+        let delete_sql = audited(&audit_delete_sql, delete_sql);
+        let catalog_sql = catalog_sql::<DB>(schema_name, table_name);
+        let create_table_sql = columns
+            .iter()
+            .map(|c| {
+                c.sql_type
+                    .map(|sql_type| column_definition::<DB>(c, sql_type))
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(|definitions| {
+                format!(
+                    "CREATE TABLE IF NOT EXISTS {} ({}, PRIMARY KEY ({}))",
+                    table,
+                    definitions.join(", "),
+                    columns
+                        .iter()
+                        .filter(|c| c.id)
+                        .map(unqualified)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            });
+        let aliased_column_list = columns
+            .iter()
+            .map(|c| {
+                format!(
+                    "{} AS {}",
+                    qualified(c),
+                    quote_ident(&format!("{}_{}", table_name, c.name))
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let patch_columns = columns
+            .iter()
+            .filter(|c| c.update)
+            .map(|c| {
+                let column = quote_ident(c.name);
+                let value = if c.version {
+                    Some(format!("{} + 1", column))
+                } else {
+                    c.value.map(str::to_string)
+                };
+                (c.name, column, value)
+            })
+            .collect();
+        let deferred = Deferred {
+            table: table.clone(),
+            column_list: column_list.clone(),
+            aliased_column_list,
+            read_condition: not_deleted.clone(),
+            order_by_id: order_by_id.clone(),
+            patch_columns,
+            patch_condition: with_condition(&id_condition, not_deleted.as_deref()),
+            versioned_patch_condition: with_condition(
+                &versioned_id_condition,
+                not_deleted.as_deref(),
+            ),
+            delete_by_ids,
+            id_columns: columns.iter().filter(|c| c.id).map(qualified).collect(),
+            quote_ident: self::quote_ident::<DB>,
+            placeholders: placeholders::<DB>,
+            cache: Mutex::new(HashMap::new()),
+        };
+
+        Self {
+            schema_name,
+            table_name,
+            qualified_table_name,
+            id_columns: columns.iter().filter(|c| c.id).map(|c| c.name).collect(),
+            columns: columns.iter().map(|c| c.name).collect(),
+            version_column: version.map(|c| c.name),
+            soft_delete_column: soft_delete.map(|c| c.name),
+            tenant_column: tenant.map(|c| c.name),
+            select_sql: placeholders::<DB>(select_sql),
+            select_all_sql: placeholders::<DB>(select_all_sql),
+            order_by_sql,
+            paginated_sql: placeholders::<DB>(paginated_sql),
+            count_sql: placeholders::<DB>(count_sql),
+            first_sql: placeholders::<DB>(first_sql),
+            last_sql: placeholders::<DB>(last_sql),
+            select_sql_unqualified: placeholders::<DB>(select_sql_unqualified),
+            unqualified_column_list,
+            select_by_id_sql: placeholders::<DB>(select_by_id_sql),
+            select_by_id_for_update_sql: placeholders::<DB>(select_by_id_for_update_sql),
+            exists_sql: placeholders::<DB>(exists_sql),
+            insert_sql,
+            upsert_sql,
+            insert_ignore_sql,
+            update_by_id_sql: placeholders::<DB>(update_by_id_sql),
+            touch_sql: touch_sql.map(placeholders::<DB>),
+            update_sql: placeholders::<DB>(update_sql),
+            delete_by_id_returning_sql: (DB::NAME != "MySQL").then(|| {
+                placeholders::<DB>(format!("{} RETURNING {}", delete_by_id_sql, returning))
+            }),
+            delete_by_id_sql: placeholders::<DB>(delete_by_id_sql),
+            delete_sql: placeholders::<DB>(delete_sql),
+            delete_permanently_by_id_sql: placeholders::<DB>(delete_permanently_by_id_sql),
+            delete_all_sql,
+            truncate_sql,
+            history_table_name: history.map(|(_, name)| name),
+            audit_update_sql: audit_update_sql.map(placeholders::<DB>),
+            audit_delete_sql: audit_delete_sql.map(placeholders::<DB>),
+            select_by_sql,
+            select_all_by_sql,
+            nullable_columns: columns
+                .iter()
+                .filter(|c| c.nullable)
+                .map(|c| c.name)
+                .collect(),
+            catalog_sql,
+            create_table_sql,
+            definitions: columns,
+            insert,
+            deferred,
+        }
+    }
+
+    /// Returns the INSERT statement with the `omitted` columns left out, so
+    /// the database applies their defaults. Each distinct statement is built
+    /// once and kept for the life of the program. Without omitted columns
+    /// this is [Metadata::insert_sql].
+    pub fn insert_sql_without(&'static self, omitted: &[&'static str]) -> &'static str {
+        if omitted.is_empty() {
+            return &self.insert_sql;
+        }
+        self.insert.cached_sql(omitted, OnConflict::Fail)
+    }
+
+    /// Returns the INSERT statement of `rows` rows with the `omitted` columns
+    /// left out, binding the inserted columns of each row in turn. Unlike
+    /// [Metadata::insert_sql] it has no RETURNING clause. The statement is
+    /// built on every call.
+    pub fn insert_many_sql(&self, rows: usize, omitted: &[&str]) -> String {
+        self.insert.many_sql(omitted, rows, false)
+    }
+
+    /// Returns [Metadata::insert_many_sql] with the RETURNING clause of
+    /// [Metadata::insert_sql], returning the inserted rows in the order of
+    /// their values on SQLite and PostgreSQL.
+    pub fn insert_many_returning_sql(&self, rows: usize, omitted: &[&str]) -> String {
+        self.insert.many_sql(omitted, rows, true)
+    }
+
+    /// Returns [Metadata::insert_many_sql], or with `returning`
+    /// [Metadata::insert_many_returning_sql], built once and kept for the
+    /// life of the program. Only called for the lengths of [batch_lens], so
+    /// few statements are kept. Run by `Crud::create_many` and
+    /// `Crud::create_many_returning`.
+    pub(crate) fn insert_many_cached_sql(
+        &'static self,
+        rows: usize,
+        omitted: &[&'static str],
+        returning: bool,
+    ) -> &'static str {
+        let cache = match returning {
+            false => &self.insert.many_cache,
+            true => &self.insert.returning_cache,
+        };
+        let mut cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache.entry((omitted.to_vec(), rows)).or_insert_with(|| {
+            Box::leak(
+                self.insert
+                    .many_sql(omitted, rows, returning)
+                    .into_boxed_str(),
+            )
+        })
+    }
+
+    /// Returns the query reading back a row inserted without RETURNING on
+    /// the same connection, as MySQL has none, and whether the primary key
+    /// is bound to it. A single key column left to the database, being
+    /// omitted or not inserted, is matched with `LAST_INSERT_ID()`, other
+    /// keys are bound. Run by `Crud::create_many_returning`.
+    pub fn select_inserted_sql(&'static self, omitted: &[&'static str]) -> (&'static str, bool) {
+        let ids = self.definitions.iter().filter(|c| c.id).collect::<Vec<_>>();
+        let assigned = matches!(ids.as_slice(), [id] if !id.insert || omitted.contains(&id.name));
+        let deferred = &self.deferred;
+        let sql = self.deferred_sql(DeferredQuery::SelectInserted(assigned), || {
+            let condition = if assigned {
+                format!("{} = LAST_INSERT_ID()", deferred.id_columns[0])
+            } else {
+                deferred
+                    .id_columns
+                    .iter()
+                    .map(|c| format!("{} = ?", c))
+                    .collect::<Vec<_>>()
+                    .join(" AND ")
+            };
+            format!(
+                "SELECT {} FROM {} WHERE {} LIMIT 1",
+                deferred.column_list, deferred.table, condition
+            )
+        });
+        (sql, !assigned)
+    }
+
+    /// Returns the INSERT copying the row with the bound primary key in to a
+    /// new row, in one statement. The new primary key is bound ahead of the
+    /// source key if `new_key`, otherwise it is left to the database. The
+    /// inserted columns are copied, except for the columns written by the
+    /// database on insert, such as `#[sqlx_crud(created_at)]` timestamps,
+    /// which are written anew. A RETURNING clause follows if `returning`.
+    /// Soft deleted rows are not copied. Run by `Crud::clone_row` and
+    /// `Crud::clone_row_as`.
+    pub fn clone_row_sql(&'static self, new_key: bool, returning: bool) -> &'static str {
+        let deferred = &self.deferred;
+        self.deferred_sql(DeferredQuery::CloneRow(new_key, returning), || {
+            let (columns, values): (Vec<_>, Vec<_>) = self
+                .definitions
+                .iter()
+                .filter(|c| if c.id { new_key } else { c.insert })
+                .map(|c| {
+                    let column = (deferred.quote_ident)(c.name);
+                    let value = match c.value {
+                        _ if c.id => "?".to_string(),
+                        Some(value) => value.to_string(),
+                        None => format!("{}.{}", deferred.table, column),
+                    };
+                    (column, value)
+                })
+                .unzip();
+            let id_condition = deferred
+                .id_columns
+                .iter()
+                .map(|c| format!("{} = ?", c))
+                .collect::<Vec<_>>()
+                .join(" AND ");
+            let insert = format!(
+                "INSERT INTO {} ({}) SELECT {} FROM {} WHERE {}",
+                deferred.table,
+                columns.join(", "),
+                values.join(", "),
+                deferred.table,
+                with_condition(&id_condition, deferred.read_condition.as_deref())
+            );
+            if returning {
+                format!("{} RETURNING {}", insert, self.insert.returning)
+            } else {
+                insert
+            }
+        })
+    }
+
+    /// Returns the number of values bound for each row by the INSERT
+    /// statement with the `omitted` columns left out.
+    pub fn insert_bind_count(&self, omitted: &[&'static str]) -> usize {
+        self.insert
+            .columns
+            .iter()
+            .filter(|(name, _, placeholder)| !omitted.contains(name) && *placeholder == "?")
+            .count()
+    }
+
+    /// Returns the upsert statement with the `omitted` columns left out, like
+    /// [Metadata::insert_sql_without]. The omitted columns are inserted with
+    /// their defaults and keep their stored value on conflict. Without
+    /// omitted columns this is [Metadata::upsert_sql].
+    pub fn upsert_sql_without(&'static self, omitted: &[&'static str]) -> &'static str {
+        if omitted.is_empty() {
+            return &self.upsert_sql;
+        }
+        self.insert.cached_sql(omitted, OnConflict::Update)
+    }
+
+    /// Returns the upsert statement of [Crud::save] with the `omitted`
+    /// columns left out, like [Metadata::upsert_sql_without], returning
+    /// whether the row was inserted after its columns. `None` except on
+    /// PostgreSQL, whose upserts tell inserted rows apart.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Schema;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// #[external_id]
+    /// struct Price {
+    ///     id: i64,
+    ///     amount: i64,
+    /// }
+    ///
+    /// assert_eq!(None, Price::metadata().save_sql_without(&[]));
+    ///
+    /// #[cfg(feature = "postgres")]
+    /// {
+    ///     #[derive(Debug, FromRow, SqlxCrud)]
+    ///     #[database(Postgres)]
+    ///     #[external_id]
+    ///     struct Price {
+    ///         id: i64,
+    ///         amount: i64,
+    ///     }
+    ///
+    ///     assert_eq!(
+    ///         Some(concat!(
+    ///             r#"INSERT INTO "prices" ("id", "amount") VALUES ($1, $2) "#,
+    ///             r#"ON CONFLICT ("id") DO UPDATE SET "amount" = excluded."amount" "#,
+    ///             r#"RETURNING "prices"."id", "prices"."amount", (xmax = 0) AS sqlx_crud_inserted"#,
+    ///         )),
+    ///         Price::metadata().save_sql_without(&[])
+    ///     );
+    /// }
+    /// # }}
+    /// ```
+    ///
+    /// [Crud::save]: ../traits/trait.Crud.html#method.save
+    pub fn save_sql_without(&'static self, omitted: &[&'static str]) -> Option<&'static str> {
+        self.insert
+            .reports_upsert
+            .then(|| self.insert.cached_sql(omitted, OnConflict::UpdateReporting))
+    }
+
+    /// Returns the ignoring insert statement with the `omitted` columns left
+    /// out, like [Metadata::insert_sql_without]. Without omitted columns
+    /// this is [Metadata::insert_ignore_sql].
+    pub fn insert_ignore_sql_without(&'static self, omitted: &[&'static str]) -> &'static str {
+        if omitted.is_empty() {
+            return &self.insert_ignore_sql;
+        }
+        self.insert.cached_sql(omitted, OnConflict::Ignore)
+    }
+}
+
+impl Metadata {
+    /// Returns the query selecting the rows whose `foreign_key` column
+    /// matches a bound value, sorted by `order_by` or else by primary key.
+    /// Soft deleted rows are left out. Each distinct statement is built once
+    /// and kept for the life of the program. Used by the accessors of
+    /// `#[sqlx_crud(has_many(...))]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `foreign_key` is not a column of the table.
+    pub fn select_related_sql(
+        &'static self,
+        foreign_key: &'static str,
+        order_by: &[OrderBy],
+    ) -> &'static str {
+        assert!(
+            self.columns.contains(&foreign_key),
+            "{} has no column {} to look up related rows by",
+            self.table_name,
+            foreign_key
+        );
+        let deferred = &self.deferred;
+        self.deferred_sql(
+            DeferredQuery::ForeignKey(foreign_key, order_by.to_vec()),
+            || {
+                let condition = format!(
+                    "{}.{} = ?",
+                    deferred.table,
+                    (deferred.quote_ident)(foreign_key)
+                );
+                let order_rows = if order_by.is_empty() {
+                    deferred.order_by_id.clone()
+                } else {
+                    order_by_clause(&deferred.table, order_by, deferred.quote_ident)
+                };
+                format!(
+                    "SELECT {} FROM {} WHERE {} ORDER BY {}",
+                    deferred.column_list,
+                    deferred.table,
+                    with_condition(&condition, deferred.read_condition.as_deref()),
+                    order_rows
+                )
+            },
+        )
+    }
+
+    /// Returns the query selecting every row of this table LEFT JOINed to the
+    /// rows of `related` whose `foreign_key` column holds its primary key.
+    /// The columns of both tables are aliased `<table>_<column>` and read
+    /// back with [TableRow]. Rows are sorted like [Metadata::select_all_sql],
+    /// the rows of a parent are adjacent and sorted by `order_by` or else by
+    /// primary key. Soft deleted rows of either table are left out. Used by
+    /// the `all_with_<relation>()` methods of `#[sqlx_crud(has_many(...))]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `foreign_key` is not a column of `related` or this table has
+    /// a composite primary key.
+    pub fn select_with_related_sql(
+        &'static self,
+        related: &'static Metadata,
+        foreign_key: &'static str,
+        order_by: &[OrderBy],
+    ) -> &'static str {
+        assert!(
+            related.columns.contains(&foreign_key),
+            "{} has no column {} to look up related rows by",
+            related.table_name,
+            foreign_key
+        );
+        assert!(
+            self.id_columns.len() == 1,
+            "{} has a composite primary key and cannot be joined to {}",
+            self.table_name,
+            related.table_name
+        );
+        let key = DeferredQuery::WithRelated(
+            &related.qualified_table_name,
+            foreign_key,
+            order_by.to_vec(),
+        );
+        self.deferred_sql(key, || {
+            let (parent, child) = (&self.deferred, &related.deferred);
+            let quote_ident = parent.quote_ident;
+            let id = format!("{}.{}", parent.table, quote_ident(self.id_columns[0]));
+            let join_condition = format!("{}.{} = {}", child.table, quote_ident(foreign_key), id);
+            let from = format!(
+                "{} LEFT JOIN {} ON {}",
+                parent.table,
+                child.table,
+                with_condition(&join_condition, child.read_condition.as_deref())
+            );
+            let child_order = if order_by.is_empty() {
+                child.order_by_id.clone()
+            } else {
+                order_by_clause(&child.table, order_by, quote_ident)
+            };
+            // The primary key keeps the rows of a parent adjacent when the
+            // parents tie on their order
+            let order = match &self.order_by_sql {
+                Some(order_by_sql) => format!("{}, {}, {}", order_by_sql, id, child_order),
+                None => format!("{}, {}", parent.order_by_id, child_order),
+            };
+            let select = format!(
+                "SELECT {}, {} FROM {}",
+                parent.aliased_column_list, child.aliased_column_list, from
+            );
+            match &parent.read_condition {
+                Some(condition) => format!("{} WHERE {} ORDER BY {}", select, condition, order),
+                None => format!("{} ORDER BY {}", select, order),
+            }
+        })
+    }
+
+    /// Returns the query selecting the rows joined through `join` to a bound
+    /// primary key of another table, sorted by primary key. Soft deleted
+    /// rows are left out. Used by the accessors of
+    /// `#[sqlx_crud(many_to_many(...))]` on the other table.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the table has a composite primary key.
+    pub fn select_through_sql(&'static self, join: JoinTable) -> &'static str {
+        assert!(
+            self.id_columns.len() == 1,
+            "{} has a composite primary key and cannot be joined through {}",
+            self.table_name,
+            join.table
+        );
+        let deferred = &self.deferred;
+        self.deferred_sql(DeferredQuery::Through(join), || {
+            let quote_ident = deferred.quote_ident;
+            let through = quote_ident(join.table);
+            let condition = format!("{}.{} = ?", through, quote_ident(join.foreign_key));
+            format!(
+                "SELECT {} FROM {} INNER JOIN {} ON {}.{} = {}.{} WHERE {} ORDER BY {}",
+                deferred.column_list,
+                deferred.table,
+                through,
+                through,
+                quote_ident(join.other_key),
+                deferred.table,
+                quote_ident(self.id_columns[0]),
+                with_condition(&condition, deferred.read_condition.as_deref()),
+                deferred.order_by_id
+            )
+        })
+    }
+
+    /// Returns the statement inserting a row of `join`, binding the primary
+    /// key of this table followed by the primary key of the other table.
+    pub fn insert_through_sql(&'static self, join: JoinTable) -> &'static str {
+        let quote_ident = self.deferred.quote_ident;
+        self.deferred_sql(DeferredQuery::InsertThrough(join), || {
+            format!(
+                "INSERT INTO {} ({}, {}) VALUES (?, ?)",
+                quote_ident(join.table),
+                quote_ident(join.foreign_key),
+                quote_ident(join.other_key)
+            )
+        })
+    }
+
+    /// Returns the statement deleting a row of `join`, binding the primary
+    /// key of this table followed by the primary key of the other table.
+    pub fn delete_through_sql(&'static self, join: JoinTable) -> &'static str {
+        let quote_ident = self.deferred.quote_ident;
+        self.deferred_sql(DeferredQuery::DeleteThrough(join), || {
+            let through = quote_ident(join.table);
+            format!(
+                "DELETE FROM {} WHERE {}.{} = ? AND {}.{} = ?",
+                through,
+                through,
+                quote_ident(join.foreign_key),
+                through,
+                quote_ident(join.other_key)
+            )
+        })
+    }
+
+    /// Returns the UPDATE statement of a partial update writing the `patched`
+    /// columns, bound in column order, followed by the primary key. Columns
+    /// with generated values, such as the version, are written as by
+    /// [Metadata::update_by_id_sql]. Soft deleted rows are not updated. Used
+    /// by the `patch()` method of `#[sqlx_crud(patch)]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `patched` is empty or lists a column not written by
+    /// [Metadata::update_by_id_sql].
+    pub fn patch_sql(&'static self, patched: &[&'static str]) -> &'static str {
+        self.patch_sql_where(patched, false)
+    }
+
+    /// Returns [Metadata::patch_sql] only writing the row if it still has
+    /// the version bound after the primary key, for structs with a version
+    /// column. The version is incremented as by [Metadata::patch_sql]. Used
+    /// by `Crud::update_fields`.
+    ///
+    /// # Panics
+    ///
+    /// Panics as [Metadata::patch_sql] does.
+    pub fn versioned_patch_sql(&'static self, patched: &[&'static str]) -> &'static str {
+        self.patch_sql_where(patched, self.version_column.is_some())
+    }
+
+    /// [Metadata::patch_sql], also comparing the version when `versioned`.
+    fn patch_sql_where(&'static self, patched: &[&'static str], versioned: bool) -> &'static str {
+        let deferred = &self.deferred;
+        assert!(
+            !patched.is_empty(),
+            "a patch of {} writes no columns",
+            self.table_name
+        );
+        for column in patched {
+            assert!(
+                self.is_updated_column(column),
+                "{} is not an updated column of {}",
+                column,
+                self.table_name
+            );
+        }
+        let key = match versioned {
+            true => DeferredQuery::VersionedPatch(patched.to_vec()),
+            false => DeferredQuery::Patch(patched.to_vec()),
+        };
+        self.deferred_sql(key, || {
+            let set = deferred
+                .patch_columns
+                .iter()
+                .filter_map(|(name, column, value)| match value {
+                    Some(value) => Some(format!("{} = {}", column, value)),
+                    None => patched.contains(name).then(|| format!("{} = ?", column)),
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            let condition = match versioned {
+                true => &deferred.versioned_patch_condition,
+                false => &deferred.patch_condition,
+            };
+            format!("UPDATE {} SET {} WHERE {}", deferred.table, set, condition)
+        })
+    }
+
+    /// Returns the UPDATE statement adding a bound delta to each of the
+    /// `incremented` columns of the row with the primary key, in the given
+    /// order and followed by the primary key. The columns are written
+    /// relative to their stored values, so concurrent increments are not
+    /// lost. Columns with generated values, such as the version, are written
+    /// as by [Metadata::update_by_id_sql]. Soft deleted rows are not updated.
+    /// Used by `Crud::increment_columns`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `incremented` is empty or lists a column not written by
+    /// [Metadata::update_by_id_sql].
+    pub fn increment_sql(&'static self, incremented: &[&'static str]) -> &'static str {
+        let deferred = &self.deferred;
+        assert!(
+            !incremented.is_empty(),
+            "an increment of {} writes no columns",
+            self.table_name
+        );
+        for column in incremented {
+            assert!(
+                self.is_updated_column(column),
+                "{} is not an updated column of {}",
+                column,
+                self.table_name
+            );
+        }
+        self.deferred_sql(DeferredQuery::Increment(incremented.to_vec()), || {
+            let added = incremented.iter().map(|c| {
+                let column = (deferred.quote_ident)(c);
+                format!("{} = {} + ?", column, column)
+            });
+            let generated = deferred
+                .patch_columns
+                .iter()
+                .filter_map(|(_, column, value)| {
+                    value
+                        .as_ref()
+                        .map(|value| format!("{} = {}", column, value))
+                });
+            format!(
+                "UPDATE {} SET {} WHERE {}",
+                deferred.table,
+                added.chain(generated).collect::<Vec<_>>().join(", "),
+                deferred.patch_condition
+            )
+        })
+    }
+
+    /// Returns the statement deleting the rows whose primary key is one of
+    /// `ids` bound keys, with the key columns of each bound in turn. A
+    /// single column key is matched with `IN`, composite keys with one
+    /// condition per key. Rows with a soft delete column are soft deleted,
+    /// like [Metadata::delete_by_id_sql]. The statement is built on every
+    /// call.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ids` is zero.
+    pub fn delete_by_ids_sql(&self, ids: usize) -> String {
+        (self.deferred.placeholders)(self.unnumbered_delete_by_ids_sql(ids))
+    }
+
+    /// Returns [Metadata::delete_by_ids_sql] for `ids` keys rounded up to
+    /// one of the few lengths kept for the life of the program, at most
+    /// `max`, and the number of keys it binds. Used by `Crud::delete_many`,
+    /// which binds the last key again in the extra places.
+    pub(crate) fn delete_by_ids_padded_sql(
+        &'static self,
+        ids: usize,
+        max: usize,
+    ) -> (&'static str, usize) {
+        let padded = padded_len(ids, max);
+        let sql = self.deferred_sql(DeferredQuery::DeleteByIds(padded), || {
+            self.unnumbered_delete_by_ids_sql(padded)
+        });
+        (sql, padded)
+    }
+
+    fn unnumbered_delete_by_ids_sql(&self, ids: usize) -> String {
+        let deferred = &self.deferred;
+        assert!(
+            ids > 0,
+            "a delete of {} by ids matches no rows",
+            self.table_name
+        );
+        format!(
+            "{} {}",
+            deferred.delete_by_ids,
+            with_condition(&self.ids_condition(ids), deferred.read_condition.as_deref())
+        )
+    }
+
+    /// Returns the SELECT of the rows whose primary key is one of `ids`
+    /// bound keys, matched as by [Metadata::delete_by_ids_sql]. Soft deleted
+    /// rows are not selected. The statement is built on every call.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ids` is zero.
+    pub fn select_by_ids_sql(&self, ids: usize) -> String {
+        (self.deferred.placeholders)(self.unnumbered_select_by_ids_sql(ids))
+    }
+
+    /// Returns [Metadata::select_by_ids_sql] for `ids` keys rounded up like
+    /// [Metadata::delete_by_ids_sql] by `delete_by_ids_padded_sql`, and the
+    /// number of keys it binds. Used by `Crud::by_ids`.
+    pub(crate) fn select_by_ids_padded_sql(
+        &'static self,
+        ids: usize,
+        max: usize,
+    ) -> (&'static str, usize) {
+        let padded = padded_len(ids, max);
+        let sql = self.deferred_sql(DeferredQuery::SelectByIds(padded), || {
+            self.unnumbered_select_by_ids_sql(padded)
+        });
+        (sql, padded)
+    }
+
+    fn unnumbered_select_by_ids_sql(&self, ids: usize) -> String {
+        let deferred = &self.deferred;
+        assert!(
+            ids > 0,
+            "a select of {} by ids matches no rows",
+            self.table_name
+        );
+        format!(
+            "SELECT {} FROM {} WHERE {}",
+            deferred.column_list,
+            deferred.table,
+            with_condition(&self.ids_condition(ids), deferred.read_condition.as_deref())
+        )
+    }
+
+    /// Returns the condition matching `ids` bound primary keys, with `IN`
+    /// for a single column key and one condition per key otherwise.
+    fn ids_condition(&self, ids: usize) -> String {
+        match self.deferred.id_columns.as_slice() {
+            [column] => format!("{} IN ({})", column, vec!["?"; ids].join(", ")),
+            columns => {
+                let key = format!(
+                    "({})",
+                    columns
+                        .iter()
+                        .map(|c| format!("{} = ?", c))
+                        .collect::<Vec<_>>()
+                        .join(" AND ")
+                );
+                format!("({})", vec![key; ids].join(" OR "))
+            }
+        }
+    }
+
+    /// Returns the statement deleting the rows matching every one of
+    /// `conditions`, with the values of each bound in turn. Rows with a soft
+    /// delete column are soft deleted, like [Metadata::delete_all_sql]. Each
+    /// distinct statement is built once and kept for the life of the
+    /// program. Used by `Crud::delete_where`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `conditions` is empty or names a column not in
+    /// [Metadata::columns].
+    pub fn delete_where_sql(&'static self, conditions: &[Condition]) -> &'static str {
+        self.assert_conditions(conditions);
+        self.deferred_sql(DeferredQuery::DeleteWhere(conditions.to_vec()), || {
+            format!(
+                "{} {}",
+                self.deferred.delete_by_ids,
+                self.filter_condition(conditions)
+            )
+        })
+    }
+
+    /// Returns the SELECT of the rows matching every one of `conditions`,
+    /// with the values of each bound in turn, ordered by
+    /// [Metadata::order_by_sql] if any. Soft deleted rows are not selected.
+    /// Each distinct statement is built once and kept for the life of the
+    /// program. Used by `Crud::stream_where`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `conditions` is empty or names a column not in
+    /// [Metadata::columns].
+    pub fn select_where_sql(&'static self, conditions: &[Condition]) -> &'static str {
+        let deferred = &self.deferred;
+        self.assert_conditions(conditions);
+        self.deferred_sql(DeferredQuery::SelectWhere(conditions.to_vec()), || {
+            let select = format!(
+                "SELECT {} FROM {} WHERE {}",
+                deferred.column_list,
+                deferred.table,
+                self.filter_condition(conditions)
+            );
+            match &self.order_by_sql {
+                Some(order_by_sql) => format!("{} ORDER BY {}", select, order_by_sql),
+                None => select,
+            }
+        })
+    }
+
+    /// Returns the SELECT of the rows matching every one of `conditions`,
+    /// with the values of each bound in turn, sorted by `order` or else by
+    /// [Metadata::order_by_sql] if any. A bound `LIMIT` follows if `limit`
+    /// and a bound `OFFSET` if `offset`. Soft deleted rows are not selected.
+    /// Each distinct statement is built once and kept for the life of the
+    /// program. Used by [Select].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `conditions` or `order` names a column not in
+    /// [Metadata::columns].
+    ///
+    /// [Select]: ../select/struct.Select.html
+    pub fn select_query_sql(
+        &'static self,
+        conditions: &[Condition],
+        order: &[OrderBy],
+        limit: bool,
+        offset: bool,
+    ) -> &'static str {
+        let deferred = &self.deferred;
+        if !conditions.is_empty() {
+            self.assert_conditions(conditions);
+        }
+        for o in order {
+            assert!(
+                self.columns.contains(&o.column),
+                "{} is not a column of {}",
+                o.column,
+                self.table_name
+            );
+        }
+        let query = DeferredQuery::Select(conditions.to_vec(), order.to_vec(), limit, offset);
+        self.deferred_sql(query, || {
+            let mut sql = format!("SELECT {} FROM {}", deferred.column_list, deferred.table);
+            let condition = match conditions.is_empty() {
+                true => deferred.read_condition.clone(),
+                false => Some(self.filter_condition(conditions)),
+            };
+            if let Some(condition) = condition {
+                sql = format!("{} WHERE {}", sql, condition);
+            }
+            let order_by = match order.is_empty() {
+                true => self.order_by_sql.clone(),
+                false => Some(order_by_clause(
+                    &deferred.table,
+                    order,
+                    deferred.quote_ident,
+                )),
+            };
+            if let Some(order_by) = order_by {
+                sql = format!("{} ORDER BY {}", sql, order_by);
+            }
+            if limit {
+                sql.push_str(" LIMIT ?");
+            }
+            if offset {
+                sql.push_str(" OFFSET ?");
+            }
+            sql
+        })
+    }
+
+    /// Returns the SELECT counting the rows matching every one of
+    /// `conditions`, with the values of each bound in turn. Soft deleted
+    /// rows are not counted. Each distinct statement is built once and kept
+    /// for the life of the program. Used by `Crud::count_where`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `conditions` is empty or names a column not in
+    /// [Metadata::columns].
+    pub fn count_where_sql(&'static self, conditions: &[Condition]) -> &'static str {
+        self.assert_conditions(conditions);
+        self.deferred_sql(DeferredQuery::CountWhere(conditions.to_vec()), || {
+            format!(
+                "SELECT COUNT(*) FROM {} WHERE {}",
+                self.deferred.table,
+                self.filter_condition(conditions)
+            )
+        })
+    }
+
+    /// Returns the SELECT of the `aggregate` of `column` over the rows of the
+    /// table, which is NULL without rows. Soft deleted rows are left out.
+    /// Each distinct statement is built once and kept for the life of the
+    /// program. Used by `Crud::min`, `Crud::max`, `Crud::sum` and
+    /// `Crud::avg`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `column` is not in [Metadata::columns].
+    pub fn aggregate_sql(
+        &'static self,
+        aggregate: Aggregate,
+        column: &'static str,
+    ) -> &'static str {
+        assert!(
+            self.columns.contains(&column),
+            "{} is not a column of {}",
+            column,
+            self.table_name
+        );
+        let deferred = &self.deferred;
+        self.deferred_sql(DeferredQuery::Aggregate(aggregate, column), || {
+            let select = format!(
+                "SELECT {}({}.{}) FROM {}",
+                aggregate.function(),
+                deferred.table,
+                (deferred.quote_ident)(column),
+                deferred.table
+            );
+            match &deferred.read_condition {
+                Some(read_condition) => format!("{} WHERE {}", select, read_condition),
+                None => select,
+            }
+        })
+    }
+
+    /// Returns the SELECT of `column` of the rows matching every one of
+    /// `conditions`, with the values of each bound in turn, or of every row
+    /// without conditions. Rows are sorted by [Metadata::order_by_sql] if
+    /// any, and soft deleted rows are not selected. Each distinct statement
+    /// is built once and kept for the life of the program. Used by
+    /// `Crud::pluck`, `Crud::pluck_where` and `Crud::ids`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `column`, or a column of `conditions`, is not in
+    /// [Metadata::columns].
+    pub fn pluck_sql(
+        &'static self,
+        column: &'static str,
+        conditions: &[Condition],
+    ) -> &'static str {
+        assert!(
+            self.columns.contains(&column),
+            "{} is not a column of {}",
+            column,
+            self.table_name
+        );
+        if !conditions.is_empty() {
+            self.assert_conditions(conditions);
+        }
+        let deferred = &self.deferred;
+        self.deferred_sql(DeferredQuery::Pluck(column, conditions.to_vec()), || {
+            let mut select = format!(
+                "SELECT {}.{} FROM {}",
+                deferred.table,
+                (deferred.quote_ident)(column),
+                deferred.table
+            );
+            let condition = match conditions {
+                [] => deferred.read_condition.clone(),
+                conditions => Some(self.filter_condition(conditions)),
+            };
+            if let Some(condition) = condition {
+                select = format!("{} WHERE {}", select, condition);
+            }
+            match &self.order_by_sql {
+                Some(order_by_sql) => format!("{} ORDER BY {}", select, order_by_sql),
+                None => select,
+            }
+        })
+    }
+
+    /// Returns the SELECT of a constant for the first row matching every one
+    /// of `conditions`, with the values of each bound in turn. Soft deleted
+    /// rows are not selected. Each distinct statement is built once and kept
+    /// for the life of the program. Used by `Crud::exists_by`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `conditions` is empty or names a column not in
+    /// [Metadata::columns].
+    pub fn exists_where_sql(&'static self, conditions: &[Condition]) -> &'static str {
+        self.assert_conditions(conditions);
+        self.deferred_sql(DeferredQuery::ExistsWhere(conditions.to_vec()), || {
+            format!(
+                "SELECT 1 FROM {} WHERE {} LIMIT 1",
+                self.deferred.table,
+                self.filter_condition(conditions)
+            )
+        })
+    }
+
+    /// Returns the UPDATE statement writing the `updated` columns of the
+    /// rows matching every one of `conditions`. The `updated` columns are
+    /// bound in the given order, followed by the values of the conditions.
+    /// Columns with generated values, such as the version, are written as by
+    /// [Metadata::update_by_id_sql]. Soft deleted rows are not updated. Each
+    /// distinct statement is built once and kept for the life of the
+    /// program. Used by `Crud::update_where`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `updated` or `conditions` is empty, if `updated` lists a
+    /// column not written by [Metadata::update_by_id_sql], or if
+    /// `conditions` names a column not in [Metadata::columns].
+    pub fn update_where_sql(
+        &'static self,
+        updated: &[&'static str],
+        conditions: &[Condition],
+    ) -> &'static str {
+        let deferred = &self.deferred;
+        assert!(
+            !updated.is_empty(),
+            "a filtered update of {} writes no columns",
+            self.table_name
+        );
+        for column in updated {
+            assert!(
+                self.is_updated_column(column),
+                "{} is not an updated column of {}",
+                column,
+                self.table_name
+            );
+        }
+        self.assert_conditions(conditions);
+        self.deferred_sql(
+            DeferredQuery::UpdateWhere(updated.to_vec(), conditions.to_vec()),
+            || {
+                let bound = updated
+                    .iter()
+                    .map(|c| format!("{} = ?", (deferred.quote_ident)(c)));
+                let generated = deferred
+                    .patch_columns
+                    .iter()
+                    .filter_map(|(_, column, value)| {
+                        value
+                            .as_ref()
+                            .map(|value| format!("{} = {}", column, value))
+                    });
+                format!(
+                    "UPDATE {} SET {} WHERE {}",
+                    deferred.table,
+                    bound.chain(generated).collect::<Vec<_>>().join(", "),
+                    self.filter_condition(conditions)
+                )
+            },
+        )
+    }
+
+    /// Returns the SELECT of a page of keyset pagination: at most a bound
+    /// `LIMIT` of rows sorted by `column` and then the primary key in
+    /// `direction`, following the bound cursor if `after_cursor`. The cursor
+    /// binds the value of `column`, if any, and then the primary key. Soft
+    /// deleted rows are not selected. Each distinct statement is built once
+    /// and kept for the life of the program. Used by `Crud::after` and
+    /// `Crud::after_by`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `column` is not in [Metadata::columns].
+    pub fn keyset_sql(
+        &'static self,
+        column: Option<&'static str>,
+        direction: Direction,
+        after_cursor: bool,
+    ) -> &'static str {
+        let deferred = &self.deferred;
+        if let Some(column) = column {
+            assert!(
+                self.columns.contains(&column),
+                "{} is not a column of {}",
+                column,
+                self.table_name
+            );
+        }
+        self.deferred_sql(
+            DeferredQuery::Keyset(column, direction, after_cursor),
+            || {
+                let mut key = deferred.id_columns.clone();
+                if let Some(column) = column {
+                    key.insert(
+                        0,
+                        format!("{}.{}", deferred.table, (deferred.quote_ident)(column)),
+                    );
+                }
+                let (operator, order) = match direction {
+                    Direction::Ascending => (">", "ASC"),
+                    Direction::Descending => ("<", "DESC"),
+                };
+                // Keys of several columns are compared as row values
+                let cursor = match key.as_slice() {
+                    [column] => format!("{} {} ?", column, operator),
+                    key => format!(
+                        "({}) {} ({})",
+                        key.join(", "),
+                        operator,
+                        vec!["?"; key.len()].join(", ")
+                    ),
+                };
+                let condition = match after_cursor {
+                    true => Some(with_condition(&cursor, deferred.read_condition.as_deref())),
+                    false => deferred.read_condition.clone(),
+                };
+                let order_by = key
+                    .iter()
+                    .map(|c| format!("{} {}", c, order))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                match condition {
+                    Some(condition) => format!(
+                        "SELECT {} FROM {} WHERE {} ORDER BY {} LIMIT ?",
+                        deferred.column_list, deferred.table, condition, order_by
+                    ),
+                    None => format!(
+                        "SELECT {} FROM {} ORDER BY {} LIMIT ?",
+                        deferred.column_list, deferred.table, order_by
+                    ),
+                }
+            },
+        )
+    }
+
+    /// Returns true if `column` is bound by [Metadata::update_by_id_sql].
+    pub(crate) fn is_updated_column(&self, column: &str) -> bool {
+        self.deferred
+            .patch_columns
+            .iter()
+            .any(|(name, _, value)| *name == column && value.is_none())
+    }
+
+    fn assert_conditions(&self, conditions: &[Condition]) {
+        assert!(
+            !conditions.is_empty(),
+            "a filter of {} has no conditions",
+            self.table_name
+        );
+        for condition in conditions {
+            assert!(
+                self.columns.contains(&condition.column()),
+                "{} is not a column of {}",
+                condition.column(),
+                self.table_name
+            );
+        }
+    }
+
+    /// Returns the WHERE clause of `conditions`, hiding soft deleted rows.
+    fn filter_condition(&self, conditions: &[Condition]) -> String {
+        let deferred = &self.deferred;
+        let condition = conditions
+            .iter()
+            .map(|c| {
+                c.sql(&format!(
+                    "{}.{}",
+                    deferred.table,
+                    (deferred.quote_ident)(c.column())
+                ))
+            })
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        with_condition(&condition, deferred.read_condition.as_deref())
+    }
+
+    /// Returns the statement of `query`, built by `sql` and numbered for the
+    /// database on first use.
+    fn deferred_sql(
+        &'static self,
+        query: DeferredQuery,
+        sql: impl FnOnce() -> String,
+    ) -> &'static str {
+        let mut cache = self
+            .deferred
+            .cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        cache
+            .entry(query)
+            .or_insert_with(|| Box::leak((self.deferred.placeholders)(sql()).into_boxed_str()))
+    }
+}
+
+/// The columns of one table in a row of a query joining several tables.
+/// Columns are looked up by their name in the table, so [FromRow] reads the
+/// columns of the table even where the joined tables share column names.
+///
+/// [FromRow]: https://docs.rs/sqlx/latest/sqlx/trait.FromRow.html
+pub struct TableRow<R> {
+    row: Arc<R>,
+    metadata: &'static Metadata,
+    offset: usize,
+}
+
+impl<R: Row> TableRow<R> {
+    /// The columns of the table of `metadata` starting at column `offset`
+    /// of `row`.
+    pub fn new(row: Arc<R>, metadata: &'static Metadata, offset: usize) -> Self {
+        TableRow {
+            row,
+            metadata,
+            offset,
+        }
+    }
+
+    /// Returns true if the primary key of the table is NULL, as it is in the
+    /// LEFT JOINed columns of a row without related rows.
+    pub fn is_null(&self) -> Result<bool, sqlx::Error>
+    where
+        usize: ColumnIndex<R>,
+    {
+        use sqlx::ValueRef;
+
+        Ok(self.try_get_raw(self.metadata.id_columns[0])?.is_null())
+    }
+}
+
+impl<R: Row> Row for TableRow<R>
+where
+    usize: ColumnIndex<R>,
+{
+    type Database = R::Database;
+
+    fn columns(&self) -> &[<Self::Database as Database>::Column] {
+        &self.row.columns()[self.offset..self.offset + self.metadata.columns.len()]
+    }
+
+    fn try_get_raw<I>(
+        &self,
+        index: I,
+    ) -> Result<<Self::Database as HasValueRef<'_>>::ValueRef, sqlx::Error>
+    where
+        I: ColumnIndex<Self>,
+    {
+        let index = index.index(self)?;
+        self.row.try_get_raw(self.offset + index)
+    }
+}
+
+impl<R> ColumnIndex<TableRow<R>> for &'_ str {
+    fn index(&self, row: &TableRow<R>) -> Result<usize, sqlx::Error> {
+        row.metadata
+            .columns
+            .iter()
+            .position(|c| c == self)
+            .ok_or_else(|| sqlx::Error::ColumnNotFound(self.to_string()))
+    }
+}
+
+impl<R> ColumnIndex<TableRow<R>> for usize {
+    fn index(&self, row: &TableRow<R>) -> Result<usize, sqlx::Error> {
+        let len = row.metadata.columns.len();
+        if *self < len {
+            Ok(*self)
+        } else {
+            Err(sqlx::Error::ColumnIndexOutOfBounds { index: *self, len })
+        }
+    }
+}
+
+/// Quotes a table or column name for database `DB`.
+pub fn quote_ident<DB: Database>(ident: &str) -> String {
+    match DB::NAME {
+        "MySQL" => format!("`{}`", ident),
+        _ => format!(r#""{}""#, ident),
+    }
+}
+
+/// Most values SQLite binds to a statement, as of SQLite 3.32 which sqlx
+/// bundles. Older builds allow 999.
+pub const SQLITE_BIND_LIMIT: usize = 32766;
+
+/// Most values PostgreSQL binds to a statement.
+pub const POSTGRES_BIND_LIMIT: usize = 65535;
+
+/// Most values MySQL binds to a statement.
+pub const MYSQL_BIND_LIMIT: usize = 65535;
+
+/// Most values database `DB` binds to a statement. `Crud::create_many`
+/// splits the rows in to statements binding at most this many values.
+pub fn bind_limit<DB: Database>() -> usize {
+    match DB::NAME {
+        "PostgreSQL" => POSTGRES_BIND_LIMIT,
+        "MySQL" => MYSQL_BIND_LIMIT,
+        _ => SQLITE_BIND_LIMIT,
+    }
+}
+
+/// Splits `rows` rows in to statements of `max` rows, the rest in to powers
+/// of two, largest first, so multi-row INSERT statements come in a few
+/// lengths only.
+pub(crate) fn batch_lens(rows: usize, max: usize) -> Vec<usize> {
+    let mut lens = vec![max; rows / max];
+    let mut rest = rows % max;
+    while rest > 0 {
+        let len = 1 << rest.ilog2();
+        lens.push(len);
+        rest -= len;
+    }
+    lens
+}
+
+/// Rounds `len` up to the next power of two, or to `max` if that is smaller,
+/// so statements binding a list of values come in a few lengths only.
+pub(crate) fn padded_len(len: usize, max: usize) -> usize {
+    len.next_power_of_two().min(max).max(len)
+}
+
+/// Rewrites the `?` bind parameters of `sql` to the placeholder syntax of
+/// database `DB`. PostgreSQL numbers its parameters `$1`, `$2`, ... in bind
+/// order. Quoted identifiers and string literals are left untouched.
+pub fn placeholders<DB: Database>(sql: String) -> String {
+    if DB::NAME != "PostgreSQL" {
+        return sql;
+    }
+    let mut numbered = String::with_capacity(sql.len());
+    let mut quote = None;
+    let mut n = 0;
+    for c in sql.chars() {
+        match (quote, c) {
+            (None, '"' | '\'' | '`') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '?') => {
+                n += 1;
+                numbered.push('$');
+                numbered.push_str(&n.to_string());
+                continue;
+            }
+            _ => {}
+        }
+        numbered.push(c);
+    }
+    numbered
+}
+
+/// INSERT clause for a row where every column takes its default value.
+/// The query of [Metadata::catalog_sql] for database `DB`. The names are
+/// written as string literals, so the statement binds nothing.
+fn catalog_sql<DB: Database>(schema_name: Option<&str>, table_name: &str) -> String {
+    let literal = |s: &str| format!("'{}'", s.replace('\'', "''"));
+    let table = literal(table_name);
+    match DB::NAME {
+        "PostgreSQL" | "MySQL" => {
+            let schema = match schema_name {
+                Some(schema_name) => literal(schema_name),
+                None if DB::NAME == "MySQL" => "DATABASE()".to_string(),
+                None => "current_schema()".to_string(),
+            };
+            let text = |column: &str| match DB::NAME {
+                "MySQL" => format!("CAST({} AS CHAR)", column),
+                _ => format!("{}::text", column),
+            };
+            format!(
+                "SELECT {}, {} FROM information_schema.columns WHERE table_schema = {} AND table_name = {} ORDER BY ordinal_position",
+                text("column_name"),
+                text("is_nullable"),
+                schema,
+                table
+            )
+        }
+        _ => {
+            let arguments = match schema_name {
+                Some(schema_name) => format!("{}, {}", table, literal(schema_name)),
+                None => table,
+            };
+            format!(
+                r#"SELECT name, CASE WHEN "notnull" = 0 THEN 'YES' ELSE 'NO' END FROM pragma_table_info({}) ORDER BY cid"#,
+                arguments
+            )
+        }
+    }
+}
+
+/// Definition of column `c` of type `sql_type` in the CREATE TABLE
+/// statement. Integer keys left to the database are auto-incremented.
+/// Columns the INSERT statement leaves to a database default accept NULL,
+/// as the default is not known.
+fn column_definition<DB: Database>(c: &Column, sql_type: SqlType) -> String {
+    let generated = c.id && !c.insert && c.value.is_none() && sql_type.is_integer();
+    let name = match (DB::NAME, generated, sql_type) {
+        ("PostgreSQL", true, SqlType::SmallInt) => "SMALLSERIAL",
+        ("PostgreSQL", true, SqlType::Integer) => "SERIAL",
+        ("PostgreSQL", true, SqlType::BigInt) => "BIGSERIAL",
+        _ => sql_type.name::<DB>(),
+    };
+    let mut definition = format!("{} {}", quote_ident::<DB>(c.name), name);
+    if c.id || (!c.nullable && (c.insert || c.value.is_some())) {
+        definition.push_str(" NOT NULL");
+    }
+    if generated && DB::NAME == "MySQL" {
+        definition.push_str(" AUTO_INCREMENT");
+    }
+    definition
+}
+
+fn default_values<DB: Database>() -> &'static str {
+    match DB::NAME {
+        "MySQL" => "() VALUES ()",
+        _ => "DEFAULT VALUES",
+    }
+}
+
+/// The columns of `order_by` prefixed with `table`, separated by commas.
+fn order_by_clause(table: &str, order_by: &[OrderBy], quote_ident: fn(&str) -> String) -> String {
+    order_by
+        .iter()
+        .map(|o| {
+            let column = format!("{}.{}", table, quote_ident(o.column));
+            if o.descending {
+                format!("{} DESC", column)
+            } else {
+                column
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Appends an optional condition to a WHERE clause.
+fn with_condition(clause: &str, condition: Option<&str>) -> String {
+    match condition {
+        Some(condition) => format!("{} AND {}", clause, condition),
+        None => clause.to_string(),
+    }
 }
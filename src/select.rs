@@ -0,0 +1,217 @@
+//! SELECT queries of a single table assembled from a [Filter], a sort order
+//! and a page, for the queries between the generated methods and raw SQL.
+//!
+//! A [Select] is started by [Schema::select] and run by one of its
+//! terminators, which return the rows as the struct. The statement follows
+//! [Schema::select_sql], so soft deleted rows are not selected, and its bind
+//! parameters are numbered for the database of the executor.
+//!
+//! [Schema::select]: ../traits/trait.Schema.html#method.select
+//! [Schema::select_sql]: ../traits/trait.Schema.html#method.select_sql
+
+use std::marker::PhantomData;
+
+use futures::{stream, TryFutureExt};
+use sqlx::database::HasArguments;
+use sqlx::{Arguments, Database, Encode, Executor, FromRow, IntoArguments, Type};
+
+use crate::error::Error;
+use crate::filter::Filter;
+use crate::page::Direction;
+use crate::schema::OrderBy;
+use crate::traits::{crud_stream, ColumnName, CrudFut, CrudStream, DatabaseSchema, Schema};
+
+/// A SELECT of the `T` rows of a [Filter], in a given order and page, run
+/// against the `DB` database.
+///
+/// Rows are sorted by the [Select::order_by] columns in turn, or else by the
+/// `#[sqlx_crud(order_by = "...")]` columns of the struct, if any. Without
+/// a [Select::limit] every row after the [Select::offset] is selected.
+///
+/// Statements are built once per distinct filter, order and page clauses
+/// and kept for the life of the program, like [Metadata::select_where_sql].
+///
+/// # Example
+///
+/// ```rust
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use sqlx_crud::filter::Filter;
+/// use sqlx_crud::page::Direction;
+/// use sqlx_crud::{Crud, Schema};
+///
+/// let users = (2..=5).map(|i| User { user_id: 0, name: format!("user {}", i) }).collect();
+/// User::create_many(&pool, users).await?;
+///
+/// let users = User::select()
+///     .filter(Filter::new().gt(UserColumn::UserId, 1))
+///     .order_by(UserColumn::Name, Direction::Descending)
+///     .limit(2)
+///     .offset(1)
+///     .fetch_all(&pool)
+///     .await?;
+/// assert_eq!(vec!["user 4", "user 3"], users.iter().map(|u| u.name.as_str()).collect::<Vec<_>>());
+///
+/// let user = User::select().filter(Filter::new().eq(UserColumn::Name, "test")).fetch_one(&pool).await?;
+/// assert_eq!(1, user.user_id);
+/// assert!(User::select()
+///     .filter(Filter::new().eq(UserColumn::Name, "nobody"))
+///     .fetch_optional(&pool)
+///     .await?
+///     .is_none());
+/// # }}
+/// ```
+///
+/// [Metadata::select_where_sql]: ../schema/struct.Metadata.html#method.select_where_sql
+pub struct Select<'e, DB: Database, T: Schema> {
+    filter: Filter<'e, DB, T::Column>,
+    order: Vec<OrderBy>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+    row: PhantomData<fn() -> T>,
+}
+
+impl<'e, DB: Database, T: Schema> Select<'e, DB, T> {
+    /// Returns a SELECT of every row of the table.
+    pub fn new() -> Self {
+        Self {
+            filter: Filter::new(),
+            order: Vec::new(),
+            limit: None,
+            offset: None,
+            row: PhantomData,
+        }
+    }
+
+    /// Selects only the rows matching `filter`, as well as the filters
+    /// added before.
+    pub fn filter(mut self, filter: Filter<'e, DB, T::Column>) -> Self {
+        self.filter = self.filter.and(filter);
+        self
+    }
+
+    /// Sorts the rows by `column` in `direction`, after the columns added
+    /// before.
+    pub fn order_by(mut self, column: T::Column, direction: Direction) -> Self {
+        self.order.push(OrderBy {
+            column: column.name(),
+            descending: direction == Direction::Descending,
+        });
+        self
+    }
+
+    /// Selects at most `limit` rows.
+    pub fn limit(mut self, limit: i64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Skips the first `offset` rows.
+    pub fn offset(mut self, offset: i64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Returns the statement of the query, with bind parameters numbered for
+    /// the database.
+    pub fn sql(&self) -> &'static str
+    where
+        T: DatabaseSchema<DB>,
+    {
+        // SQLite and MySQL only take an OFFSET after a LIMIT
+        let limit = self.limit.is_some() || self.offset.is_some();
+        T::database_metadata().select_query_sql(
+            self.filter.conditions(),
+            &self.order,
+            limit,
+            self.offset.is_some(),
+        )
+    }
+}
+
+impl<'e, DB, T> Select<'e, DB, T>
+where
+    DB: Database,
+    T: 'e + Schema + DatabaseSchema<DB> + for<'r> FromRow<'r, DB::Row> + Send + Unpin,
+    i64: Encode<'e, DB> + Type<DB>,
+    <DB as HasArguments<'e>>::Arguments: IntoArguments<'e, DB>,
+{
+    /// Runs the query and returns a future resolving to the rows.
+    pub fn fetch_all<E>(self, executor: E) -> CrudFut<'e, Vec<T>>
+    where
+        E: 'e + Executor<'e, Database = DB>,
+    {
+        match self.query() {
+            Ok(query) => Box::pin(query.fetch_all(executor).map_err(Error::from)),
+            Err(e) => Box::pin(futures::future::err(e)),
+        }
+    }
+
+    /// Runs the query and returns a future resolving to the first row, or to
+    /// `sqlx::Error::RowNotFound` if there is none.
+    pub fn fetch_one<E>(self, executor: E) -> CrudFut<'e, T>
+    where
+        E: 'e + Executor<'e, Database = DB>,
+    {
+        match self.query() {
+            Ok(query) => Box::pin(query.fetch_one(executor).map_err(Error::from)),
+            Err(e) => Box::pin(futures::future::err(e)),
+        }
+    }
+
+    /// Runs the query and returns a future resolving to the first row, if
+    /// any.
+    pub fn fetch_optional<E>(self, executor: E) -> CrudFut<'e, Option<T>>
+    where
+        E: 'e + Executor<'e, Database = DB>,
+    {
+        match self.query() {
+            Ok(query) => Box::pin(query.fetch_optional(executor).map_err(Error::from)),
+            Err(e) => Box::pin(futures::future::err(e)),
+        }
+    }
+
+    /// Runs the query and returns a stream of the rows, read as the stream
+    /// is polled.
+    pub fn stream<E>(self, executor: E) -> CrudStream<'e, T>
+    where
+        E: 'e + Executor<'e, Database = DB>,
+    {
+        match self.query() {
+            Ok(query) => crud_stream(query.fetch(executor)),
+            Err(e) => Box::pin(stream::once(futures::future::err(e))),
+        }
+    }
+
+    /// Returns the query with the values of the filter and the page bound,
+    /// or [Error::InvalidPage] for a limit that is not positive or a
+    /// negative offset.
+    fn query(
+        self,
+    ) -> Result<sqlx::query::QueryAs<'e, DB, T, <DB as HasArguments<'e>>::Arguments>, Error> {
+        if self.limit.is_some_and(|limit| limit <= 0)
+            || self.offset.is_some_and(|offset| offset < 0)
+        {
+            return Err(Error::InvalidPage {
+                table_name: T::table_name(),
+                limit: self.limit.unwrap_or(i64::MAX),
+                offset: self.offset.unwrap_or(0),
+            });
+        }
+        let sql = self.sql();
+        let mut args = <DB as HasArguments<'e>>::Arguments::default();
+        self.filter.bind_to(&mut args);
+        if self.limit.is_some() || self.offset.is_some() {
+            args.add(self.limit.unwrap_or(i64::MAX));
+        }
+        if let Some(offset) = self.offset {
+            args.add(offset);
+        }
+        Ok(sqlx::query_as_with(sql, args))
+    }
+}
+
+impl<DB: Database, T: Schema> Default for Select<'_, DB, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
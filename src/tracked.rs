@@ -0,0 +1,192 @@
+//! Change tracking for updates writing only the modified columns.
+//!
+//! [Crud::update] writes every column of the row. A [Tracked] value records
+//! the columns changed since it was loaded, and [Tracked::update_changed]
+//! writes only those in a single UPDATE, leaving concurrent writes to the
+//! other columns in place. Structs opt in with the `#[sqlx_crud(tracked)]`
+//! struct attribute, which implements [Track] for them.
+//!
+//! [Crud::update]: ../traits/trait.Crud.html#method.update
+
+use std::ops::Deref;
+
+use futures::future;
+use sqlx::database::HasArguments;
+use sqlx::{Executor, IntoArguments};
+
+use crate::traits::{ColumnName, Crud, CrudFut, DatabaseSchema, Schema, Track, Updatable};
+use crate::Error;
+
+/// A [Schema] value recording which of its columns were changed.
+///
+/// The value is read through `Deref` and changed with [Tracked::modify],
+/// which compares the fields before and after the change.
+/// [Tracked::update_changed] then writes the changed columns with
+/// [Metadata::patch_sql], like the `patch()` method of
+/// `#[sqlx_crud(patch)]`: the version and `updated_at` columns are written
+/// by the database, and the [Validate] check and [CrudHooks] are not run. A
+/// value without changes is not written. The struct may not have a tenant
+/// column, be audited, be generic or have flattened fields.
+///
+/// # Example
+///
+/// ```rust
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use sqlx_crud::{ColumnName, Crud, Schema, Tracked};
+///
+/// #[derive(Debug, Clone, FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// #[sqlx_crud(tracked)]
+/// struct Article {
+///     id: i64,
+///     title: String,
+///     body: String,
+///     views: i64,
+/// }
+///
+/// sqlx::query("CREATE TABLE articles (id INTEGER PRIMARY KEY, title TEXT NOT NULL, body TEXT NOT NULL, views INTEGER NOT NULL)")
+///     .execute(&pool)
+///     .await?;
+/// let article = Article { id: 0, title: "Draft".to_string(), body: "...".to_string(), views: 0 };
+/// let mut article = Tracked::new(article.create(&pool).await?);
+///
+/// // Another writer counts a view in the meantime
+/// sqlx::query("UPDATE articles SET views = views + 1").execute(&pool).await?;
+///
+/// article.modify(|a| {
+///     a.title = "Published".to_string();
+///     a.body = "...".to_string();
+/// });
+/// assert_eq!(&[ArticleColumn::Title], article.changed());
+///
+/// let columns = article.changed().iter().map(|c| c.name()).collect::<Vec<_>>();
+/// assert_eq!(
+///     r#"UPDATE "articles" SET "title" = ? WHERE "articles"."id" = ?"#,
+///     Article::metadata().patch_sql(&columns)
+/// );
+/// article.update_changed(&pool).await?;
+/// assert!(!article.is_changed());
+///
+/// let stored = Article::by_id(&pool, article.id).await?.unwrap();
+/// assert_eq!("Published", stored.title);
+/// assert_eq!(1, stored.views);
+///
+/// // Nothing left to write
+/// article.update_changed(&pool).await?;
+/// # }}
+/// ```
+///
+/// Structs have to opt in to tracking:
+///
+/// ```compile_fail
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use sqlx_crud::Tracked;
+///
+/// #[derive(Debug, Clone, FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// struct Article {
+///     id: i64,
+///     title: String,
+/// }
+///
+/// let mut article = Tracked::new(Article { id: 1, title: "Draft".to_string() });
+/// article.modify(|a| a.title = "Published".to_string());
+/// # }}
+/// ```
+///
+/// [CrudHooks]: ../traits/trait.CrudHooks.html
+/// [Metadata::patch_sql]: ../schema/struct.Metadata.html#method.patch_sql
+/// [Validate]: ../traits/trait.Validate.html
+#[derive(Debug, Clone)]
+pub struct Tracked<T: Schema> {
+    value: T,
+    changed: Vec<T::Column>,
+}
+
+impl<T: Track> Tracked<T> {
+    /// Tracks the changes to `value`, which starts out unchanged.
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            changed: Vec::new(),
+        }
+    }
+
+    /// Returns the columns changed since the value was tracked or last
+    /// written, in the order they were first changed.
+    pub fn changed(&self) -> &[T::Column] {
+        &self.changed
+    }
+
+    /// Returns true if any column was changed.
+    pub fn is_changed(&self) -> bool {
+        !self.changed.is_empty()
+    }
+
+    /// Changes the value with `f`, recording the columns whose fields differ
+    /// afterwards. Setting a field to its current value is not a change.
+    pub fn modify<F>(&mut self, f: F)
+    where
+        T: Clone,
+        F: FnOnce(&mut T),
+    {
+        let before = self.value.clone();
+        f(&mut self.value);
+        for column in self.value.changed_columns(&before) {
+            if !self.changed.contains(&column) {
+                self.changed.push(column);
+            }
+        }
+    }
+
+    /// Writes the changed columns to the row in a single UPDATE and returns a
+    /// future that resolves once written, after which the value is
+    /// unchanged again. Resolves to `Ok` without a query if nothing changed,
+    /// and to [Error::NotFound] if there is no such row.
+    pub fn update_changed<'e, E>(&'e mut self, pool: E) -> CrudFut<'e, ()>
+    where
+        T: Crud<'e, E> + Updatable,
+        E: Executor<'e> + 'e,
+        <E::Database as HasArguments<'e>>::Arguments: IntoArguments<'e, E::Database>,
+    {
+        if self.changed.is_empty() {
+            return Box::pin(future::ok(()));
+        }
+        let columns = self
+            .changed
+            .iter()
+            .map(ColumnName::name)
+            .collect::<Vec<_>>();
+        let sql = <T as DatabaseSchema<E::Database>>::database_metadata().patch_sql(&columns);
+        // The arguments borrow the value while the changes are cleared
+        let Tracked { value, changed } = self;
+        let id = value.id();
+        let args = match value.changed_args(changed) {
+            Ok(args) => args,
+            Err(e) => return Box::pin(future::err(e)),
+        };
+        Box::pin(async move {
+            let result = sqlx::query_with::<E::Database, _>(sql, args)
+                .execute(pool)
+                .await?;
+            if <T as DatabaseSchema<E::Database>>::rows_affected(&result) == 0 {
+                return Err(Error::not_found::<T>(&id));
+            }
+            changed.clear();
+            Ok(())
+        })
+    }
+
+    /// Returns the value, dropping the recorded changes.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: Schema> Deref for Tracked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
@@ -1,23 +1,203 @@
+use std::collections::HashMap;
 use std::pin::Pin;
+use std::sync::Arc;
 
 use futures::stream::Stream;
 use futures::stream::TryCollect;
 use futures::Future;
 use futures::{future, TryFutureExt, TryStreamExt};
 use sqlx::database::HasArguments;
-use sqlx::{Database, Encode, Executor, FromRow, IntoArguments, Type};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{
+    Acquire, Arguments, ColumnIndex, Database, Encode, Executor, FromRow, IntoArguments, Pool, Type,
+};
+
+use crate::check::SchemaMismatch;
+use crate::error::{HookError, ValidationError};
+use crate::filter::{Assignments, Filter, Increments, Numeric, TypedColumn};
+use crate::page::{next_cursor, Direction, Page};
+use crate::schema::{Aggregate, Column, Metadata, OrderBy, TableRow};
+use crate::select::Select;
+use crate::Error;
 
 /// Type alias for methods returning a single element. The future resolves to and
-/// `Result<T, sqlx::Error>`.
-pub type CrudFut<'e, T> = Pin<Box<dyn Future<Output = Result<T, sqlx::Error>> + Send + 'e>>;
+/// `Result<T, sqlx_crud::Error>`.
+pub type CrudFut<'e, T> = Pin<Box<dyn Future<Output = Result<T, Error>> + Send + 'e>>;
 
-/// Type alias for a [`Stream`] returning items of type `Result<T, sqlxError>`.
-pub type CrudStream<'e, T> =
-    Pin<Box<dyn Stream<Item = Result<T, sqlx::Error>> + std::marker::Send + 'e>>;
+/// Type alias for a [`Stream`] returning items of type `Result<T, sqlx_crud::Error>`.
+pub type CrudStream<'e, T> = Pin<Box<dyn Stream<Item = Result<T, Error>> + std::marker::Send + 'e>>;
 
-/// Type alias for a [`TryCollect`] future that resolves to `Result<Vec<T>, sqlx::Error>`.
+/// Type alias for a [`TryCollect`] future that resolves to `Result<Vec<T>, sqlx_crud::Error>`.
 pub type TryCollectFut<'e, T> = TryCollect<CrudStream<'e, T>, Vec<T>>;
 
+/// The stored row written by [Crud::save], telling whether it was inserted
+/// or updated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SaveOutcome<T> {
+    /// No row had the primary key, the record was inserted.
+    Created(T),
+    /// The row with the primary key was updated.
+    Updated(T),
+}
+
+impl<T> SaveOutcome<T> {
+    /// Returns true if the record was inserted.
+    pub fn is_created(&self) -> bool {
+        matches!(self, SaveOutcome::Created(_))
+    }
+
+    /// Returns the stored row.
+    pub fn into_inner(self) -> T {
+        match self {
+            SaveOutcome::Created(row) | SaveOutcome::Updated(row) => row,
+        }
+    }
+}
+
+/// The CREATE TABLE statement of `metadata`, see [Schema::create_table_sql].
+/// Only called for [CreateTable] structs, whose columns all have a type.
+fn create_table_sql(metadata: &'static Metadata) -> &'static str {
+    metadata
+        .create_table_sql
+        .as_deref()
+        .expect("the columns of a CreateTable struct have SQL types")
+}
+
+/// Queries the rows of `P` with their `C` rows, whose `foreign_key` column
+/// holds the primary key of `P`, in one LEFT JOIN. Used by the code generated
+/// for `#[sqlx_crud(has_many(...))]`.
+#[doc(hidden)]
+pub fn fetch_with_related<'e, P, C, E>(
+    pool: E,
+    foreign_key: &'static str,
+    order_by: &'static [OrderBy],
+) -> CrudFut<'e, Vec<(P, Vec<C>)>>
+where
+    E: Executor<'e> + 'e,
+    P: DatabaseSchema<E::Database>
+        + for<'r> FromRow<'r, TableRow<<E::Database as Database>::Row>>
+        + Send
+        + 'e,
+    P::Id: PartialEq,
+    C: DatabaseSchema<E::Database>
+        + for<'r> FromRow<'r, TableRow<<E::Database as Database>::Row>>
+        + Send
+        + 'e,
+    usize: ColumnIndex<<E::Database as Database>::Row>,
+    <E::Database as HasArguments<'e>>::Arguments: IntoArguments<'e, E::Database>,
+{
+    let parent = P::database_metadata();
+    let child = C::database_metadata();
+    let sql = parent.select_with_related_sql(child, foreign_key, order_by);
+    Box::pin(async move {
+        let rows = sqlx::query::<E::Database>(sql).fetch_all(pool).await?;
+        let mut grouped: Vec<(P, Vec<C>)> = Vec::new();
+        for row in rows {
+            let row = Arc::new(row);
+            let p = P::from_row(&TableRow::new(row.clone(), parent, 0))?;
+            let related = TableRow::new(row, child, parent.columns.len());
+            let c = if related.is_null()? {
+                None
+            } else {
+                Some(C::from_row(&related)?)
+            };
+            match grouped.last_mut() {
+                Some((last, children)) if last.id() == p.id() => children.extend(c),
+                _ => grouped.push((p, c.into_iter().collect())),
+            }
+        }
+        Ok(grouped)
+    })
+}
+
+/// Boxes a sqlx row stream as a [CrudStream]. Used by the code generated for
+/// `#[sqlx_crud(indexed)]` fields and the streams of [Crud].
+#[doc(hidden)]
+pub fn crud_stream<'e, T>(
+    stream: impl Stream<Item = Result<T, sqlx::Error>> + Send + 'e,
+) -> CrudStream<'e, T> {
+    Box::pin(stream.map_err(Error::from))
+}
+
+/// A primary key of type `Id`, as taken by the [Crud] methods looking up
+/// rows by key such as [Crud::by_id]. Besides the key itself, a reference to
+/// the key and the borrowed forms of owned keys, `&str` for a `String` key
+/// and `&[u8]` for a `Vec<u8>` key, are accepted. Lookups bind them as they
+/// are through [BindId], without cloning the key or building one from a
+/// literal. They are only converted where an owned key is needed, such as
+/// for [CrudHooks::after_delete].
+///
+/// # Example
+///
+/// ```rust
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use sqlx_crud::Crud;
+///
+/// #[derive(Debug, FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// #[external_id]
+/// struct Country {
+///     code: String,
+///     name: String,
+/// }
+///
+/// sqlx::query("CREATE TABLE countries (code TEXT PRIMARY KEY, name TEXT NOT NULL)")
+///     .execute(&pool)
+///     .await?;
+/// let mut country = Country { code: "abc".to_string(), name: "Abcland".to_string() }.create(&pool).await?;
+/// country.name = "Abcia".to_string();
+/// let country = country.update(&pool).await?;
+///
+/// assert_eq!("Abcia", Country::by_id(&pool, "abc").await?.unwrap().name);
+/// assert!(Country::exists(&pool, &country.code).await?);
+/// assert!(Country::by_id(&pool, country.code.clone()).await?.is_some());
+/// assert_eq!(1, Country::by_ids(&pool, &["abc", "xyz"]).await?.len());
+/// assert!(Country::delete_by_id(&pool, "abc").await?);
+/// assert!(!Country::exists(&pool, "abc").await?);
+/// # }}
+/// ```
+pub trait IntoId<Id> {
+    /// Returns the key.
+    fn into_id(self) -> Id;
+}
+
+impl<Id> IntoId<Id> for Id {
+    fn into_id(self) -> Id {
+        self
+    }
+}
+
+impl<Id: Clone> IntoId<Id> for &Id {
+    fn into_id(self) -> Id {
+        self.clone()
+    }
+}
+
+impl IntoId<String> for &str {
+    fn into_id(self) -> String {
+        self.to_string()
+    }
+}
+
+impl IntoId<Vec<u8>> for &[u8] {
+    fn into_id(self) -> Vec<u8> {
+        self.to_vec()
+    }
+}
+
+/// Binds a key accepted by [IntoId] as the primary key of the by id queries.
+/// Keys that can be encoded themselves, such as `&str` for `String` keys,
+/// are bound as they are. The tuples of a composite key take the columns in
+/// the order of their fields.
+///
+/// This trait is implemented by the [SqlxCrud] derive macro.
+///
+/// [SqlxCrud]: ../derive.SqlxCrud.html
+pub trait BindId<'q, DB: Database, K>: Schema {
+    /// Binds `key` to `args`, after the values already bound.
+    fn bind_key(key: K, args: &mut <DB as HasArguments<'q>>::Arguments);
+}
+
 /// Database schema information about a struct implementing sqlx [FromRow].
 /// [Schema] defines methods for accessing the derived database schema
 /// and query information.
@@ -40,12 +220,21 @@ pub type TryCollectFut<'e, T> = TryCollect<CrudStream<'e, T>, Vec<T>>;
 ///
 /// [FromRow]: https://docs.rs/sqlx/latest/sqlx/trait.FromRow.html
 pub trait Schema {
-    /// Type of the table primary key column.
-    type Id: Copy + Send + Sync;
+    /// Type of the table primary key column, or a tuple of the key columns
+    /// of a composite key. Keys are cloned where they are needed after
+    /// being bound, so owned keys such as `String` are accepted, and
+    /// formatted with `Debug` in [Error::NotFound].
+    type Id: Clone + std::fmt::Debug + Send + Sync;
+
+    /// The generated `<Struct>Column` enum naming the columns of the table.
+    /// See [ColumnName].
+    type Column: ColumnName;
 
     /// Database name of the table. Used by the query generation code and
     /// available for introspection. This is generated by taking the plural
-    /// _snake_case_ of the struct's name. See: [Inflector to_table_case].
+    /// _snake_case_ of the struct's name. See: [Inflector to_table_case]. The
+    /// `#[sqlx_crud(table_naming = "...")]` struct attribute selects another
+    /// inflection, see [TableNaming].
     ///
     /// ```rust
     /// use sqlx::FromRow;
@@ -61,88 +250,136 @@ pub trait Schema {
     /// assert_eq!("google_id_tokens", GoogleIdToken::table_name());
     /// ```
     ///
-    /// [Inflector to_table_case]: https://docs.rs/Inflector/latest/inflector/cases/tablecase/fn.to_table_case.html
-    fn table_name() -> &'static str;
-
-    /// Returns the id of the current instance.
-    fn id(&self) -> Self::Id;
-
-    /// Returns the column name of the primary key.
-    fn id_column() -> &'static str;
-
-    /// Returns an array of column names.
-    fn columns() -> &'static [&'static str];
-
-    /// Returns the SQL string for a SELECT query against the table.
-    ///
-    /// # Example
+    /// The inferred name can be overridden with the `#[sqlx_crud(table_name = "...")]`
+    /// struct attribute. The override is used by all of the generated queries.
     ///
     /// ```rust
     /// # sqlx_crud::doctest_setup! { |pool| {
-    /// use sqlx_crud::Schema;
+    /// use sqlx_crud::{Crud, Schema};
     ///
-    /// assert_eq!(r#"SELECT "users"."user_id", "users"."name" FROM "users""#, User::select_sql());
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// #[external_id]
+    /// #[sqlx_crud(table_name = "tbl_user_accounts")]
+    /// struct UserAccount {
+    ///     id: i32,
+    ///     login: String,
+    /// }
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// #[external_id]
+    /// #[sqlx_crud(table_name = "tbl_user_groups")]
+    /// struct UserGroup {
+    ///     id: i32,
+    ///     label: String,
+    /// }
+    ///
+    /// assert_eq!("tbl_user_accounts", UserAccount::table_name());
+    /// assert_eq!(r#"SELECT "tbl_user_accounts"."id", "tbl_user_accounts"."login" FROM "tbl_user_accounts""#, UserAccount::select_sql());
+    ///
+    /// sqlx::query("CREATE TABLE tbl_user_accounts (id INTEGER PRIMARY KEY NOT NULL, login TEXT NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// sqlx::query("CREATE TABLE tbl_user_groups (id INTEGER PRIMARY KEY NOT NULL, label TEXT NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    ///
+    /// let account = UserAccount { id: 1, login: "alice".to_string() }.create(&pool).await?;
+    /// let group = UserGroup { id: 1, label: "admins".to_string() }.create(&pool).await?;
+    ///
+    /// let mut account = UserAccount::by_id(&pool, account.id).await?.unwrap();
+    /// assert_eq!("alice", account.login);
+    /// account.login = "bob".to_string();
+    /// account.update(&pool).await?;
+    /// assert_eq!("bob", UserAccount::by_id(&pool, 1).await?.unwrap().login);
+    ///
+    /// group.delete(&pool).await?;
+    /// assert!(UserGroup::by_id(&pool, 1).await?.is_none());
+    /// assert!(UserAccount::by_id(&pool, 1).await?.is_some());
     /// # }}
     /// ```
-    fn select_sql() -> &'static str;
-
-    /// Returns the SQL string for a SELECT query against the table with a
-    /// WHERE clause for the primary key.
     ///
-    /// # Example
+    /// An empty table name is rejected at compile time:
     ///
-    /// ```rust
-    /// # sqlx_crud::doctest_setup! { |pool| {
-    /// use sqlx_crud::Schema;
+    /// ```compile_fail
+    /// use sqlx::FromRow;
+    /// use sqlx_crud::SqlxCrud;
     ///
-    /// assert_eq!(
-    ///     r#"SELECT "users"."user_id", "users"."name" FROM "users" WHERE "users"."user_id" = ? LIMIT 1"#,
-    ///     User::select_by_id_sql()
-    /// );
-    /// # }}
+    /// #[derive(FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// #[sqlx_crud(table_name = "")]
+    /// struct UserAccount {
+    ///     id: i32,
+    ///     login: String,
+    /// }
     /// ```
-    fn select_by_id_sql() -> &'static str;
+    ///
+    /// [Inflector to_table_case]: https://docs.rs/Inflector/latest/inflector/cases/tablecase/fn.to_table_case.html
+    /// [TableNaming]: ../naming/enum.TableNaming.html
+    fn table_name() -> &'static str {
+        Self::metadata().table_name
+    }
 
-    /// Returns the SQL for inserting a new record in to the database. The
-    /// `#[external_id]` attribute may be used to specify IDs are assigned
-    /// outside of the database.
+    /// Returns the database schema of the table set with the
+    /// `#[sqlx_crud(schema = "...")]` struct attribute. Without the attribute
+    /// the table is unqualified and resolved by the database search path.
     ///
+    /// The generated queries and column references use the qualified name.
     ///
     /// # Example
     ///
     /// ```rust
     /// # sqlx_crud::doctest_setup! { |pool| {
-    /// use sqlx::FromRow;
-    /// use sqlx_crud::{Schema, SqlxCrud};
+    /// use sqlx_crud::{Crud, Schema};
     ///
     /// #[derive(Debug, FromRow, SqlxCrud)]
     /// #[database(Sqlite)]
-    /// #[external_id]
-    /// pub struct UserExternalId {
-    ///     pub user_id: i32,
-    ///     pub name: String,
+    /// #[sqlx_crud(schema = "billing")]
+    /// struct Invoice {
+    ///     id: i64,
+    ///     amount: i64,
     /// }
     ///
-    /// assert_eq!(r#"INSERT INTO "users" ("name") VALUES (?) RETURNING "users"."user_id", "users"."name""#, User::insert_sql());
-    /// assert_eq!(r#"INSERT INTO "user_external_ids" ("user_id", "name") VALUES (?, ?) RETURNING "user_external_ids"."user_id", "user_external_ids"."name""#, UserExternalId::insert_sql());
-    /// # }}
-    /// ```
-    fn insert_sql() -> &'static str;
-
-    /// Returns the SQL for updating an existing record in the database.
+    /// assert_eq!(Some("billing"), Invoice::schema_name());
+    /// assert_eq!("invoices", Invoice::table_name());
+    /// assert_eq!("billing.invoices", Invoice::qualified_table_name());
+    /// assert_eq!(None, User::schema_name());
+    /// assert_eq!("users", User::qualified_table_name());
+    /// assert_eq!(
+    ///     r#"SELECT "billing"."invoices"."id", "billing"."invoices"."amount" FROM "billing"."invoices""#,
+    ///     Invoice::select_sql()
+    /// );
     ///
-    /// # Example
+    /// sqlx::query("ATTACH DATABASE ':memory:' AS billing")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// sqlx::query("CREATE TABLE billing.invoices (id INTEGER PRIMARY KEY, amount INTEGER NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
     ///
-    /// ```rust
-    /// # sqlx_crud::doctest_setup! { |pool| {
-    /// use sqlx_crud::Schema;
+    /// let mut invoice = Invoice { id: 0, amount: 100 }.create(&pool).await?;
+    /// invoice.amount = 200;
+    /// let invoice = invoice.update(&pool).await?;
+    /// assert_eq!(200, Invoice::by_id(&pool, invoice.id).await?.unwrap().amount);
     ///
-    /// assert_eq!(r#"UPDATE "users" SET "name" = ? WHERE "users"."user_id" = ? RETURNING "users"."user_id", "users"."name""#, User::update_by_id_sql());
+    /// invoice.delete(&pool).await?;
+    /// assert!(Invoice::all(&pool).await?.is_empty());
     /// # }}
     /// ```
-    fn update_by_id_sql() -> &'static str;
+    fn schema_name() -> Option<&'static str> {
+        Self::metadata().schema_name
+    }
 
-    /// Returns the SQL for deleting an existing record by ID from the database.
+    /// Returns the table name qualified with [Schema::schema_name], or the
+    /// plain [Schema::table_name] for unqualified tables. The name is not
+    /// quoted.
+    fn qualified_table_name() -> &'static str {
+        &Self::metadata().qualified_table_name
+    }
+
+    /// Returns the table metadata and queries the other [Schema] methods
+    /// read from. The metadata is built once, on first use.
     ///
     /// # Example
     ///
@@ -150,41 +387,64 @@ pub trait Schema {
     /// # sqlx_crud::doctest_setup! { |pool| {
     /// use sqlx_crud::Schema;
     ///
-    /// assert_eq!(r#"DELETE FROM "users" WHERE "users"."user_id" = ?"#, User::delete_by_id_sql());
+    /// let metadata = User::metadata();
+    /// assert_eq!("users", metadata.table_name);
+    /// assert_eq!(User::select_sql(), metadata.select_sql);
     /// # }}
     /// ```
-    fn delete_by_id_sql() -> &'static str;
-}
+    fn metadata() -> &'static Metadata;
 
-/// Common Create, Read, Update, and Delete behaviors. This trait requires that
-/// [Schema] and [FromRow] are implemented for Self.
-///
-/// This trait is implemented by the [SqlxCrud] derive macro. Implementors
-/// define how to assign query insert and update bindings.
-///
-/// [FromRow]: https://docs.rs/sqlx/latest/sqlx/trait.FromRow.html
-/// [Schema]: trait.Schema.html
-/// [SqlxCrud]: ../derive.SqlxCrud.html
-pub trait Crud<'e, E>
-where
-    Self: 'e + Sized + Send + Unpin + for<'r> FromRow<'r, <E::Database as Database>::Row> + Schema,
-    <Self as Schema>::Id:
-        Encode<'e, <E as Executor<'e>>::Database> + Type<<E as Executor<'e>>::Database>,
-    E: Executor<'e> + 'e,
-    <E::Database as HasArguments<'e>>::Arguments: IntoArguments<'e, <E as Executor<'e>>::Database>,
-{
-    /// Returns an owned instance of [sqlx::Arguments]. self is consumed.
-    /// Values in the fields are moved in to the `Arguments` instance.
-    ///
-    fn insert_args(self) -> <E::Database as HasArguments<'e>>::Arguments;
+    /// Returns the id of the current instance.
+    fn id(&self) -> Self::Id;
 
-    /// Returns an owned instance of [sqlx::Arguments]. self is consumed.
-    /// Values in the fields are moved in to the `Arguments` instance.
-    ///
-    fn update_args(self) -> <E::Database as HasArguments<'e>>::Arguments;
+    /// Runs the [Validate] check of structs with `#[sqlx_crud(validate)]`.
+    /// [Crud::create] and [Crud::update] call it before binding the fields.
+    #[doc(hidden)]
+    fn validate_write(&self) -> Result<(), Error> {
+        Ok(())
+    }
 
-    /// Returns a future that resolves to an insert or `sqlx::Error` of the
-    /// current instance.
+    /// Runs the [CrudHooks] hook `hook` of structs with `#[sqlx_crud(hooks)]`.
+    #[doc(hidden)]
+    fn run_hook(&mut self, hook: Hook) -> Result<(), Error> {
+        let _ = hook;
+        Ok(())
+    }
+
+    /// Whether [Crud::save] may write the row with a single upsert: the
+    /// struct is [Upsertable] and has no [CrudHooks] to run around the write.
+    #[doc(hidden)]
+    fn save_upserts() -> bool {
+        false
+    }
+
+    /// Runs [CrudHooks::after_delete] of structs with `#[sqlx_crud(hooks)]`.
+    #[doc(hidden)]
+    fn run_after_delete(id: &Self::Id) -> Result<(), Error> {
+        let _ = id;
+        Ok(())
+    }
+
+    /// Returns the span [Crud] runs `operation` in, named
+    /// `sqlx_crud.<table>.<operation>`. The derive implements it, the default
+    /// is disabled.
+    #[cfg(feature = "tracing")]
+    #[doc(hidden)]
+    fn tracing_span(
+        operation: crate::instrument::Operation,
+        id: Option<&dyn std::fmt::Debug>,
+    ) -> tracing::Span {
+        let _ = (operation, id);
+        tracing::Span::none()
+    }
+
+    /// Returns the column name of the primary key. For composite keys this is
+    /// the first key column, see [Schema::id_columns].
+    ///
+    /// The column defaults to the name of the `#[id]` field and can be set
+    /// with `#[id(column = "...")]`, which takes precedence over
+    /// `#[sqlx_crud(rename = "...")]`. [FromRow] reads the column by field
+    /// name, so pair it with sqlx's `#[sqlx(rename = "...")]`.
     ///
     /// # Example
     ///
@@ -192,123 +452,6552 @@ where
     /// # sqlx_crud::doctest_setup! { |pool| {
     /// use sqlx_crud::{Crud, Schema};
     ///
-    /// let user = User { user_id: 1, name: "test".to_string() };
-    /// let user = user.create(&pool).await?;
-    /// assert_eq!("test", user.name);
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Account {
+    ///     #[id(column = "account_no", generated)]
+    ///     #[sqlx(rename = "account_no")]
+    ///     id: i64,
+    ///     name: String,
+    /// }
+    ///
+    /// assert_eq!("account_no", Account::id_column());
+    /// assert_eq!(
+    ///     r#"SELECT "accounts"."account_no", "accounts"."name" FROM "accounts" WHERE "accounts"."account_no" = ? LIMIT 1"#,
+    ///     Account::select_by_id_sql()
+    /// );
+    /// assert_eq!(
+    ///     r#"DELETE FROM "accounts" WHERE "accounts"."account_no" = ?"#,
+    ///     Account::delete_by_id_sql()
+    /// );
+    ///
+    /// sqlx::query("CREATE TABLE accounts (account_no INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    ///
+    /// let mut account = Account { id: 0, name: "ada".to_string() }.create(&pool).await?;
+    /// assert_eq!(1, account.id);
+    ///
+    /// account.name = "grace".to_string();
+    /// account.update(&pool).await?;
+    /// assert_eq!("grace", Account::by_id(&pool, 1).await?.unwrap().name);
+    ///
+    /// Account::by_id(&pool, 1).await?.unwrap().delete(&pool).await?;
+    /// assert!(Account::by_id(&pool, 1).await?.is_none());
     /// # }}
     /// ```
-    fn create(self, pool: E) -> CrudFut<'e, Self> {
-        Box::pin({
-            let args = self.insert_args();
-            ::sqlx::query_with::<E::Database, _>(Self::insert_sql(), args)
-                .try_map(|r| Self::from_row(&r))
-                .fetch_one(pool)
-        })
-    }
-
-    /// Queries all records from the table and returns a future that returns
-    /// to a [try_collect] stream, which resolves to a `Vec<Self>` or a
-    /// `sqlx::Error` on error.
     ///
-    /// # Example
+    /// The column [FromRow] reads must agree:
     ///
-    /// ```rust
-    /// # sqlx_crud::doctest_setup! { |pool| {
-    /// use sqlx_crud::Crud;
+    /// ```rust,compile_fail
+    /// use sqlx::FromRow;
+    /// use sqlx_crud::SqlxCrud;
     ///
-    /// let all_users: Vec<User> = User::all(&pool).await?;
-    /// # }}
+    /// #[derive(FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Account {
+    ///     #[id(column = "account_no")]
+    ///     #[sqlx(rename = "account_id")]
+    ///     id: i64,
+    ///     name: String,
+    /// }
     /// ```
     ///
-    /// [try_collect]: https://docs.rs/futures/latest/futures/stream/trait.TryStreamExt.html#method.try_collect
-    fn all(pool: E) -> TryCollectFut<'e, Self> {
-        let stream =
-            sqlx::query_as::<E::Database, Self>(<Self as Schema>::select_sql()).fetch(pool);
-        stream.try_collect()
-    }
-
-    #[doc(hidden)]
-    fn paged(_pool: E) -> TryCollectFut<'e, Self> {
-        unimplemented!()
+    /// [FromRow]: https://docs.rs/sqlx/latest/sqlx/trait.FromRow.html
+    fn id_column() -> &'static str {
+        Self::metadata().id_columns[0]
     }
 
-    /// Looks up a row by ID and returns a future that resolves an
-    /// `Option<Self>`. Returns `None` if and a record with the corresponding ID
-    /// cannot be found and `Some` if it exists.
+    /// Returns the column names making up the primary key.
+    ///
+    /// Tagging more than one field with `#[id]` declares a composite primary
+    /// key. [Schema::Id] is then a tuple of the key field types in field
+    /// order, and the generated queries match on every key column. Composite
+    /// keys are always assigned outside of the database and included in the
+    /// INSERT statement.
     ///
     /// # Example
     ///
     /// ```rust
     /// # sqlx_crud::doctest_setup! { |pool| {
-    /// use sqlx_crud::Crud;
+    /// use sqlx_crud::{Crud, Schema};
     ///
-    /// let user: Option<User> = User::by_id(&pool, 1).await?;
-    /// assert!(user.is_some());
-    /// # }}
-    /// ```
-    fn by_id(pool: E, id: <Self as Schema>::Id) -> CrudFut<'e, Option<Self>> {
-        Box::pin({
-            use ::sqlx::Arguments as _;
-            let arg0 = id;
-            let mut args = <E::Database as HasArguments<'e>>::Arguments::default();
-            args.reserve(
-                1usize,
-                ::sqlx::encode::Encode::<E::Database>::size_hint(&arg0),
-            );
-            args.add(arg0);
-            ::sqlx::query_with::<E::Database, _>(Self::select_by_id_sql(), args)
-                .try_map(|r| Self::from_row(&r))
-                .fetch_optional(pool)
-        })
-    }
-
-    /// Updates the database with the current instance state and returns a
-    /// future that resolves to the new `Self` returned from the database.
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct UserRole {
+    ///     #[id]
+    ///     user_id: i32,
+    ///     #[id]
+    ///     role_id: i32,
+    ///     granted_by: String,
+    /// }
     ///
-    /// # Example
+    /// assert_eq!(&["user_id", "role_id"], UserRole::id_columns());
+    /// assert_eq!(
+    ///     r#"DELETE FROM "user_roles" WHERE "user_roles"."user_id" = ? AND "user_roles"."role_id" = ?"#,
+    ///     UserRole::delete_by_id_sql()
+    /// );
     ///
-    /// ```rust
-    /// # sqlx_crud::doctest_setup! { |pool| {
-    /// use sqlx_crud::Crud;
+    /// sqlx::query("CREATE TABLE user_roles (user_id INTEGER NOT NULL, role_id INTEGER NOT NULL, granted_by TEXT NOT NULL, PRIMARY KEY (user_id, role_id))")
+    ///     .execute(&pool)
+    ///     .await?;
     ///
-    /// if let Some(mut user) = User::by_id(&pool, 1).await? {
-    ///     assert_eq!("test", user.name);
+    /// UserRole { user_id: 1, role_id: 1, granted_by: "root".to_string() }.create(&pool).await?;
+    /// UserRole { user_id: 1, role_id: 2, granted_by: "root".to_string() }.create(&pool).await?;
     ///
-    ///     // Update the record
-    ///     user.name = "Harry".to_string();
-    ///     let user = user.update(&pool).await?;
+    /// let mut user_role = UserRole::by_id(&pool, (1, 2)).await?.unwrap();
+    /// assert_eq!((1, 2), user_role.id());
     ///
-    ///     // Confirm the name changed
-    ///     assert_eq!("Harry", user.name);
-    /// }
+    /// user_role.granted_by = "admin".to_string();
+    /// user_role.update(&pool).await?;
+    /// assert_eq!("admin", UserRole::by_id(&pool, (1, 2)).await?.unwrap().granted_by);
+    /// assert_eq!("root", UserRole::by_id(&pool, (1, 1)).await?.unwrap().granted_by);
+    ///
+    /// UserRole::by_id(&pool, (1, 1)).await?.unwrap().delete(&pool).await?;
+    /// assert!(UserRole::by_id(&pool, (1, 1)).await?.is_none());
+    /// assert!(UserRole::by_id(&pool, (1, 2)).await?.is_some());
     /// # }}
     /// ```
-    fn update(self, pool: E) -> CrudFut<'e, Self> {
-        Box::pin({
-            let args = self.update_args();
-            ::sqlx::query_with::<E::Database, _>(Self::update_by_id_sql(), args)
-                .try_map(|r| Self::from_row(&r))
-                .fetch_one(pool)
-        })
+    fn id_columns() -> &'static [&'static str] {
+        &Self::metadata().id_columns
     }
 
-    /// Deletes a record from the database by ID and returns a future that
-    /// resolves to `()` on success or `sqlx::Error` on failure.
+    /// Returns an array of column names.
+    ///
+    /// Column names default to the field names. A field can be mapped to a
+    /// differently named column with sqlx's `#[sqlx(rename = "...")]` field
+    /// attribute, which renames the column for [FromRow] and for the generated
+    /// queries alike. The `#[sqlx_crud(rename = "...")]` field attribute
+    /// renames the column for the generated queries only. When both are given
+    /// they must agree.
     ///
     /// # Example
     ///
     /// ```rust
     /// # sqlx_crud::doctest_setup! { |pool| {
-    /// use sqlx_crud::Crud;
+    /// use sqlx_crud::{Crud, Schema};
     ///
-    /// if let Some(user) = User::by_id(&pool, 1).await? {
-    ///     user.delete(&pool).await?;
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// #[external_id]
+    /// struct Pet {
+    ///     #[sqlx(rename = "pet_id")]
+    ///     id: i32,
+    ///     #[sqlx(rename = "type")]
+    ///     #[sqlx_crud(rename = "type")]
+    ///     kind: String,
+    /// }
+    ///
+    /// assert_eq!(&["pet_id", "type"], Pet::columns());
+    /// assert_eq!("pet_id", Pet::id_column());
+    ///
+    /// sqlx::query(r#"CREATE TABLE pets (pet_id INTEGER PRIMARY KEY NOT NULL, "type" TEXT NOT NULL)"#)
+    ///     .execute(&pool)
+    ///     .await?;
+    ///
+    /// Pet { id: 1, kind: "cat".to_string() }.create(&pool).await?;
+    /// let mut pet = Pet::by_id(&pool, 1).await?.unwrap();
+    /// assert_eq!("cat", pet.kind);
+    ///
+    /// pet.kind = "dog".to_string();
+    /// let pet = pet.update(&pool).await?;
+    /// assert_eq!("dog", pet.kind);
+    ///
+    /// pet.delete(&pool).await?;
+    /// assert!(Pet::by_id(&pool, 1).await?.is_none());
+    /// # }}
+    /// ```
+    ///
+    /// Conflicting renames are rejected at compile time:
+    ///
+    /// ```compile_fail
+    /// use sqlx::FromRow;
+    /// use sqlx_crud::SqlxCrud;
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Pet {
+    ///     id: i32,
+    ///     #[sqlx(rename = "type")]
+    ///     #[sqlx_crud(rename = "kind")]
+    ///     kind: String,
+    /// }
+    /// ```
+    ///
+    /// Raw identifier fields such as `r#type` map to the column without the
+    /// `r#` prefix. Column names are always quoted, so reserved words like
+    /// `type` or `order` work without a rename.
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, Schema};
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Token {
+    ///     id: i64,
+    ///     r#type: String,
+    ///     r#order: i32,
+    /// }
+    ///
+    /// assert_eq!(&["id", "type", "order"], Token::columns());
+    /// assert_eq!(
+    ///     r#"UPDATE "tokens" SET "type" = ?, "order" = ? WHERE "tokens"."id" = ? RETURNING "tokens"."id", "tokens"."type", "tokens"."order""#,
+    ///     Token::update_by_id_sql()
+    /// );
+    ///
+    /// sqlx::query(r#"CREATE TABLE tokens (id INTEGER PRIMARY KEY, "type" TEXT NOT NULL, "order" INTEGER NOT NULL)"#)
+    ///     .execute(&pool)
+    ///     .await?;
+    ///
+    /// let mut token = Token { id: 0, r#type: "bearer".to_string(), r#order: 1 }.create(&pool).await?;
+    /// assert_eq!("bearer", Token::by_id(&pool, token.id).await?.unwrap().r#type);
+    ///
+    /// token.r#type = "refresh".to_string();
+    /// token.r#order = 2;
+    /// let token = token.update(&pool).await?;
+    /// let stored = Token::by_id(&pool, token.id).await?.unwrap();
+    /// assert_eq!("refresh", stored.r#type);
+    /// assert_eq!(2, stored.r#order);
+    /// # }}
+    /// ```
+    ///
+    /// The `#[sqlx_crud(rename_all = "...")]` struct attribute applies a naming
+    /// policy to every column. Supported policies are `lowercase`,
+    /// `UPPERCASE`, `camelCase`, `PascalCase`, `snake_case`,
+    /// `SCREAMING_SNAKE_CASE` and `kebab-case`. An explicit `rename` on a
+    /// field takes precedence. As with `rename`, sqlx's matching
+    /// `#[sqlx(rename_all = "...")]` is needed for [FromRow].
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, Schema};
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// #[external_id]
+    /// #[sqlx(rename_all = "camelCase")]
+    /// #[sqlx_crud(rename_all = "camelCase")]
+    /// struct Account {
+    ///     account_id: i32,
+    ///     display_name: String,
+    ///     #[sqlx(rename = "created")]
+    ///     #[sqlx_crud(rename = "created")]
+    ///     created_at: i64,
+    /// }
+    ///
+    /// assert_eq!(&["accountId", "displayName", "created"], Account::columns());
+    ///
+    /// sqlx::query("CREATE TABLE accounts (accountId INTEGER PRIMARY KEY NOT NULL, displayName TEXT NOT NULL, created INTEGER NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    ///
+    /// Account { account_id: 1, display_name: "Ada".to_string(), created_at: 0 }.create(&pool).await?;
+    /// assert_eq!("Ada", Account::by_id(&pool, 1).await?.unwrap().display_name);
+    /// # }}
+    /// ```
+    ///
+    /// Unknown policies are rejected at compile time:
+    ///
+    /// ```compile_fail
+    /// use sqlx::FromRow;
+    /// use sqlx_crud::SqlxCrud;
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// #[sqlx_crud(rename_all = "Title Case")]
+    /// struct Account {
+    ///     account_id: i32,
+    ///     display_name: String,
+    /// }
+    /// ```
+    ///
+    /// [FromRow]: https://docs.rs/sqlx/latest/sqlx/trait.FromRow.html
+    fn columns() -> &'static [&'static str] {
+        &Self::metadata().columns
+    }
+
+    /// Returns the columns of [Schema::columns] as variants of the generated
+    /// `<Struct>Column` enum. See [ColumnName].
+    fn typed_columns() -> &'static [Self::Column] {
+        <Self::Column as ColumnName>::ALL
+    }
+
+    /// Returns the SQL string for a SELECT query against the table.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Schema;
+    ///
+    /// assert_eq!(r#"SELECT "users"."user_id", "users"."name" FROM "users""#, User::select_sql());
+    /// # }}
+    /// ```
+    fn select_sql() -> &'static str
+    where
+        Self: Readable,
+    {
+        &Self::metadata().select_sql
+    }
+
+    /// Returns a [Select] of every row of the table, narrowed, sorted and
+    /// paged by its builder methods before it is run.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::filter::Filter;
+    /// use sqlx_crud::page::Direction;
+    /// use sqlx_crud::Schema;
+    ///
+    /// let select = User::select::<sqlx::Sqlite>()
+    ///     .filter(Filter::new().eq(UserColumn::Name, "test"))
+    ///     .order_by(UserColumn::UserId, Direction::Descending)
+    ///     .limit(10);
+    /// assert_eq!(
+    ///     r#"SELECT "users"."user_id", "users"."name" FROM "users" WHERE "users"."name" = ? ORDER BY "users"."user_id" DESC LIMIT ?"#,
+    ///     select.sql()
+    /// );
+    /// assert_eq!(1, select.fetch_all(&pool).await?.len());
+    /// # }}
+    /// ```
+    ///
+    /// [Select]: ../select/struct.Select.html
+    fn select<'e, DB: Database>() -> Select<'e, DB, Self>
+    where
+        Self: Sized + Unscoped + Readable,
+    {
+        Select::new()
+    }
+
+    /// Returns [Schema::select_sql] with the columns not prefixed by the
+    /// table name, for example to run against a table alias.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Schema;
+    ///
+    /// assert_eq!(r#"SELECT "user_id", "name" FROM "users""#, User::select_sql_unqualified());
+    /// # }}
+    /// ```
+    fn select_sql_unqualified() -> &'static str
+    where
+        Self: Readable,
+    {
+        &Self::metadata().select_sql_unqualified
+    }
+
+    /// Returns the quoted columns of [Schema::columns] separated by commas and
+    /// not prefixed by the table name. This selects the struct's columns from
+    /// a common table expression or subquery of the same shape.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Schema;
+    ///
+    /// assert_eq!(r#""user_id", "name""#, User::unqualified_column_list());
+    ///
+    /// let sql = format!(
+    ///     "WITH recent AS (SELECT * FROM users ORDER BY user_id DESC LIMIT 1) SELECT {} FROM recent",
+    ///     User::unqualified_column_list()
+    /// );
+    /// let users: Vec<User> = sqlx::query_as(&sql).fetch_all(&pool).await?;
+    /// assert_eq!(1, users.len());
+    /// # }}
+    /// ```
+    fn unqualified_column_list() -> &'static str
+    where
+        Self: Readable,
+    {
+        &Self::metadata().unqualified_column_list
+    }
+
+    /// Returns the ORDER BY clause, without the `ORDER BY` keywords, set with
+    /// the `#[sqlx_crud(order_by = "...")]` struct attribute. The listed
+    /// columns are checked against the struct's columns when it is derived.
+    ///
+    /// [Crud::all] and the `all_by_<field>()` finders return rows in this
+    /// order, by default the order of the primary key for the finders and
+    /// unspecified for [Crud::all]. Lookups of a single row are unaffected.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, Schema};
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// #[sqlx_crud(order_by = "priority DESC, title")]
+    /// struct Task {
+    ///     id: i64,
+    ///     title: String,
+    ///     priority: i32,
+    /// }
+    ///
+    /// assert_eq!(Some(r#""tasks"."priority" DESC, "tasks"."title""#), Task::order_by_sql());
+    /// assert_eq!(r#"SELECT "tasks"."id", "tasks"."title", "tasks"."priority" FROM "tasks""#, Task::select_sql());
+    ///
+    /// sqlx::query("CREATE TABLE tasks (id INTEGER PRIMARY KEY, title TEXT NOT NULL, priority INTEGER NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// for (title, priority) in [("c", 1), ("b", 2), ("d", 1), ("a", 2)] {
+    ///     Task { id: 0, title: title.to_string(), priority }.create(&pool).await?;
+    /// }
+    ///
+    /// let titles = Task::all(&pool).await?.into_iter().map(|t| t.title).collect::<Vec<_>>();
+    /// assert_eq!(vec!["a", "b", "c", "d"], titles);
+    /// # }}
+    /// ```
+    ///
+    /// Unknown columns are rejected:
+    ///
+    /// ```rust,compile_fail
+    /// use sqlx::FromRow;
+    /// use sqlx_crud::SqlxCrud;
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// #[sqlx_crud(order_by = "created_at DESC")]
+    /// struct Task {
+    ///     id: i64,
+    ///     title: String,
+    /// }
+    /// ```
+    fn order_by_sql() -> Option<&'static str> {
+        Self::metadata().order_by_sql.as_deref()
+    }
+
+    /// Returns the query of [Crud::paginated]: [Schema::select_sql] ordered
+    /// by [Schema::order_by_sql] and then the primary key, or else by the
+    /// primary key, binding the `LIMIT` and `OFFSET` of the page.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Schema;
+    ///
+    /// assert_eq!(
+    ///     r#"SELECT "users"."user_id", "users"."name" FROM "users" ORDER BY "users"."user_id" LIMIT ? OFFSET ?"#,
+    ///     User::paginated_sql()
+    /// );
+    /// # }}
+    /// ```
+    fn paginated_sql() -> &'static str
+    where
+        Self: Readable,
+    {
+        &Self::metadata().paginated_sql
+    }
+
+    /// Returns the query counting the rows of [Crud::count] and
+    /// [Crud::page], which does not count soft deleted rows.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Schema;
+    ///
+    /// assert_eq!(r#"SELECT COUNT(*) FROM "users""#, User::count_sql());
+    /// # }}
+    /// ```
+    fn count_sql() -> &'static str
+    where
+        Self: Readable,
+    {
+        &Self::metadata().count_sql
+    }
+
+    /// Returns the SQL string for a SELECT query against the table with a
+    /// WHERE clause for the primary key.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Schema;
+    ///
+    /// assert_eq!(
+    ///     r#"SELECT "users"."user_id", "users"."name" FROM "users" WHERE "users"."user_id" = ? LIMIT 1"#,
+    ///     User::select_by_id_sql()
+    /// );
+    /// # }}
+    /// ```
+    fn select_by_id_sql() -> &'static str
+    where
+        Self: Readable,
+    {
+        &Self::metadata().select_by_id_sql
+    }
+
+    /// Returns the query of [Crud::by_id_for_update]: [Schema::select_by_id_sql]
+    /// with `FOR UPDATE` on PostgreSQL and MySQL, and unchanged on SQLite.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Schema;
+    ///
+    /// // SQLite has no row locks
+    /// assert_eq!(User::select_by_id_sql(), User::select_by_id_for_update_sql());
+    /// # }}
+    /// ```
+    fn select_by_id_for_update_sql() -> &'static str
+    where
+        Self: Readable,
+    {
+        &Self::metadata().select_by_id_for_update_sql
+    }
+
+    /// Returns the query of [Crud::exists], selecting a constant instead of
+    /// the columns of the row with the primary key.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Schema;
+    ///
+    /// assert_eq!(r#"SELECT 1 FROM "users" WHERE "users"."user_id" = ? LIMIT 1"#, User::exists_sql());
+    /// # }}
+    /// ```
+    fn exists_sql() -> &'static str
+    where
+        Self: Readable,
+    {
+        &Self::metadata().exists_sql
+    }
+
+    /// Returns the query of [Crud::by_ids] selecting the rows whose primary
+    /// key is one of `ids` bound keys. A single column key is matched with
+    /// `IN`, composite keys with one condition per key, see
+    /// [Schema::delete_by_ids_sql]. The statement can be embedded in larger
+    /// queries, binding the key columns of each key in turn.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Schema;
+    ///
+    /// assert_eq!(
+    ///     r#"SELECT "users"."user_id", "users"."name" FROM "users" WHERE "users"."user_id" IN (?, ?, ?)"#,
+    ///     User::select_by_ids_sql(3)
+    /// );
+    ///
+    /// let sql = format!("SELECT name FROM ({}) ORDER BY name", User::select_by_ids_sql(2));
+    /// let names: Vec<String> = sqlx::query_scalar(&sql).bind(1).bind(2).fetch_all(&pool).await?;
+    /// assert_eq!(vec!["test"], names);
+    /// # }}
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ids` is zero.
+    fn select_by_ids_sql(ids: usize) -> String
+    where
+        Self: Readable,
+    {
+        Self::metadata().select_by_ids_sql(ids)
+    }
+
+    /// Returns the SQL for inserting a new record in to the database. The
+    /// `#[external_id]` attribute may be used to specify IDs are assigned
+    /// outside of the database.
+    ///
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx::FromRow;
+    /// use sqlx_crud::{Schema, SqlxCrud};
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// #[external_id]
+    /// pub struct UserExternalId {
+    ///     pub user_id: i32,
+    ///     pub name: String,
+    /// }
+    ///
+    /// assert_eq!(r#"INSERT INTO "users" ("name") VALUES (?) RETURNING "users"."user_id", "users"."name""#, User::insert_sql());
+    /// assert_eq!(r#"INSERT INTO "user_external_ids" ("user_id", "name") VALUES (?, ?) RETURNING "user_external_ids"."user_id", "user_external_ids"."name""#, UserExternalId::insert_sql());
+    /// # }}
+    /// ```
+    ///
+    /// Fields tagged `#[sqlx_crud(insert_default)]` are left out of the INSERT
+    /// statement so the database applies the column default. They are still
+    /// selected, returned from [Crud::create] and written by [Crud::update].
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, Schema};
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Job {
+    ///     id: i64,
+    ///     name: String,
+    ///     #[sqlx_crud(insert_default)]
+    ///     state: String,
+    ///     priority: i32,
+    /// }
+    ///
+    /// assert_eq!(
+    ///     r#"INSERT INTO "jobs" ("name", "priority") VALUES (?, ?) RETURNING "jobs"."id", "jobs"."name", "jobs"."state", "jobs"."priority""#,
+    ///     Job::insert_sql()
+    /// );
+    ///
+    /// sqlx::query("CREATE TABLE jobs (id INTEGER PRIMARY KEY, name TEXT NOT NULL, state TEXT NOT NULL DEFAULT 'pending', priority INTEGER NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    ///
+    /// let job = Job { id: 0, name: "backup".to_string(), state: "ignored".to_string(), priority: 5 };
+    /// let mut job = job.create(&pool).await?;
+    /// assert_eq!("backup", job.name);
+    /// assert_eq!("pending", job.state);
+    /// assert_eq!(5, job.priority);
+    ///
+    /// job.state = "running".to_string();
+    /// let job = job.update(&pool).await?;
+    /// assert_eq!("running", job.state);
+    /// # }}
+    /// ```
+    ///
+    /// Fields tagged `#[sqlx_crud(generated)]` hold columns computed by the
+    /// database, such as `GENERATED ALWAYS AS (...)` columns. They are
+    /// selected and returned like any other column but never written, unlike
+    /// skipped fields which have no column at all.
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, Schema};
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct LineItem {
+    ///     id: i64,
+    ///     price: i64,
+    ///     quantity: i64,
+    ///     #[sqlx_crud(generated)]
+    ///     total: i64,
+    /// }
+    ///
+    /// assert_eq!(
+    ///     r#"INSERT INTO "line_items" ("price", "quantity") VALUES (?, ?) RETURNING "line_items"."id", "line_items"."price", "line_items"."quantity", "line_items"."total""#,
+    ///     LineItem::insert_sql()
+    /// );
+    /// assert_eq!(
+    ///     r#"UPDATE "line_items" SET "price" = ?, "quantity" = ? WHERE "line_items"."id" = ? RETURNING "line_items"."id", "line_items"."price", "line_items"."quantity", "line_items"."total""#,
+    ///     LineItem::update_by_id_sql()
+    /// );
+    ///
+    /// sqlx::query("CREATE TABLE line_items (id INTEGER PRIMARY KEY, price INTEGER NOT NULL, quantity INTEGER NOT NULL, total INTEGER GENERATED ALWAYS AS (price * quantity) STORED)")
+    ///     .execute(&pool)
+    ///     .await?;
+    ///
+    /// let item = LineItem { id: 0, price: 250, quantity: 4, total: 0 }.create(&pool).await?;
+    /// assert_eq!(1000, item.total);
+    /// assert_eq!(1000, LineItem::by_id(&pool, item.id).await?.unwrap().total);
+    ///
+    /// let mut item = item;
+    /// item.quantity = 2;
+    /// item.total = -1;
+    /// let item = item.update(&pool).await?;
+    /// assert_eq!(500, item.total);
+    /// # }}
+    /// ```
+    ///
+    /// [Crud::create]: trait.Crud.html#method.create
+    /// [Crud::update]: trait.Crud.html#method.update
+    fn insert_sql() -> &'static str
+    where
+        Self: Creatable,
+    {
+        &Self::metadata().insert_sql
+    }
+
+    /// Returns the columns [Crud::create] leaves out of the INSERT statement
+    /// for this instance.
+    ///
+    /// Fields tagged `#[sqlx_crud(none_as_default)]` must be an `Option`.
+    /// When they are `None` the column is left out, so the database applies
+    /// the column DEFAULT instead of storing NULL. `Some` values are inserted
+    /// as usual. The statement for each combination of omitted columns is
+    /// built once, see [Metadata::insert_sql_without].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, Schema};
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Ticket {
+    ///     id: i64,
+    ///     #[sqlx_crud(none_as_default)]
+    ///     status: Option<String>,
+    ///     note: Option<String>,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE tickets (id INTEGER PRIMARY KEY, status TEXT DEFAULT 'x', note TEXT DEFAULT 'x')")
+    ///     .execute(&pool)
+    ///     .await?;
+    ///
+    /// let ticket = Ticket { id: 0, status: None, note: None };
+    /// assert_eq!(vec!["status"], ticket.defaulted_columns());
+    /// let ticket = ticket.create(&pool).await?;
+    /// assert_eq!(Some("x".to_string()), ticket.status);
+    /// assert_eq!(None, ticket.note);
+    ///
+    /// let ticket = Ticket { id: 0, status: Some("open".to_string()), note: None };
+    /// assert!(ticket.defaulted_columns().is_empty());
+    /// let ticket = ticket.create(&pool).await?;
+    /// assert_eq!(Some("open".to_string()), ticket.status);
+    ///
+    /// let (defaulted, nulls): (i64, i64) = sqlx::query_as(
+    ///     "SELECT SUM(status = 'x'), SUM(note IS NULL) FROM tickets",
+    /// )
+    /// .fetch_one(&pool)
+    /// .await?;
+    /// assert_eq!((1, 2), (defaulted, nulls));
+    /// # }}
+    /// ```
+    ///
+    /// [Crud::create]: trait.Crud.html#method.create
+    /// [Metadata::insert_sql_without]: ../schema/struct.Metadata.html#method.insert_sql_without
+    fn defaulted_columns(&self) -> Vec<&'static str>;
+
+    /// Returns the INSERT statement of [Schema::insert_sql] for `rows` rows,
+    /// without the RETURNING clause, as run by [Crud::create_many]. The
+    /// values of the inserted columns are bound for each row in turn.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Schema;
+    ///
+    /// assert_eq!(r#"INSERT INTO "users" ("name") VALUES (?), (?), (?)"#, User::insert_sql_n(3));
+    /// # }}
+    /// ```
+    ///
+    /// [Crud::create_many]: trait.Crud.html#method.create_many
+    fn insert_sql_n(rows: usize) -> String
+    where
+        Self: Creatable,
+    {
+        Self::metadata().insert_many_sql(rows, &[])
+    }
+
+    /// Returns the SQL used by [Crud::create_ignore], the INSERT statement of
+    /// [Schema::insert_sql] skipping the row when one with the same primary
+    /// key exists. MySQL has no conflict target and runs `INSERT IGNORE`,
+    /// which also skips rows conflicting on other unique keys and turns other
+    /// errors in to warnings.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Schema;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// #[external_id]
+    /// struct Event {
+    ///     id: i64,
+    ///     kind: String,
+    /// }
+    ///
+    /// assert_eq!(
+    ///     r#"INSERT INTO "events" ("id", "kind") VALUES (?, ?) ON CONFLICT ("id") DO NOTHING"#,
+    ///     Event::insert_ignore_sql()
+    /// );
+    /// # }}
+    /// ```
+    ///
+    /// [Crud::create_ignore]: trait.Crud.html#method.create_ignore
+    fn insert_ignore_sql() -> &'static str
+    where
+        Self: Creatable,
+    {
+        &Self::metadata().insert_ignore_sql
+    }
+
+    /// Returns the SQL used by [Crud::upsert], the INSERT statement of
+    /// [Schema::insert_sql] updating the row with the same primary key on
+    /// conflict. Each updated column is set to its inserted value, with
+    /// `excluded` or with `VALUES()` on MySQL, so the values are bound once.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Schema;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// #[external_id]
+    /// struct Price {
+    ///     id: i64,
+    ///     amount: i64,
+    ///     currency: String,
+    /// }
+    ///
+    /// assert_eq!(
+    ///     r#"INSERT INTO "prices" ("id", "amount", "currency") VALUES (?, ?, ?) ON CONFLICT ("id") DO UPDATE SET "amount" = excluded."amount", "currency" = excluded."currency" RETURNING "prices"."id", "prices"."amount", "prices"."currency""#,
+    ///     Price::upsert_sql()
+    /// );
+    /// # }}
+    /// ```
+    ///
+    /// [Crud::upsert]: trait.Crud.html#method.upsert
+    fn upsert_sql() -> &'static str
+    where
+        Self: Upsertable,
+    {
+        &Self::metadata().upsert_sql
+    }
+
+    /// Returns the SQL for updating an existing record in the database.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Schema;
+    ///
+    /// assert_eq!(r#"UPDATE "users" SET "name" = ? WHERE "users"."user_id" = ? RETURNING "users"."user_id", "users"."name""#, User::update_by_id_sql());
+    /// # }}
+    /// ```
+    ///
+    /// Fields tagged `#[sqlx_crud(readonly)]` are inserted and selected but
+    /// never updated. Tagging every non-id field as readonly is a compile
+    /// error.
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, Schema};
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Invoice {
+    ///     id: i64,
+    ///     #[sqlx_crud(readonly)]
+    ///     tenant_id: i32,
+    ///     amount: i64,
+    /// }
+    ///
+    /// assert_eq!(
+    ///     r#"UPDATE "invoices" SET "amount" = ? WHERE "invoices"."id" = ? RETURNING "invoices"."id", "invoices"."tenant_id", "invoices"."amount""#,
+    ///     Invoice::update_by_id_sql()
+    /// );
+    ///
+    /// sqlx::query("CREATE TABLE invoices (id INTEGER PRIMARY KEY, tenant_id INTEGER NOT NULL, amount INTEGER NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    ///
+    /// let mut invoice = Invoice { id: 0, tenant_id: 7, amount: 100 }.create(&pool).await?;
+    /// invoice.tenant_id = 8;
+    /// invoice.amount = 200;
+    /// let invoice = invoice.update(&pool).await?;
+    /// assert_eq!(7, invoice.tenant_id);
+    /// assert_eq!(200, invoice.amount);
+    ///
+    /// let stored = Invoice::by_id(&pool, invoice.id).await?.unwrap();
+    /// assert_eq!(7, stored.tenant_id);
+    /// # }}
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use sqlx::FromRow;
+    /// use sqlx_crud::SqlxCrud;
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Invoice {
+    ///     id: i64,
+    ///     #[sqlx_crud(readonly)]
+    ///     tenant_id: i32,
+    /// }
+    /// ```
+    fn update_by_id_sql() -> &'static str
+    where
+        Self: Updatable,
+    {
+        &Self::metadata().update_by_id_sql
+    }
+
+    /// Returns the SQL for deleting an existing record by ID from the database.
+    /// This is an UPDATE for structs with a [Schema::soft_delete_column].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Schema;
+    ///
+    /// assert_eq!(r#"DELETE FROM "users" WHERE "users"."user_id" = ?"#, User::delete_by_id_sql());
+    /// # }}
+    /// ```
+    fn delete_by_id_sql() -> &'static str
+    where
+        Self: Deletable,
+    {
+        &Self::metadata().delete_by_id_sql
+    }
+
+    /// Returns the SQL used by [Crud::delete_many] for `ids` primary keys,
+    /// binding the key columns of each in turn. This is an UPDATE for structs
+    /// with a [Schema::soft_delete_column].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Schema;
+    ///
+    /// assert_eq!(r#"DELETE FROM "users" WHERE "users"."user_id" IN (?, ?, ?)"#, User::delete_by_ids_sql(3));
+    /// # }}
+    /// ```
+    ///
+    /// Composite keys are matched one key at a time:
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, Schema};
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Membership {
+    ///     #[id]
+    ///     group_id: i64,
+    ///     #[id]
+    ///     user_id: i64,
+    /// }
+    ///
+    /// assert_eq!(
+    ///     r#"DELETE FROM "memberships" WHERE (("memberships"."group_id" = ? AND "memberships"."user_id" = ?) OR ("memberships"."group_id" = ? AND "memberships"."user_id" = ?))"#,
+    ///     Membership::delete_by_ids_sql(2)
+    /// );
+    ///
+    /// sqlx::query("CREATE TABLE memberships (group_id INTEGER, user_id INTEGER, PRIMARY KEY (group_id, user_id))")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// let memberships = vec![Membership { group_id: 1, user_id: 1 }, Membership { group_id: 1, user_id: 2 }];
+    /// Membership::create_many(&pool, memberships).await?;
+    /// assert_eq!(1, Membership::delete_many(&pool, &[(1, 2), (2, 1)]).await?);
+    /// assert_eq!(1, Membership::all(&pool).await?.len());
+    /// # }}
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ids` is zero.
+    ///
+    /// [Crud::delete_many]: trait.Crud.html#method.delete_many
+    fn delete_by_ids_sql(ids: usize) -> String
+    where
+        Self: Deletable,
+    {
+        Self::metadata().delete_by_ids_sql(ids)
+    }
+
+    /// Returns the SQL used by [Crud::delete] to delete the current instance.
+    /// This is [Schema::delete_by_id_sql] with an additional version check
+    /// when the struct has a [Schema::version_column].
+    /// Audited structs run [Schema::audit_delete_sql] first, see
+    /// [Schema::history_table_name].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Schema;
+    ///
+    /// assert_eq!(User::delete_by_id_sql(), User::delete_sql());
+    /// # }}
+    /// ```
+    ///
+    /// [Crud::delete]: trait.Crud.html#method.delete
+    fn delete_sql() -> &'static str
+    where
+        Self: Deletable,
+    {
+        &Self::metadata().delete_sql
+    }
+
+    /// Returns the column used for optimistic locking, if any.
+    ///
+    /// Tagging an integer field `#[sqlx_crud(version)]` makes [Crud::update]
+    /// and [Crud::delete] match on the version the instance was loaded with.
+    /// The UPDATE statement increments the version, so the instance returned
+    /// by [Crud::update] carries the new version. When the stored version no
+    /// longer matches, both methods fail with [Error::StaleVersion] instead
+    /// of silently overwriting or ignoring the concurrent change.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Schema;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Document {
+    ///     id: i64,
+    ///     body: String,
+    ///     #[sqlx_crud(version)]
+    ///     version: i32,
+    /// }
+    ///
+    /// assert_eq!(Some("version"), Document::version_column());
+    /// assert_eq!(None, User::version_column());
+    /// assert_eq!(
+    ///     r#"UPDATE "documents" SET "body" = ?, "version" = "version" + 1 WHERE "documents"."id" = ? AND "documents"."version" = ? RETURNING "documents"."id", "documents"."body", "documents"."version""#,
+    ///     Document::update_by_id_sql()
+    /// );
+    /// assert_eq!(
+    ///     r#"DELETE FROM "documents" WHERE "documents"."id" = ? AND "documents"."version" = ?"#,
+    ///     Document::delete_sql()
+    /// );
+    /// # }}
+    /// ```
+    ///
+    /// Only one version field is allowed and it must be an integer:
+    ///
+    /// ```compile_fail
+    /// use sqlx::FromRow;
+    /// use sqlx_crud::SqlxCrud;
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Document {
+    ///     id: i64,
+    ///     #[sqlx_crud(version)]
+    ///     version: String,
+    /// }
+    /// ```
+    ///
+    /// [Crud::update]: trait.Crud.html#method.update
+    /// [Crud::delete]: trait.Crud.html#method.delete
+    /// [Error::StaleVersion]: ../enum.Error.html#variant.StaleVersion
+    fn version_column() -> Option<&'static str> {
+        Self::metadata().version_column
+    }
+
+    /// Returns the column marking soft deleted rows, if any.
+    ///
+    /// Tagging an `Option` timestamp field `#[sqlx_crud(soft_delete)]` turns
+    /// [Crud::delete] in to an UPDATE setting the column to the current time.
+    /// [Schema::select_sql] and [Schema::select_by_id_sql] only return rows
+    /// where the column is NULL, so queries built from them exclude soft
+    /// deleted rows too. Note the WHERE clause is already present in
+    /// [Schema::select_sql]; further conditions need to be joined with AND.
+    /// The field is never written by [Crud::update].
+    /// [Crud::delete_permanently] still removes the row.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx::types::chrono::{DateTime, Utc};
+    /// use sqlx_crud::{Crud, Schema};
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Comment {
+    ///     id: i64,
+    ///     body: String,
+    ///     #[sqlx_crud(soft_delete)]
+    ///     deleted_at: Option<DateTime<Utc>>,
+    /// }
+    ///
+    /// assert_eq!(Some("deleted_at"), Comment::soft_delete_column());
+    /// assert_eq!(
+    ///     r#"SELECT "comments"."id", "comments"."body", "comments"."deleted_at" FROM "comments" WHERE "comments"."deleted_at" IS NULL"#,
+    ///     Comment::select_sql()
+    /// );
+    /// assert_eq!(
+    ///     r#"UPDATE "comments" SET "deleted_at" = CURRENT_TIMESTAMP WHERE "comments"."id" = ? AND "comments"."deleted_at" IS NULL"#,
+    ///     Comment::delete_by_id_sql()
+    /// );
+    ///
+    /// sqlx::query("CREATE TABLE comments (id INTEGER PRIMARY KEY, body TEXT NOT NULL, deleted_at TIMESTAMP)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// let kept = Comment { id: 0, body: "kept".to_string(), deleted_at: None }.create(&pool).await?;
+    /// let removed = Comment { id: 0, body: "removed".to_string(), deleted_at: None }.create(&pool).await?;
+    /// let removed_id = removed.id;
+    ///
+    /// removed.delete(&pool).await?;
+    /// assert!(Comment::by_id(&pool, removed_id).await?.is_none());
+    /// assert_eq!(1, Comment::all(&pool).await?.len());
+    ///
+    /// // The row is still stored with its deletion time
+    /// let (deleted_at,): (Option<DateTime<Utc>>,) = sqlx::query_as("SELECT deleted_at FROM comments WHERE id = ?")
+    ///     .bind(removed_id)
+    ///     .fetch_one(&pool)
+    ///     .await?;
+    /// assert!(deleted_at.is_some());
+    ///
+    /// kept.delete_permanently(&pool).await?;
+    /// let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM comments")
+    ///     .fetch_one(&pool)
+    ///     .await?;
+    /// assert_eq!(1, count);
+    /// # }}
+    /// ```
+    ///
+    /// [Crud::delete]: trait.Crud.html#method.delete
+    /// [Crud::update]: trait.Crud.html#method.update
+    /// [Crud::delete_permanently]: trait.Crud.html#method.delete_permanently
+    fn soft_delete_column() -> Option<&'static str> {
+        Self::metadata().soft_delete_column
+    }
+
+    /// Returns the column every query of the struct is scoped to, if any.
+    ///
+    /// Tagging a field `#[sqlx_crud(tenant)]` adds a match on the tenant to
+    /// the WHERE clause of every generated statement, bound after the primary
+    /// key. [Crud::update], [Crud::delete] and [Crud::delete_permanently]
+    /// match on the tenant of the instance, which [Crud::update] never
+    /// changes. Reads need the tenant, so [Crud::all] and [Crud::by_id] are
+    /// replaced by the generated `all_for_tenant(pool, tenant)` and
+    /// `by_id_for_tenant(pool, tenant, id)`, and the `find_by` and `indexed`
+    /// finders take the tenant before the value. Calling [Crud::all] or
+    /// [Crud::by_id] does not compile, see [Unscoped].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, Schema};
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Project {
+    ///     id: i64,
+    ///     #[sqlx_crud(tenant)]
+    ///     tenant_id: i32,
+    ///     #[sqlx_crud(find_by)]
+    ///     name: String,
+    /// }
+    ///
+    /// assert_eq!(Some("tenant_id"), Project::tenant_column());
+    /// assert_eq!(
+    ///     r#"SELECT "projects"."id", "projects"."tenant_id", "projects"."name" FROM "projects" WHERE "projects"."tenant_id" = ?"#,
+    ///     Project::select_sql()
+    /// );
+    /// assert_eq!(
+    ///     r#"UPDATE "projects" SET "name" = ? WHERE "projects"."id" = ? AND "projects"."tenant_id" = ? RETURNING "projects"."id", "projects"."tenant_id", "projects"."name""#,
+    ///     Project::update_by_id_sql()
+    /// );
+    ///
+    /// sqlx::query("CREATE TABLE projects (id INTEGER PRIMARY KEY, tenant_id INTEGER NOT NULL, name TEXT NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// let ours = Project { id: 0, tenant_id: 1, name: "ours".to_string() }.create(&pool).await?;
+    /// let theirs = Project { id: 0, tenant_id: 2, name: "theirs".to_string() }.create(&pool).await?;
+    ///
+    /// // Reads only see the rows of the given tenant
+    /// assert_eq!(1, Project::all_for_tenant(&pool, 1).await?.len());
+    /// assert!(Project::by_id_for_tenant(&pool, 1, ours.id).await?.is_some());
+    /// assert!(Project::by_id_for_tenant(&pool, 1, theirs.id).await?.is_none());
+    /// assert!(Project::by_name(&pool, 1, "theirs").await?.is_none());
+    /// assert!(Project::by_name(&pool, 2, "theirs").await?.is_some());
+    ///
+    /// // Writes never reach the row of another tenant
+    /// let forged = Project { id: theirs.id, tenant_id: 1, name: "forged".to_string() };
+    /// assert!(forged.update(&pool).await.is_err());
+    /// assert!(Project { id: theirs.id, tenant_id: 1, name: String::new() }.delete(&pool).await.is_err());
+    /// Project { id: theirs.id, tenant_id: 1, name: String::new() }.delete_permanently(&pool).await?;
+    /// let theirs = Project::by_id_for_tenant(&pool, 2, theirs.id).await?.unwrap();
+    /// assert_eq!("theirs", theirs.name);
+    ///
+    /// // The tenant of a row is never changed
+    /// let mut ours = ours;
+    /// ours.name = "renamed".to_string();
+    /// let ours = ours.update(&pool).await?;
+    /// assert_eq!(1, ours.tenant_id);
+    /// # }}
+    /// ```
+    ///
+    /// Unscoped reads are rejected at compile time:
+    ///
+    /// ```compile_fail
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Crud;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Project {
+    ///     id: i64,
+    ///     #[sqlx_crud(tenant)]
+    ///     tenant_id: i32,
+    /// }
+    ///
+    /// Project::all(&pool).await?;
+    /// # }}
+    /// ```
+    ///
+    /// [Crud::all]: trait.Crud.html#method.all
+    /// [Crud::by_id]: trait.Crud.html#method.by_id
+    /// [Crud::update]: trait.Crud.html#method.update
+    /// [Crud::delete]: trait.Crud.html#method.delete
+    /// [Crud::delete_permanently]: trait.Crud.html#method.delete_permanently
+    fn tenant_column() -> Option<&'static str> {
+        Self::metadata().tenant_column
+    }
+
+    /// Returns a `CREATE TABLE IF NOT EXISTS` statement for the table, for
+    /// bootstrapping test and prototype databases. [Crud::create_table] runs
+    /// it. Column types are inferred from the field types, see [SqlType], and
+    /// set for other types with the `#[sqlx_crud(sql_type = "...")]` field
+    /// attribute. `Option` fields are nullable, as are columns the INSERT
+    /// statement leaves to a database default. The `#[id]` columns form the
+    /// primary key, integer keys assigned by the database auto-increment.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, Schema};
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Gadget {
+    ///     id: i64,
+    ///     name: String,
+    ///     weight: Option<f64>,
+    ///     #[sqlx_crud(sql_type = "VARCHAR(8)")]
+    ///     code: String,
+    /// }
+    ///
+    /// assert_eq!(
+    ///     r#"CREATE TABLE IF NOT EXISTS "gadgets" ("id" INTEGER NOT NULL, "name" TEXT NOT NULL, "weight" REAL, "code" VARCHAR(8) NOT NULL, PRIMARY KEY ("id"))"#,
+    ///     Gadget::create_table_sql()
+    /// );
+    ///
+    /// Gadget::create_table(&pool).await?;
+    /// let gadget = Gadget { id: 0, name: "lamp".to_string(), weight: None, code: "L1".to_string() };
+    /// let gadget = gadget.create(&pool).await?;
+    /// assert_eq!("lamp", Gadget::by_id(&pool, gadget.id).await?.unwrap().name);
+    /// # }}
+    /// ```
+    ///
+    /// Fields of types without an inferred SQL type need the attribute:
+    ///
+    /// ```compile_fail
+    /// use sqlx::FromRow;
+    /// use sqlx_crud::{Schema, SqlxCrud};
+    ///
+    /// #[derive(sqlx::Type)]
+    /// #[sqlx(transparent)]
+    /// struct Code(String);
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Gadget {
+    ///     id: i64,
+    ///     code: Code,
+    /// }
+    ///
+    /// Gadget::create_table_sql();
+    /// ```
+    ///
+    /// [Crud::create_table]: trait.Crud.html#method.create_table
+    /// [SqlType]: ../schema/enum.SqlType.html
+    fn create_table_sql() -> &'static str
+    where
+        Self: CreateTable,
+    {
+        create_table_sql(Self::metadata())
+    }
+
+    /// Returns the SQL used by [Crud::delete_all] to delete every row. This
+    /// is an UPDATE of the rows not yet deleted for structs with a
+    /// [Schema::soft_delete_column].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Schema;
+    ///
+    /// assert_eq!(r#"DELETE FROM "users""#, User::delete_all_sql());
+    /// # }}
+    /// ```
+    ///
+    /// [Crud::delete_all]: trait.Crud.html#method.delete_all
+    fn delete_all_sql() -> &'static str
+    where
+        Self: Unscoped + Deletable,
+    {
+        &Self::metadata().delete_all_sql
+    }
+
+    /// Returns the SQL used by [Crud::truncate] to remove every row,
+    /// `TRUNCATE TABLE` on PostgreSQL and MySQL. SQLite has no TRUNCATE and
+    /// runs a DELETE without WHERE clause, which it optimizes likewise.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Schema;
+    ///
+    /// assert_eq!(r#"DELETE FROM "users""#, User::truncate_sql());
+    /// # }}
+    /// ```
+    ///
+    /// [Crud::truncate]: trait.Crud.html#method.truncate
+    fn truncate_sql() -> &'static str
+    where
+        Self: Unscoped + Deletable,
+    {
+        &Self::metadata().truncate_sql
+    }
+
+    /// Returns the SQL for removing a record by ID from the database. This
+    /// is the same as [Schema::delete_by_id_sql] unless the struct has a
+    /// [Schema::soft_delete_column].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Schema;
+    ///
+    /// assert_eq!(r#"DELETE FROM "users" WHERE "users"."user_id" = ?"#, User::delete_permanently_by_id_sql());
+    /// # }}
+    /// ```
+    fn delete_permanently_by_id_sql() -> &'static str
+    where
+        Self: Deletable,
+    {
+        &Self::metadata().delete_permanently_by_id_sql
+    }
+
+    /// Returns the history table of a struct tagged with the
+    /// `#[sqlx_crud(audit)]` struct attribute, `<table>_history` in the same
+    /// database schema.
+    ///
+    /// [Crud::update] and [Crud::delete] of audited structs first copy the
+    /// row they are about to write in to the history table, along with an
+    /// `audited_at` timestamp and an `operation` of `'update'` or
+    /// `'delete'`. The history table has the columns of the table followed by
+    /// those two, see [Schema::audit_update_sql] for the INSERT statement.
+    /// PostgreSQL runs the copy and the write as a single statement. Other
+    /// databases run them as two statements in a transaction begun on the
+    /// executor. [Crud::delete_permanently] is not recorded, and the methods
+    /// writing rows without recording them are refused, see [Unaudited].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, Schema};
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// #[sqlx_crud(audit)]
+    /// struct Price {
+    ///     id: i64,
+    ///     amount: i64,
+    /// }
+    ///
+    /// assert_eq!(Some("prices_history"), Price::history_table_name());
+    /// assert_eq!(
+    ///     Some(r#"INSERT INTO "prices_history" ("id", "amount", "audited_at", "operation") SELECT "prices"."id", "prices"."amount", CURRENT_TIMESTAMP, 'update' FROM "prices" WHERE "prices"."id" = ?"#),
+    ///     Price::audit_update_sql()
+    /// );
+    ///
+    /// sqlx::query("CREATE TABLE prices (id INTEGER PRIMARY KEY, amount INTEGER NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// sqlx::query("CREATE TABLE prices_history (id INTEGER NOT NULL, amount INTEGER NOT NULL, audited_at TEXT NOT NULL, operation TEXT NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    ///
+    /// let mut price = Price { id: 0, amount: 100 }.create(&pool).await?;
+    /// price.amount = 120;
+    /// let mut price = price.update(&pool).await?;
+    /// price.amount = 150;
+    /// let price = price.update(&pool).await?;
+    /// price.delete(&pool).await?;
+    ///
+    /// let history: Vec<(i64, String)> =
+    ///     sqlx::query_as("SELECT amount, operation FROM prices_history ORDER BY rowid")
+    ///         .fetch_all(&pool)
+    ///         .await?;
+    /// assert_eq!(
+    ///     vec![
+    ///         (100, "update".to_string()),
+    ///         (120, "update".to_string()),
+    ///         (150, "delete".to_string()),
+    ///     ],
+    ///     history
+    /// );
+    /// # }}
+    /// ```
+    fn history_table_name() -> Option<&'static str> {
+        Self::metadata().history_table_name.as_deref()
+    }
+
+    /// Returns the statement of the `touch()` method generated for structs
+    /// with an `#[sqlx_crud(updated_at)]` field, writing only the timestamp
+    /// and the version of a row. `None` for structs without one.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx::types::chrono::{DateTime, Utc};
+    /// use sqlx_crud::Schema;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Heartbeat {
+    ///     id: i64,
+    ///     #[sqlx_crud(version)]
+    ///     version: i64,
+    ///     #[sqlx_crud(updated_at)]
+    ///     seen_at: DateTime<Utc>,
+    /// }
+    ///
+    /// assert_eq!(
+    ///     Some(r#"UPDATE "heartbeats" SET "version" = "version" + 1, "seen_at" = CURRENT_TIMESTAMP WHERE "heartbeats"."id" = ? AND "heartbeats"."version" = ? RETURNING "heartbeats"."id", "heartbeats"."version", "heartbeats"."seen_at""#),
+    ///     Heartbeat::touch_sql()
+    /// );
+    /// assert_eq!(None, User::touch_sql());
+    /// # }}
+    /// ```
+    fn touch_sql() -> Option<&'static str>
+    where
+        Self: Updatable,
+    {
+        Self::metadata().touch_sql.as_deref()
+    }
+
+    /// Returns the INSERT statement copying the row [Crud::update] writes in
+    /// to the history table of an audited struct. It binds the WHERE clause
+    /// of [Schema::update_by_id_sql]. See [Schema::history_table_name].
+    fn audit_update_sql() -> Option<&'static str>
+    where
+        Self: Updatable,
+    {
+        Self::metadata().audit_update_sql.as_deref()
+    }
+
+    /// Returns the INSERT statement copying the row [Crud::delete] deletes in
+    /// to the history table of an audited struct. It binds the WHERE clause
+    /// of the delete statement. See [Schema::history_table_name].
+    fn audit_delete_sql() -> Option<&'static str>
+    where
+        Self: Deletable,
+    {
+        Self::metadata().audit_delete_sql.as_deref()
+    }
+}
+
+/// A column of a [Schema] or [Embedded] struct, naming it without a string.
+///
+/// The [SqlxCrud] and [SqlxCrudEmbedded] derive macros implement this trait
+/// for a generated `<Struct>Column` enum with one variant per column, named
+/// after the field in _PascalCase_. Skipped fields have no variant and the
+/// variants name their columns as renamed. Flattened fields are a variant
+/// holding the column enum of the embedded struct. As the enum is
+/// exhaustive, adding or removing a field breaks each `match` on it.
+///
+/// `Display` and `AsRef<str>` yield the unquoted column name. The `ALL`
+/// constant lists the variants in the order of [Schema::columns], also
+/// available as an inherent constant of the enum. Each column also has an
+/// inherent [TypedColumn] constant named after its field, building filters
+/// typed as the field.
+///
+/// [TypedColumn]: ../filter/struct.TypedColumn.html
+///
+/// # Example
+///
+/// ```rust
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use sqlx_crud::{ColumnName, Schema};
+///
+/// #[derive(FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// struct Account {
+///     account_id: i64,
+///     #[sqlx_crud(rename = "display_name")]
+///     name: String,
+///     #[sqlx(skip)]
+///     #[sqlx_crud(skip)]
+///     selected: bool,
+/// }
+///
+/// assert_eq!("display_name", AccountColumn::Name.to_string());
+/// assert_eq!("account_id", AccountColumn::AccountId.as_ref());
+/// assert_eq!(&[AccountColumn::AccountId, AccountColumn::Name], AccountColumn::ALL);
+/// assert_eq!(AccountColumn::ALL, Account::typed_columns());
+///
+/// let names = Account::typed_columns().iter().map(|c| c.name()).collect::<Vec<_>>();
+/// assert_eq!(Account::columns(), names);
+///
+/// sqlx::query("CREATE TABLE accounts (account_id INTEGER PRIMARY KEY, display_name TEXT NOT NULL)")
+///     .execute(&pool)
+///     .await?;
+/// let sql = format!("SELECT {} FROM {}", AccountColumn::Name, Account::table_name());
+/// let names: Vec<String> = sqlx::query_scalar(&sql).fetch_all(&pool).await?;
+/// assert!(names.is_empty());
+/// # }}
+/// ```
+///
+/// [SqlxCrud]: ../derive.SqlxCrud.html
+/// [SqlxCrudEmbedded]: ../derive.SqlxCrudEmbedded.html
+pub trait ColumnName:
+    Copy
+    + Eq
+    + std::hash::Hash
+    + std::fmt::Debug
+    + std::fmt::Display
+    + AsRef<str>
+    + Send
+    + Sync
+    + 'static
+{
+    /// Every column, in field order.
+    const ALL: &'static [Self];
+
+    /// Returns the unquoted column name.
+    fn name(&self) -> &'static str;
+}
+
+/// Compares the updated fields of two values of a [Schema] struct, used by
+/// [Tracked] to find the columns a change wrote to.
+///
+/// This trait is implemented by the [SqlxCrud] derive macro for structs
+/// tagged with the `#[sqlx_crud(tracked)]` struct attribute. The fields
+/// written by [Crud::update] must implement `PartialEq` and `Clone`.
+///
+/// [SqlxCrud]: ../derive.SqlxCrud.html
+/// [Tracked]: ../tracked/struct.Tracked.html
+pub trait Track: Schema {
+    /// Returns the columns whose fields differ between `self` and `before`,
+    /// in field order. Only columns written by [Crud::update] are compared.
+    fn changed_columns(&self, before: &Self) -> Vec<Self::Column>;
+}
+
+/// Marker for [Schema] structs whose rows can be read without a tenant,
+/// bounding [Crud::all] and [Crud::by_id].
+///
+/// This trait is implemented by the [SqlxCrud] derive macro for structs
+/// without a [Schema::tenant_column].
+///
+/// [SqlxCrud]: ../derive.SqlxCrud.html
+pub trait Unscoped: Schema {}
+
+/// Marker for [Schema] structs whose rows can be inserted, bounding
+/// [Crud::create] and [Schema::insert_sql].
+///
+/// This trait is implemented by the [SqlxCrud] derive macro unless `create`
+/// is left out with the `#[sqlx_crud(only(...))]` or
+/// `#[sqlx_crud(except(...))]` struct attribute. Structs tagged
+/// `#[sqlx_crud(view)]` only implement [Readable].
+///
+/// # Example
+///
+/// Append-only tables keep `update` and `delete` from compiling:
+///
+/// ```rust
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use sqlx_crud::{Crud, Schema};
+///
+/// #[derive(Debug, FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// #[sqlx_crud(only(create, read))]
+/// struct Event {
+///     id: i64,
+///     kind: String,
+/// }
+///
+/// sqlx::query("CREATE TABLE events (id INTEGER PRIMARY KEY, kind TEXT NOT NULL)")
+///     .execute(&pool)
+///     .await?;
+/// let event = Event { id: 0, kind: "signup".to_string() }.create(&pool).await?;
+/// assert_eq!("signup", Event::by_id(&pool, event.id).await?.unwrap().kind);
+/// assert_eq!(r#"INSERT INTO "events" ("kind") VALUES (?) RETURNING "events"."id", "events"."kind""#, Event::insert_sql());
+/// # }}
+/// ```
+///
+/// ```rust,compile_fail
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use sqlx_crud::Crud;
+///
+/// #[derive(Debug, FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// #[sqlx_crud(except(update, delete))]
+/// struct Event {
+///     id: i64,
+///     kind: String,
+/// }
+///
+/// let event = Event::by_id(&pool, 1).await?.unwrap();
+/// event.delete(&pool).await?;
+/// # }}
+/// ```
+///
+/// ```rust,compile_fail
+/// use sqlx::FromRow;
+/// use sqlx_crud::{Schema, SqlxCrud};
+///
+/// #[derive(FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// #[sqlx_crud(except(update, delete))]
+/// struct Event {
+///     id: i64,
+///     kind: String,
+/// }
+///
+/// let _ = Event::update_by_id_sql();
+/// ```
+///
+/// Leaving out every operation is rejected:
+///
+/// ```rust,compile_fail
+/// use sqlx::FromRow;
+/// use sqlx_crud::SqlxCrud;
+///
+/// #[derive(FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// #[sqlx_crud(except(create, read, update, delete))]
+/// struct Event {
+///     id: i64,
+///     kind: String,
+/// }
+/// ```
+///
+/// [SqlxCrud]: ../derive.SqlxCrud.html
+pub trait Creatable: Schema {}
+
+/// Marker for [Schema] structs whose rows can be read, bounding [Crud::all],
+/// [Crud::by_id], the select queries and the generated finders.
+///
+/// This trait is implemented by the [SqlxCrud] derive macro unless `read` is
+/// left out, see [Creatable].
+///
+/// [SqlxCrud]: ../derive.SqlxCrud.html
+pub trait Readable: Schema {}
+
+/// Marker for [Schema] structs whose rows can be updated, bounding
+/// [Crud::update] and [Schema::update_by_id_sql].
+///
+/// This trait is implemented by the [SqlxCrud] derive macro unless `update`
+/// is left out, see [Creatable]. Structs made up of only `#[id]` columns
+/// have nothing to update, and `update` is left out for them:
+///
+/// ```rust
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use sqlx_crud::{Crud, Schema};
+///
+/// #[derive(Debug, PartialEq, FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// #[external_id]
+/// struct Marker {
+///     id: i64,
+/// }
+///
+/// sqlx::query("CREATE TABLE markers (id INTEGER PRIMARY KEY)")
+///     .execute(&pool)
+///     .await?;
+/// assert_eq!(
+///     r#"INSERT INTO "markers" ("id") VALUES (?) RETURNING "markers"."id""#,
+///     Marker::insert_sql()
+/// );
+///
+/// let marker = Marker { id: 7 }.create(&pool).await?;
+/// assert_eq!(Some(&marker), Marker::by_id(&pool, 7).await?.as_ref());
+/// assert_eq!(vec![marker], Marker::all(&pool).await?);
+///
+/// Marker { id: 7 }.delete(&pool).await?;
+/// assert!(Marker::by_id(&pool, 7).await?.is_none());
+/// # }}
+/// ```
+///
+/// ```rust,compile_fail
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use sqlx_crud::Crud;
+///
+/// #[derive(Debug, FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// #[external_id]
+/// struct Marker {
+///     id: i64,
+/// }
+///
+/// Marker { id: 7 }.update(&pool).await?;
+/// # }}
+/// ```
+///
+/// [SqlxCrud]: ../derive.SqlxCrud.html
+pub trait Updatable: Schema {}
+
+/// Marker for [Schema] structs whose rows can be upserted, bounding
+/// [Crud::upsert], [Crud::find_or_create] and [Schema::upsert_sql].
+///
+/// This trait is implemented by the [SqlxCrud] derive macro for structs that
+/// can be created and updated whose primary key is inserted rather than
+/// assigned by the database: `#[external_id]`, `#[id(uuid)]`, composite and
+/// flattened keys. Upserts bypass the tenant scope, the version check and the
+/// audit history, so structs with a `#[sqlx_crud(tenant)]` or
+/// `#[sqlx_crud(version)]` field or `#[sqlx_crud(audit)]` are not upserted.
+///
+/// ```rust,compile_fail
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use sqlx_crud::Schema;
+///
+/// #[derive(Debug, FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// struct Reading {
+///     id: i64,
+///     value: f64,
+/// }
+///
+/// let _ = Reading::upsert_sql();
+/// # }}
+/// ```
+///
+/// [SqlxCrud]: ../derive.SqlxCrud.html
+pub trait Upsertable: Schema {}
+
+/// Marker for [Schema] and [Embedded] structs with an SQL type for each
+/// column, bounding [Schema::create_table_sql] and [Crud::create_table].
+///
+/// This trait is implemented by the [SqlxCrud] and [SqlxCrudEmbedded] derive
+/// macros when the type of each field is inferred or set with
+/// `#[sqlx_crud(sql_type = "...")]`, and the embedded struct of each
+/// flattened field implements it too.
+///
+/// ```rust
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use sqlx_crud::{Crud, Schema, SqlxCrudEmbedded};
+///
+/// #[derive(Debug, FromRow, SqlxCrudEmbedded)]
+/// #[database(Sqlite)]
+/// struct Size {
+///     width: f64,
+///     height: f64,
+/// }
+///
+/// #[derive(Debug, FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// struct Frame {
+///     id: i64,
+///     #[sqlx(flatten)]
+///     size: Size,
+/// }
+///
+/// assert_eq!(
+///     r#"CREATE TABLE IF NOT EXISTS "frames" ("id" INTEGER NOT NULL, "width" REAL NOT NULL, "height" REAL NOT NULL, PRIMARY KEY ("id"))"#,
+///     Frame::create_table_sql()
+/// );
+/// Frame::create_table(&pool).await?;
+/// # }}
+/// ```
+///
+/// An embedded struct with an untyped column leaves the outer struct
+/// without the marker:
+///
+/// ```rust,compile_fail
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use sqlx_crud::{Crud, SqlxCrudEmbedded};
+///
+/// #[derive(sqlx::Type)]
+/// #[sqlx(transparent)]
+/// struct Code(String);
+///
+/// #[derive(FromRow, SqlxCrudEmbedded)]
+/// #[database(Sqlite)]
+/// struct Labels {
+///     code: Code,
+/// }
+///
+/// #[derive(FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// struct Gadget {
+///     id: i64,
+///     #[sqlx(flatten)]
+///     labels: Labels,
+/// }
+///
+/// Gadget::create_table(&pool).await?;
+/// # }}
+/// ```
+///
+/// [SqlxCrud]: ../derive.SqlxCrud.html
+/// [SqlxCrudEmbedded]: ../derive.SqlxCrudEmbedded.html
+pub trait CreateTable {}
+
+/// Marker for [Schema] structs whose rows can be deleted, bounding
+/// [Crud::delete], [Crud::delete_permanently] and the delete queries.
+///
+/// This trait is implemented by the [SqlxCrud] derive macro unless `delete`
+/// is left out, see [Creatable].
+///
+/// [SqlxCrud]: ../derive.SqlxCrud.html
+pub trait Deletable: Schema {}
+
+/// Marker for [Schema] structs whose writes are not recorded in a history
+/// table, bounding the methods that write rows without copying them there
+/// first: [Crud::delete_by_id], [Crud::delete_many], [Crud::update_where],
+/// [Crud::increment] and the like.
+///
+/// This trait is implemented by the [SqlxCrud] derive macro for structs
+/// without `#[sqlx_crud(audit)]`, which are only written by [Crud::update]
+/// and [Crud::delete], and deleted without history by
+/// [Crud::delete_permanently]. See [Schema::history_table_name].
+///
+/// ```rust,compile_fail
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use sqlx_crud::Crud;
+///
+/// #[derive(Debug, FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// #[sqlx_crud(audit)]
+/// struct Price {
+///     id: i64,
+///     amount: i64,
+/// }
+///
+/// Price::delete_by_id(&pool, 1).await?;
+/// # }}
+/// ```
+///
+/// [SqlxCrud]: ../derive.SqlxCrud.html
+pub trait Unaudited: Schema {}
+
+/// Checks the invariants of an instance before it is written. Structs with
+/// the `#[sqlx_crud(validate)]` container attribute implement this trait by
+/// hand and [Crud::create] and [Crud::update] return [Error::Validation]
+/// without running a query when the check fails. Without the attribute the
+/// trait is not called.
+///
+/// # Example
+///
+/// ```rust
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use sqlx_crud::{Crud, Error, Validate, ValidationError};
+///
+/// #[derive(Debug, FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// #[sqlx_crud(validate)]
+/// struct Account {
+///     id: i64,
+///     name: String,
+///     email: String,
+/// }
+///
+/// impl Validate for Account {
+///     fn validate(&self) -> Result<(), ValidationError> {
+///         if self.name.is_empty() {
+///             return Err(ValidationError::field("name", "must not be empty"));
+///         }
+///         if !self.email.contains('@') {
+///             return Err(ValidationError::field("email", "must be an email address"));
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// sqlx::query("CREATE TABLE accounts (id INTEGER PRIMARY KEY, name TEXT NOT NULL, email TEXT NOT NULL)")
+///     .execute(&pool)
+///     .await?;
+///
+/// let invalid = Account { id: 1, name: String::new(), email: "ada@example.com".to_string() };
+/// let err = invalid.create(&pool).await.unwrap_err();
+/// assert_eq!("accounts row is invalid: name: must not be empty", err.to_string());
+/// assert!(Account::all(&pool).await?.is_empty());
+///
+/// let account = Account { id: 1, name: "ada".to_string(), email: "ada@example.com".to_string() }
+///     .create(&pool)
+///     .await?;
+///
+/// let invalid = Account { email: "nowhere".to_string(), ..account };
+/// let err = invalid.update(&pool).await.unwrap_err();
+/// assert!(matches!(
+///     err,
+///     Error::Validation { table_name: "accounts", source: ValidationError { field: Some("email"), .. } }
+/// ));
+/// assert_eq!("ada@example.com", Account::by_id(&pool, 1).await?.unwrap().email);
+/// # }}
+/// ```
+///
+/// A struct with the attribute must implement the trait:
+///
+/// ```rust,compile_fail
+/// use sqlx::FromRow;
+/// use sqlx_crud::SqlxCrud;
+///
+/// #[derive(FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// #[sqlx_crud(validate)]
+/// struct Account {
+///     id: i64,
+///     name: String,
+/// }
+/// ```
+///
+/// [Error::Validation]: ../enum.Error.html#variant.Validation
+pub trait Validate {
+    /// Returns an error describing the first violated invariant.
+    fn validate(&self) -> Result<(), ValidationError>;
+}
+
+/// Hooks [Crud] calls around its writes. Structs with the
+/// `#[sqlx_crud(hooks)]` container attribute implement this trait and
+/// override the hooks they need, the others do nothing.
+///
+/// The `before_*` hooks run before the fields are bound, so changes they make
+/// to the instance are written. An error returned by a hook is wrapped in
+/// [Error::Hook]. A failed `before_*` hook aborts the operation without
+/// running a query, a failed `after_*` hook is reported after the query has
+/// run. Hooks are synchronous and run in the order `before_create`,
+/// [Validate::validate], the query and `after_create`, and likewise for
+/// updates. [Crud::delete] and [Crud::delete_permanently] both run the delete
+/// hooks.
+///
+/// # Example
+///
+/// ```rust
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use std::sync::Mutex;
+///
+/// use sqlx_crud::{Crud, CrudHooks, Error, HookError};
+///
+/// static EVICTED: Mutex<Vec<i64>> = Mutex::new(Vec::new());
+///
+/// #[derive(Debug, FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// #[sqlx_crud(hooks)]
+/// struct Article {
+///     id: i64,
+///     title: String,
+///     slug: String,
+/// }
+///
+/// impl CrudHooks for Article {
+///     fn before_create(&mut self) -> Result<(), HookError> {
+///         if self.title.is_empty() {
+///             return Err("an article needs a title".into());
+///         }
+///         self.slug = self.title.to_lowercase().replace(' ', "-");
+///         Ok(())
+///     }
+///
+///     fn after_update(&self) -> Result<(), HookError> {
+///         EVICTED.lock().unwrap().push(self.id);
+///         Ok(())
+///     }
+///
+///     fn after_delete(id: &i64) -> Result<(), HookError> {
+///         EVICTED.lock().unwrap().push(*id);
+///         Ok(())
+///     }
+/// }
+///
+/// sqlx::query("CREATE TABLE articles (id INTEGER PRIMARY KEY, title TEXT NOT NULL, slug TEXT NOT NULL)")
+///     .execute(&pool)
+///     .await?;
+///
+/// let article = Article { id: 1, title: "Hello World".to_string(), slug: String::new() };
+/// let mut article = article.create(&pool).await?;
+/// assert_eq!("hello-world", Article::by_id(&pool, 1).await?.unwrap().slug);
+///
+/// article.title = "Hello Again".to_string();
+/// let article = article.update(&pool).await?;
+/// article.delete(&pool).await?;
+/// assert_eq!(vec![1, 1], *EVICTED.lock().unwrap());
+///
+/// let untitled = Article { id: 2, title: String::new(), slug: String::new() };
+/// let err = untitled.create(&pool).await.unwrap_err();
+/// assert!(matches!(err, Error::Hook { table_name: "articles", hook: "before_create", .. }));
+/// assert!(Article::all(&pool).await?.is_empty());
+/// # }}
+/// ```
+///
+/// [Error::Hook]: ../enum.Error.html#variant.Hook
+pub trait CrudHooks: Schema {
+    /// Runs before the row is inserted by [Crud::create].
+    fn before_create(&mut self) -> Result<(), HookError> {
+        Ok(())
+    }
+
+    /// Runs on the row returned by [Crud::create].
+    fn after_create(&self) -> Result<(), HookError> {
+        Ok(())
+    }
+
+    /// Runs before the row is updated by [Crud::update].
+    fn before_update(&mut self) -> Result<(), HookError> {
+        Ok(())
+    }
+
+    /// Runs on the row returned by [Crud::update].
+    fn after_update(&self) -> Result<(), HookError> {
+        Ok(())
+    }
+
+    /// Runs before the row is deleted by [Crud::delete] or
+    /// [Crud::delete_permanently].
+    fn before_delete(&mut self) -> Result<(), HookError> {
+        Ok(())
+    }
+
+    /// Runs with the id of the row deleted by [Crud::delete] or
+    /// [Crud::delete_permanently].
+    fn after_delete(id: &Self::Id) -> Result<(), HookError> {
+        let _ = id;
+        Ok(())
+    }
+}
+
+/// The [CrudHooks] hooks taking the instance, see [Schema::run_hook].
+#[doc(hidden)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hook {
+    BeforeCreate,
+    AfterCreate,
+    BeforeUpdate,
+    AfterUpdate,
+    BeforeDelete,
+}
+
+impl Hook {
+    /// Name of the hook method.
+    pub fn name(self) -> &'static str {
+        match self {
+            Hook::BeforeCreate => "before_create",
+            Hook::AfterCreate => "after_create",
+            Hook::BeforeUpdate => "before_update",
+            Hook::AfterUpdate => "after_update",
+            Hook::BeforeDelete => "before_delete",
+        }
+    }
+}
+
+/// The [Schema] queries of a struct rendered for database `DB`. [Crud] runs
+/// the queries of the database of its executor.
+///
+/// This trait is implemented by the [SqlxCrud] derive macro for every
+/// database listed in the `#[database(...)]` attribute. The [Schema] methods
+/// return the queries of the first listed database.
+///
+/// # Example
+///
+/// ```rust
+/// use sqlx::{FromRow, Postgres, Sqlite};
+/// use sqlx_crud::traits::DatabaseSchema;
+/// use sqlx_crud::SqlxCrud;
+///
+/// #[derive(FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// struct User {
+///     user_id: i32,
+///     name: String,
+/// }
+///
+/// let metadata = <User as DatabaseSchema<Sqlite>>::database_metadata();
+/// assert_eq!(
+///     r#"UPDATE "users" SET "name" = ? WHERE "users"."user_id" = ? RETURNING "users"."user_id", "users"."name""#,
+///     metadata.update_by_id_sql
+/// );
+///
+/// // Postgres numbers the bind parameters
+/// assert_eq!(
+///     r#"UPDATE "users" SET "name" = $1 WHERE "users"."user_id" = $2"#,
+///     sqlx_crud::schema::placeholders::<Postgres>(
+///         r#"UPDATE "users" SET "name" = ? WHERE "users"."user_id" = ?"#.to_string()
+///     )
+/// );
+/// ```
+///
+/// [SqlxCrud]: ../derive.SqlxCrud.html
+pub trait DatabaseSchema<DB: Database>: Schema {
+    /// Returns the table metadata and queries for database `DB`. The metadata
+    /// is built once, on first use.
+    fn database_metadata() -> &'static Metadata;
+
+    /// Returns the number of rows affected by a statement. sqlx only exposes
+    /// this on the concrete backend result types.
+    #[doc(hidden)]
+    fn rows_affected(result: &DB::QueryResult) -> u64;
+
+    /// Returns whether the row returned by [Metadata::save_sql_without] was
+    /// inserted rather than updated.
+    ///
+    /// [Metadata::save_sql_without]: ../schema/struct.Metadata.html#method.save_sql_without
+    #[doc(hidden)]
+    fn upsert_inserted(row: &DB::Row) -> Result<bool, sqlx::Error>;
+
+    /// Returns the INSERT statement of [Crud::create] with the fields of self
+    /// bound, to be run by the caller. The binds borrow self rather than
+    /// copying the values. Unlike [Crud::create] the [Validate] check and
+    /// the [CrudHooks] are not run.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::traits::DatabaseSchema;
+    /// use sqlx_crud::Crud;
+    ///
+    /// let user = User { user_id: 2, name: "new".to_string() };
+    /// user.insert_query().persistent(false).execute(&pool).await?;
+    /// assert_eq!("new", User::by_id(&pool, 2).await?.unwrap().name);
+    ///
+    /// let user = User { name: "renamed".to_string(), ..user };
+    /// let updated = user.update_query().try_map(|row| User::from_row(&row)).fetch_one(&pool).await?;
+    /// assert_eq!("renamed", updated.name);
+    ///
+    /// let user = User::select_by_id_query(2).fetch_optional(&pool).await?;
+    /// assert_eq!("renamed", user.unwrap().name);
+    /// # }}
+    /// ```
+    fn insert_query<'q>(&'q self) -> Query<'q, DB, <DB as HasArguments<'q>>::Arguments>
+    where
+        Self: Crud<'q, &'q Pool<DB>> + Creatable,
+        for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+        <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+    {
+        let sql = Self::database_metadata().insert_sql_without(&self.defaulted_columns());
+        sqlx::query_with(sql, <Self as Crud<'q, &'q Pool<DB>>>::insert_args_ref(self))
+    }
+
+    /// Returns the UPDATE statement of [Crud::update] with the fields of self
+    /// bound, to be run by the caller. The binds borrow self rather than
+    /// copying the values. Unlike [Crud::update] the [Validate] check and
+    /// the [CrudHooks] are not run, and a version mismatch updates no row
+    /// rather than returning [Error::StaleVersion]. Audited structs are
+    /// refused, see [Unaudited].
+    fn update_query<'q>(&'q self) -> Query<'q, DB, <DB as HasArguments<'q>>::Arguments>
+    where
+        Self: Crud<'q, &'q Pool<DB>> + Updatable + Unaudited,
+        for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+        <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+    {
+        let sql = &Self::database_metadata().update_sql;
+        sqlx::query_with(sql, <Self as Crud<'q, &'q Pool<DB>>>::update_args_ref(self))
+    }
+
+    /// Returns the SELECT statement of [Crud::by_id] with `id` bound, to be
+    /// run by the caller.
+    fn select_by_id_query<'q, K>(
+        id: K,
+    ) -> QueryAs<'q, DB, Self, <DB as HasArguments<'q>>::Arguments>
+    where
+        Self: Crud<'q, &'q Pool<DB>> + Unscoped + Readable,
+        for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+        <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+        Self: BindId<'q, DB, K>,
+    {
+        let sql = &Self::database_metadata().select_by_id_sql;
+        sqlx::query_as_with(sql, <Self as Crud<'q, &'q Pool<DB>>>::id_args(id))
+    }
+}
+
+/// Columns embedded in a [Schema] with sqlx's `#[sqlx(flatten)]` field
+/// attribute. The fields of the embedded struct are expanded in to the
+/// columns, binds and queries of the struct they are flattened in to, in
+/// declaration order.
+///
+/// This trait is implemented by the [SqlxCrudEmbedded] derive macro, which
+/// accepts the same field attributes as [SqlxCrud] except `version` and
+/// `soft_delete`. An embedded struct may itself flatten another embedded
+/// struct.
+///
+/// Tagging the flattened field `#[id]` makes the `#[id]` fields of the
+/// embedded struct the primary key. [Schema::Id] is then [Embedded::Id].
+/// Like composite keys, embedded keys are assigned outside of the database.
+///
+/// # Example
+///
+/// ```rust
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use sqlx::types::chrono::{DateTime, Utc};
+/// use sqlx_crud::{Crud, Schema, SqlxCrudEmbedded};
+///
+/// #[derive(Debug, FromRow, SqlxCrudEmbedded)]
+/// #[database(Sqlite)]
+/// struct Audit {
+///     #[sqlx_crud(created_at)]
+///     created_at: DateTime<Utc>,
+///     #[sqlx_crud(updated_at)]
+///     updated_at: DateTime<Utc>,
+/// }
+///
+/// #[derive(Debug, FromRow, SqlxCrudEmbedded)]
+/// #[database(Sqlite)]
+/// struct Authorship {
+///     author: String,
+///     #[sqlx(flatten)]
+///     audit: Audit,
+/// }
+///
+/// #[derive(Debug, FromRow, SqlxCrudEmbedded)]
+/// #[database(Sqlite)]
+/// struct NoteKey {
+///     #[id]
+///     owner_id: i32,
+///     #[id]
+///     seq: i32,
+/// }
+///
+/// #[derive(Debug, FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// struct Note {
+///     #[id]
+///     #[sqlx(flatten)]
+///     key: NoteKey,
+///     body: String,
+///     #[sqlx(flatten)]
+///     authorship: Authorship,
+/// }
+///
+/// assert_eq!(&["owner_id", "seq", "body", "author", "created_at", "updated_at"], Note::columns());
+/// assert_eq!(&["owner_id", "seq"], Note::id_columns());
+/// assert_eq!(6, NoteColumn::ALL.len());
+/// assert_eq!(NoteColumn::Authorship(AuthorshipColumn::Audit(AuditColumn::UpdatedAt)), NoteColumn::ALL[5]);
+/// assert_eq!("updated_at", NoteColumn::ALL[5].to_string());
+/// assert_eq!(
+///     r#"UPDATE "notes" SET "body" = ?, "author" = ?, "updated_at" = CURRENT_TIMESTAMP WHERE "notes"."owner_id" = ? AND "notes"."seq" = ? RETURNING "notes"."owner_id", "notes"."seq", "notes"."body", "notes"."author", "notes"."created_at", "notes"."updated_at""#,
+///     Note::update_by_id_sql()
+/// );
+///
+/// sqlx::query("CREATE TABLE notes (owner_id INTEGER NOT NULL, seq INTEGER NOT NULL, body TEXT NOT NULL, author TEXT NOT NULL, created_at TEXT NOT NULL, updated_at TEXT NOT NULL, PRIMARY KEY (owner_id, seq))")
+///     .execute(&pool)
+///     .await?;
+///
+/// let audit = Audit { created_at: DateTime::<Utc>::UNIX_EPOCH, updated_at: DateTime::<Utc>::UNIX_EPOCH };
+/// let note = Note {
+///     key: NoteKey { owner_id: 1, seq: 1 },
+///     body: "hello".to_string(),
+///     authorship: Authorship { author: "ada".to_string(), audit },
+/// };
+/// let mut note = note.create(&pool).await?;
+/// assert!(note.authorship.audit.created_at > DateTime::<Utc>::UNIX_EPOCH);
+///
+/// note.body = "hello again".to_string();
+/// note.authorship.author = "grace".to_string();
+/// note.update(&pool).await?;
+///
+/// let note = Note::by_id(&pool, (1, 1)).await?.unwrap();
+/// assert_eq!((1, 1), note.id());
+/// assert_eq!("hello again", note.body);
+/// assert_eq!("grace", note.authorship.author);
+///
+/// note.delete(&pool).await?;
+/// assert!(Note::by_id(&pool, (1, 1)).await?.is_none());
+/// # }}
+/// ```
+///
+/// [Schema]: trait.Schema.html
+/// [SqlxCrud]: ../derive.SqlxCrud.html
+/// [SqlxCrudEmbedded]: ../derive.SqlxCrudEmbedded.html
+pub trait Embedded: Sized {
+    /// Database the embedded fields are bound for.
+    type Database: Database;
+
+    /// Type of the embedded primary key columns when the flattened field is
+    /// the `#[id]` field. This is `()` for structs without `#[id]` fields.
+    type Id: Clone + std::fmt::Debug + Send + Sync;
+
+    /// The generated `<Struct>Column` enum naming the embedded columns. See
+    /// [ColumnName].
+    type Column: ColumnName;
+
+    /// Returns the embedded columns in field order.
+    fn columns() -> Vec<Column>;
+
+    /// Returns the embedded primary key.
+    fn id(&self) -> Self::Id;
+
+    /// Appends the columns left out of the INSERT statement for this instance
+    /// to `columns`. See [Schema::defaulted_columns].
+    fn defaulted_columns(&self, columns: &mut Vec<&'static str>);
+
+    /// Moves the values bound by the INSERT statement in to `args`.
+    fn insert_args<'q>(self, args: &mut <Self::Database as HasArguments<'q>>::Arguments)
+    where
+        Self: 'q;
+
+    /// Moves the values bound by the SET clause of the UPDATE statement in to
+    /// `args`. `id` is true when the embedded struct is the primary key, in
+    /// which case its id columns are left out.
+    fn update_args<'q>(self, id: bool, args: &mut <Self::Database as HasArguments<'q>>::Arguments)
+    where
+        Self: 'q;
+
+    /// Binds the values of [Embedded::insert_args] borrowed from `self`.
+    fn insert_args_ref<'q>(&'q self, args: &mut <Self::Database as HasArguments<'q>>::Arguments);
+
+    /// Binds the values of [Embedded::update_args] borrowed from `self`.
+    fn update_args_ref<'q>(
+        &'q self,
+        id: bool,
+        args: &mut <Self::Database as HasArguments<'q>>::Arguments,
+    );
+
+    /// Binds the primary key columns of `id` to `args`.
+    fn id_args<'q>(id: Self::Id, args: &mut <Self::Database as HasArguments<'q>>::Arguments)
+    where
+        Self: 'q;
+}
+
+/// Common Create, Read, Update, and Delete behaviors. This trait requires that
+/// [Schema] and [FromRow] are implemented for Self.
+///
+/// This trait is implemented by the [SqlxCrud] derive macro. Implementors
+/// define how to assign query insert and update bindings.
+///
+/// # Executors
+///
+/// The methods run on any sqlx executor of the database: a pool such as
+/// `&SqlitePool`, or a connection such as `&mut *conn` for a pooled
+/// connection `conn` or `&mut *tx` for a transaction `tx`. sqlx executes on
+/// the connection a transaction derefs to rather than on `&mut tx`. The
+/// methods running several statements, such as [Crud::create_many] and
+/// [Crud::by_ids], also acquire a connection from the executor, or begin a
+/// transaction on it, and take the same executors.
+///
+/// ```rust
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use sqlx_crud::Crud;
+///
+/// let mut tx = pool.begin().await?;
+/// let mut user = User { user_id: 0, name: "draft".to_string() }.create(&mut *tx).await?;
+/// user.name = "edited".to_string();
+/// let user = user.update_and_fetch(&mut *tx).await?;
+/// assert_eq!(2, User::by_ids(&mut *tx, &[1, user.user_id]).await?.len());
+/// tx.rollback().await?;
+/// assert!(User::by_id(&pool, user.user_id).await?.is_none());
+///
+/// let mut conn = pool.acquire().await?;
+/// assert_eq!(1, User::page(&mut *conn, 10, 0).await?.total);
+/// # }}
+/// ```
+///
+/// [FromRow]: https://docs.rs/sqlx/latest/sqlx/trait.FromRow.html
+/// [Schema]: trait.Schema.html
+/// [SqlxCrud]: ../derive.SqlxCrud.html
+pub trait Crud<'e, E>
+where
+    Self: 'e
+        + Sized
+        + Send
+        + Unpin
+        + for<'r> FromRow<'r, <E::Database as Database>::Row>
+        + DatabaseSchema<E::Database>
+        + BindId<'e, E::Database, <Self as Schema>::Id>,
+    E: Executor<'e> + 'e,
+    <E::Database as HasArguments<'e>>::Arguments: IntoArguments<'e, <E as Executor<'e>>::Database>,
+{
+    /// Returns an owned instance of [sqlx::Arguments]. self is consumed.
+    /// Values in the fields are moved in to the `Arguments` instance.
+    ///
+    fn insert_args(self) -> <E::Database as HasArguments<'e>>::Arguments;
+
+    /// Moves the values bound by the INSERT statement in to `args`, after
+    /// the values already bound. Used by [Crud::create_many] to bind several
+    /// rows.
+    #[doc(hidden)]
+    fn bind_insert_args(self, args: &mut <E::Database as HasArguments<'e>>::Arguments);
+
+    /// Returns an owned instance of [sqlx::Arguments]. self is consumed.
+    /// Values in the fields are moved in to the `Arguments` instance.
+    ///
+    fn update_args(self) -> <E::Database as HasArguments<'e>>::Arguments;
+
+    /// Returns an instance of [sqlx::Arguments] binding the values of
+    /// [Crud::insert_args] borrowed from self, which stays borrowed for the
+    /// life of the arguments.
+    ///
+    fn insert_args_ref(&'e self) -> <E::Database as HasArguments<'e>>::Arguments;
+
+    /// Returns an instance of [sqlx::Arguments] binding the values of
+    /// [Crud::update_args] borrowed from self, which stays borrowed for the
+    /// life of the arguments.
+    ///
+    fn update_args_ref(&'e self) -> <E::Database as HasArguments<'e>>::Arguments;
+
+    /// Returns an owned instance of [sqlx::Arguments] binding the primary key
+    /// columns in the order they appear in the WHERE clause of the by id
+    /// queries.
+    ///
+    fn id_args<K>(id: K) -> <E::Database as HasArguments<'e>>::Arguments
+    where
+        Self: BindId<'e, E::Database, K>,
+    {
+        let mut args = <E::Database as HasArguments<'e>>::Arguments::default();
+        Self::bind_id_args(id, &mut args);
+        args
+    }
+
+    /// Binds the primary key columns of `id` to `args`, after the values
+    /// already bound. Used by [Crud::delete_many] to bind several keys.
+    #[doc(hidden)]
+    fn bind_id_args<K>(id: K, args: &mut <E::Database as HasArguments<'e>>::Arguments)
+    where
+        Self: BindId<'e, E::Database, K>,
+    {
+        <Self as BindId<'e, E::Database, K>>::bind_key(id, args);
+    }
+
+    /// Returns an owned instance of [sqlx::Arguments] for [Schema::delete_sql].
+    /// This binds the primary key columns followed by the version column,
+    /// if any.
+    ///
+    fn delete_args(self) -> <E::Database as HasArguments<'e>>::Arguments;
+
+    /// Returns an owned instance of [sqlx::Arguments] for
+    /// [Schema::delete_permanently_by_id_sql]. This binds the primary key
+    /// columns followed by the tenant column, if any.
+    ///
+    fn delete_permanently_args(self) -> <E::Database as HasArguments<'e>>::Arguments;
+
+    /// Binds the version column, if any, borrowed from self to `args`, after
+    /// the values already bound. Used by [Crud::update_fields] to compare
+    /// the version.
+    #[doc(hidden)]
+    fn bind_version_ref(&'e self, _args: &mut <E::Database as HasArguments<'e>>::Arguments) {}
+
+    /// Returns an owned instance of [sqlx::Arguments] for
+    /// [Schema::audit_update_sql] and [Schema::audit_delete_sql], run on
+    /// their own outside PostgreSQL. Empty for structs without audit.
+    #[doc(hidden)]
+    fn audit_args(&self) -> <E::Database as HasArguments<'e>>::Arguments {
+        Default::default()
+    }
+
+    /// Returns a future that resolves to an insert or `sqlx_crud::Error` of the
+    /// current instance.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, Schema};
+    ///
+    /// let user = User { user_id: 1, name: "test".to_string() };
+    /// let user = user.create(&pool).await?;
+    /// assert_eq!("test", user.name);
+    /// # }}
+    /// ```
+    ///
+    /// Database assigned ids are returned in the created instance:
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Crud;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Task {
+    ///     #[id(generated)]
+    ///     id: i64,
+    ///     title: String,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE tasks (id INTEGER PRIMARY KEY AUTOINCREMENT, title TEXT NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    ///
+    /// let mut ids = Vec::new();
+    /// for title in ["first", "second", "third"] {
+    ///     let task = Task { id: 0, title: title.to_string() }.create(&pool).await?;
+    ///     ids.push(task.id);
+    /// }
+    /// assert_eq!(vec![1, 2, 3], ids);
+    ///
+    /// let task = Task::by_id(&pool, ids[1]).await?.unwrap();
+    /// assert_eq!("second", task.title);
+    /// # }}
+    /// ```
+    ///
+    /// With the `uuid` feature, UUID keys can be minted on create with the
+    /// `#[id(uuid)]` field attribute on an `Option<Uuid>` field. A new v4
+    /// UUID is inserted when the field is `None`, a `Some` id is inserted
+    /// as is:
+    ///
+    #[cfg_attr(feature = "uuid", doc = "```rust")]
+    #[cfg_attr(not(feature = "uuid"), doc = "```rust,ignore")]
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx::types::Uuid;
+    /// use sqlx_crud::Crud;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Session {
+    ///     #[id(uuid)]
+    ///     id: Option<Uuid>,
+    ///     user_id: i32,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE sessions (id BLOB PRIMARY KEY, user_id INTEGER NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    ///
+    /// let session = Session { id: None, user_id: 1 }.create(&pool).await?;
+    /// assert!(session.id.is_some());
+    /// assert_eq!(1, Session::by_id(&pool, session.id).await?.unwrap().user_id);
+    ///
+    /// let fixture = Uuid::from_u128(1);
+    /// let session = Session { id: Some(fixture), user_id: 2 }.create(&pool).await?;
+    /// assert_eq!(Some(fixture), session.id);
+    /// # }}
+    /// ```
+    fn create(self, pool: E) -> CrudFut<'e, Self>
+    where
+        Self: Creatable,
+    {
+        let future = create_on::<Self, E, E>(self, pool);
+        #[cfg(feature = "tracing")]
+        let future = crate::instrument::traced(
+            Self::tracing_span(crate::instrument::Operation::Create, None),
+            future,
+        );
+        future
+    }
+
+    /// Inserts `rows` with multi-row INSERT statements, [Schema::insert_sql_n],
+    /// and returns a future resolving to the number of rows inserted. No
+    /// query is run for an empty `Vec`.
+    ///
+    /// The rows are split in to statements binding at most the
+    /// [bind_limit] of the database, which are run in a transaction so
+    /// either all rows are inserted or none. The executor begins the
+    /// transaction, or a savepoint within a transaction, and is a pool or a
+    /// connection such as `&mut *tx`. Rows short of a full statement are
+    /// inserted by statements of a power of two rows, so only a few
+    /// statements of [Metadata::insert_many_sql] are kept.
+    ///
+    /// The `before_create` hook of the [CrudHooks] and the [Validate] check
+    /// are run for each row before the query. `after_create` is not, as the
+    /// inserted rows are not returned. The rows have to leave the same
+    /// `#[sqlx_crud(none_as_default)]` columns to their defaults, otherwise
+    /// [Error::MixedDefaults] is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Crud;
+    ///
+    /// let users = (0..100).map(|i| User { user_id: 0, name: format!("user {}", i) }).collect();
+    /// assert_eq!(100, User::create_many(&pool, users).await?);
+    /// assert_eq!(0, User::create_many(&pool, Vec::new()).await?);
+    ///
+    /// let users = User::all(&pool).await?;
+    /// assert_eq!(101, users.len());
+    /// assert_eq!("user 99", users[100].name);
+    /// # }}
+    /// ```
+    ///
+    /// The rows are stored as `before_create` leaves them:
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, CrudHooks, HookError};
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// #[sqlx_crud(hooks)]
+    /// struct Article {
+    ///     id: i64,
+    ///     title: String,
+    ///     slug: String,
+    /// }
+    ///
+    /// impl CrudHooks for Article {
+    ///     fn before_create(&mut self) -> Result<(), HookError> {
+    ///         self.slug = self.title.to_lowercase().replace(' ', "-");
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE articles (id INTEGER PRIMARY KEY, title TEXT NOT NULL, slug TEXT NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    ///
+    /// let articles = ["Hello World", "Hello Again"]
+    ///     .map(|title| Article { id: 0, title: title.to_string(), slug: String::new() });
+    /// assert_eq!(2, Article::create_many(&pool, articles.into()).await?);
+    /// let slugs = Article::all(&pool).await?.into_iter().map(|a| a.slug).collect::<Vec<_>>();
+    /// assert_eq!(vec!["hello-world", "hello-again"], slugs);
+    /// # }}
+    /// ```
+    ///
+    /// Large batches take several statements, all of them rolled back if one
+    /// fails:
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::schema::SQLITE_BIND_LIMIT;
+    /// use sqlx_crud::{Crud, Schema};
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// #[external_id]
+    /// struct Sample {
+    ///     id: i64,
+    ///     a: i64,
+    ///     b: i64,
+    ///     c: i64,
+    ///     d: i64,
+    ///     e: i64,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE samples (id INTEGER PRIMARY KEY, a INTEGER, b INTEGER, c INTEGER, d INTEGER, e INTEGER)")
+    ///     .execute(&pool)
+    ///     .await?;
+    ///
+    /// let samples = |ids: std::ops::Range<i64>| ids.map(|id| Sample { id, a: id, b: 0, c: 0, d: 0, e: 0 }).collect::<Vec<_>>();
+    /// let rows_per_statement = SQLITE_BIND_LIMIT / Sample::metadata().insert_bind_count(&[]);
+    /// assert_eq!(5461, rows_per_statement);
+    ///
+    /// // Three statements, the last one failing on a duplicate key
+    /// let mut batch = samples(0..12000);
+    /// batch.push(Sample { id: 0, a: 0, b: 0, c: 0, d: 0, e: 0 });
+    /// assert!(Sample::create_many(&pool, batch).await.is_err());
+    /// assert!(Sample::all(&pool).await?.is_empty());
+    ///
+    /// assert_eq!(12000, Sample::create_many(&pool, samples(0..12000)).await?);
+    /// assert_eq!(11999, Sample::by_id(&pool, 11999).await?.unwrap().a);
+    /// # }}
+    /// ```
+    ///
+    /// [bind_limit]: ../schema/fn.bind_limit.html
+    /// [CrudHooks]: trait.CrudHooks.html
+    /// [Error::MixedDefaults]: ../enum.Error.html#variant.MixedDefaults
+    /// [Metadata::insert_many_sql]: ../schema/struct.Metadata.html#method.insert_many_sql
+    /// [Validate]: trait.Validate.html
+    fn create_many(pool: E, mut rows: Vec<Self>) -> CrudFut<'e, u64>
+    where
+        Self: Creatable,
+        E: Acquire<'e, Database = <E as Executor<'e>>::Database>,
+        for<'c> &'c mut <<E as Executor<'e>>::Database as Database>::Connection:
+            Executor<'c, Database = <E as Executor<'e>>::Database>,
+    {
+        let Some(first) = rows.first() else {
+            return Box::pin(future::ok(0));
+        };
+        let defaulted = first.defaulted_columns();
+        for row in &mut rows {
+            if let Err(e) = row
+                .run_hook(Hook::BeforeCreate)
+                .and_then(|_| row.validate_write())
+            {
+                return Box::pin(future::err(e));
+            }
+            if row.defaulted_columns() != defaulted {
+                return Box::pin(future::err(Error::MixedDefaults {
+                    table_name: Self::table_name(),
+                }));
+            }
+        }
+        #[cfg(feature = "tracing")]
+        let span = Self::tracing_span(crate::instrument::Operation::CreateMany, None);
+
+        let metadata = <Self as DatabaseSchema<<E as Executor<'e>>::Database>>::database_metadata();
+        let limit = crate::schema::bind_limit::<<E as Executor<'e>>::Database>();
+        let chunk_rows = match metadata.insert_bind_count(&defaulted) {
+            0 => 1,
+            binds => (limit / binds).max(1),
+        };
+        let mut statements = Vec::new();
+        let mut rows = rows.into_iter();
+        for len in crate::schema::batch_lens(rows.len(), chunk_rows) {
+            let sql = metadata.insert_many_cached_sql(len, &defaulted, false);
+            let mut args =
+                <<E as Executor<'e>>::Database as HasArguments<'e>>::Arguments::default();
+            for row in rows.by_ref().take(len) {
+                row.bind_insert_args(&mut args);
+            }
+            statements.push((sql, args));
+        }
+        let future = execute_in_transaction(
+            pool,
+            statements,
+            <Self as DatabaseSchema<<E as Executor<'e>>::Database>>::rows_affected,
+        );
+        #[cfg(feature = "tracing")]
+        let future = crate::instrument::traced(span, future);
+        future
+    }
+
+    /// Inserts `rows` like [Crud::create_many] and returns a future resolving
+    /// to the inserted rows, with the values assigned by the database, in
+    /// the order of `rows`. No query is run for an empty `Vec`.
+    ///
+    /// On SQLite and PostgreSQL each statement is the multi-row INSERT with
+    /// a RETURNING clause, [Metadata::insert_many_returning_sql], and the
+    /// statements of a batch split at the [bind_limit] run in order. MySQL
+    /// has no RETURNING clause, so each row is inserted on its own and read
+    /// back by `LAST_INSERT_ID()` or by its primary key, see
+    /// [Metadata::select_inserted_sql]. Either way the statements run in a
+    /// transaction, or a savepoint within a transaction.
+    ///
+    /// The [Validate] check and the create [CrudHooks] are run for each
+    /// row, as with [Crud::create]. The rows have to leave the same
+    /// `#[sqlx_crud(none_as_default)]` columns to their defaults, otherwise
+    /// [Error::MixedDefaults] is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Crud;
+    ///
+    /// let users = (0..50).map(|i| User { user_id: 0, name: format!("user {}", i) }).collect();
+    /// let users = User::create_many_returning(&pool, users).await?;
+    /// assert_eq!(50, users.len());
+    /// for (i, user) in users.iter().enumerate() {
+    ///     assert_eq!(format!("user {}", i), user.name);
+    ///     assert_eq!(user.name, User::by_id(&pool, user.user_id).await?.unwrap().name);
+    /// }
+    /// assert!(User::create_many_returning(&pool, Vec::new()).await?.is_empty());
+    /// # }}
+    /// ```
+    ///
+    /// The order holds across the statements of a large batch:
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::schema::SQLITE_BIND_LIMIT;
+    /// use sqlx_crud::{Crud, Schema};
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Sample {
+    ///     id: i64,
+    ///     a: i64,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE samples (id INTEGER PRIMARY KEY, a INTEGER NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    ///
+    /// let count = SQLITE_BIND_LIMIT + 10;
+    /// assert!(count > SQLITE_BIND_LIMIT / Sample::metadata().insert_bind_count(&[]));
+    /// let samples = (0..count as i64).map(|a| Sample { id: 0, a }).collect();
+    /// let samples = Sample::create_many_returning(&pool, samples).await?;
+    /// assert!(samples.iter().enumerate().all(|(i, s)| s.a == i as i64));
+    /// assert!(samples.windows(2).all(|w| w[0].id < w[1].id));
+    /// assert_eq!(7, Sample::by_id(&pool, samples[7].id).await?.unwrap().a);
+    /// # }}
+    /// ```
+    ///
+    /// [bind_limit]: ../schema/fn.bind_limit.html
+    /// [CrudHooks]: trait.CrudHooks.html
+    /// [Error::MixedDefaults]: ../enum.Error.html#variant.MixedDefaults
+    /// [Metadata::insert_many_returning_sql]: ../schema/struct.Metadata.html#method.insert_many_returning_sql
+    /// [Metadata::select_inserted_sql]: ../schema/struct.Metadata.html#method.select_inserted_sql
+    /// [Validate]: trait.Validate.html
+    fn create_many_returning(pool: E, mut rows: Vec<Self>) -> CrudFut<'e, Vec<Self>>
+    where
+        Self: Creatable,
+        E: Acquire<'e, Database = <E as Executor<'e>>::Database>,
+        for<'c> &'c mut <<E as Executor<'e>>::Database as Database>::Connection:
+            Executor<'c, Database = <E as Executor<'e>>::Database>,
+    {
+        let Some(first) = rows.first() else {
+            return Box::pin(future::ok(Vec::new()));
+        };
+        let defaulted = first.defaulted_columns();
+        for row in &mut rows {
+            if let Err(e) = row
+                .run_hook(Hook::BeforeCreate)
+                .and_then(|_| row.validate_write())
+            {
+                return Box::pin(future::err(e));
+            }
+            if row.defaulted_columns() != defaulted {
+                return Box::pin(future::err(Error::MixedDefaults {
+                    table_name: Self::table_name(),
+                }));
+            }
+        }
+        #[cfg(feature = "tracing")]
+        let span = Self::tracing_span(crate::instrument::Operation::CreateManyReturning, None);
+
+        let metadata = <Self as DatabaseSchema<<E as Executor<'e>>::Database>>::database_metadata();
+        let count = rows.len();
+        // Each statement is followed by the query reading its row back on
+        // MySQL
+        let mut statements = Vec::new();
+        if <<E as Executor<'e>>::Database as Database>::NAME == "MySQL" {
+            let (select_sql, bind_id) = metadata.select_inserted_sql(&defaulted);
+            for row in rows {
+                let mut select_args =
+                    <<E as Executor<'e>>::Database as HasArguments<'e>>::Arguments::default();
+                if bind_id {
+                    Self::bind_id_args(row.id(), &mut select_args);
+                }
+                let mut args =
+                    <<E as Executor<'e>>::Database as HasArguments<'e>>::Arguments::default();
+                row.bind_insert_args(&mut args);
+                statements.push((
+                    metadata.insert_many_cached_sql(1, &defaulted, false),
+                    args,
+                    Some((select_sql, select_args)),
+                ));
+            }
+        } else {
+            let limit = crate::schema::bind_limit::<<E as Executor<'e>>::Database>();
+            let chunk_rows = match metadata.insert_bind_count(&defaulted) {
+                0 => 1,
+                binds => (limit / binds).max(1),
+            };
+            let mut rows = rows.into_iter();
+            for len in crate::schema::batch_lens(count, chunk_rows) {
+                let sql = metadata.insert_many_cached_sql(len, &defaulted, true);
+                let mut args =
+                    <<E as Executor<'e>>::Database as HasArguments<'e>>::Arguments::default();
+                for row in rows.by_ref().take(len) {
+                    row.bind_insert_args(&mut args);
+                }
+                statements.push((sql, args, None));
+            }
+        }
+        let future = create_returning_in_transaction::<Self, E, _>(pool, statements, count);
+        #[cfg(feature = "tracing")]
+        let future = crate::instrument::traced(span, future);
+        future
+    }
+
+    /// Copies the row with the primary key `source_id` in to a new row whose
+    /// key is assigned by the database, such as an `#[id(generated)]` key,
+    /// and returns a future resolving to the new row, or to `None` if there
+    /// is no such row. The row is copied by the single INSERT ... SELECT of
+    /// [Metadata::clone_row_sql], without reading it first. Timestamps and
+    /// other values written by the database on insert, such as
+    /// `#[sqlx_crud(created_at)]` columns, are written anew rather than
+    /// copied. Soft deleted rows are not copied.
+    ///
+    /// [Crud::clone_row_as] copies a row under a given key. The [Validate]
+    /// check and the [CrudHooks] are not run. MySQL has no RETURNING clause,
+    /// there the new row is read back in a transaction, by
+    /// `LAST_INSERT_ID()` or by its key.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx::types::chrono::{DateTime, Utc};
+    /// use sqlx_crud::Crud;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Template {
+    ///     #[id(generated)]
+    ///     id: i64,
+    ///     title: String,
+    ///     #[sqlx_crud(created_at)]
+    ///     created_at: DateTime<Utc>,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE templates (id INTEGER PRIMARY KEY AUTOINCREMENT, title TEXT NOT NULL, created_at TEXT NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// Template { id: 0, title: "invoice".to_string(), created_at: DateTime::<Utc>::UNIX_EPOCH }.create(&pool).await?;
+    /// sqlx::query("UPDATE templates SET created_at = '2000-01-01 00:00:00'").execute(&pool).await?;
+    ///
+    /// let copy = Template::clone_row(&pool, 1).await?.unwrap();
+    /// assert_eq!(2, copy.id);
+    /// assert_eq!("invoice", copy.title);
+    /// // The copy is created now rather than when the template was
+    /// let source = Template::by_id(&pool, 1).await?.unwrap();
+    /// assert_eq!("2000-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap(), source.created_at);
+    /// assert!(copy.created_at > source.created_at);
+    ///
+    /// assert!(Template::clone_row(&pool, 9).await?.is_none());
+    ///
+    /// // Rows of keys assigned outside of the database are copied under a new key
+    /// let user = User::clone_row_as(&pool, 1, 5).await?.unwrap();
+    /// assert_eq!((5, "test"), (user.user_id, user.name.as_str()));
+    /// assert!(User::clone_row_as(&pool, 1, 5).await.is_err());
+    /// # }}
+    /// ```
+    ///
+    /// [CrudHooks]: trait.CrudHooks.html
+    /// [Metadata::clone_row_sql]: ../schema/struct.Metadata.html#method.clone_row_sql
+    /// [Validate]: trait.Validate.html
+    fn clone_row<K>(pool: E, source_id: K) -> CrudFut<'e, Option<Self>>
+    where
+        Self: Unscoped + Readable + Creatable,
+        E: Acquire<'e, Database = <E as Executor<'e>>::Database>,
+        for<'c> &'c mut <<E as Executor<'e>>::Database as Database>::Connection:
+            Executor<'c, Database = <E as Executor<'e>>::Database>,
+        K: IntoId<<Self as Schema>::Id>,
+    {
+        let source_id = source_id.into_id();
+        #[cfg(feature = "tracing")]
+        let span = Self::tracing_span(crate::instrument::Operation::CloneRow, Some(&source_id));
+        let future = clone_row_acquired::<Self, E, _>(pool, source_id, None);
+        #[cfg(feature = "tracing")]
+        let future = crate::instrument::traced(span, future);
+        future
+    }
+
+    /// Copies the row with the primary key `source_id` in to a new row with
+    /// the key `new_id`, like [Crud::clone_row], and returns a future
+    /// resolving to the new row, or to `None` if there is no such row. A
+    /// row with the key `new_id` fails the insert.
+    fn clone_row_as<K, N>(pool: E, source_id: K, new_id: N) -> CrudFut<'e, Option<Self>>
+    where
+        Self: Unscoped + Readable + Creatable,
+        E: Acquire<'e, Database = <E as Executor<'e>>::Database>,
+        for<'c> &'c mut <<E as Executor<'e>>::Database as Database>::Connection:
+            Executor<'c, Database = <E as Executor<'e>>::Database>,
+        K: IntoId<<Self as Schema>::Id>,
+        N: IntoId<<Self as Schema>::Id>,
+    {
+        let source_id = source_id.into_id();
+        #[cfg(feature = "tracing")]
+        let span = Self::tracing_span(crate::instrument::Operation::CloneRow, Some(&source_id));
+        let future = clone_row_acquired::<Self, E, _>(pool, source_id, Some(new_id.into_id()));
+        #[cfg(feature = "tracing")]
+        let future = crate::instrument::traced(span, future);
+        future
+    }
+
+    /// Inserts the record unless a row with the same primary key exists, with
+    /// [Schema::insert_ignore_sql]. Returns a future resolving to `true` if
+    /// the row was inserted and `false` if it was skipped, leaving the stored
+    /// row as is. Other constraint violations fail as with [Crud::create].
+    ///
+    /// The [Validate] check is run. The [CrudHooks] are not, as no row is
+    /// returned to run them on.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Crud;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// #[external_id]
+    /// struct Event {
+    ///     id: i64,
+    ///     kind: String,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE events (id INTEGER PRIMARY KEY, kind TEXT NOT NULL UNIQUE)")
+    ///     .execute(&pool)
+    ///     .await?;
+    ///
+    /// assert!(Event { id: 1, kind: "signup".to_string() }.create_ignore(&pool).await?);
+    /// assert!(!Event { id: 1, kind: "login".to_string() }.create_ignore(&pool).await?);
+    /// assert_eq!("signup", Event::by_id(&pool, 1).await?.unwrap().kind);
+    ///
+    /// // A conflict on another unique column still fails
+    /// assert!(Event { id: 2, kind: "signup".to_string() }.create_ignore(&pool).await.is_err());
+    /// # }}
+    /// ```
+    ///
+    /// [CrudHooks]: trait.CrudHooks.html
+    /// [Validate]: trait.Validate.html
+    fn create_ignore(self, pool: E) -> CrudFut<'e, bool>
+    where
+        Self: Creatable,
+    {
+        create_ignore_on::<Self, E, E>(self, pool)
+    }
+
+    /// Returns the row with primary key `id`, creating it from the record
+    /// built by `f` if there is none. The record must have primary key `id`.
+    ///
+    /// The row is looked up first and only created if missing, skipping the
+    /// insert if a concurrent caller created it in the meantime as with
+    /// [Crud::create_ignore]. The row is then read back, so concurrent callers
+    /// converge on the same row and the returned value is always the stored
+    /// row rather than the record built by `f`. The up to three queries run
+    /// on one connection acquired from the executor. A record built by `f`
+    /// with another primary key is not inserted and resolves to
+    /// [Error::IdMismatch].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, Error};
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// #[external_id]
+    /// struct Counter {
+    ///     id: i64,
+    ///     label: String,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE counters (id INTEGER PRIMARY KEY, label TEXT NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    ///
+    /// let counter = Counter::find_or_create(&pool, 3, || Counter { id: 3, label: "first".to_string() }).await?;
+    /// assert_eq!("first", counter.label);
+    /// let counter = Counter::find_or_create(&pool, 3, || Counter { id: 3, label: "second".to_string() }).await?;
+    /// assert_eq!("first", counter.label);
+    /// assert_eq!(1, Counter::all(&pool).await?.len());
+    ///
+    /// let err = Counter::find_or_create(&pool, 4, || Counter { id: 5, label: "other".to_string() })
+    ///     .await
+    ///     .unwrap_err();
+    /// assert!(matches!(err, Error::IdMismatch { table_name: "counters", .. }));
+    /// assert_eq!(1, Counter::all(&pool).await?.len());
+    /// # }}
+    /// ```
+    ///
+    fn find_or_create<K, F>(pool: E, id: K, f: F) -> CrudFut<'e, Self>
+    where
+        Self: Creatable + Upsertable + Unscoped + Readable,
+        <Self as Schema>::Id: PartialEq,
+        E: Acquire<'e, Database = <E as Executor<'e>>::Database>,
+        for<'c> &'c mut <<E as Executor<'e>>::Database as Database>::Connection:
+            Executor<'c, Database = <E as Executor<'e>>::Database>,
+        F: FnOnce() -> Self + Send + 'e,
+        K: IntoId<<Self as Schema>::Id>,
+    {
+        find_or_create_acquired::<Self, E, _, _>(pool, id.into_id(), f)
+    }
+
+    /// Inserts the record, or updates the row with the same primary key if
+    /// there is one, in a single statement with [Schema::upsert_sql]. Returns
+    /// a future resolving to the stored row.
+    ///
+    /// The [Validate] check is run. The [CrudHooks] are not, as the database
+    /// decides between insert and update. `#[sqlx_crud(none_as_default)]`
+    /// fields that are `None` are inserted with the column default and keep
+    /// their stored value on conflict.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Crud;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// #[external_id]
+    /// struct Price {
+    ///     id: i64,
+    ///     amount: i64,
+    ///     currency: String,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE prices (id INTEGER PRIMARY KEY, amount INTEGER NOT NULL, currency TEXT NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    ///
+    /// let price = Price { id: 7, amount: 100, currency: "EUR".to_string() }.upsert(&pool).await?;
+    /// assert_eq!(100, price.amount);
+    /// let price = Price { id: 7, amount: 250, currency: "USD".to_string() }.upsert(&pool).await?;
+    /// assert_eq!(250, price.amount);
+    ///
+    /// let prices = Price::all(&pool).await?;
+    /// assert_eq!(1, prices.len());
+    /// assert_eq!((7, 250, "USD"), (prices[0].id, prices[0].amount, prices[0].currency.as_str()));
+    /// # }}
+    /// ```
+    ///
+    /// [CrudHooks]: trait.CrudHooks.html
+    /// [Validate]: trait.Validate.html
+    fn upsert(self, pool: E) -> CrudFut<'e, Self>
+    where
+        Self: Upsertable,
+    {
+        if let Err(e) = self.validate_write() {
+            return Box::pin(future::err(e));
+        }
+        #[cfg(feature = "tracing")]
+        let span = Self::tracing_span(crate::instrument::Operation::Upsert, Some(&self.id()));
+        let future: CrudFut<'e, Self> = Box::pin({
+            let sql = <Self as DatabaseSchema<E::Database>>::database_metadata()
+                .upsert_sql_without(&self.defaulted_columns());
+            let args = self.insert_args();
+            ::sqlx::query_with::<E::Database, _>(sql, args)
+                .try_map(|r| Self::from_row(&r))
+                .fetch_one(pool)
+                .map_err(Error::from)
+        });
+        #[cfg(feature = "tracing")]
+        let future = crate::instrument::traced(span, future);
+        future
+    }
+
+    /// Queries all records from the table and returns a future that returns
+    /// to a [try_collect] stream, which resolves to a `Vec<Self>` or a
+    /// `sqlx_crud::Error` on error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Crud;
+    ///
+    /// let all_users: Vec<User> = User::all(&pool).await?;
+    /// # }}
+    /// ```
+    ///
+    /// [try_collect]: https://docs.rs/futures/latest/futures/stream/trait.TryStreamExt.html#method.try_collect
+    fn all(pool: E) -> TryCollectFut<'e, Self>
+    where
+        Self: Unscoped + Readable,
+    {
+        Self::all_stream(pool).try_collect()
+    }
+
+    /// Queries the first record of the table with [Metadata::first_sql] and
+    /// returns a future resolving to the record, or `None` for an empty
+    /// table. Records are sorted by [Schema::order_by_sql] and then the
+    /// primary key, or else by the primary key.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx::types::chrono::{NaiveDate, NaiveDateTime};
+    /// use sqlx_crud::Crud;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// #[external_id]
+    /// #[sqlx_crud(order_by = "created_at")]
+    /// struct Event {
+    ///     id: i64,
+    ///     created_at: NaiveDateTime,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE events (id INTEGER PRIMARY KEY, created_at DATETIME NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// assert!(Event::first(&pool).await?.is_none());
+    /// assert!(Event::last(&pool).await?.is_none());
+    ///
+    /// let day = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap().and_hms_opt(0, 0, 0).unwrap();
+    /// for (id, d) in [(2, 5), (3, 1), (1, 9)] {
+    ///     Event { id, created_at: day(d) }.create(&pool).await?;
+    /// }
+    /// assert_eq!(3, Event::first(&pool).await?.unwrap().id);
+    /// assert_eq!(1, Event::last(&pool).await?.unwrap().id);
+    ///
+    /// // Without order_by the primary key sorts
+    /// User { user_id: 0, name: "second".to_string() }.create(&pool).await?;
+    /// assert_eq!(1, User::first(&pool).await?.unwrap().user_id);
+    /// assert_eq!(2, User::last(&pool).await?.unwrap().user_id);
+    /// # }}
+    /// ```
+    ///
+    /// [Metadata::first_sql]: ../schema/struct.Metadata.html#structfield.first_sql
+    fn first(pool: E) -> CrudFut<'e, Option<Self>>
+    where
+        Self: Unscoped + Readable,
+    {
+        let sql = &<Self as DatabaseSchema<E::Database>>::database_metadata().first_sql;
+        Box::pin(
+            sqlx::query_as::<E::Database, Self>(sql)
+                .fetch_optional(pool)
+                .map_err(Error::from),
+        )
+    }
+
+    /// Queries the last record of the table with [Metadata::last_sql], in
+    /// the order of [Crud::first], and returns a future resolving to the
+    /// record, or `None` for an empty table.
+    ///
+    /// [Metadata::last_sql]: ../schema/struct.Metadata.html#structfield.last_sql
+    fn last(pool: E) -> CrudFut<'e, Option<Self>>
+    where
+        Self: Unscoped + Readable,
+    {
+        let sql = &<Self as DatabaseSchema<E::Database>>::database_metadata().last_sql;
+        Box::pin(
+            sqlx::query_as::<E::Database, Self>(sql)
+                .fetch_optional(pool)
+                .map_err(Error::from),
+        )
+    }
+
+    /// Queries all records from the table like [Crud::all] and returns a
+    /// [CrudStream] of the records as they are read, without holding the
+    /// table in memory. The stream borrows the executor until it is dropped.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use futures::stream::TryStreamExt;
+    /// use sqlx_crud::Crud;
+    ///
+    /// let users = (0..5000).map(|i| User { user_id: 0, name: format!("user {}", i) }).collect();
+    /// User::create_many(&pool, users).await?;
+    ///
+    /// let mut stream = User::all_stream(&pool);
+    /// let mut count = 0;
+    /// while let Some(user) = stream.try_next().await? {
+    ///     assert!(!user.name.is_empty());
+    ///     count += 1;
+    /// }
+    /// assert_eq!(5001, count);
+    /// # }}
+    /// ```
+    fn all_stream(pool: E) -> CrudStream<'e, Self>
+    where
+        Self: Unscoped + Readable,
+    {
+        let sql = &<Self as DatabaseSchema<E::Database>>::database_metadata().select_all_sql;
+        crud_stream(sqlx::query_as::<E::Database, Self>(sql).fetch(pool))
+    }
+
+    /// Queries the records matching every condition of `filter` with
+    /// [Metadata::select_where_sql] and returns a [CrudStream] of the records
+    /// as they are read, sorted like [Crud::all]. Soft deleted rows are not
+    /// read. A filter without conditions reads every record, like
+    /// [Crud::all_stream].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use futures::stream::TryStreamExt;
+    /// use sqlx_crud::filter::Filter;
+    /// use sqlx_crud::Crud;
+    ///
+    /// let users = (2..=5).map(|i| User { user_id: 0, name: format!("user {}", i) }).collect();
+    /// User::create_many(&pool, users).await?;
+    ///
+    /// let filter = Filter::new().gt(UserColumn::UserId, 3).ne(UserColumn::Name, "user 5");
+    /// let names = User::stream_where(&pool, filter).map_ok(|u| u.name).try_collect::<Vec<_>>().await?;
+    /// assert_eq!(vec!["user 4"], names);
+    /// # }}
+    /// ```
+    ///
+    /// [Metadata::select_where_sql]: ../schema/struct.Metadata.html#method.select_where_sql
+    fn stream_where(
+        pool: E,
+        filter: Filter<'e, E::Database, <Self as Schema>::Column>,
+    ) -> CrudStream<'e, Self>
+    where
+        Self: Unscoped + Readable,
+    {
+        if filter.is_empty() {
+            return Self::all_stream(pool);
+        }
+        let sql = <Self as DatabaseSchema<E::Database>>::database_metadata()
+            .select_where_sql(filter.conditions());
+        crud_stream(
+            sqlx::query_as_with::<E::Database, Self, _>(sql, filter.into_arguments()).fetch(pool),
+        )
+    }
+
+    /// Queries the records matching `filter` like [Crud::stream_where] and
+    /// returns a future resolving to the records. The filter is usually
+    /// built from the typed columns of the column enum, see [TypedColumn].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Crud;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Member {
+    ///     id: i64,
+    ///     name: String,
+    ///     age: Option<i32>,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE members (id INTEGER PRIMARY KEY, name TEXT NOT NULL, age INTEGER)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// for (name, age) in [("ann", Some(30)), ("amy", Some(12)), ("bob", Some(40)), ("abe", None)] {
+    ///     Member { id: 0, name: name.to_string(), age }.create(&pool).await?;
+    /// }
+    /// let names = |members: Vec<Member>| members.into_iter().map(|p| p.name).collect::<Vec<_>>();
+    ///
+    /// // The values are bound in condition order
+    /// let adults = Member::all_where(&pool, MemberColumn::age.ge(18).and(MemberColumn::name.like("a%"))).await?;
+    /// assert_eq!(vec!["ann"], names(adults));
+    /// let adults = Member::all_where(&pool, MemberColumn::name.like("a%").and(MemberColumn::age.ge(18))).await?;
+    /// assert_eq!(vec!["ann"], names(adults));
+    ///
+    /// // Comparisons with NULL never match
+    /// assert!(Member::all_where(&pool, MemberColumn::age.eq(None)).await?.is_empty());
+    /// let not_30 = Member::all_where(&pool, MemberColumn::age.ne(30)).await?;
+    /// assert_eq!(vec!["amy", "bob"], names(not_30));
+    /// let unknown = Member::all_where(&pool, MemberColumn::age.is_null()).await?;
+    /// assert_eq!(vec!["abe"], names(unknown));
+    ///
+    /// let bob = Member::one_where(&pool, MemberColumn::name.eq("bob")).await?.unwrap();
+    /// assert_eq!(Some(40), bob.age);
+    /// assert!(Member::one_where(&pool, MemberColumn::age.gt(40)).await?.is_none());
+    /// # }}
+    /// ```
+    ///
+    /// [TypedColumn]: ../filter/struct.TypedColumn.html
+    fn all_where(
+        pool: E,
+        filter: Filter<'e, E::Database, <Self as Schema>::Column>,
+    ) -> CrudFut<'e, Vec<Self>>
+    where
+        Self: Unscoped + Readable,
+    {
+        Box::pin(Self::stream_where(pool, filter).try_collect())
+    }
+
+    /// Queries the records matching `filter` like [Crud::stream_where] and
+    /// returns a future resolving to the first one read, or `None` if no
+    /// row matches. See [Crud::all_where] for an example.
+    fn one_where(
+        pool: E,
+        filter: Filter<'e, E::Database, <Self as Schema>::Column>,
+    ) -> CrudFut<'e, Option<Self>>
+    where
+        Self: Unscoped + Readable,
+    {
+        Box::pin(async move { Self::stream_where(pool, filter).try_next().await })
+    }
+
+    /// Queries the records whose `column` equals one of `values` and returns
+    /// a future resolving to them, like [Crud::all_where] with
+    /// [Filter::in_list]. No query is run for an empty slice.
+    ///
+    /// Lists longer than the [bind_limit] of the database are split in to
+    /// several queries, whose rows are merged in query order with each
+    /// record returned once. The `#[sqlx_crud(order_by = "...")]` order, if
+    /// any, holds within each query.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::schema::SQLITE_BIND_LIMIT;
+    /// use sqlx_crud::Crud;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// #[sqlx_crud(order_by = "id")]
+    /// struct Ticket {
+    ///     id: i64,
+    ///     status: String,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE tickets (id INTEGER PRIMARY KEY, status TEXT NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// for status in ["open", "closed", "pending", "open", "spam"] {
+    ///     Ticket { id: 0, status: status.to_string() }.create(&pool).await?;
+    /// }
+    ///
+    /// let tickets = Ticket::all_where_in(&pool, TicketColumn::Status, &["open", "pending", "stale"]).await?;
+    /// assert_eq!(vec![1, 3, 4], tickets.iter().map(|t| t.id).collect::<Vec<_>>());
+    /// assert!(Ticket::all_where_in::<&str>(&pool, TicketColumn::Status, &[]).await?.is_empty());
+    ///
+    /// // Ids over several queries, with repeats across them
+    /// let ids = (0..SQLITE_BIND_LIMIT as i64 + 10).rev().chain([2, 5]).collect::<Vec<_>>();
+    /// let tickets = Ticket::all_where_in(&pool, TicketColumn::Id, &ids).await?;
+    /// assert_eq!(5, tickets.len());
+    /// # }}
+    /// ```
+    ///
+    /// [bind_limit]: ../schema/fn.bind_limit.html
+    /// [Filter::in_list]: ../filter/struct.Filter.html#method.in_list
+    fn all_where_in<V>(
+        pool: E,
+        column: <Self as Schema>::Column,
+        values: &[V],
+    ) -> CrudFut<'e, Vec<Self>>
+    where
+        Self: Unscoped + Readable,
+        E: Acquire<'e, Database = <E as Executor<'e>>::Database>,
+        for<'c> &'c mut <<E as Executor<'e>>::Database as Database>::Connection:
+            Executor<'c, Database = <E as Executor<'e>>::Database>,
+        <Self as Schema>::Id: Eq + std::hash::Hash,
+        V: 'e
+            + Clone
+            + Send
+            + Encode<'e, <E as Executor<'e>>::Database>
+            + Type<<E as Executor<'e>>::Database>,
+    {
+        if values.is_empty() {
+            return Box::pin(future::ok(Vec::new()));
+        }
+        let metadata = <Self as DatabaseSchema<<E as Executor<'e>>::Database>>::database_metadata();
+        let limit = crate::schema::bind_limit::<<E as Executor<'e>>::Database>();
+        let statements = values
+            .chunks(limit)
+            .map(|chunk| {
+                let filter = Filter::<'e, _, <Self as Schema>::Column>::new()
+                    .in_list(column, chunk.iter().cloned());
+                (
+                    metadata.select_where_sql(filter.conditions()),
+                    filter.into_arguments(),
+                )
+            })
+            .collect();
+        Box::pin(
+            fetch_all_acquired::<Self, E, _>(pool, statements).map_ok(|rows| {
+                let mut seen = std::collections::HashSet::new();
+                rows.into_iter()
+                    .filter(|row| seen.insert(row.id()))
+                    .collect()
+            }),
+        )
+    }
+
+    /// Counts the rows of the table with [Schema::count_sql] and returns a
+    /// future resolving to the count. Soft deleted rows are not counted.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx::types::chrono::{DateTime, Utc};
+    /// use sqlx_crud::Crud;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Entry {
+    ///     id: i64,
+    ///     #[sqlx_crud(soft_delete)]
+    ///     deleted_at: Option<DateTime<Utc>>,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE entries (id INTEGER PRIMARY KEY, deleted_at TIMESTAMP)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// assert_eq!(0, Entry::count(&pool).await?);
+    ///
+    /// let entries = (0..3).map(|_| Entry { id: 0, deleted_at: None }).collect();
+    /// Entry::create_many(&pool, entries).await?;
+    /// assert_eq!(3, Entry::count(&pool).await?);
+    ///
+    /// Entry::delete_by_id(&pool, 2).await?;
+    /// assert_eq!(2, Entry::count(&pool).await?);
+    /// # }}
+    /// ```
+    fn count(pool: E) -> CrudFut<'e, u64>
+    where
+        Self: Unscoped + Readable,
+        (i64,): for<'r> FromRow<'r, <E::Database as Database>::Row>,
+    {
+        let sql = &<Self as DatabaseSchema<E::Database>>::database_metadata().count_sql;
+        Box::pin(
+            sqlx::query_as::<E::Database, (i64,)>(sql)
+                .fetch_one(pool)
+                .map_ok(|(count,)| count as u64)
+                .map_err(Error::from),
+        )
+    }
+
+    /// Counts the rows matching every condition of `filter` with
+    /// [Metadata::count_where_sql] and returns a future resolving to the
+    /// count. Soft deleted rows are not counted. A filter without conditions
+    /// counts every row, like [Crud::count].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::filter::Filter;
+    /// use sqlx_crud::Crud;
+    ///
+    /// let users = (2..=5).map(|i| User { user_id: 0, name: format!("user {}", i % 2) }).collect();
+    /// User::create_many(&pool, users).await?;
+    ///
+    /// let filter = Filter::new().eq(UserColumn::Name, "user 0").gt(UserColumn::UserId, 2);
+    /// assert_eq!(1, User::count_where(&pool, filter).await?);
+    /// assert_eq!(5, User::count_where(&pool, Filter::new()).await?);
+    /// # }}
+    /// ```
+    ///
+    /// [Metadata::count_where_sql]: ../schema/struct.Metadata.html#method.count_where_sql
+    fn count_where(
+        pool: E,
+        filter: Filter<'e, E::Database, <Self as Schema>::Column>,
+    ) -> CrudFut<'e, u64>
+    where
+        Self: Unscoped + Readable,
+        (i64,): for<'r> FromRow<'r, <E::Database as Database>::Row>,
+    {
+        if filter.is_empty() {
+            return Self::count(pool);
+        }
+        let sql = <Self as DatabaseSchema<E::Database>>::database_metadata()
+            .count_where_sql(filter.conditions());
+        Box::pin(
+            sqlx::query_as_with::<E::Database, (i64,), _>(sql, filter.into_arguments())
+                .fetch_one(pool)
+                .map_ok(|(count,)| count as u64)
+                .map_err(Error::from),
+        )
+    }
+
+    /// Queries the smallest value of `column` with
+    /// [Metadata::aggregate_sql] and returns a future resolving to it, or to
+    /// `None` if the table has no rows or the column only NULLs. Soft
+    /// deleted rows are left out. `V` is the type the database returns for
+    /// the column.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx::types::chrono::{DateTime, Utc};
+    /// use sqlx_crud::Crud;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Sale {
+    ///     id: i64,
+    ///     amount: i64,
+    ///     #[sqlx_crud(soft_delete)]
+    ///     deleted_at: Option<DateTime<Utc>>,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE sales (id INTEGER PRIMARY KEY, amount INTEGER NOT NULL, deleted_at TIMESTAMP)")
+    ///     .execute(&pool)
+    ///     .await?;
+    ///
+    /// // Every aggregate of an empty table is None
+    /// assert_eq!(None, Sale::min::<i64>(&pool, SaleColumn::Amount).await?);
+    /// assert_eq!(None, Sale::max::<i64>(&pool, SaleColumn::Amount).await?);
+    /// assert_eq!(None, Sale::sum::<i64>(&pool, SaleColumn::Amount).await?);
+    /// assert_eq!(None, Sale::avg::<f64>(&pool, SaleColumn::Amount).await?);
+    ///
+    /// for amount in [10, 20, 60, 1000] {
+    ///     Sale { id: 0, amount, deleted_at: None }.create(&pool).await?;
+    /// }
+    /// Sale::delete_by_id(&pool, 4).await?;
+    ///
+    /// assert_eq!(Some(10), Sale::min::<i64>(&pool, SaleColumn::Amount).await?);
+    /// assert_eq!(Some(60), Sale::max::<i64>(&pool, SaleColumn::Amount).await?);
+    /// assert_eq!(Some(90), Sale::sum::<i64>(&pool, SaleColumn::Amount).await?);
+    /// assert_eq!(Some(30.0), Sale::avg::<f64>(&pool, SaleColumn::Amount).await?);
+    /// # }}
+    /// ```
+    ///
+    /// [Metadata::aggregate_sql]: ../schema/struct.Metadata.html#method.aggregate_sql
+    fn min<V>(pool: E, column: <Self as Schema>::Column) -> CrudFut<'e, Option<V>>
+    where
+        Self: Unscoped + Readable,
+        V: 'e + Send + Unpin,
+        (Option<V>,): for<'r> FromRow<'r, <E::Database as Database>::Row>,
+    {
+        aggregate_on::<Self, E, V>(pool, Aggregate::Min, column)
+    }
+
+    /// Queries the largest value of `column` like [Crud::min].
+    fn max<V>(pool: E, column: <Self as Schema>::Column) -> CrudFut<'e, Option<V>>
+    where
+        Self: Unscoped + Readable,
+        V: 'e + Send + Unpin,
+        (Option<V>,): for<'r> FromRow<'r, <E::Database as Database>::Row>,
+    {
+        aggregate_on::<Self, E, V>(pool, Aggregate::Max, column)
+    }
+
+    /// Queries the sum of `column` like [Crud::min]. The sum of no rows is
+    /// `None` rather than zero, as SQL has it. PostgreSQL sums integers as
+    /// `BIGINT` or `NUMERIC`, which `V` has to match.
+    fn sum<V>(pool: E, column: <Self as Schema>::Column) -> CrudFut<'e, Option<V>>
+    where
+        Self: Unscoped + Readable,
+        V: 'e + Send + Unpin,
+        (Option<V>,): for<'r> FromRow<'r, <E::Database as Database>::Row>,
+    {
+        aggregate_on::<Self, E, V>(pool, Aggregate::Sum, column)
+    }
+
+    /// Queries the average of `column` like [Crud::min]. SQLite averages as
+    /// `REAL` and PostgreSQL integers as `NUMERIC`, which `V` has to match.
+    fn avg<V>(pool: E, column: <Self as Schema>::Column) -> CrudFut<'e, Option<V>>
+    where
+        Self: Unscoped + Readable,
+        V: 'e + Send + Unpin,
+        (Option<V>,): for<'r> FromRow<'r, <E::Database as Database>::Row>,
+    {
+        aggregate_on::<Self, E, V>(pool, Aggregate::Avg, column)
+    }
+
+    /// Counts the rows whose `column` equals `value` and returns a future
+    /// resolving to the count, without fetching the rows. A NULL `value`
+    /// matches no rows, [Filter::is_null] with [Crud::count_where] counts
+    /// the rows where the column is NULL.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::filter::Filter;
+    /// use sqlx_crud::Crud;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Post {
+    ///     id: i64,
+    ///     author_id: Option<i64>,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE posts (id INTEGER PRIMARY KEY, author_id INTEGER)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// let posts = [Some(7), Some(7), Some(8), None].into_iter().map(|author_id| Post { id: 0, author_id }).collect();
+    /// Post::create_many(&pool, posts).await?;
+    ///
+    /// assert_eq!(2, Post::count_by(&pool, PostColumn::AuthorId, 7_i64).await?);
+    /// assert_eq!(0, Post::count_by(&pool, PostColumn::AuthorId, 9_i64).await?);
+    /// assert_eq!(0, Post::count_by(&pool, PostColumn::AuthorId, None::<i64>).await?);
+    /// assert_eq!(1, Post::count_where(&pool, Filter::new().is_null(PostColumn::AuthorId)).await?);
+    /// # }}
+    /// ```
+    ///
+    /// [Filter::is_null]: ../filter/struct.Filter.html#method.is_null
+    fn count_by<V>(pool: E, column: <Self as Schema>::Column, value: V) -> CrudFut<'e, u64>
+    where
+        Self: Unscoped + Readable,
+        V: 'e + Send + Encode<'e, E::Database> + Type<E::Database>,
+        (i64,): for<'r> FromRow<'r, <E::Database as Database>::Row>,
+    {
+        Self::count_where(pool, Filter::new().eq(column, value))
+    }
+
+    /// Queries `column` of every row with [Metadata::pluck_sql] and returns
+    /// a future resolving to the values, without fetching the other columns.
+    /// Soft deleted rows are left out. `V` is the type the column decodes
+    /// to, a column of another type resolves to the decode error of sqlx.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx::types::chrono::{DateTime, Utc};
+    /// use sqlx_crud::filter::Filter;
+    /// use sqlx_crud::Crud;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// #[sqlx_crud(order_by = "id")]
+    /// struct Post {
+    ///     id: i64,
+    ///     title: String,
+    ///     author_id: i64,
+    ///     #[sqlx_crud(soft_delete)]
+    ///     deleted_at: Option<DateTime<Utc>>,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE posts (id INTEGER PRIMARY KEY, title TEXT NOT NULL, author_id INTEGER NOT NULL, deleted_at TIMESTAMP)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// for (title, author_id) in [("first", 7), ("second", 8), ("third", 7), ("fourth", 7)] {
+    ///     Post { id: 0, title: title.to_string(), author_id, deleted_at: None }.create(&pool).await?;
+    /// }
+    /// Post::delete_by_id(&pool, 4).await?;
+    ///
+    /// assert_eq!(vec![1, 2, 3], Post::ids(&pool).await?);
+    /// assert_eq!(vec!["first", "second", "third"], Post::pluck::<String>(&pool, PostColumn::Title).await?);
+    /// assert_eq!(
+    ///     vec!["first", "third"],
+    ///     Post::pluck_where::<String>(&pool, PostColumn::Title, Filter::new().eq(PostColumn::AuthorId, 7)).await?
+    /// );
+    ///
+    /// let mismatch = Post::pluck::<i64>(&pool, PostColumn::Title).await;
+    /// assert!(matches!(mismatch, Err(sqlx_crud::Error::Sqlx(sqlx::Error::ColumnDecode { .. }))));
+    /// # }}
+    /// ```
+    ///
+    /// [Metadata::pluck_sql]: ../schema/struct.Metadata.html#method.pluck_sql
+    fn pluck<V>(pool: E, column: <Self as Schema>::Column) -> CrudFut<'e, Vec<V>>
+    where
+        Self: Unscoped + Readable,
+        V: 'e + Send + Unpin,
+        (V,): for<'r> FromRow<'r, <E::Database as Database>::Row>,
+    {
+        let sql = <Self as DatabaseSchema<E::Database>>::database_metadata()
+            .pluck_sql(column.name(), &[]);
+        Box::pin(
+            sqlx::query_as::<E::Database, (V,)>(sql)
+                .fetch_all(pool)
+                .map_ok(|rows| rows.into_iter().map(|(value,)| value).collect())
+                .map_err(Error::from),
+        )
+    }
+
+    /// Queries `column` of the rows matching every condition of `filter`,
+    /// like [Crud::pluck]. An empty filter selects every row.
+    fn pluck_where<V>(
+        pool: E,
+        column: <Self as Schema>::Column,
+        filter: Filter<'e, E::Database, <Self as Schema>::Column>,
+    ) -> CrudFut<'e, Vec<V>>
+    where
+        Self: Unscoped + Readable,
+        V: 'e + Send + Unpin,
+        (V,): for<'r> FromRow<'r, <E::Database as Database>::Row>,
+    {
+        if filter.is_empty() {
+            return Self::pluck(pool, column);
+        }
+        let sql = <Self as DatabaseSchema<E::Database>>::database_metadata()
+            .pluck_sql(column.name(), filter.conditions());
+        Box::pin(
+            sqlx::query_as_with::<E::Database, (V,), _>(sql, filter.into_arguments())
+                .fetch_all(pool)
+                .map_ok(|rows| rows.into_iter().map(|(value,)| value).collect())
+                .map_err(Error::from),
+        )
+    }
+
+    /// Queries the primary key of every row, like [Crud::pluck] of the
+    /// [Schema::id_column]. Only structs with a single column key decode
+    /// their keys this way.
+    fn ids(pool: E) -> CrudFut<'e, Vec<<Self as Schema>::Id>>
+    where
+        Self: Unscoped + Readable,
+        <Self as Schema>::Id: 'e + Unpin,
+        (<Self as Schema>::Id,): for<'r> FromRow<'r, <E::Database as Database>::Row>,
+    {
+        let sql = <Self as DatabaseSchema<E::Database>>::database_metadata()
+            .pluck_sql(Self::id_column(), &[]);
+        Box::pin(
+            sqlx::query_as::<E::Database, (<Self as Schema>::Id,)>(sql)
+                .fetch_all(pool)
+                .map_ok(|rows| rows.into_iter().map(|(id,)| id).collect())
+                .map_err(Error::from),
+        )
+    }
+
+    /// Queries a page of at most `limit` records, skipping the first
+    /// `offset`, with [Schema::paginated_sql] and returns a future resolving
+    /// to the records of the page. The rows are sorted like [Crud::all] with
+    /// ties broken by the primary key, so consecutive pages neither repeat
+    /// nor skip rows while the table is unchanged. Resolves to
+    /// [Error::InvalidPage] without a query if `limit` is not positive or
+    /// `offset` is negative.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, Error};
+    ///
+    /// let users = (2..=7).map(|i| User { user_id: 0, name: format!("user {}", i) }).collect();
+    /// User::create_many(&pool, users).await?;
+    ///
+    /// let mut pages = Vec::new();
+    /// for page in 0..3 {
+    ///     let users = User::paginated(&pool, 3, page * 3).await?;
+    ///     pages.push(users.into_iter().map(|u| u.user_id).collect::<Vec<_>>());
+    /// }
+    /// assert_eq!(vec![vec![1, 2, 3], vec![4, 5, 6], vec![7]], pages);
+    /// assert!(User::paginated(&pool, 3, 9).await?.is_empty());
+    ///
+    /// let result = User::paginated(&pool, 0, 0).await;
+    /// assert!(matches!(result, Err(Error::InvalidPage { limit: 0, .. })));
+    /// # }}
+    /// ```
+    fn paginated(pool: E, limit: i64, offset: i64) -> CrudFut<'e, Vec<Self>>
+    where
+        Self: Unscoped + Readable,
+        i64: for<'q> Encode<'q, E::Database> + Type<E::Database>,
+    {
+        if limit <= 0 || offset < 0 {
+            return Box::pin(future::err(Error::InvalidPage {
+                table_name: Self::table_name(),
+                limit,
+                offset,
+            }));
+        }
+        let sql = &<Self as DatabaseSchema<E::Database>>::database_metadata().paginated_sql;
+        Box::pin(
+            sqlx::query_as::<E::Database, Self>(sql)
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(pool)
+                .map_err(Error::from),
+        )
+    }
+
+    /// Queries a page of records like [Crud::paginated] and counts the rows
+    /// of the table with [Schema::count_sql], returning a future resolving
+    /// to the [Page]. The two queries run one after the other, so rows
+    /// written in between can make the count differ from the rows paged
+    /// through. A page past the last row has no items but the total.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, Error};
+    ///
+    /// let users = (2..=7).map(|i| User { user_id: 0, name: format!("user {}", i) }).collect();
+    /// User::create_many(&pool, users).await?;
+    ///
+    /// let page = User::page(&pool, 3, 3).await?;
+    /// assert_eq!(vec![4, 5, 6], page.items.iter().map(|u| u.user_id).collect::<Vec<_>>());
+    /// assert_eq!((7, 3), (page.total, page.total_pages()));
+    /// assert!(page.has_next());
+    ///
+    /// let page = User::page(&pool, 3, 10).await?;
+    /// assert!(page.items.is_empty());
+    /// assert_eq!(7, page.total);
+    /// assert!(!page.has_next());
+    ///
+    /// User::delete_all(&pool).await?;
+    /// let page = User::page(&pool, 3, 0).await?;
+    /// assert_eq!((0, 0), (page.total, page.total_pages()));
+    ///
+    /// let result = User::page(&pool, 3, -1).await;
+    /// assert!(matches!(result, Err(Error::InvalidPage { offset: -1, .. })));
+    /// # }}
+    /// ```
+    fn page(pool: E, limit: i64, offset: i64) -> CrudFut<'e, Page<Self>>
+    where
+        Self: Unscoped + Readable,
+        E: Acquire<'e, Database = <E as Executor<'e>>::Database>,
+        for<'c> &'c mut <<E as Executor<'e>>::Database as Database>::Connection:
+            Executor<'c, Database = <E as Executor<'e>>::Database>,
+        i64:
+            for<'q> Encode<'q, <E as Executor<'e>>::Database> + Type<<E as Executor<'e>>::Database>,
+        (i64,): for<'r> FromRow<'r, <<E as Executor<'e>>::Database as Database>::Row>,
+    {
+        if limit <= 0 || offset < 0 {
+            return Box::pin(future::err(Error::InvalidPage {
+                table_name: Self::table_name(),
+                limit,
+                offset,
+            }));
+        }
+        page_acquired::<Self, E, _>(pool, limit, offset)
+    }
+
+    /// Queries at most `limit` records following the record with primary key
+    /// `cursor` in `direction`, or from the first record for `None`, with
+    /// [Metadata::keyset_sql]. Returns a future resolving to the records
+    /// sorted by primary key. The cursor of the next page is the key of the
+    /// last record, see [next_cursor]. Resolves to [Error::InvalidPage]
+    /// without a query if `limit` is not positive.
+    ///
+    /// Unlike [Crud::paginated] the pages stay exact while rows are inserted
+    /// and deleted, and the database finds the start of a page with the
+    /// primary key index. [Crud::after_by] sorts by another column first,
+    /// for keys that do not follow the order of the rows.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::page::{next_cursor, Direction};
+    /// use sqlx_crud::Crud;
+    ///
+    /// let users = (2..=7).map(|i| User { user_id: 0, name: format!("user {}", i) }).collect();
+    /// User::create_many(&pool, users).await?;
+    ///
+    /// let mut pages = Vec::new();
+    /// let mut cursor = None;
+    /// loop {
+    ///     let users = User::after(&pool, cursor, 3, Direction::Ascending).await?;
+    ///     if users.is_empty() {
+    ///         break;
+    ///     }
+    ///     cursor = next_cursor(&users);
+    ///     pages.push(users.into_iter().map(|u| u.user_id).collect::<Vec<_>>());
+    /// }
+    /// assert_eq!(vec![vec![1, 2, 3], vec![4, 5, 6], vec![7]], pages);
+    ///
+    /// let users = User::after(&pool, Some(5), 3, Direction::Descending).await?;
+    /// assert_eq!(vec![4, 3, 2], users.iter().map(|u| u.user_id).collect::<Vec<_>>());
+    /// # }}
+    /// ```
+    ///
+    /// [Metadata::keyset_sql]: ../schema/struct.Metadata.html#method.keyset_sql
+    /// [next_cursor]: ../page/fn.next_cursor.html
+    fn after(
+        pool: E,
+        cursor: Option<<Self as Schema>::Id>,
+        limit: i64,
+        direction: Direction,
+    ) -> CrudFut<'e, Vec<Self>>
+    where
+        Self: Unscoped + Readable,
+        i64: for<'q> Encode<'q, E::Database> + Type<E::Database>,
+    {
+        if limit <= 0 {
+            return Box::pin(future::err(Error::InvalidPage {
+                table_name: Self::table_name(),
+                limit,
+                offset: 0,
+            }));
+        }
+        let sql = <Self as DatabaseSchema<E::Database>>::database_metadata().keyset_sql(
+            None,
+            direction,
+            cursor.is_some(),
+        );
+        let mut args = <E::Database as HasArguments<'e>>::Arguments::default();
+        if let Some(id) = cursor {
+            Self::bind_id_args(id, &mut args);
+        }
+        args.add(limit);
+        Box::pin(
+            sqlx::query_as_with::<E::Database, Self, _>(sql, args)
+                .fetch_all(pool)
+                .map_err(Error::from),
+        )
+    }
+
+    /// Walks the table in batches of at most `batch_size` records sorted by
+    /// primary key and passes each batch to `f` in turn, returning a future
+    /// resolving once every batch is processed. Each batch is read with
+    /// [Crud::after] from the key of the last record of the previous batch,
+    /// so every step costs the same however large the table is, and only
+    /// one batch is held in memory. The batches are read on one connection
+    /// acquired from the executor and held for the whole walk, so writes
+    /// made by `f` through a pool use another connection of the pool.
+    ///
+    /// An error returned by `f` stops the walk and the future resolves to
+    /// it. Resolves to [Error::InvalidPage] without a query if `batch_size`
+    /// is not positive.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, Error};
+    ///
+    /// let users = (2..=11).map(|i| User { user_id: 0, name: format!("user {}", i) }).collect();
+    /// User::create_many(&pool, users).await?;
+    ///
+    /// let mut batches = Vec::new();
+    /// let mut names = Vec::new();
+    /// User::for_each_batch(&pool, 4, |users| {
+    ///     batches.push(users.iter().map(|u| u.user_id).collect::<Vec<_>>());
+    ///     names.extend(users.into_iter().map(|u| u.name));
+    ///     async { Ok(()) }
+    /// })
+    /// .await?;
+    /// assert_eq!(vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8], vec![9, 10, 11]], batches);
+    /// assert_eq!(Some("user 11"), names.last().map(String::as_str));
+    ///
+    /// // An error of the callback stops the walk
+    /// let mut seen = 0;
+    /// let result = User::for_each_batch(&pool, 4, |users| {
+    ///     seen += users.len();
+    ///     async { Err(Error::Sqlx(sqlx::Error::Protocol("stop".to_string()))) }
+    /// })
+    /// .await;
+    /// assert!(matches!(result, Err(Error::Sqlx(sqlx::Error::Protocol(_)))));
+    /// assert_eq!(4, seen);
+    /// # }}
+    /// ```
+    fn for_each_batch<F, Fut>(pool: E, batch_size: i64, f: F) -> CrudFut<'e, ()>
+    where
+        Self: Unscoped + Readable,
+        E: Acquire<'e, Database = <E as Executor<'e>>::Database>,
+        for<'c> &'c mut <<E as Executor<'e>>::Database as Database>::Connection:
+            Executor<'c, Database = <E as Executor<'e>>::Database>,
+        i64:
+            for<'q> Encode<'q, <E as Executor<'e>>::Database> + Type<<E as Executor<'e>>::Database>,
+        F: FnMut(Vec<Self>) -> Fut + Send + 'e,
+        Fut: Future<Output = Result<(), Error>> + Send + 'e,
+    {
+        if batch_size <= 0 {
+            return Box::pin(future::err(Error::InvalidPage {
+                table_name: Self::table_name(),
+                limit: batch_size,
+                offset: 0,
+            }));
+        }
+        for_each_batch_acquired::<Self, E, _, _, _>(pool, batch_size, f)
+    }
+
+    /// Queries at most `limit` records following `cursor` in `direction`,
+    /// sorted by `column` and then by primary key, like [Crud::after]. The
+    /// cursor is the value of `column` and the primary key of the last
+    /// record of the previous page, `None` for the first page. Resolves to
+    /// [Error::InvalidPage] without a query if `limit` is not positive.
+    ///
+    /// Rows whose `column` is NULL are never selected after a cursor, so the
+    /// column should be `NOT NULL`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use std::collections::HashSet;
+    ///
+    /// use sqlx::types::chrono::{NaiveDate, NaiveDateTime};
+    /// use sqlx_crud::page::Direction;
+    /// use sqlx_crud::Crud;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// #[external_id]
+    /// struct Event {
+    ///     id: i64,
+    ///     created_at: NaiveDateTime,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE events (id INTEGER PRIMARY KEY, created_at DATETIME NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// // Ids do not follow creation and several events share a time
+    /// let day = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap().and_hms_opt(0, 0, 0).unwrap();
+    /// let events = [(9, 1), (3, 2), (7, 2), (1, 2), (8, 3), (2, 5), (5, 5), (4, 6), (6, 9)]
+    ///     .into_iter()
+    ///     .map(|(id, d)| Event { id, created_at: day(d) })
+    ///     .collect();
+    /// Event::create_many(&pool, events).await?;
+    ///
+    /// let mut seen = Vec::new();
+    /// let mut cursor = None;
+    /// loop {
+    ///     let events = Event::after_by(&pool, EventColumn::CreatedAt, cursor, 2, Direction::Descending).await?;
+    ///     let Some(last) = events.last() else { break };
+    ///     cursor = Some((last.created_at, last.id));
+    ///     seen.extend(events.iter().map(|e| e.id));
+    /// }
+    /// assert_eq!(vec![6, 4, 5, 2, 8, 7, 3, 1, 9], seen);
+    /// assert_eq!(9, seen.iter().collect::<HashSet<_>>().len());
+    /// # }}
+    /// ```
+    fn after_by<V>(
+        pool: E,
+        column: <Self as Schema>::Column,
+        cursor: Option<(V, <Self as Schema>::Id)>,
+        limit: i64,
+        direction: Direction,
+    ) -> CrudFut<'e, Vec<Self>>
+    where
+        Self: Unscoped + Readable,
+        V: 'e + Send + Encode<'e, E::Database> + Type<E::Database>,
+        i64: for<'q> Encode<'q, E::Database> + Type<E::Database>,
+    {
+        if limit <= 0 {
+            return Box::pin(future::err(Error::InvalidPage {
+                table_name: Self::table_name(),
+                limit,
+                offset: 0,
+            }));
+        }
+        let metadata = <Self as DatabaseSchema<E::Database>>::database_metadata();
+        let sql = metadata.keyset_sql(Some(column.name()), direction, cursor.is_some());
+        let mut args = <E::Database as HasArguments<'e>>::Arguments::default();
+        if let Some((value, id)) = cursor {
+            args.add(value);
+            Self::bind_id_args(id, &mut args);
+        }
+        args.add(limit);
+        Box::pin(
+            sqlx::query_as_with::<E::Database, Self, _>(sql, args)
+                .fetch_all(pool)
+                .map_err(Error::from),
+        )
+    }
+
+    /// Returns an instance of [sqlx::Arguments] for the partial update of
+    /// [Metadata::patch_sql] writing `columns`. The column values are
+    /// borrowed and bound in column order, followed by the primary key and the tenant.
+    /// Fails with [Error::ColumnNotUpdatable] for the columns of flattened
+    /// fields and of fields of a generic type, which are not bound on their
+    /// own. Without a derived implementation no column can be bound and any
+    /// column fails.
+    ///
+    /// [Metadata::patch_sql]: ../schema/struct.Metadata.html#method.patch_sql
+    #[doc(hidden)]
+    fn changed_args(
+        &'e self,
+        columns: &[<Self as Schema>::Column],
+    ) -> Result<<E::Database as HasArguments<'e>>::Arguments, Error>
+    where
+        Self: Updatable,
+    {
+        match columns.first() {
+            Some(column) => Err(Error::ColumnNotUpdatable {
+                table_name: Self::table_name(),
+                column: column.name(),
+            }),
+            None => Ok(Default::default()),
+        }
+    }
+
+    /// Writes the current values of `columns` to the row in a single UPDATE
+    /// with [Metadata::versioned_patch_sql], leaving the other columns unchanged, and
+    /// returns a future that resolves once written. The values are bound in
+    /// column order whatever the order of `columns`, and repeated columns
+    /// are written once. An empty slice resolves to `Ok` without a query.
+    /// Resolves to [Error::NotFound] if there is no such row.
+    ///
+    /// Columns not written by [Crud::update], such as the primary key,
+    /// resolve to [Error::ColumnNotUpdatable] without a query, as do the
+    /// columns of flattened fields and of fields of a generic type. The
+    /// [CrudHooks] are not run. Audited structs, which would not be recorded
+    /// in the history table, are refused, see [Unaudited].
+    ///
+    /// For structs with a [Schema::version_column] the row is only written
+    /// if it still has the version of this instance, otherwise
+    /// [Error::StaleVersion] is returned. The stored version is incremented,
+    /// but not that of this instance, which is only borrowed, so read the
+    /// row again before writing it once more.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, Error};
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Profile {
+    ///     id: i64,
+    ///     name: String,
+    ///     email: String,
+    ///     bio: String,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE profiles (id INTEGER PRIMARY KEY, name TEXT NOT NULL, email TEXT NOT NULL, bio TEXT NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// sqlx::query("INSERT INTO profiles VALUES (1, 'ann', 'ann@example.com', 'stored')")
+    ///     .execute(&pool)
+    ///     .await?;
+    ///
+    /// let mut profile = Profile::by_id(&pool, 1).await?.unwrap();
+    /// profile.name = "Ann".to_string();
+    /// profile.email = "ann@example.org".to_string();
+    /// profile.bio = "unsaved".to_string();
+    /// profile.update_fields(&pool, &[ProfileColumn::Email, ProfileColumn::Name]).await?;
+    ///
+    /// let stored = Profile::by_id(&pool, 1).await?.unwrap();
+    /// assert_eq!(("Ann", "ann@example.org", "stored"), (&*stored.name, &*stored.email, &*stored.bio));
+    ///
+    /// profile.update_fields(&pool, &[]).await?;
+    /// assert!(matches!(
+    ///     profile.update_fields(&pool, &[ProfileColumn::Id]).await,
+    ///     Err(Error::ColumnNotUpdatable { column: "id", .. })
+    /// ));
+    /// # }}
+    /// ```
+    ///
+    /// A concurrent write of a versioned row is not overwritten:
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, Error};
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Document {
+    ///     id: i64,
+    ///     title: String,
+    ///     body: String,
+    ///     #[sqlx_crud(version)]
+    ///     version: i32,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE documents (id INTEGER PRIMARY KEY, title TEXT NOT NULL, body TEXT NOT NULL, version INTEGER NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// let document = Document { id: 0, title: "draft".to_string(), body: String::new(), version: 1 }
+    ///     .create(&pool)
+    ///     .await?;
+    ///
+    /// let mut stale = Document::by_id(&pool, document.id).await?.unwrap();
+    /// let mut current = Document::by_id(&pool, document.id).await?.unwrap();
+    /// current.body = "written".to_string();
+    /// current.update_fields(&pool, &[DocumentColumn::Body]).await?;
+    ///
+    /// stale.title = "renamed".to_string();
+    /// let err = stale.update_fields(&pool, &[DocumentColumn::Title]).await.unwrap_err();
+    /// assert!(matches!(err, Error::StaleVersion { table_name: "documents" }));
+    ///
+    /// let stored = Document::by_id(&pool, document.id).await?.unwrap();
+    /// assert_eq!(("draft", "written", 2), (&*stored.title, &*stored.body, stored.version));
+    /// # }}
+    /// ```
+    ///
+    /// [Error::StaleVersion]: ../enum.Error.html#variant.StaleVersion
+    /// [Metadata::versioned_patch_sql]: ../schema/struct.Metadata.html#method.versioned_patch_sql
+    fn update_fields(&'e self, pool: E, columns: &[<Self as Schema>::Column]) -> CrudFut<'e, ()>
+    where
+        Self: Updatable + Unaudited,
+    {
+        let metadata = <Self as DatabaseSchema<E::Database>>::database_metadata();
+        let table_name = metadata.table_name;
+        if let Some(column) = columns
+            .iter()
+            .find(|c| !metadata.is_updated_column(c.name()))
+        {
+            return Box::pin(future::err(Error::ColumnNotUpdatable {
+                table_name,
+                column: column.name(),
+            }));
+        }
+        let columns = <<Self as Schema>::Column as ColumnName>::ALL
+            .iter()
+            .filter(|c| columns.contains(c))
+            .copied()
+            .collect::<Vec<_>>();
+        if columns.is_empty() {
+            return Box::pin(future::ok(()));
+        }
+        let mut args = match self.changed_args(&columns) {
+            Ok(args) => args,
+            Err(e) => return Box::pin(future::err(e)),
+        };
+        self.bind_version_ref(&mut args);
+        let versioned = metadata.version_column.is_some();
+        let id = self.id();
+        let names = columns.iter().map(ColumnName::name).collect::<Vec<_>>();
+        let sql = metadata.versioned_patch_sql(&names);
+        Box::pin(async move {
+            let result = sqlx::query_with::<E::Database, _>(sql, args)
+                .execute(pool)
+                .await?;
+            if <Self as DatabaseSchema<E::Database>>::rows_affected(&result) == 0 {
+                return Err(match versioned {
+                    true => Error::StaleVersion { table_name },
+                    false => Error::not_found::<Self>(&id),
+                });
+            }
+            Ok(())
+        })
+    }
+
+    /// Creates the table unless it exists, running
+    /// [Schema::create_table_sql] for the database of the executor.
+    fn create_table(pool: E) -> CrudFut<'e, ()>
+    where
+        Self: CreateTable,
+    {
+        let sql = create_table_sql(<Self as DatabaseSchema<E::Database>>::database_metadata());
+        Box::pin(
+            sqlx::query::<E::Database>(sql)
+                .execute(pool)
+                .map_ok(|_| ())
+                .map_err(Error::from),
+        )
+    }
+
+    /// Compares the columns of the struct with the table in the database
+    /// catalog, for a check at startup that the structs match the deployed
+    /// tables. Resolves to [Error::SchemaMismatch] listing the columns the
+    /// table is missing, its extra columns, and the columns whose
+    /// nullability differs from the `Option`-ness of their field. The
+    /// nullability of primary key columns is not compared. SQLite and MySQL
+    /// column names are compared ignoring ASCII case.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::check::NullabilityMismatch;
+    /// use sqlx_crud::{Crud, Error};
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Device {
+    ///     id: i64,
+    ///     name: String,
+    ///     serial: Option<String>,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE devices (id INTEGER PRIMARY KEY, name TEXT NOT NULL, serial TEXT)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// Device::verify(&pool).await?;
+    ///
+    /// // A missing column, an extra column and a NOT NULL column read in to an Option
+    /// sqlx::query("DROP TABLE devices").execute(&pool).await?;
+    /// sqlx::query("CREATE TABLE devices (id INTEGER PRIMARY KEY, serial TEXT NOT NULL, location TEXT)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// let Err(Error::SchemaMismatch(mismatch)) = Device::verify(&pool).await else {
+    ///     panic!("devices should not match");
+    /// };
+    /// assert_eq!(vec!["name"], mismatch.missing);
+    /// assert_eq!(vec!["location".to_string()], mismatch.extra);
+    /// assert_eq!(
+    ///     vec![NullabilityMismatch { column: "serial", field_nullable: true, column_nullable: false }],
+    ///     mismatch.nullability
+    /// );
+    /// assert_eq!(
+    ///     "devices does not match the database: missing columns name; extra columns location; column serial is NOT NULL but its field is an Option",
+    ///     mismatch.to_string()
+    /// );
+    ///
+    /// // Every column of a table that does not exist is missing
+    /// sqlx::query("DROP TABLE devices").execute(&pool).await?;
+    /// let Err(Error::SchemaMismatch(mismatch)) = Device::verify(&pool).await else {
+    ///     panic!("devices should not match");
+    /// };
+    /// assert_eq!(vec!["id", "name", "serial"], mismatch.missing);
+    /// # }}
+    /// ```
+    fn verify(pool: E) -> CrudFut<'e, ()>
+    where
+        (String, String): for<'r> FromRow<'r, <E::Database as Database>::Row>,
+    {
+        let metadata = <Self as DatabaseSchema<E::Database>>::database_metadata();
+        let ignore_case = <E::Database as Database>::NAME != "PostgreSQL";
+        Box::pin(async move {
+            let catalog = sqlx::query_as::<E::Database, (String, String)>(&metadata.catalog_sql)
+                .fetch_all(pool)
+                .await?;
+            SchemaMismatch::compare(metadata, &catalog, ignore_case)?;
+            Ok(())
+        })
+    }
+
+    #[doc(hidden)]
+    fn paged(_pool: E) -> TryCollectFut<'e, Self>
+    where
+        Self: Readable,
+    {
+        unimplemented!()
+    }
+
+    /// Looks up a row by ID and returns a future that resolves an
+    /// `Option<Self>`. Returns `None` if and a record with the corresponding ID
+    /// cannot be found and `Some` if it exists.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Crud;
+    ///
+    /// let user: Option<User> = User::by_id(&pool, 1).await?;
+    /// assert!(user.is_some());
+    /// # }}
+    /// ```
+    ///
+    /// With the `tracing` feature, [Crud::create], [Crud::by_id],
+    /// [Crud::update] and [Crud::delete] run in an info span named
+    /// `sqlx_crud.<table>.<method>` with `table` and `id` fields. The id is
+    /// recorded when its type implements `Debug`. Failed queries are logged as
+    /// error events in the span:
+    ///
+    #[cfg_attr(feature = "tracing", doc = "```rust")]
+    #[cfg_attr(not(feature = "tracing"), doc = "```rust,ignore")]
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// use sqlx_crud::Crud;
+    /// use tracing::field::{Field, Visit};
+    /// use tracing::span::{Attributes, Id, Record};
+    /// use tracing::{Event, Metadata, Subscriber};
+    ///
+    /// #[derive(Clone, Default)]
+    /// struct Spans(Arc<Mutex<Vec<String>>>);
+    ///
+    /// struct IdVisitor<'a>(&'a mut String);
+    ///
+    /// impl Visit for IdVisitor<'_> {
+    ///     fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+    ///         if field.name() == "id" {
+    ///             *self.0 = format!("{:?}", value);
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// impl Subscriber for Spans {
+    ///     fn enabled(&self, _: &Metadata<'_>) -> bool { true }
+    ///     fn new_span(&self, span: &Attributes<'_>) -> Id {
+    ///         let mut spans = self.0.lock().unwrap();
+    ///         spans.push(span.metadata().name().to_string());
+    ///         Id::from_u64(spans.len() as u64)
+    ///     }
+    ///     fn record(&self, span: &Id, values: &Record<'_>) {
+    ///         let mut id = String::new();
+    ///         values.record(&mut IdVisitor(&mut id));
+    ///         let name = &mut self.0.lock().unwrap()[span.into_u64() as usize - 1];
+    ///         *name = format!("{} id={}", name, id);
+    ///     }
+    ///     fn record_follows_from(&self, _: &Id, _: &Id) {}
+    ///     fn event(&self, _: &Event<'_>) {}
+    ///     fn enter(&self, _: &Id) {}
+    ///     fn exit(&self, _: &Id) {}
+    /// }
+    ///
+    /// let spans = Spans::default();
+    /// let _guard = tracing::subscriber::set_default(spans.clone());
+    ///
+    /// let user = User { user_id: 3, name: "traced".to_string() }.create(&pool).await?;
+    /// let id = user.user_id;
+    /// User::by_id(&pool, id).await?;
+    /// user.delete(&pool).await?;
+    ///
+    /// assert_eq!(
+    ///     vec![
+    ///         "sqlx_crud.users.create".to_string(),
+    ///         format!("sqlx_crud.users.by_id id={}", id),
+    ///         format!("sqlx_crud.users.delete id={}", id),
+    ///     ],
+    ///     *spans.0.lock().unwrap(),
+    /// );
+    /// # }}
+    /// ```
+    fn by_id<K>(pool: E, id: K) -> CrudFut<'e, Option<Self>>
+    where
+        Self: Unscoped + Readable + BindId<'e, E::Database, K>,
+        K: std::fmt::Debug,
+    {
+        by_id_on::<Self, E, E, K>(id, pool)
+    }
+
+    /// Queries the record with primary key `id` like [Crud::by_id] and
+    /// returns a future resolving to the record, or to [Error::NotFound]
+    /// naming the table and the id if there is none.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, Error};
+    ///
+    /// assert_eq!("test", User::try_by_id(&pool, 1).await?.name);
+    ///
+    /// let err = User::try_by_id(&pool, 42).await.unwrap_err();
+    /// assert!(matches!(err, Error::NotFound { table_name: "users", .. }));
+    /// assert_eq!("no users row with id 42", err.to_string());
+    /// # }}
+    /// ```
+    fn try_by_id<K>(pool: E, id: K) -> CrudFut<'e, Self>
+    where
+        Self: Unscoped + Readable + BindId<'e, E::Database, K>,
+        K: std::fmt::Debug,
+    {
+        let not_found = Error::not_found::<Self>(&id);
+        Box::pin(Self::by_id(pool, id).and_then(|row| future::ready(row.ok_or(not_found))))
+    }
+
+    /// Queries the record with primary key `id` with
+    /// [Schema::select_by_id_for_update_sql] and returns a future resolving
+    /// to the record, if found. On PostgreSQL and MySQL the row stays locked
+    /// against other writers and locking reads until the transaction ends,
+    /// for reading a row and writing it back within a transaction. SQLite
+    /// has no row locks and runs the plain select: its transactions already
+    /// serialize writers.
+    ///
+    /// A lock only lasts as long as the transaction, so the query is run on
+    /// a connection, such as `&mut *tx` for a transaction `tx`, and not on a
+    /// pool.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Crud;
+    ///
+    /// let mut tx = pool.begin().await?;
+    /// let mut user = User::by_id_for_update(&mut *tx, 1).await?.unwrap();
+    /// user.name = format!("{} again", user.name);
+    /// user.update(&mut *tx).await?;
+    /// tx.commit().await?;
+    /// assert_eq!("test again", User::by_id(&pool, 1).await?.unwrap().name);
+    ///
+    /// #[cfg(feature = "postgres")]
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite, Postgres)]
+    /// struct Counter {
+    ///     id: i64,
+    ///     value: i64,
+    /// }
+    ///
+    /// #[cfg(feature = "postgres")]
+    /// {
+    ///     use sqlx::Postgres;
+    ///     use sqlx_crud::traits::DatabaseSchema;
+    ///
+    ///     assert_eq!(
+    ///         r#"SELECT "counters"."id", "counters"."value" FROM "counters" WHERE "counters"."id" = $1 LIMIT 1 FOR UPDATE"#,
+    ///         <Counter as DatabaseSchema<Postgres>>::database_metadata().select_by_id_for_update_sql
+    ///     );
+    /// }
+    ///
+    /// // The second transaction waits for the lock of the first
+    /// #[cfg(feature = "postgres")]
+    /// if let Ok(url) = std::env::var("DATABASE_URL") {
+    ///     let pool = sqlx::PgPool::connect(&url).await?;
+    ///     sqlx::query("DROP TABLE IF EXISTS counters").execute(&pool).await?;
+    ///     sqlx::query("CREATE TABLE counters (id BIGSERIAL PRIMARY KEY, value BIGINT NOT NULL)")
+    ///         .execute(&pool)
+    ///         .await?;
+    ///     let counter = Counter { id: 0, value: 0 }.create(&pool).await?;
+    ///
+    ///     let mut first = pool.begin().await?;
+    ///     assert!(Counter::by_id_for_update(&mut *first, counter.id).await?.is_some());
+    ///
+    ///     let mut second = pool.begin().await?;
+    ///     sqlx::query("SET LOCAL lock_timeout = '100ms'").execute(&mut *second).await?;
+    ///     assert!(Counter::by_id_for_update(&mut *second, counter.id).await.is_err());
+    ///     second.rollback().await?;
+    ///     first.commit().await?;
+    /// }
+    /// # }}
+    /// ```
+    ///
+    /// Locking outside a transaction is meaningless, so a pool is refused:
+    ///
+    /// ```rust,compile_fail
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Crud;
+    ///
+    /// User::by_id_for_update(&pool, 1).await?;
+    /// # }}
+    /// ```
+    fn by_id_for_update<K>(conn: E, id: K) -> CrudFut<'e, Option<Self>>
+    where
+        Self: Unscoped + Readable,
+        E: std::ops::DerefMut<Target = <E::Database as Database>::Connection>,
+        Self: BindId<'e, E::Database, K>,
+        K: std::fmt::Debug,
+    {
+        #[cfg(feature = "tracing")]
+        let span = Self::tracing_span(crate::instrument::Operation::ByIdForUpdate, Some(&id));
+        let query = sqlx::query_with::<E::Database, _>(
+            &<Self as DatabaseSchema<E::Database>>::database_metadata().select_by_id_for_update_sql,
+            Self::id_args(id),
+        );
+        let future: CrudFut<'e, Option<Self>> = Box::pin(
+            query
+                .try_map(|r| Self::from_row(&r))
+                .fetch_optional(conn)
+                .map_err(Error::from),
+        );
+        #[cfg(feature = "tracing")]
+        let future = crate::instrument::traced(span, future);
+        future
+    }
+
+    /// Queries the records whose primary key is one of `ids` with
+    /// [Schema::select_by_ids_sql] and returns a future resolving to the
+    /// records found, in no particular order. Ids without a row are left
+    /// out, as are soft deleted rows. Repeated ids are queried once. No query
+    /// is run for an empty slice.
+    ///
+    /// The keys are split in to statements binding at most the
+    /// [bind_limit] of the database, run one after the other. Statements
+    /// bind a power of two keys, the last key repeated, so few are built.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Crud;
+    ///
+    /// let users = (2..=4).map(|i| User { user_id: 0, name: format!("user {}", i) }).collect();
+    /// User::create_many(&pool, users).await?;
+    ///
+    /// let mut ids = User::by_ids(&pool, &[4, 2, 99, 2]).await?.iter().map(|u| u.user_id).collect::<Vec<_>>();
+    /// ids.sort();
+    /// assert_eq!(vec![2, 4], ids);
+    /// assert!(User::by_ids::<i32>(&pool, &[]).await?.is_empty());
+    ///
+    /// // More keys than SQLite binds to a statement
+    /// let ids = (1..=40_000).collect::<Vec<_>>();
+    /// assert_eq!(4, User::by_ids(&pool, &ids).await?.len());
+    /// # }}
+    /// ```
+    ///
+    /// [bind_limit]: ../schema/fn.bind_limit.html
+    fn by_ids<K>(pool: E, ids: &[K]) -> CrudFut<'e, Vec<Self>>
+    where
+        Self: Unscoped + Readable + BindId<'e, <E as Executor<'e>>::Database, K>,
+        E: Acquire<'e, Database = <E as Executor<'e>>::Database>,
+        for<'c> &'c mut <<E as Executor<'e>>::Database as Database>::Connection:
+            Executor<'c, Database = <E as Executor<'e>>::Database>,
+        K: Clone + Eq + std::hash::Hash,
+    {
+        let mut seen = std::collections::HashSet::new();
+        let ids = ids.iter().filter(|id| seen.insert(*id)).collect::<Vec<_>>();
+        if ids.is_empty() {
+            return Box::pin(future::ok(Vec::new()));
+        }
+        let metadata = <Self as DatabaseSchema<<E as Executor<'e>>::Database>>::database_metadata();
+        let limit = crate::schema::bind_limit::<<E as Executor<'e>>::Database>();
+        let chunk_ids = (limit / metadata.id_columns.len()).max(1);
+        let statements = ids
+            .chunks(chunk_ids)
+            .map(|chunk| {
+                let (sql, padded) = metadata.select_by_ids_padded_sql(chunk.len(), chunk_ids);
+                (sql, bind_padded_ids::<Self, E, K>(chunk, padded))
+            })
+            .collect();
+        fetch_all_acquired::<Self, E, _>(pool, statements)
+    }
+
+    /// Queries the records whose primary key is one of `ids` like
+    /// [Crud::by_ids] and returns a future resolving to the records keyed by
+    /// [Schema::id]. Ids without a row have no entry.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Crud;
+    ///
+    /// User { user_id: 0, name: "second".to_string() }.create(&pool).await?;
+    ///
+    /// let users = User::by_ids_map(&pool, &[2, 1, 99, 2]).await?;
+    /// assert_eq!(2, users.len());
+    /// assert_eq!("test", users[&1].name);
+    /// assert_eq!("second", users[&2].name);
+    /// assert!(!users.contains_key(&99));
+    /// # }}
+    /// ```
+    fn by_ids_map<K>(pool: E, ids: &[K]) -> CrudFut<'e, HashMap<<Self as Schema>::Id, Self>>
+    where
+        Self: Unscoped + Readable + BindId<'e, <E as Executor<'e>>::Database, K>,
+        E: Acquire<'e, Database = <E as Executor<'e>>::Database>,
+        for<'c> &'c mut <<E as Executor<'e>>::Database as Database>::Connection:
+            Executor<'c, Database = <E as Executor<'e>>::Database>,
+        <Self as Schema>::Id: Eq + std::hash::Hash,
+        K: Clone + Eq + std::hash::Hash,
+    {
+        Box::pin(
+            Self::by_ids(pool, ids)
+                .map_ok(|rows| rows.into_iter().map(|row| (row.id(), row)).collect()),
+        )
+    }
+
+    /// Looks up the row with primary key `id` with [Schema::exists_sql] and
+    /// returns a future resolving to `true` if there is one, without reading
+    /// its columns. Soft deleted rows do not exist.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx::types::chrono::{DateTime, Utc};
+    /// use sqlx_crud::Crud;
+    ///
+    /// assert!(User::exists(&pool, 1).await?);
+    /// assert!(!User::exists(&pool, 2).await?);
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Entry {
+    ///     id: i64,
+    ///     #[sqlx_crud(soft_delete)]
+    ///     deleted_at: Option<DateTime<Utc>>,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE entries (id INTEGER PRIMARY KEY, deleted_at TIMESTAMP)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// let entry = Entry { id: 0, deleted_at: None }.create(&pool).await?;
+    /// assert!(Entry::exists(&pool, entry.id).await?);
+    /// entry.delete(&pool).await?;
+    /// assert!(!Entry::exists(&pool, 1).await?);
+    /// # }}
+    /// ```
+    fn exists<K>(pool: E, id: K) -> CrudFut<'e, bool>
+    where
+        Self: Unscoped + Readable + BindId<'e, E::Database, K>,
+        K: std::fmt::Debug,
+    {
+        #[cfg(feature = "tracing")]
+        let span = Self::tracing_span(crate::instrument::Operation::Exists, Some(&id));
+        let query = sqlx::query_with::<E::Database, _>(
+            &<Self as DatabaseSchema<E::Database>>::database_metadata().exists_sql,
+            Self::id_args(id),
+        );
+        let future: CrudFut<'e, bool> = Box::pin(
+            query
+                .fetch_optional(pool)
+                .map_ok(|r| r.is_some())
+                .map_err(Error::from),
+        );
+        #[cfg(feature = "tracing")]
+        let future = crate::instrument::traced(span, future);
+        future
+    }
+
+    /// Looks up a row whose `column` equals `value` with
+    /// [Metadata::exists_where_sql] and returns a future resolving to `true`
+    /// if there is one, without reading its columns. Soft deleted rows do not
+    /// exist. A NULL `value` matches no rows.
+    ///
+    /// The values are compared by the database, with the collation of the
+    /// column: SQLite and PostgreSQL compare text case sensitively by
+    /// default, the default collations of MySQL do not.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Crud;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Account {
+    ///     id: i64,
+    ///     email: String,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE accounts (id INTEGER PRIMARY KEY, email TEXT NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// Account { id: 0, email: "ada@example.com".to_string() }.create(&pool).await?;
+    ///
+    /// assert!(Account::exists_by(&pool, AccountColumn::Email, "ada@example.com").await?);
+    /// assert!(!Account::exists_by(&pool, AccountColumn::Email, "grace@example.com").await?);
+    /// // SQLite compares text case sensitively
+    /// assert!(!Account::exists_by(&pool, AccountColumn::Email, "ADA@example.com").await?);
+    /// # }}
+    /// ```
+    ///
+    /// [Metadata::exists_where_sql]: ../schema/struct.Metadata.html#method.exists_where_sql
+    fn exists_by<V>(pool: E, column: <Self as Schema>::Column, value: V) -> CrudFut<'e, bool>
+    where
+        Self: Unscoped + Readable,
+        V: 'e + Send + Encode<'e, E::Database> + Type<E::Database>,
+    {
+        let filter = Filter::<E::Database, _>::new().eq(column, value);
+        let sql = <Self as DatabaseSchema<E::Database>>::database_metadata()
+            .exists_where_sql(filter.conditions());
+        let query = sqlx::query_with::<E::Database, _>(sql, filter.into_arguments());
+        Box::pin(
+            query
+                .fetch_optional(pool)
+                .map_ok(|r| r.is_some())
+                .map_err(Error::from),
+        )
+    }
+
+    /// Inserts the record with [Crud::create] if no row has its primary key
+    /// and updates the row with [Crud::update] otherwise. Returns a future
+    /// resolving to the stored row in a [SaveOutcome] telling which was
+    /// done.
+    ///
+    /// On PostgreSQL the rows of [Upsertable] structs without
+    /// `#[sqlx_crud(hooks)]` are written with a single upsert statement,
+    /// like [Crud::upsert], which also reports whether the row was inserted.
+    /// Otherwise the existence of the row is checked and the write run in a
+    /// transaction begun on `pool`, and the [Validate] check and
+    /// [CrudHooks] of the write are run. For keys generated by the database
+    /// the primary key of a new record, such as `0`, matches no row and the
+    /// record is inserted. Two transactions saving the same new record can
+    /// still both insert, the second failing on the primary key.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, SaveOutcome};
+    ///
+    /// let saved = User { user_id: 0, name: "new".to_string() }.save(&pool).await?;
+    /// assert!(saved.is_created());
+    ///
+    /// let mut user = saved.into_inner();
+    /// user.name = "renamed".to_string();
+    /// let SaveOutcome::Updated(user) = user.save(&pool).await? else {
+    ///     panic!("the user should be updated");
+    /// };
+    /// assert_eq!("renamed", user.name);
+    /// assert_eq!(2, User::count(&pool).await?);
+    ///
+    /// #[cfg(feature = "postgres")]
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite, Postgres)]
+    /// #[external_id]
+    /// struct Price {
+    ///     id: i64,
+    ///     amount: i64,
+    /// }
+    ///
+    /// #[cfg(feature = "postgres")]
+    /// if let Ok(url) = std::env::var("DATABASE_URL") {
+    ///     let pool = sqlx::PgPool::connect(&url).await?;
+    ///     sqlx::query("DROP TABLE IF EXISTS prices").execute(&pool).await?;
+    ///     sqlx::query("CREATE TABLE prices (id BIGINT PRIMARY KEY, amount BIGINT NOT NULL)")
+    ///         .execute(&pool)
+    ///         .await?;
+    ///     assert!(Price { id: 7, amount: 100 }.save(&pool).await?.is_created());
+    ///     let SaveOutcome::Updated(price) = Price { id: 7, amount: 250 }.save(&pool).await? else {
+    ///         panic!("the price should be updated");
+    ///     };
+    ///     assert_eq!(250, price.amount);
+    /// }
+    /// # }}
+    /// ```
+    ///
+    /// [CrudHooks]: trait.CrudHooks.html
+    /// [Validate]: trait.Validate.html
+    fn save(self, pool: E) -> CrudFut<'e, SaveOutcome<Self>>
+    where
+        Self: Creatable + Updatable + Unscoped + Readable,
+        E: Acquire<'e, Database = <E as Executor<'e>>::Database>,
+        for<'c> &'c mut <<E as Executor<'e>>::Database as Database>::Connection:
+            Executor<'c, Database = <E as Executor<'e>>::Database>,
+    {
+        let metadata = <Self as DatabaseSchema<<E as Executor<'e>>::Database>>::database_metadata();
+        let sql = match Self::save_upserts() {
+            true => metadata.save_sql_without(&self.defaulted_columns()),
+            false => None,
+        };
+        match sql {
+            Some(sql) => save_upserting::<Self, E, _>(self, pool, sql),
+            None => save_in_transaction::<Self, E, _>(self, pool),
+        }
+    }
+
+    /// Reads the row with the primary key of this instance with
+    /// [Crud::by_id] and replaces the instance with it, returning a future
+    /// resolving to `true`. Resolves to `false` and leaves the instance as
+    /// it is if the row no longer exists.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Crud;
+    ///
+    /// let mut user = User::by_id(&pool, 1).await?.unwrap();
+    ///
+    /// // Another writer changes the row
+    /// let mut other = User::by_id(&pool, 1).await?.unwrap();
+    /// other.name = "renamed".to_string();
+    /// other.update(&pool).await?;
+    ///
+    /// assert!(user.reload(&pool).await?);
+    /// assert_eq!("renamed", user.name);
+    ///
+    /// User::delete_by_id(&pool, 1).await?;
+    /// assert!(!user.reload(&pool).await?);
+    /// assert_eq!("renamed", user.name);
+    /// # }}
+    /// ```
+    fn reload(&'e mut self, pool: E) -> CrudFut<'e, bool>
+    where
+        Self: Unscoped + Readable,
+    {
+        let row = Self::by_id(pool, self.id());
+        Box::pin(async move {
+            match row.await? {
+                Some(row) => {
+                    *self = row;
+                    Ok(true)
+                }
+                None => Ok(false),
+            }
+        })
+    }
+
+    /// Updates the database with the current instance state and returns a
+    /// future that resolves to the new `Self` returned from the database.
+    /// Resolves to [Error::NotFound] if the row no longer exists,
+    /// such as when it was deleted since it was loaded, and the
+    /// `after_update` hook is not run.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, Error};
+    ///
+    /// if let Some(mut user) = User::by_id(&pool, 1).await? {
+    ///     assert_eq!("test", user.name);
+    ///
+    ///     // Update the record
+    ///     user.name = "Harry".to_string();
+    ///     let user = user.update(&pool).await?;
+    ///
+    ///     // Confirm the name changed
+    ///     assert_eq!("Harry", user.name);
+    /// }
+    ///
+    /// // The row is deleted underneath a loaded instance
+    /// let mut user = User::by_id(&pool, 1).await?.unwrap();
+    /// User::delete_by_id(&pool, 1).await?;
+    /// user.name = "Sally".to_string();
+    /// let err = user.update(&pool).await.unwrap_err();
+    /// assert!(matches!(err, Error::NotFound { table_name: "users", .. }));
+    /// # }}
+    /// ```
+    ///
+    /// For structs with a [Schema::version_column] the update only succeeds
+    /// if the row still has the version this instance was loaded with.
+    /// Otherwise [Error::StaleVersion] is returned and the row is left
+    /// untouched.
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, Error};
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Document {
+    ///     id: i64,
+    ///     body: String,
+    ///     #[sqlx_crud(version)]
+    ///     version: i32,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE documents (id INTEGER PRIMARY KEY, body TEXT NOT NULL, version INTEGER NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// let document = Document { id: 0, body: "draft".to_string(), version: 1 }.create(&pool).await?;
+    ///
+    /// // Two writers load the same row
+    /// let mut first = Document::by_id(&pool, document.id).await?.unwrap();
+    /// let mut second = Document::by_id(&pool, document.id).await?.unwrap();
+    ///
+    /// first.body = "first".to_string();
+    /// let first = first.update(&pool).await?;
+    /// assert_eq!(2, first.version);
+    ///
+    /// // The second writer still holds version 1 and loses
+    /// second.body = "second".to_string();
+    /// let err = second.update(&pool).await.unwrap_err();
+    /// assert!(matches!(err, Error::StaleVersion { table_name: "documents" }));
+    ///
+    /// let stored = Document::by_id(&pool, document.id).await?.unwrap();
+    /// assert_eq!("first", stored.body);
+    /// assert_eq!(2, stored.version);
+    /// # }}
+    /// ```
+    ///
+    /// [Error::StaleVersion]: ../enum.Error.html#variant.StaleVersion
+    fn update(self, pool: E) -> CrudFut<'e, Self>
+    where
+        Self: Updatable,
+        E: Acquire<'e, Database = <E as Executor<'e>>::Database>,
+        for<'c> &'c mut <<E as Executor<'e>>::Database as Database>::Connection:
+            Executor<'c, Database = <E as Executor<'e>>::Database>,
+    {
+        #[cfg(feature = "tracing")]
+        let span = Self::tracing_span(crate::instrument::Operation::Update, Some(&self.id()));
+        let future = update_audited::<Self, E, _>(self, pool);
+        #[cfg(feature = "tracing")]
+        let future = crate::instrument::traced(span, future);
+        future
+    }
+
+    /// Updates the row as [Crud::update] does, then reads it back with
+    /// [Crud::by_id] and returns a future resolving to the row as stored.
+    ///
+    /// The row returned by [Crud::update] comes from the `RETURNING` clause,
+    /// which reflects `BEFORE` triggers but not the changes of `AFTER`
+    /// triggers. Resolves to [Error::NotFound] if the row is gone
+    /// by the time it is read.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Crud;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Page {
+    ///     id: i64,
+    ///     body: String,
+    ///     edits: i32,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE pages (id INTEGER PRIMARY KEY, body TEXT NOT NULL, edits INTEGER NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// sqlx::query(
+    ///     "CREATE TRIGGER count_edits AFTER UPDATE OF body ON pages
+    ///      BEGIN UPDATE pages SET edits = edits + 1 WHERE id = NEW.id; END",
+    /// )
+    /// .execute(&pool)
+    /// .await?;
+    /// let page = Page { id: 0, body: "draft".to_string(), edits: 0 }.create(&pool).await?;
+    ///
+    /// // The trigger runs after the row is returned
+    /// let mut page = page;
+    /// page.body = "first".to_string();
+    /// let page = page.update(&pool).await?;
+    /// assert_eq!(0, page.edits);
+    /// assert_eq!(1, Page::by_id(&pool, page.id).await?.unwrap().edits);
+    ///
+    /// let mut page = Page::by_id(&pool, page.id).await?.unwrap();
+    /// page.body = "second".to_string();
+    /// let page = page.update_and_fetch(&pool).await?;
+    /// assert_eq!(2, page.edits);
+    /// # }}
+    /// ```
+    fn update_and_fetch(self, pool: E) -> CrudFut<'e, Self>
+    where
+        Self: Updatable + Unscoped + Readable,
+        E: Acquire<'e, Database = <E as Executor<'e>>::Database>,
+        for<'c> &'c mut <<E as Executor<'e>>::Database as Database>::Connection:
+            Executor<'c, Database = <E as Executor<'e>>::Database>,
+    {
+        update_and_fetch_acquired::<Self, E, _>(self, pool)
+    }
+
+    /// Deletes a record from the database by ID and returns a future that
+    /// resolves to `()` on success or `sqlx_crud::Error` on failure. Resolves
+    /// to [Error::NotFound] if the row no longer exists, such as
+    /// when it was deleted since it was loaded, and the `after_delete` hook
+    /// is not run. [Crud::try_delete] and [Crud::delete_by_id] report a
+    /// missing row with `false` instead.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, Error};
+    ///
+    /// if let Some(user) = User::by_id(&pool, 1).await? {
+    ///     user.delete(&pool).await?;
+    /// }
+    /// assert!(User::by_id(&pool, 1).await?.is_none());
+    ///
+    /// // Deleting the row again matches nothing
+    /// let user = User { user_id: 1, name: "test".to_string() };
+    /// let err = user.delete(&pool).await.unwrap_err();
+    /// assert!(matches!(err, Error::NotFound { table_name: "users", .. }));
+    /// # }}
+    /// ```
+    ///
+    /// For structs with a [Schema::version_column] the row is only deleted
+    /// if it still has the version this instance was loaded with. Otherwise
+    /// [Error::StaleVersion] is returned.
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::{Crud, Error};
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Document {
+    ///     id: i64,
+    ///     body: String,
+    ///     #[sqlx_crud(version)]
+    ///     version: i32,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE documents (id INTEGER PRIMARY KEY, body TEXT NOT NULL, version INTEGER NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// let document = Document { id: 0, body: "draft".to_string(), version: 1 }.create(&pool).await?;
+    /// let stale = Document::by_id(&pool, document.id).await?.unwrap();
+    ///
+    /// let mut current = Document::by_id(&pool, document.id).await?.unwrap();
+    /// current.body = "edited".to_string();
+    /// let current = current.update(&pool).await?;
+    ///
+    /// let err = stale.delete(&pool).await.unwrap_err();
+    /// assert!(matches!(err, Error::StaleVersion { .. }));
+    /// assert!(Document::by_id(&pool, document.id).await?.is_some());
+    ///
+    /// current.delete(&pool).await?;
+    /// assert!(Document::by_id(&pool, document.id).await?.is_none());
+    /// # }}
+    /// ```
+    ///
+    /// [Error::StaleVersion]: ../enum.Error.html#variant.StaleVersion
+    fn delete(self, pool: E) -> CrudFut<'e, ()>
+    where
+        Self: Deletable,
+        E: Acquire<'e, Database = <E as Executor<'e>>::Database>,
+        for<'c> &'c mut <<E as Executor<'e>>::Database as Database>::Connection:
+            Executor<'c, Database = <E as Executor<'e>>::Database>,
+    {
+        #[cfg(feature = "tracing")]
+        let span = Self::tracing_span(crate::instrument::Operation::Delete, Some(&self.id()));
+        let future = delete_audited::<Self, E, _>(self, pool);
+        #[cfg(feature = "tracing")]
+        let future = crate::instrument::traced(span, future);
+        future
+    }
+
+    /// Deletes a record like [Crud::delete] and returns a future resolving to
+    /// `true` if the row was deleted and `false` if the DELETE matched no
+    /// row, rather than failing. Rows with a [Schema::soft_delete_column]
+    /// are soft deleted, an already deleted row matching nothing.
+    ///
+    /// For structs with a [Schema::version_column] a row whose version
+    /// changed since the instance was loaded is not deleted either, and
+    /// also resolves to `false`, the two cases being one to the database.
+    /// The `after_delete` hook is only run for a deleted row. Audited
+    /// structs are refused, see [Unaudited].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx::types::chrono::{DateTime, Utc};
+    /// use sqlx_crud::Crud;
+    ///
+    /// let user = User::by_id(&pool, 1).await?.unwrap();
+    /// assert!(user.try_delete(&pool).await?);
+    /// let user = User { user_id: 1, name: "test".to_string() };
+    /// assert!(!user.try_delete(&pool).await?);
+    ///
+    /// #[derive(Clone, Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Document {
+    ///     id: i64,
+    ///     body: String,
+    ///     #[sqlx_crud(version)]
+    ///     version: i32,
+    ///     #[sqlx_crud(soft_delete)]
+    ///     deleted_at: Option<DateTime<Utc>>,
+    /// }
+    ///
+    /// sqlx::query(
+    ///     "CREATE TABLE documents (id INTEGER PRIMARY KEY, body TEXT NOT NULL, version INTEGER NOT NULL, deleted_at TIMESTAMP)",
+    /// )
+    /// .execute(&pool)
+    /// .await?;
+    /// let document = Document { id: 0, body: "draft".to_string(), version: 1, deleted_at: None }.create(&pool).await?;
+    ///
+    /// // A stale version deletes nothing
+    /// let stale = document.clone();
+    /// let mut current = document;
+    /// current.body = "edited".to_string();
+    /// let current = current.update(&pool).await?;
+    /// assert!(!stale.try_delete(&pool).await?);
+    /// assert!(Document::by_id(&pool, current.id).await?.is_some());
+    ///
+    /// // The row is soft deleted once
+    /// assert!(current.clone().try_delete(&pool).await?);
+    /// assert!(Document::by_id(&pool, current.id).await?.is_none());
+    /// assert!(!current.try_delete(&pool).await?);
+    /// # }}
+    /// ```
+    fn try_delete(self, pool: E) -> CrudFut<'e, bool>
+    where
+        Self: Deletable + Unaudited,
+    {
+        #[cfg(feature = "tracing")]
+        let span = Self::tracing_span(crate::instrument::Operation::Delete, Some(&self.id()));
+        let future = delete_on::<Self, E, E>(self, pool);
+        #[cfg(feature = "tracing")]
+        let future = crate::instrument::traced(span, future);
+        Box::pin(future.map_ok(|()| true).or_else(|e| {
+            future::ready(match e {
+                Error::NotFound { .. } | Error::StaleVersion { .. } => Ok(false),
+                e => Err(e),
+            })
+        }))
+    }
+
+    /// Deletes the row with primary key `id` with [Schema::delete_by_id_sql],
+    /// without loading it first. Returns a future resolving to `true` if a
+    /// row was deleted and `false` if there was no such row.
+    ///
+    /// Rows with a [Schema::soft_delete_column] are soft deleted, an already
+    /// deleted row counting as missing. Without the row there is no version
+    /// to check and no instance to run `before_delete` on, so
+    /// [CrudHooks::after_delete] is the only hook run, and only for a deleted
+    /// row. Audited structs, which would not be recorded in the history
+    /// table, are refused, see [Unaudited].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Crud;
+    ///
+    /// assert!(User::delete_by_id(&pool, 1).await?);
+    /// assert!(User::by_id(&pool, 1).await?.is_none());
+    /// assert!(!User::delete_by_id(&pool, 1).await?);
+    /// # }}
+    /// ```
+    ///
+    /// [CrudHooks::after_delete]: trait.CrudHooks.html#method.after_delete
+    fn delete_by_id<K>(pool: E, id: K) -> CrudFut<'e, bool>
+    where
+        Self: Unscoped + Deletable + Unaudited + BindId<'e, E::Database, K>,
+        K: IntoId<<Self as Schema>::Id> + Clone + std::fmt::Debug + Send + 'e,
+    {
+        #[cfg(feature = "tracing")]
+        let span = Self::tracing_span(crate::instrument::Operation::Delete, Some(&id));
+        let args = Self::id_args(id.clone());
+        let query = sqlx::query_with(
+            &<Self as DatabaseSchema<E::Database>>::database_metadata().delete_by_id_sql,
+            args,
+        );
+        let future: CrudFut<'e, bool> =
+            Box::pin(query.execute(pool).map_err(Error::from).and_then(move |r| {
+                let rows_affected = <Self as DatabaseSchema<E::Database>>::rows_affected(&r);
+                #[cfg(feature = "tracing")]
+                tracing::debug!(rows_affected, "deleted");
+                if rows_affected == 0 {
+                    future::ok(false)
+                } else {
+                    future::ready(Self::run_after_delete(&id.into_id()).map(|_| true))
+                }
+            }));
+        #[cfg(feature = "tracing")]
+        let future = crate::instrument::traced(span, future);
+        future
+    }
+
+    /// Deletes the row with the primary key `id` like [Crud::delete_by_id]
+    /// and returns a future resolving to the deleted row, or to `None` if
+    /// there was none. The row is read back with the RETURNING clause of
+    /// [Metadata::delete_by_id_returning_sql], in the same statement, so
+    /// concurrent callers never get the same row. For structs with a soft
+    /// delete column the row is returned with its soft delete timestamp
+    /// set.
+    ///
+    /// MySQL has no RETURNING clause, there the row is locked and read with
+    /// [Schema::select_by_id_for_update_sql] and then deleted, in a
+    /// transaction. The row is returned as it was read.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Crud;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Job {
+    ///     id: i64,
+    ///     payload: String,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE jobs (id INTEGER PRIMARY KEY, payload TEXT NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// Job { id: 0, payload: "send mail".to_string() }.create(&pool).await?;
+    ///
+    /// let job = Job::delete_by_id_returning(&pool, 1).await?;
+    /// assert_eq!("send mail", job.unwrap().payload);
+    /// assert!(Job::delete_by_id_returning(&pool, 1).await?.is_none());
+    /// assert!(!Job::exists(&pool, 1).await?);
+    /// # }}
+    /// ```
+    ///
+    /// [Metadata::delete_by_id_returning_sql]: ../schema/struct.Metadata.html#structfield.delete_by_id_returning_sql
+    fn delete_by_id_returning<K>(pool: E, id: K) -> CrudFut<'e, Option<Self>>
+    where
+        Self: Unscoped + Deletable + Unaudited,
+        E: Acquire<'e, Database = <E as Executor<'e>>::Database>,
+        for<'c> &'c mut <<E as Executor<'e>>::Database as Database>::Connection:
+            Executor<'c, Database = <E as Executor<'e>>::Database>,
+        Self: BindId<'e, <E as Executor<'e>>::Database, K>,
+        K: IntoId<<Self as Schema>::Id> + Clone + std::fmt::Debug + Send + 'e,
+    {
+        #[cfg(feature = "tracing")]
+        let span = Self::tracing_span(crate::instrument::Operation::Delete, Some(&id));
+        let metadata = <Self as DatabaseSchema<<E as Executor<'e>>::Database>>::database_metadata();
+        let future: CrudFut<'e, Option<Self>> = match &metadata.delete_by_id_returning_sql {
+            Some(sql) => Box::pin(
+                sqlx::query_with::<<E as Executor<'e>>::Database, _>(
+                    sql,
+                    Self::id_args(id.clone()),
+                )
+                .try_map(|r| Self::from_row(&r))
+                .fetch_optional(pool)
+                .map_err(Error::from)
+                .and_then(move |row| match row {
+                    Some(row) => {
+                        future::ready(Self::run_after_delete(&id.into_id()).map(|_| Some(row)))
+                    }
+                    None => future::ok(None),
+                }),
+            ),
+            None => delete_returning_in_transaction::<Self, E, _>(pool, id.into_id()),
+        };
+        #[cfg(feature = "tracing")]
+        let future = crate::instrument::traced(span, future);
+        future
+    }
+
+    /// Deletes the rows whose primary key is one of `ids` with
+    /// [Schema::delete_by_ids_sql], without loading them first. Returns a
+    /// future resolving to the number of rows deleted, missing rows not
+    /// counting. No query is run for an empty slice.
+    ///
+    /// The keys are split in to statements binding at most the
+    /// [bind_limit] of the database, run in a transaction as by
+    /// [Crud::create_many], and padded like those of [Crud::by_ids]. As
+    /// with [Crud::delete_by_id] soft deletes apply and audited structs are
+    /// refused. The [CrudHooks] are not run, as the deleted keys are not
+    /// known.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Crud;
+    ///
+    /// let users = (0..3).map(|i| User { user_id: 0, name: format!("user {}", i) }).collect();
+    /// User::create_many(&pool, users).await?;
+    ///
+    /// assert_eq!(2, User::delete_many(&pool, &[1, 2, 99]).await?);
+    /// assert_eq!(0, User::delete_many::<i32>(&pool, &[]).await?);
+    /// let ids = User::all(&pool).await?.iter().map(|u| u.user_id).collect::<Vec<_>>();
+    /// assert_eq!(vec![3, 4], ids);
+    /// # }}
+    /// ```
+    ///
+    /// [bind_limit]: ../schema/fn.bind_limit.html
+    /// [CrudHooks]: trait.CrudHooks.html
+    fn delete_many<K>(pool: E, ids: &[K]) -> CrudFut<'e, u64>
+    where
+        Self: Unscoped + Deletable + Unaudited + BindId<'e, <E as Executor<'e>>::Database, K>,
+        E: Acquire<'e, Database = <E as Executor<'e>>::Database>,
+        for<'c> &'c mut <<E as Executor<'e>>::Database as Database>::Connection:
+            Executor<'c, Database = <E as Executor<'e>>::Database>,
+        K: Clone,
+    {
+        if ids.is_empty() {
+            return Box::pin(future::ok(0));
+        }
+        #[cfg(feature = "tracing")]
+        let span = Self::tracing_span(crate::instrument::Operation::DeleteMany, None);
+
+        let metadata = <Self as DatabaseSchema<<E as Executor<'e>>::Database>>::database_metadata();
+        let limit = crate::schema::bind_limit::<<E as Executor<'e>>::Database>();
+        let chunk_ids = (limit / metadata.id_columns.len()).max(1);
+        let statements = ids
+            .chunks(chunk_ids)
+            .map(|chunk| {
+                let (sql, padded) = metadata.delete_by_ids_padded_sql(chunk.len(), chunk_ids);
+                let chunk = chunk.iter().collect::<Vec<_>>();
+                (sql, bind_padded_ids::<Self, E, K>(&chunk, padded))
+            })
+            .collect();
+        let future = execute_in_transaction(
+            pool,
+            statements,
+            <Self as DatabaseSchema<<E as Executor<'e>>::Database>>::rows_affected,
+        );
+        #[cfg(feature = "tracing")]
+        let future = crate::instrument::traced(span, future);
+        future
+    }
+
+    /// Writes `assignments` to the rows matching every condition of `filter`
+    /// in a single UPDATE with [Metadata::update_where_sql] and returns a
+    /// future resolving to the number of rows updated. The assigned values
+    /// are bound before those of the filter. Resolves without a query to
+    /// `0` if nothing is assigned, to [Error::EmptyFilter] if the filter has
+    /// no conditions and to [Error::ColumnNotUpdatable] if a column is not
+    /// written by [Crud::update], such as the primary key.
+    ///
+    /// Columns written by the database, such as the version, are written as
+    /// by [Crud::update]. Soft deleted rows are not updated. The [Validate]
+    /// check and the [CrudHooks] are not run and audited structs are
+    /// refused, see [Unaudited].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx::types::chrono::{NaiveDate, NaiveDateTime};
+    /// use sqlx_crud::filter::{Assignments, Filter};
+    /// use sqlx_crud::{Crud, Error};
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Post {
+    ///     id: i64,
+    ///     status: String,
+    ///     score: f64,
+    ///     created_at: NaiveDateTime,
+    ///     #[sqlx_crud(version)]
+    ///     version: i32,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE posts (id INTEGER PRIMARY KEY, status TEXT NOT NULL, score REAL NOT NULL, created_at DATETIME NOT NULL, version INTEGER NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// let day = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap().and_hms_opt(0, 0, 0).unwrap();
+    /// let posts = vec![
+    ///     Post { id: 1, status: "draft".to_string(), score: 1.0, created_at: day(1), version: 1 },
+    ///     Post { id: 2, status: "published".to_string(), score: 2.0, created_at: day(2), version: 1 },
+    ///     Post { id: 3, status: "published".to_string(), score: 3.0, created_at: day(20), version: 1 },
+    ///     Post { id: 4, status: "archived".to_string(), score: 4.0, created_at: day(3), version: 1 },
+    /// ];
+    /// Post::create_many(&pool, posts).await?;
+    ///
+    /// // The SET values are bound before the WHERE values
+    /// let assignments = Assignments::new()
+    ///     .set(PostColumn::Status, "archived")
+    ///     .set(PostColumn::Score, 0.5);
+    /// let filter = Filter::new()
+    ///     .lt(PostColumn::CreatedAt, day(10))
+    ///     .ne(PostColumn::Status, "archived")
+    ///     .in_list(PostColumn::Id, [1_i64, 2, 3]);
+    /// assert_eq!(2, Post::update_where(&pool, assignments, filter).await?);
+    ///
+    /// let posts = Post::all(&pool).await?;
+    /// let rows = posts.iter().map(|p| (p.id, p.status.as_str(), p.score, p.version)).collect::<Vec<_>>();
+    /// assert_eq!(
+    ///     vec![(1, "archived", 0.5, 2), (2, "archived", 0.5, 2), (3, "published", 3.0, 1), (4, "archived", 4.0, 1)],
+    ///     rows
+    /// );
+    ///
+    /// // The primary key is never assigned
+    /// let assignments = Assignments::new().set(PostColumn::Id, 10_i64);
+    /// let filter = Filter::new().eq(PostColumn::Id, 1_i64);
+    /// let result = Post::update_where(&pool, assignments, filter).await;
+    /// assert!(matches!(result, Err(Error::ColumnNotUpdatable { column: "id", .. })));
+    /// # }}
+    /// ```
+    ///
+    /// [CrudHooks]: trait.CrudHooks.html
+    /// [Metadata::update_where_sql]: ../schema/struct.Metadata.html#method.update_where_sql
+    /// [Validate]: trait.Validate.html
+    fn update_where(
+        pool: E,
+        assignments: Assignments<'e, E::Database, <Self as Schema>::Column>,
+        filter: Filter<'e, E::Database, <Self as Schema>::Column>,
+    ) -> CrudFut<'e, u64>
+    where
+        Self: Unscoped + Updatable + Unaudited,
+    {
+        let metadata = <Self as DatabaseSchema<E::Database>>::database_metadata();
+        let table_name = metadata.table_name;
+        if filter.is_empty() {
+            return Box::pin(future::err(Error::EmptyFilter { table_name }));
+        }
+        if let Some(column) = assignments
+            .columns()
+            .iter()
+            .find(|c| !metadata.is_updated_column(c))
+        {
+            return Box::pin(future::err(Error::ColumnNotUpdatable {
+                table_name,
+                column,
+            }));
+        }
+        if assignments.is_empty() {
+            return Box::pin(future::ok(0));
+        }
+        #[cfg(feature = "tracing")]
+        let span = Self::tracing_span(crate::instrument::Operation::UpdateWhere, None);
+        let sql = metadata.update_where_sql(assignments.columns(), filter.conditions());
+        let mut args = <E::Database as HasArguments<'e>>::Arguments::default();
+        assignments.bind_to(&mut args);
+        filter.bind_to(&mut args);
+        let future: CrudFut<'e, u64> = Box::pin(
+            sqlx::query_with::<E::Database, _>(sql, args)
+                .execute(pool)
+                .map_ok(|r| <Self as DatabaseSchema<E::Database>>::rows_affected(&r))
+                .map_err(Error::from),
+        );
+        #[cfg(feature = "tracing")]
+        let future = crate::instrument::traced(span, future);
+        future
+    }
+
+    /// Adds `delta` to the numeric `column` of the row with primary key `id`
+    /// with [Metadata::increment_sql] and returns a future resolving to the
+    /// number of rows updated, 0 if there is no such row. The column is
+    /// written relative to its stored value in a single statement, so
+    /// concurrent increments are all counted. Resolves to
+    /// [Error::ColumnNotUpdatable] without a query for a column not written
+    /// by [Crud::update], such as the primary key.
+    ///
+    /// Generated columns such as the version are written as by
+    /// [Crud::update], but the [CrudHooks] are not run and audited structs
+    /// are refused, see [Unaudited].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use futures::future::try_join_all;
+    /// use sqlx_crud::Crud;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Product {
+    ///     id: i64,
+    ///     stock: i32,
+    ///     price: f64,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE products (id INTEGER PRIMARY KEY, stock INTEGER NOT NULL, price REAL NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// let product = Product { id: 0, stock: 0, price: 1.5 }.create(&pool).await?;
+    ///
+    /// let increments = (0..100).map(|_| Product::increment(&pool, product.id, ProductColumn::stock, 3));
+    /// assert_eq!(vec![1; 100], try_join_all(increments).await?);
+    /// Product::decrement(&pool, product.id, ProductColumn::stock, 20).await?;
+    /// assert_eq!(280, Product::by_id(&pool, product.id).await?.unwrap().stock);
+    ///
+    /// assert_eq!(0, Product::increment(&pool, 99, ProductColumn::stock, 1).await?);
+    /// # }}
+    /// ```
+    ///
+    /// Columns of other types do not compile:
+    ///
+    /// ```rust,compile_fail
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Crud;
+    ///
+    /// User::increment(&pool, 1, UserColumn::name, 1).await?;
+    /// # }}
+    /// ```
+    ///
+    /// [Metadata::increment_sql]: ../schema/struct.Metadata.html#method.increment_sql
+    fn increment<F: Numeric>(
+        pool: E,
+        id: <Self as Schema>::Id,
+        column: TypedColumn<<Self as Schema>::Column, F>,
+        delta: i64,
+    ) -> CrudFut<'e, u64>
+    where
+        Self: Unscoped + Updatable + Unaudited,
+        i64: for<'q> Encode<'q, E::Database> + Type<E::Database>,
+    {
+        Self::increment_columns(pool, id, Increments::new().add(column, delta))
+    }
+
+    /// Subtracts `delta` from the numeric `column` of the row with primary
+    /// key `id`, like [Crud::increment].
+    fn decrement<F: Numeric>(
+        pool: E,
+        id: <Self as Schema>::Id,
+        column: TypedColumn<<Self as Schema>::Column, F>,
+        delta: i64,
+    ) -> CrudFut<'e, u64>
+    where
+        Self: Unscoped + Updatable + Unaudited,
+        i64: for<'q> Encode<'q, E::Database> + Type<E::Database>,
+    {
+        Self::increment(pool, id, column, -delta)
+    }
+
+    /// Adds the deltas of `increments` to the columns of the row with
+    /// primary key `id` in a single statement, like [Crud::increment].
+    /// Resolves to 0 without a query if no column is incremented.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::filter::Increments;
+    /// use sqlx_crud::Crud;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Account {
+    ///     id: i64,
+    ///     login_count: i64,
+    ///     credits: Option<i32>,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE accounts (id INTEGER PRIMARY KEY, login_count INTEGER NOT NULL, credits INTEGER)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// let account = Account { id: 0, login_count: 4, credits: Some(10) }.create(&pool).await?;
+    ///
+    /// let increments = Increments::new().add(AccountColumn::login_count, 1).add(AccountColumn::credits, -3);
+    /// assert_eq!(1, Account::increment_columns(&pool, account.id, increments).await?);
+    /// let account = Account::by_id(&pool, account.id).await?.unwrap();
+    /// assert_eq!((5, Some(7)), (account.login_count, account.credits));
+    ///
+    /// assert!(Account::increment(&pool, account.id, AccountColumn::id, 1).await.is_err());
+    /// # }}
+    /// ```
+    fn increment_columns(
+        pool: E,
+        id: <Self as Schema>::Id,
+        increments: Increments<<Self as Schema>::Column>,
+    ) -> CrudFut<'e, u64>
+    where
+        Self: Unscoped + Updatable + Unaudited,
+        i64: for<'q> Encode<'q, E::Database> + Type<E::Database>,
+    {
+        let metadata = <Self as DatabaseSchema<E::Database>>::database_metadata();
+        let table_name = metadata.table_name;
+        if let Some(column) = increments
+            .columns()
+            .iter()
+            .find(|c| !metadata.is_updated_column(c))
+        {
+            return Box::pin(future::err(Error::ColumnNotUpdatable {
+                table_name,
+                column,
+            }));
+        }
+        if increments.is_empty() {
+            return Box::pin(future::ok(0));
+        }
+        let sql = metadata.increment_sql(increments.columns());
+        let mut args = <E::Database as HasArguments<'e>>::Arguments::default();
+        for delta in increments.deltas() {
+            args.add(*delta);
+        }
+        Self::bind_id_args(id, &mut args);
+        Box::pin(
+            sqlx::query_with::<E::Database, _>(sql, args)
+                .execute(pool)
+                .map_ok(|r| <Self as DatabaseSchema<E::Database>>::rows_affected(&r))
+                .map_err(Error::from),
+        )
+    }
+
+    /// Deletes the rows matching every condition of `filter` with
+    /// [Metadata::delete_where_sql] and returns a future resolving to the
+    /// number of rows deleted. Resolves to [Error::EmptyFilter] without a
+    /// query if the filter has no conditions, [Crud::delete_all] deletes
+    /// every row.
+    ///
+    /// As with [Crud::delete_all] soft deletes apply, audited structs are
+    /// refused and the [CrudHooks] are not run.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx::types::chrono::{NaiveDate, NaiveDateTime};
+    /// use sqlx_crud::filter::Filter;
+    /// use sqlx_crud::{Crud, Error};
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Session {
+    ///     id: i64,
+    ///     status: String,
+    ///     created_at: NaiveDateTime,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE sessions (id INTEGER PRIMARY KEY, status TEXT NOT NULL, created_at DATETIME NOT NULL)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// let day = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap().and_hms_opt(0, 0, 0).unwrap();
+    /// let sessions = vec![
+    ///     Session { id: 1, status: "expired".to_string(), created_at: day(1) },
+    ///     Session { id: 2, status: "expired".to_string(), created_at: day(20) },
+    ///     Session { id: 3, status: "active".to_string(), created_at: day(1) },
+    /// ];
+    /// Session::create_many(&pool, sessions).await?;
+    ///
+    /// let filter = Filter::new()
+    ///     .eq(SessionColumn::Status, "expired")
+    ///     .lt(SessionColumn::CreatedAt, day(10));
+    /// assert_eq!(1, Session::delete_where(&pool, filter).await?);
+    /// let ids = Session::all(&pool).await?.iter().map(|s| s.id).collect::<Vec<_>>();
+    /// assert_eq!(vec![2, 3], ids);
+    ///
+    /// let result = Session::delete_where(&pool, Filter::new()).await;
+    /// assert!(matches!(result, Err(Error::EmptyFilter { table_name: "sessions" })));
+    /// assert_eq!(2, Session::all(&pool).await?.len());
+    /// # }}
+    /// ```
+    ///
+    /// [CrudHooks]: trait.CrudHooks.html
+    /// [Metadata::delete_where_sql]: ../schema/struct.Metadata.html#method.delete_where_sql
+    fn delete_where(
+        pool: E,
+        filter: Filter<'e, E::Database, <Self as Schema>::Column>,
+    ) -> CrudFut<'e, u64>
+    where
+        Self: Unscoped + Deletable + Unaudited,
+    {
+        let metadata = <Self as DatabaseSchema<E::Database>>::database_metadata();
+        if filter.is_empty() {
+            return Box::pin(future::err(Error::EmptyFilter {
+                table_name: metadata.table_name,
+            }));
+        }
+        #[cfg(feature = "tracing")]
+        let span = Self::tracing_span(crate::instrument::Operation::DeleteWhere, None);
+        let sql = metadata.delete_where_sql(filter.conditions());
+        let query = sqlx::query_with::<E::Database, _>(sql, filter.into_arguments());
+        let future: CrudFut<'e, u64> = Box::pin(
+            query
+                .execute(pool)
+                .map_ok(|r| <Self as DatabaseSchema<E::Database>>::rows_affected(&r))
+                .map_err(Error::from),
+        );
+        #[cfg(feature = "tracing")]
+        let future = crate::instrument::traced(span, future);
+        future
+    }
+
+    /// Deletes every row of the table with [Schema::delete_all_sql] and
+    /// returns a future resolving to the number of rows deleted. Structs with
+    /// a [Schema::soft_delete_column] soft delete the rows not yet deleted,
+    /// [Crud::truncate] removes them.
+    ///
+    /// As with [Crud::delete_many] audited structs are refused and the
+    /// [CrudHooks] are not run.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Crud;
+    ///
+    /// let users = (0..3).map(|i| User { user_id: 0, name: format!("user {}", i) }).collect();
+    /// User::create_many(&pool, users).await?;
+    ///
+    /// assert_eq!(4, User::delete_all(&pool).await?);
+    /// assert!(User::all(&pool).await?.is_empty());
+    /// assert!(User::by_id(&pool, 1).await?.is_none());
+    /// # }}
+    /// ```
+    ///
+    /// [CrudHooks]: trait.CrudHooks.html
+    fn delete_all(pool: E) -> CrudFut<'e, u64>
+    where
+        Self: Unscoped + Deletable + Unaudited,
+    {
+        #[cfg(feature = "tracing")]
+        let span = Self::tracing_span(crate::instrument::Operation::DeleteAll, None);
+        let query =
+            sqlx::query(&<Self as DatabaseSchema<E::Database>>::database_metadata().delete_all_sql);
+        let future: CrudFut<'e, u64> = Box::pin(
+            query
+                .execute(pool)
+                .map_ok(|r| <Self as DatabaseSchema<E::Database>>::rows_affected(&r))
+                .map_err(Error::from),
+        );
+        #[cfg(feature = "tracing")]
+        let future = crate::instrument::traced(span, future);
+        future
+    }
+
+    /// Removes every row of the table with [Schema::truncate_sql], bypassing
+    /// soft deletes. Returns a future resolving to `()` once the table is
+    /// empty.
+    ///
+    /// `TRUNCATE TABLE` is faster than [Crud::delete_all] on large tables
+    /// but differs in its transactional behavior: MySQL commits the
+    /// transaction it runs in, PostgreSQL locks the whole table until the
+    /// transaction ends. Neither runs DELETE triggers or reports the number
+    /// of rows removed. PostgreSQL does not truncate tables referenced by
+    /// foreign keys.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx::types::chrono::{DateTime, Utc};
+    /// use sqlx_crud::Crud;
+    ///
+    /// #[derive(Debug, FromRow, SqlxCrud)]
+    /// #[database(Sqlite)]
+    /// struct Entry {
+    ///     id: i64,
+    ///     key: String,
+    ///     #[sqlx_crud(soft_delete)]
+    ///     deleted_at: Option<DateTime<Utc>>,
+    /// }
+    ///
+    /// sqlx::query("CREATE TABLE entries (id INTEGER PRIMARY KEY, key TEXT NOT NULL, deleted_at TIMESTAMP)")
+    ///     .execute(&pool)
+    ///     .await?;
+    /// let entries = (0..3).map(|i| Entry { id: 0, key: i.to_string(), deleted_at: None }).collect();
+    /// Entry::create_many(&pool, entries).await?;
+    ///
+    /// // Soft deleted rows stay in the table
+    /// assert_eq!(3, Entry::delete_all(&pool).await?);
+    /// assert!(Entry::all(&pool).await?.is_empty());
+    /// assert_eq!(0, Entry::delete_all(&pool).await?);
+    ///
+    /// Entry::truncate(&pool).await?;
+    /// let (rows,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM entries").fetch_one(&pool).await?;
+    /// assert_eq!(0, rows);
+    /// # }}
+    /// ```
+    fn truncate(pool: E) -> CrudFut<'e, ()>
+    where
+        Self: Unscoped + Deletable + Unaudited,
+    {
+        #[cfg(feature = "tracing")]
+        let span = Self::tracing_span(crate::instrument::Operation::Truncate, None);
+        let query =
+            sqlx::query(&<Self as DatabaseSchema<E::Database>>::database_metadata().truncate_sql);
+        let future: CrudFut<'e, ()> =
+            Box::pin(query.execute(pool).map_ok(|_| ()).map_err(Error::from));
+        #[cfg(feature = "tracing")]
+        let future = crate::instrument::traced(span, future);
+        future
+    }
+
+    /// Removes a record from the database by ID, bypassing soft deletes and
+    /// version checks. Returns a future that resolves to `()` on success or
+    /// `sqlx_crud::Error` on failure. See [Schema::soft_delete_column].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Crud;
+    ///
+    /// if let Some(user) = User::by_id(&pool, 1).await? {
+    ///     user.delete_permanently(&pool).await?;
     /// }
     /// assert!(User::by_id(&pool, 1).await?.is_none());
     /// # }}
     /// ```
-    fn delete(self, pool: E) -> CrudFut<'e, ()> {
-        let query = sqlx::query(<Self as Schema>::delete_by_id_sql()).bind(self.id());
-        Box::pin(query.execute(pool).and_then(|_| future::ok(())))
+    fn delete_permanently(mut self, pool: E) -> CrudFut<'e, ()>
+    where
+        Self: Deletable,
+    {
+        if let Err(e) = self.run_hook(Hook::BeforeDelete) {
+            return Box::pin(future::err(e));
+        }
+        let id = self.id();
+        let args = self.delete_permanently_args();
+        let query = sqlx::query_with(
+            &<Self as DatabaseSchema<E::Database>>::database_metadata()
+                .delete_permanently_by_id_sql,
+            args,
+        );
+        Box::pin(
+            query
+                .execute(pool)
+                .map_err(Error::from)
+                .and_then(move |_| future::ready(Self::run_after_delete(&id))),
+        )
+    }
+}
+
+/// Runs the INSERT of [Crud::create] for `row` on `executor`, the executor
+/// of `T` or a connection of a transaction begun on it.
+fn create_on<'e, 'c, T, E, X>(mut row: T, executor: X) -> CrudFut<'c, T>
+where
+    'e: 'c,
+    T: Crud<'e, E> + Creatable,
+    E: Executor<'e> + 'e,
+    <E::Database as HasArguments<'e>>::Arguments: IntoArguments<'e, E::Database>,
+    X: Executor<'c, Database = E::Database> + 'c,
+{
+    if let Err(e) = row
+        .run_hook(Hook::BeforeCreate)
+        .and_then(|_| row.validate_write())
+    {
+        return Box::pin(future::err(e));
+    }
+    let sql = <T as DatabaseSchema<E::Database>>::database_metadata()
+        .insert_sql_without(&row.defaulted_columns());
+    let args = row.insert_args();
+    Box::pin(
+        sqlx::query_with::<E::Database, _>(sql, args)
+            .try_map(|r| T::from_row(&r))
+            .fetch_one(executor)
+            .map_err(Error::from)
+            .and_then(|mut row| future::ready(row.run_hook(Hook::AfterCreate).map(|_| row))),
+    )
+}
+
+/// Runs the `aggregate` of `column` of [Crud::min] and the like on `pool`.
+fn aggregate_on<'e, T, E, V>(
+    pool: E,
+    aggregate: Aggregate,
+    column: T::Column,
+) -> CrudFut<'e, Option<V>>
+where
+    T: Crud<'e, E>,
+    E: Executor<'e> + 'e,
+    <E::Database as HasArguments<'e>>::Arguments: IntoArguments<'e, E::Database>,
+    V: 'e + Send + Unpin,
+    (Option<V>,): for<'r> FromRow<'r, <E::Database as Database>::Row>,
+{
+    let sql = <T as DatabaseSchema<E::Database>>::database_metadata()
+        .aggregate_sql(aggregate, column.name());
+    Box::pin(
+        sqlx::query_as::<E::Database, (Option<V>,)>(sql)
+            .fetch_one(pool)
+            .map_ok(|(value,)| value)
+            .map_err(Error::from),
+    )
+}
+
+/// Runs the INSERT of [Crud::create_ignore] for `row` on `executor`, like
+/// [create_on].
+fn create_ignore_on<'e, 'c, T, E, X>(row: T, executor: X) -> CrudFut<'c, bool>
+where
+    'e: 'c,
+    T: Crud<'e, E> + Creatable,
+    E: Executor<'e> + 'e,
+    <E::Database as HasArguments<'e>>::Arguments: IntoArguments<'e, E::Database>,
+    X: Executor<'c, Database = E::Database> + 'c,
+{
+    if let Err(e) = row.validate_write() {
+        return Box::pin(future::err(e));
+    }
+    #[cfg(feature = "tracing")]
+    let span = T::tracing_span(crate::instrument::Operation::CreateIgnore, Some(&row.id()));
+    let sql = <T as DatabaseSchema<E::Database>>::database_metadata()
+        .insert_ignore_sql_without(&row.defaulted_columns());
+    let args = row.insert_args();
+    let future: CrudFut<'c, bool> = Box::pin(
+        sqlx::query_with::<E::Database, _>(sql, args)
+            .execute(executor)
+            .map_ok(|result| <T as DatabaseSchema<E::Database>>::rows_affected(&result) > 0)
+            .map_err(Error::from),
+    );
+    #[cfg(feature = "tracing")]
+    let future = crate::instrument::traced(span, future);
+    future
+}
+
+/// Runs the SELECT of [Crud::by_id] for `id` on `executor`, like
+/// [create_on].
+fn by_id_on<'e, 'c, T, E, X, K>(id: K, executor: X) -> CrudFut<'c, Option<T>>
+where
+    'e: 'c,
+    T: Crud<'e, E> + BindId<'e, E::Database, K>,
+    K: std::fmt::Debug,
+    E: Executor<'e> + 'e,
+    <E::Database as HasArguments<'e>>::Arguments: IntoArguments<'e, E::Database>,
+    X: Executor<'c, Database = E::Database> + 'c,
+{
+    #[cfg(feature = "tracing")]
+    let span = T::tracing_span(crate::instrument::Operation::ById, Some(&id));
+    let future: CrudFut<'c, Option<T>> = Box::pin(
+        sqlx::query_with::<E::Database, _>(
+            &<T as DatabaseSchema<E::Database>>::database_metadata().select_by_id_sql,
+            T::id_args(id),
+        )
+        .try_map(|r| T::from_row(&r))
+        .fetch_optional(executor)
+        .map_err(Error::from),
+    );
+    #[cfg(feature = "tracing")]
+    let future = crate::instrument::traced(span, future);
+    future
+}
+
+/// Runs the UPDATE of [Crud::update] for `row` on `executor`, like
+/// [create_on].
+fn update_on<'e, 'c, T, E, X>(mut row: T, executor: X) -> CrudFut<'c, T>
+where
+    'e: 'c,
+    T: Crud<'e, E> + Updatable,
+    E: Executor<'e> + 'e,
+    <E::Database as HasArguments<'e>>::Arguments: IntoArguments<'e, E::Database>,
+    X: Executor<'c, Database = E::Database> + 'c,
+{
+    let table_name = T::table_name();
+    let versioned = T::version_column().is_some();
+    if let Err(e) = row
+        .run_hook(Hook::BeforeUpdate)
+        .and_then(|_| row.validate_write())
+    {
+        return Box::pin(future::err(e));
+    }
+    let id = row.id();
+    let args = row.update_args();
+    Box::pin(
+        sqlx::query_with::<E::Database, _>(
+            &<T as DatabaseSchema<E::Database>>::database_metadata().update_sql,
+            args,
+        )
+        .try_map(|r| T::from_row(&r))
+        .fetch_one(executor)
+        .map_err(move |e| match e {
+            sqlx::Error::RowNotFound if versioned => Error::StaleVersion { table_name },
+            sqlx::Error::RowNotFound => Error::not_found::<T>(&id),
+            e => Error::from(e),
+        })
+        .and_then(|mut row| future::ready(row.run_hook(Hook::AfterUpdate).map(|_| row))),
+    )
+}
+
+/// Runs the DELETE of [Crud::delete] for `row` on `executor`.
+fn delete_on<'e, 'c, T, E, X>(mut row: T, executor: X) -> CrudFut<'c, ()>
+where
+    'e: 'c,
+    T: Crud<'e, E> + Deletable,
+    E: Executor<'e> + 'e,
+    <E::Database as HasArguments<'e>>::Arguments: IntoArguments<'e, E::Database>,
+    X: Executor<'c, Database = E::Database> + 'c,
+{
+    let table_name = T::table_name();
+    let versioned = T::version_column().is_some();
+    if let Err(e) = row.run_hook(Hook::BeforeDelete) {
+        return Box::pin(future::err(e));
+    }
+    let id = row.id();
+    let args = row.delete_args();
+    Box::pin(
+        sqlx::query_with::<E::Database, _>(
+            &<T as DatabaseSchema<E::Database>>::database_metadata().delete_sql,
+            args,
+        )
+        .execute(executor)
+        .map_err(Error::from)
+        .and_then(move |r| {
+            let rows_affected = <T as DatabaseSchema<E::Database>>::rows_affected(&r);
+            #[cfg(feature = "tracing")]
+            tracing::debug!(rows_affected, "deleted");
+            if rows_affected == 0 {
+                future::err(match versioned {
+                    true => Error::StaleVersion { table_name },
+                    false => Error::not_found::<T>(&id),
+                })
+            } else {
+                future::ready(T::run_after_delete(&id))
+            }
+        }),
+    )
+}
+
+/// The history INSERT `sql` of an audited struct binding the key of `row`,
+/// run as a statement of its own outside PostgreSQL. `None` for structs
+/// without audit and on PostgreSQL, whose writes include the INSERT.
+fn audit_statement<'e, T, E>(
+    row: &T,
+    sql: &'static Option<String>,
+) -> Option<(&'static str, <E::Database as HasArguments<'e>>::Arguments)>
+where
+    T: Crud<'e, E>,
+    E: Executor<'e> + 'e,
+    <E::Database as HasArguments<'e>>::Arguments: IntoArguments<'e, E::Database>,
+{
+    match sql {
+        Some(sql) if <E::Database as Database>::NAME != "PostgreSQL" => {
+            Some((sql.as_str(), row.audit_args()))
+        }
+        _ => None,
+    }
+}
+
+/// Runs [Crud::update] on `acquire`. The history INSERT of an audited
+/// struct outside PostgreSQL runs first in a transaction begun by `acquire`.
+fn update_audited<'e, T, E, DB>(row: T, acquire: E) -> CrudFut<'e, T>
+where
+    T: Crud<'e, E> + Updatable,
+    E: Executor<'e, Database = DB> + Acquire<'e, Database = DB> + 'e,
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    <DB as HasArguments<'e>>::Arguments: IntoArguments<'e, DB>,
+{
+    let metadata = <T as DatabaseSchema<DB>>::database_metadata();
+    let Some((audit_sql, audit_args)) = audit_statement::<T, E>(&row, &metadata.audit_update_sql)
+    else {
+        return update_on::<T, E, E>(row, acquire);
+    };
+    let begin = acquire.begin();
+    Box::pin(async move {
+        let mut tx = begin.await?;
+        sqlx::query_with::<DB, _>(audit_sql, audit_args)
+            .execute(&mut *tx)
+            .await?;
+        let row = update_on::<T, E, _>(row, &mut *tx).await?;
+        tx.commit().await?;
+        Ok(row)
+    })
+}
+
+/// Runs [Crud::delete] on `acquire`. The history INSERT of an audited
+/// struct outside PostgreSQL runs first in a transaction begun by `acquire`.
+fn delete_audited<'e, T, E, DB>(row: T, acquire: E) -> CrudFut<'e, ()>
+where
+    T: Crud<'e, E> + Deletable,
+    E: Executor<'e, Database = DB> + Acquire<'e, Database = DB> + 'e,
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    <DB as HasArguments<'e>>::Arguments: IntoArguments<'e, DB>,
+{
+    let metadata = <T as DatabaseSchema<DB>>::database_metadata();
+    let Some((audit_sql, audit_args)) = audit_statement::<T, E>(&row, &metadata.audit_delete_sql)
+    else {
+        return delete_on::<T, E, E>(row, acquire);
+    };
+    let begin = acquire.begin();
+    Box::pin(async move {
+        let mut tx = begin.await?;
+        sqlx::query_with::<DB, _>(audit_sql, audit_args)
+            .execute(&mut *tx)
+            .await?;
+        delete_on::<T, E, _>(row, &mut *tx).await?;
+        tx.commit().await?;
+        Ok(())
+    })
+}
+
+/// Runs the INSERT of [Crud::clone_row] and [Crud::clone_row_as] copying
+/// the row `source`, under `new_id` if any. On MySQL the insert and the
+/// query reading the new row back run in a transaction begun on `acquire`.
+fn clone_row_acquired<'e, T, E, DB>(
+    acquire: E,
+    source: T::Id,
+    new_id: Option<T::Id>,
+) -> CrudFut<'e, Option<T>>
+where
+    T: Crud<'e, E> + Creatable,
+    E: Executor<'e, Database = DB> + Acquire<'e, Database = DB> + 'e,
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    <DB as HasArguments<'e>>::Arguments: IntoArguments<'e, DB>,
+{
+    let metadata = <T as DatabaseSchema<DB>>::database_metadata();
+    let mysql = DB::NAME == "MySQL";
+    let sql = metadata.clone_row_sql(new_id.is_some(), !mysql);
+    let mut args = <DB as HasArguments<'e>>::Arguments::default();
+    if let Some(id) = &new_id {
+        T::bind_id_args(id.clone(), &mut args);
+    }
+    T::bind_id_args(source, &mut args);
+    if !mysql {
+        return Box::pin(
+            sqlx::query_with::<DB, _>(sql, args)
+                .try_map(|r| T::from_row(&r))
+                .fetch_optional(acquire)
+                .map_err(Error::from),
+        );
+    }
+    let (select_sql, select_args) = match new_id {
+        Some(id) => (metadata.select_by_id_sql.as_str(), T::id_args(id)),
+        None => (
+            metadata.select_inserted_sql(&metadata.id_columns).0,
+            <DB as HasArguments<'e>>::Arguments::default(),
+        ),
+    };
+    let begin = acquire.begin();
+    Box::pin(async move {
+        let mut tx = begin.await?;
+        let result = sqlx::query_with::<DB, _>(sql, args)
+            .execute(&mut *tx)
+            .await?;
+        if <T as DatabaseSchema<DB>>::rows_affected(&result) == 0 {
+            return Ok(None);
+        }
+        let select: CrudFut<'_, T> = Box::pin(
+            sqlx::query_with::<DB, _>(select_sql, select_args)
+                .try_map(|r| T::from_row(&r))
+                .fetch_one(&mut *tx)
+                .map_err(Error::from),
+        );
+        let row = select.await?;
+        tx.commit().await?;
+        Ok(Some(row))
+    })
+}
+
+/// Runs [Crud::delete_by_id_returning] for `id` in a transaction begun on
+/// `acquire`, for databases without a RETURNING clause.
+fn delete_returning_in_transaction<'e, T, E, DB>(acquire: E, id: T::Id) -> CrudFut<'e, Option<T>>
+where
+    T: Crud<'e, E> + Deletable,
+    E: Executor<'e, Database = DB> + Acquire<'e, Database = DB> + 'e,
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    <DB as HasArguments<'e>>::Arguments: IntoArguments<'e, DB>,
+{
+    let metadata = <T as DatabaseSchema<DB>>::database_metadata();
+    let select_args = T::id_args(id.clone());
+    let delete_args = T::id_args(id.clone());
+    let begin = acquire.begin();
+    Box::pin(async move {
+        let mut tx = begin.await?;
+        let select: CrudFut<'_, Option<T>> = Box::pin(
+            sqlx::query_with::<DB, _>(&metadata.select_by_id_for_update_sql, select_args)
+                .try_map(|r| T::from_row(&r))
+                .fetch_optional(&mut *tx)
+                .map_err(Error::from),
+        );
+        let Some(row) = select.await? else {
+            return Ok(None);
+        };
+        sqlx::query_with::<DB, _>(&metadata.delete_by_id_sql, delete_args)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        T::run_after_delete(&id)?;
+        Ok(Some(row))
+    })
+}
+
+/// Runs the SELECT `statements` of [Crud::by_ids] and [Crud::all_where_in]
+/// one after the other on a connection acquired from `acquire`, returning
+/// their rows in turn.
+/// Binds the keys of `ids` in turn, then the last key again up to `padded`
+/// keys, for the statements of [Metadata::select_by_ids_sql] and
+/// [Metadata::delete_by_ids_sql] rounded up to a cached length.
+fn bind_padded_ids<'e, T, E, K>(
+    ids: &[&K],
+    padded: usize,
+) -> <E::Database as HasArguments<'e>>::Arguments
+where
+    T: Crud<'e, E> + BindId<'e, E::Database, K>,
+    E: Executor<'e> + 'e,
+    <E::Database as HasArguments<'e>>::Arguments: IntoArguments<'e, E::Database>,
+    K: Clone,
+{
+    let mut args = <E::Database as HasArguments<'e>>::Arguments::default();
+    for id in ids
+        .iter()
+        .chain(std::iter::repeat(&ids[ids.len() - 1]))
+        .take(padded)
+    {
+        T::bind_id_args((*id).clone(), &mut args);
+    }
+    args
+}
+
+fn fetch_all_acquired<'e, T, E, DB>(
+    acquire: E,
+    statements: Vec<(&'static str, <DB as HasArguments<'e>>::Arguments)>,
+) -> CrudFut<'e, Vec<T>>
+where
+    T: Crud<'e, E>,
+    E: Executor<'e, Database = DB> + Acquire<'e, Database = DB> + 'e,
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    <DB as HasArguments<'e>>::Arguments: IntoArguments<'e, DB>,
+{
+    let acquire = acquire.acquire();
+    Box::pin(async move {
+        let mut conn = acquire.await?;
+        let mut rows = Vec::new();
+        for (sql, args) in statements {
+            let chunk: CrudFut<'_, Vec<T>> = Box::pin(
+                sqlx::query_with::<DB, _>(sql, args)
+                    .try_map(|r| T::from_row(&r))
+                    .fetch_all(&mut *conn)
+                    .map_err(Error::from),
+            );
+            rows.extend(chunk.await?);
+        }
+        Ok(rows)
+    })
+}
+
+/// Runs the queries of [Crud::page] on a connection acquired from
+/// `acquire`.
+fn page_acquired<'e, T, E, DB>(acquire: E, limit: i64, offset: i64) -> CrudFut<'e, Page<T>>
+where
+    T: Crud<'e, E>,
+    E: Executor<'e, Database = DB> + Acquire<'e, Database = DB> + 'e,
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    <DB as HasArguments<'e>>::Arguments: IntoArguments<'e, DB>,
+    i64: for<'q> Encode<'q, DB> + Type<DB>,
+    (i64,): for<'r> FromRow<'r, DB::Row>,
+{
+    let metadata = <T as DatabaseSchema<DB>>::database_metadata();
+    let mut args = <DB as HasArguments<'e>>::Arguments::default();
+    args.add(limit);
+    args.add(offset);
+    let acquire = acquire.acquire();
+    Box::pin(async move {
+        let mut conn = acquire.await?;
+        let items: CrudFut<'_, Vec<T>> = Box::pin(
+            sqlx::query_with::<DB, _>(&metadata.paginated_sql, args)
+                .try_map(|r| T::from_row(&r))
+                .fetch_all(&mut *conn)
+                .map_err(Error::from),
+        );
+        let items = items.await?;
+        let total: CrudFut<'_, (i64,)> = Box::pin(
+            sqlx::query_as::<DB, (i64,)>(&metadata.count_sql)
+                .fetch_one(&mut *conn)
+                .map_err(Error::from),
+        );
+        let (total,) = total.await?;
+        Ok(Page {
+            items,
+            total: total as u64,
+            limit,
+            offset,
+        })
+    })
+}
+
+/// Runs [Crud::for_each_batch] on a connection acquired from `acquire`,
+/// reading each batch with the keyset query of [Crud::after].
+fn for_each_batch_acquired<'e, T, E, DB, F, Fut>(
+    acquire: E,
+    batch_size: i64,
+    mut f: F,
+) -> CrudFut<'e, ()>
+where
+    T: Crud<'e, E>,
+    E: Executor<'e, Database = DB> + Acquire<'e, Database = DB> + 'e,
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    <DB as HasArguments<'e>>::Arguments: IntoArguments<'e, DB>,
+    i64: for<'q> Encode<'q, DB> + Type<DB>,
+    F: FnMut(Vec<T>) -> Fut + Send + 'e,
+    Fut: Future<Output = Result<(), Error>> + Send + 'e,
+{
+    let metadata = <T as DatabaseSchema<DB>>::database_metadata();
+    let first_sql = metadata.keyset_sql(None, Direction::Ascending, false);
+    let next_sql = metadata.keyset_sql(None, Direction::Ascending, true);
+    let acquire = acquire.acquire();
+    Box::pin(async move {
+        let mut conn = acquire.await?;
+        let mut cursor = None;
+        loop {
+            let mut args = <DB as HasArguments<'e>>::Arguments::default();
+            let sql = match cursor {
+                Some(id) => {
+                    T::bind_id_args(id, &mut args);
+                    next_sql
+                }
+                None => first_sql,
+            };
+            args.add(batch_size);
+            let batch: CrudFut<'_, Vec<T>> = Box::pin(
+                sqlx::query_with::<DB, _>(sql, args)
+                    .try_map(|r| T::from_row(&r))
+                    .fetch_all(&mut *conn)
+                    .map_err(Error::from),
+            );
+            let batch = batch.await?;
+            let last = (batch.len() as i64) < batch_size;
+            cursor = next_cursor(&batch);
+            if batch.is_empty() {
+                return Ok(());
+            }
+            f(batch).await?;
+            if last {
+                return Ok(());
+            }
+        }
+    })
+}
+
+/// Runs [Crud::update_and_fetch] in a transaction begun by `acquire`, after
+/// the history INSERT of an audited struct outside PostgreSQL.
+fn update_and_fetch_acquired<'e, T, E, DB>(row: T, acquire: E) -> CrudFut<'e, T>
+where
+    T: Crud<'e, E> + Updatable,
+    E: Executor<'e, Database = DB> + Acquire<'e, Database = DB> + 'e,
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    <DB as HasArguments<'e>>::Arguments: IntoArguments<'e, DB>,
+{
+    #[cfg(feature = "tracing")]
+    let span = T::tracing_span(crate::instrument::Operation::Update, Some(&row.id()));
+    let audit = audit_statement::<T, E>(
+        &row,
+        &<T as DatabaseSchema<DB>>::database_metadata().audit_update_sql,
+    );
+    let begin = acquire.begin();
+    Box::pin(async move {
+        let mut tx = begin.await?;
+        if let Some((audit_sql, audit_args)) = audit {
+            sqlx::query_with::<DB, _>(audit_sql, audit_args)
+                .execute(&mut *tx)
+                .await?;
+        }
+        let update = update_on::<T, E, _>(row, &mut *tx);
+        #[cfg(feature = "tracing")]
+        let update = crate::instrument::traced(span, update);
+        let id = update.await?.id();
+        let row = by_id_on::<T, E, _, _>(id.clone(), &mut *tx)
+            .await?
+            .ok_or_else(|| Error::not_found::<T>(&id))?;
+        tx.commit().await?;
+        Ok(row)
+    })
+}
+
+/// Runs [Crud::find_or_create] on a connection acquired from `acquire`.
+fn find_or_create_acquired<'e, T, E, DB, F>(acquire: E, id: T::Id, f: F) -> CrudFut<'e, T>
+where
+    T: Crud<'e, E> + Creatable,
+    E: Executor<'e, Database = DB> + Acquire<'e, Database = DB> + 'e,
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    <DB as HasArguments<'e>>::Arguments: IntoArguments<'e, DB>,
+    F: FnOnce() -> T + Send + 'e,
+    T::Id: PartialEq,
+{
+    let acquire = acquire.acquire();
+    Box::pin(async move {
+        let mut conn = acquire.await?;
+        if let Some(row) = by_id_on::<T, E, _, _>(id.clone(), &mut *conn).await? {
+            return Ok(row);
+        }
+        let row = f();
+        let found = row.id();
+        if found != id {
+            return Err(Error::IdMismatch {
+                table_name: T::table_name(),
+                id: format!("{:?}", id),
+                found: format!("{:?}", found),
+            });
+        }
+        create_ignore_on::<T, E, _>(row, &mut *conn).await?;
+        by_id_on::<T, E, _, _>(id.clone(), &mut *conn)
+            .await?
+            .ok_or_else(|| Error::not_found::<T>(&id))
+    })
+}
+
+/// Runs [Crud::save] with the upsert statement `sql` of
+/// [Metadata::save_sql_without].
+///
+/// [Metadata::save_sql_without]: ../schema/struct.Metadata.html#method.save_sql_without
+fn save_upserting<'e, T, E, DB>(
+    row: T,
+    executor: E,
+    sql: &'static str,
+) -> CrudFut<'e, SaveOutcome<T>>
+where
+    T: Crud<'e, E> + Creatable + Updatable,
+    E: Executor<'e, Database = DB> + 'e,
+    DB: Database,
+    <DB as HasArguments<'e>>::Arguments: IntoArguments<'e, DB>,
+{
+    if let Err(e) = row.validate_write() {
+        return Box::pin(future::err(e));
     }
+    #[cfg(feature = "tracing")]
+    let span = T::tracing_span(crate::instrument::Operation::Upsert, Some(&row.id()));
+    let future: CrudFut<'e, SaveOutcome<T>> = Box::pin(
+        sqlx::query_with::<DB, _>(sql, row.insert_args())
+            .try_map(|r| {
+                let row = T::from_row(&r)?;
+                Ok(match <T as DatabaseSchema<DB>>::upsert_inserted(&r)? {
+                    true => SaveOutcome::Created(row),
+                    false => SaveOutcome::Updated(row),
+                })
+            })
+            .fetch_one(executor)
+            .map_err(Error::from),
+    );
+    #[cfg(feature = "tracing")]
+    let future = crate::instrument::traced(span, future);
+    future
+}
+
+/// Runs [Crud::save] in a transaction begun by `acquire`.
+fn save_in_transaction<'e, T, E, DB>(row: T, acquire: E) -> CrudFut<'e, SaveOutcome<T>>
+where
+    T: Crud<'e, E> + Creatable + Updatable,
+    E: Executor<'e, Database = DB> + Acquire<'e, Database = DB> + 'e,
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    <DB as HasArguments<'e>>::Arguments: IntoArguments<'e, DB>,
+{
+    let exists = sqlx::query_with::<DB, _>(
+        &<T as DatabaseSchema<DB>>::database_metadata().exists_sql,
+        T::id_args(row.id()),
+    );
+    let audit = audit_statement::<T, E>(
+        &row,
+        &<T as DatabaseSchema<DB>>::database_metadata().audit_update_sql,
+    );
+    let begin = acquire.begin();
+    Box::pin(async move {
+        let mut tx = begin.await?;
+        let saved = if exists.fetch_optional(&mut *tx).await?.is_some() {
+            if let Some((audit_sql, audit_args)) = audit {
+                sqlx::query_with::<DB, _>(audit_sql, audit_args)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            SaveOutcome::Updated(update_on::<T, E, _>(row, &mut *tx).await?)
+        } else {
+            SaveOutcome::Created(create_on::<T, E, _>(row, &mut *tx).await?)
+        };
+        tx.commit().await?;
+        Ok(saved)
+    })
+}
+
+/// An INSERT statement of [Crud::create_many_returning] and its values,
+/// with the query reading the row back and its values on MySQL.
+type ReturningStatement<'e, DB> = (
+    &'static str,
+    <DB as HasArguments<'e>>::Arguments,
+    Option<(&'static str, <DB as HasArguments<'e>>::Arguments)>,
+);
+
+/// Runs the `statements` of [Crud::create_many_returning] in a transaction
+/// begun by `acquire`, returning the rows in turn. A statement followed by a
+/// query reads its row back with the query rather than RETURNING.
+fn create_returning_in_transaction<'e, T, E, DB>(
+    acquire: E,
+    statements: Vec<ReturningStatement<'e, DB>>,
+    count: usize,
+) -> CrudFut<'e, Vec<T>>
+where
+    T: Crud<'e, E> + Creatable,
+    E: Executor<'e, Database = DB> + Acquire<'e, Database = DB> + 'e,
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    <DB as HasArguments<'e>>::Arguments: IntoArguments<'e, DB>,
+{
+    let begin = acquire.begin();
+    Box::pin(async move {
+        let mut tx = begin.await?;
+        let mut created = Vec::with_capacity(count);
+        for (sql, args, select) in statements {
+            let query = sqlx::query_with::<DB, _>(sql, args);
+            let rows: CrudFut<'_, Vec<T>> = match select {
+                Some((select_sql, select_args)) => {
+                    query.execute(&mut *tx).await?;
+                    Box::pin(
+                        sqlx::query_with::<DB, _>(select_sql, select_args)
+                            .try_map(|r| T::from_row(&r))
+                            .fetch_one(&mut *tx)
+                            .map_ok(|row| vec![row])
+                            .map_err(Error::from),
+                    )
+                }
+                None => Box::pin(
+                    query
+                        .try_map(|r| T::from_row(&r))
+                        .fetch_all(&mut *tx)
+                        .map_err(Error::from),
+                ),
+            };
+            created.extend(rows.await?);
+        }
+        tx.commit().await?;
+        for row in &mut created {
+            row.run_hook(Hook::AfterCreate)?;
+        }
+        Ok(created)
+    })
+}
+
+/// Runs the `statements` of [Crud::create_many] and [Crud::delete_many] in a
+/// transaction begun by `acquire`, returning the number of rows affected.
+fn execute_in_transaction<'e, A, DB>(
+    acquire: A,
+    statements: Vec<(&'static str, <DB as HasArguments<'e>>::Arguments)>,
+    rows_affected: fn(&DB::QueryResult) -> u64,
+) -> CrudFut<'e, u64>
+where
+    A: Acquire<'e, Database = DB> + 'e,
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    <DB as HasArguments<'e>>::Arguments: IntoArguments<'e, DB>,
+{
+    let begin = acquire.begin();
+    Box::pin(async move {
+        let mut tx = begin.await?;
+        let mut affected = 0;
+        for (sql, args) in statements {
+            let result = sqlx::query_with::<DB, _>(sql, args)
+                .execute(&mut *tx)
+                .await?;
+            affected += rows_affected(&result);
+        }
+        tx.commit().await?;
+        Ok(affected)
+    })
 }
@@ -0,0 +1,90 @@
+//! Types adapting field values for [FromRow].
+//!
+//! [FromRow]: https://docs.rs/sqlx/latest/sqlx/trait.FromRow.html
+
+use sqlx::database::HasValueRef;
+use sqlx::decode::Decode;
+use sqlx::error::BoxDynError;
+use sqlx::types::Json;
+use sqlx::{Database, Type, ValueRef};
+
+/// Reads a nullable JSON column in to an `Option` field. NULL is read as
+/// `None`, other values are deserialized from JSON.
+///
+/// sqlx's `#[sqlx(json)]` field attribute fails on NULL, so optional
+/// `#[sqlx_crud(json)]` fields are read through this type with
+/// `#[sqlx(try_from = "sqlx_crud::types::JsonOption<T>")]` instead.
+///
+/// # Example
+///
+#[cfg_attr(feature = "json", doc = "```rust")]
+#[cfg_attr(not(feature = "json"), doc = "```rust,ignore")]
+/// # sqlx_crud::doctest_setup! { |pool| {
+/// use serde::{Deserialize, Serialize};
+/// use sqlx_crud::Crud;
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Theme {
+///     colors: Vec<String>,
+/// }
+///
+/// #[derive(Debug, FromRow, SqlxCrud)]
+/// #[database(Sqlite)]
+/// struct Profile {
+///     id: i64,
+///     #[sqlx(try_from = "sqlx_crud::types::JsonOption<Theme>")]
+///     #[sqlx_crud(json)]
+///     theme: Option<Theme>,
+/// }
+///
+/// sqlx::query("CREATE TABLE profiles (id INTEGER PRIMARY KEY, theme TEXT)")
+///     .execute(&pool)
+///     .await?;
+///
+/// let mut profile = Profile { id: 0, theme: None }.create(&pool).await?;
+/// assert_eq!(None, profile.theme);
+/// let stored: Option<String> = sqlx::query_scalar("SELECT theme FROM profiles")
+///     .fetch_one(&pool)
+///     .await?;
+/// assert_eq!(None, stored);
+///
+/// profile.theme = Some(Theme { colors: vec!["teal".to_string()] });
+/// profile.update(&pool).await?;
+/// let profile = Profile::by_id(&pool, 1).await?.unwrap();
+/// assert_eq!(Some(Theme { colors: vec!["teal".to_string()] }), profile.theme);
+/// # }}
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonOption<T>(pub Option<T>);
+
+impl<T> From<JsonOption<T>> for Option<T> {
+    fn from(value: JsonOption<T>) -> Self {
+        value.0
+    }
+}
+
+impl<T, DB: Database> Type<DB> for JsonOption<T>
+where
+    Json<T>: Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <Json<T> as Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <Json<T> as Type<DB>>::compatible(ty)
+    }
+}
+
+impl<'r, T, DB: Database> Decode<'r, DB> for JsonOption<T>
+where
+    Json<T>: Decode<'r, DB>,
+{
+    fn decode(value: <DB as HasValueRef<'r>>::ValueRef) -> Result<Self, BoxDynError> {
+        if value.is_null() {
+            Ok(Self(None))
+        } else {
+            <Json<T> as Decode<'r, DB>>::decode(value).map(|json| Self(Some(json.0)))
+        }
+    }
+}